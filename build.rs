@@ -0,0 +1,107 @@
+//! Record what a binary was built from for `--version`: the commit, the
+//! target and the Cargo features enabled. Also build the library stdbuf
+//! preloads, which is compiled on its own for the target.
+
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+fn main() {
+    let dir = env::var("CARGO_MANIFEST_DIR").unwrap_or_default();
+    let revision = Command::new("git")
+        .args(["rev-parse", "--short=12", "HEAD"])
+        .current_dir(&dir)
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .and_then(|output| String::from_utf8(output.stdout).ok())
+        .map(|hash| hash.trim().to_string())
+        .filter(|hash| !hash.is_empty())
+        .unwrap_or_else(|| "unknown".to_string());
+
+    let mut features = env::vars()
+        .filter_map(|(name, _)| {
+            let feature = name.strip_prefix("CARGO_FEATURE_")?;
+            Some(feature.to_lowercase().replace('_', "-"))
+        })
+        .collect::<Vec<_>>();
+    features.sort();
+    let features = match features.is_empty() {
+        true => "none".to_string(),
+        false => features.join(", "),
+    };
+
+    println!("cargo:rustc-env=COREUTILS_REVISION={}", revision);
+    println!(
+        "cargo:rustc-env=COREUTILS_TARGET={}",
+        env::var("TARGET").unwrap_or_default()
+    );
+    println!("cargo:rustc-env=COREUTILS_FEATURES={}", features);
+
+    stdbuf();
+
+    // Build again when the commit checked out changes.
+    println!("cargo:rerun-if-changed=build.rs");
+    let git = Path::new(&dir).join(".git");
+    if git.is_dir() {
+        println!("cargo:rerun-if-changed={}", git.join("HEAD").display());
+        if let Ok(head) = fs::read_to_string(git.join("HEAD")) {
+            if let Some(reference) = head.trim().strip_prefix("ref: ") {
+                println!("cargo:rerun-if-changed={}", git.join(reference).display());
+            }
+        }
+        println!(
+            "cargo:rerun-if-changed={}",
+            git.join("packed-refs").display()
+        );
+    }
+}
+
+/// Compile src/stdbuf/libstdbuf.rs into OUT_DIR as a shared library, and
+/// name it by a hash of its bytes. Where it cannot be preloaded, or cannot
+/// be built, the library is left empty and stdbuf says so when run.
+fn stdbuf() {
+    let source = "src/stdbuf/libstdbuf.rs";
+    println!("cargo:rerun-if-changed={}", source);
+    let out = PathBuf::from(env::var_os("OUT_DIR").expect("OUT_DIR is set by Cargo"));
+    let library = out.join("libstdbuf.so");
+    let target = env::var("TARGET").unwrap_or_default();
+    let linux = env::var("CARGO_CFG_TARGET_OS").as_deref() == Ok("linux");
+    let built = linux
+        && Command::new(env::var_os("RUSTC").unwrap_or_else(|| "rustc".into()))
+            .args([
+                "--crate-type",
+                "cdylib",
+                "--edition",
+                "2021",
+                "--target",
+                &target,
+            ])
+            .args([
+                "-C",
+                "opt-level=2",
+                "-C",
+                "panic=abort",
+                "-C",
+                "strip=symbols",
+            ])
+            .arg("-o")
+            .arg(&library)
+            .arg(source)
+            .status()
+            .is_ok_and(|status| status.success());
+    if !built {
+        if linux {
+            println!("cargo:warning=stdbuf will not work: its library was not built");
+        }
+        fs::write(&library, []).expect("cannot write to OUT_DIR");
+    }
+
+    // FNV-1a, which is enough to tell one build of the library from another.
+    let bytes = fs::read(&library).expect("cannot read the library back");
+    let hash = bytes.iter().fold(0xcbf29ce484222325u64, |hash, &b| {
+        (hash ^ b as u64).wrapping_mul(0x100000001b3)
+    });
+    println!("cargo:rustc-env=COREUTILS_STDBUF_HASH={:016x}", hash);
+}
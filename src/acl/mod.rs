@@ -0,0 +1,173 @@
+//! POSIX access control lists, as Linux keeps them in the
+//! `system.posix_acl_access` and `system.posix_acl_default` extended
+//! attributes.
+
+use std::fmt;
+use std::io;
+use std::path::Path;
+
+use crate::usergroup::{group_name, user_name};
+use crate::xattr;
+
+const ACCESS: &str = "system.posix_acl_access";
+const DEFAULT: &str = "system.posix_acl_default";
+
+/// The version the kernel's attribute format starts with.
+const VERSION: u32 = 2;
+
+/// Whom an entry grants permissions to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Tag {
+    /// The owner, whose permissions are the owner bits of the mode.
+    UserObj,
+    User(u32),
+    /// The owning group; with a mask, only the mask is in the mode.
+    GroupObj,
+    Group(u32),
+    /// The most the named entries and the owning group may be granted.
+    Mask,
+    Other,
+}
+
+impl Tag {
+    fn from_raw(tag: u16, id: u32) -> Option<Tag> {
+        Some(match tag {
+            0x01 => Tag::UserObj,
+            0x02 => Tag::User(id),
+            0x04 => Tag::GroupObj,
+            0x08 => Tag::Group(id),
+            0x10 => Tag::Mask,
+            0x20 => Tag::Other,
+            _ => return None,
+        })
+    }
+
+    fn to_raw(self) -> (u16, u32) {
+        const UNDEFINED: u32 = u32::MAX;
+        match self {
+            Tag::UserObj => (0x01, UNDEFINED),
+            Tag::User(uid) => (0x02, uid),
+            Tag::GroupObj => (0x04, UNDEFINED),
+            Tag::Group(gid) => (0x08, gid),
+            Tag::Mask => (0x10, UNDEFINED),
+            Tag::Other => (0x20, UNDEFINED),
+        }
+    }
+}
+
+/// One line of an ACL: read, write and execute bits for someone.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Entry {
+    pub tag: Tag,
+    /// `rwx` as 4, 2 and 1.
+    pub perm: u16,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Acl {
+    pub entries: Vec<Entry>,
+}
+
+impl Acl {
+    /// Decode the attribute value the kernel stores.
+    pub fn parse(bytes: &[u8]) -> Option<Acl> {
+        let (version, body) = bytes.split_first_chunk::<4>()?;
+        if u32::from_le_bytes(*version) != VERSION || body.len() % 8 != 0 {
+            return None;
+        }
+        let entries = body
+            .chunks_exact(8)
+            .map(|entry| {
+                let tag = u16::from_le_bytes([entry[0], entry[1]]);
+                let perm = u16::from_le_bytes([entry[2], entry[3]]);
+                let id = u32::from_le_bytes([entry[4], entry[5], entry[6], entry[7]]);
+                Some(Entry {
+                    tag: Tag::from_raw(tag, id)?,
+                    perm,
+                })
+            })
+            .collect::<Option<_>>()?;
+        Some(Acl { entries })
+    }
+
+    /// Encode the ACL as the kernel stores it.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = VERSION.to_le_bytes().to_vec();
+        for entry in &self.entries {
+            let (tag, id) = entry.tag.to_raw();
+            bytes.extend(tag.to_le_bytes());
+            bytes.extend(entry.perm.to_le_bytes());
+            bytes.extend(id.to_le_bytes());
+        }
+        bytes
+    }
+
+    /// Whether the ACL says no more than the mode bits do.
+    pub fn is_trivial(&self) -> bool {
+        self.entries
+            .iter()
+            .all(|e| matches!(e.tag, Tag::UserObj | Tag::GroupObj | Tag::Other))
+    }
+}
+
+/// The ACL in the text form `getfacl` prints, one entry per line.
+impl fmt::Display for Acl {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for entry in &self.entries {
+            let (kind, qualifier) = match entry.tag {
+                Tag::UserObj => ("user", String::new()),
+                Tag::User(uid) => ("user", user_name(uid).unwrap_or_else(|| uid.to_string())),
+                Tag::GroupObj => ("group", String::new()),
+                Tag::Group(gid) => ("group", group_name(gid).unwrap_or_else(|| gid.to_string())),
+                Tag::Mask => ("mask", String::new()),
+                Tag::Other => ("other", String::new()),
+            };
+            let bit = |bit, c| if entry.perm & bit != 0 { c } else { '-' };
+            writeln!(
+                f,
+                "{kind}:{qualifier}:{}{}{}",
+                bit(4, 'r'),
+                bit(2, 'w'),
+                bit(1, 'x')
+            )?;
+        }
+        Ok(())
+    }
+}
+
+fn read(path: &Path, name: &str) -> io::Result<Option<Acl>> {
+    match xattr::get(path, name.as_ref(), true) {
+        Ok(bytes) => Acl::parse(&bytes)
+            .map(Some)
+            .ok_or_else(|| io::ErrorKind::InvalidData.into()),
+        Err(e) if xattr::is_absent(&e) => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+/// The ACL deciding who may access `path`, if it has more than its mode.
+pub fn access(path: &Path) -> io::Result<Option<Acl>> {
+    read(path, ACCESS)
+}
+
+/// The ACL files created in the directory `path` start with, if any.
+pub fn default(path: &Path) -> io::Result<Option<Acl>> {
+    read(path, DEFAULT)
+}
+
+/// Whether `path` grants anything its mode bits do not show, which `ls -l`
+/// marks with a `+`. Symlinks have no ACLs of their own.
+pub fn has_extended(path: &Path) -> bool {
+    let Ok(names) = xattr::list(path, false) else {
+        return false;
+    };
+    let present = |name| names.iter().any(|n| n == name);
+    present(DEFAULT)
+        || present(ACCESS) && access(path).is_ok_and(|acl| acl.is_some_and(|a| !a.is_trivial()))
+}
+
+/// Give `to` the access and default ACLs of `from`. Setting the access ACL
+/// sets the mode bits too, so this follows any change of mode.
+pub fn copy(from: &Path, to: &Path) -> io::Result<()> {
+    xattr::copy(from, to, |name| name == ACCESS || name == DEFAULT)
+}
@@ -0,0 +1,128 @@
+//! Writing a file so that it appears under its name whole or not at all:
+//! the data goes to a temporary file beside it, which is renamed over the
+//! name once complete, as sort -o onto one of its inputs, shuf -o and
+//! install want. A run that is interrupted leaves the old file in place.
+
+use std::ffi::{CString, OsString};
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::os::fd::FromRawFd;
+use std::os::unix::ffi::OsStringExt;
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Path, PathBuf};
+
+use crate::mode::umask;
+
+/// How much is flushed to the device before the file counts as written.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Durability {
+    /// Leave it to the kernel, so that a crash may lose the new file.
+    #[default]
+    None,
+    /// Sync the data before renaming, so that the name never refers to a
+    /// partly written file even after a crash.
+    Data,
+    /// Sync the directory after renaming too, so that the rename itself
+    /// survives a crash.
+    Directory,
+}
+
+/// A file being written in place of `target`.
+pub struct AtomicFile {
+    file: File,
+    temp: PathBuf,
+    target: PathBuf,
+    durability: Durability,
+    committed: bool,
+}
+
+/// The directory `path` is in.
+fn parent(path: &Path) -> &Path {
+    match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    }
+}
+
+impl AtomicFile {
+    /// Start writing a replacement for `target`, which keeps the mode of
+    /// the file it replaces, if there is one, and is written through a
+    /// symlink rather than over it.
+    pub fn create(target: &Path, durability: Durability) -> io::Result<Self> {
+        let target = match fs::symlink_metadata(target) {
+            Ok(metadata) if metadata.file_type().is_symlink() => fs::canonicalize(target)?,
+            _ => target.to_path_buf(),
+        };
+        let mode = match fs::metadata(&target) {
+            Ok(metadata) => metadata.permissions().mode() & 0o7777,
+            Err(_) => 0o666 & !umask(),
+        };
+        let dir = parent(&target);
+        let mut name = OsString::from(".");
+        name.push(target.file_name().unwrap_or_default());
+        name.push(".XXXXXX");
+        let template = CString::new(dir.join(name).into_os_string().into_vec())
+            .map_err(|_| io::Error::from(io::ErrorKind::InvalidInput))?;
+        let mut template = template.into_bytes_with_nul();
+        let fd = unsafe { libc::mkstemp(template.as_mut_ptr().cast()) };
+        if fd < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        template.pop();
+        let temp = PathBuf::from(OsString::from_vec(template));
+        let file = unsafe { File::from_raw_fd(fd) };
+        let atomic = AtomicFile {
+            file,
+            temp,
+            target,
+            durability,
+            committed: false,
+        };
+        atomic
+            .file
+            .set_permissions(fs::Permissions::from_mode(mode))?;
+        Ok(atomic)
+    }
+
+    /// The name the file is written under until it is committed.
+    pub fn temp_path(&self) -> &Path {
+        &self.temp
+    }
+
+    pub fn file(&mut self) -> &mut File {
+        &mut self.file
+    }
+
+    /// Put the file in place under its name, with the durability asked
+    /// for.
+    pub fn commit(mut self) -> io::Result<()> {
+        if self.durability != Durability::None {
+            self.file.sync_all()?;
+        }
+        fs::rename(&self.temp, &self.target)?;
+        self.committed = true;
+        if self.durability == Durability::Directory {
+            File::open(parent(&self.target))?.sync_all()?;
+        }
+        Ok(())
+    }
+}
+
+impl Write for AtomicFile {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.file.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.file.flush()
+    }
+}
+
+impl Drop for AtomicFile {
+    /// A file that was never committed is abandoned.
+    fn drop(&mut self) {
+        if !self.committed {
+            let _ = fs::remove_file(&self.temp);
+        }
+    }
+}
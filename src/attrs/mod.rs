@@ -0,0 +1,143 @@
+//! Applying a change of mode, owner or security context to each file
+//! named, and to the trees under them for `-R`, as chmod, chown, chgrp and
+//! chcon do: choosing which symlinks to follow, refusing to work on `/`
+//! recursively, reporting what changed and carrying on past files that
+//! cannot be changed.
+
+use std::fs::{self, Metadata};
+use std::io;
+use std::os::unix::fs::MetadataExt;
+use std::path::{Path, PathBuf};
+
+use crate::error::{strerror, ExitStatus};
+use crate::fs::{EntryKind, SymlinkPolicy, WalkErrorKind, Walker};
+use crate::quote::quote;
+use crate::show_error;
+
+/// How much a run says about the files it works on.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Verbosity {
+    /// `-f`: not even why a file could not be changed.
+    Silent,
+    #[default]
+    Normal,
+    /// `-c`: describe each file that was changed.
+    Changes,
+    /// `-v`: describe every file.
+    Verbose,
+}
+
+/// What became of one file.
+pub struct Applied {
+    pub changed: bool,
+    /// What `-v`, and `-c` for a changed file, print about it.
+    pub message: String,
+}
+
+/// The change made to each file, which is all that differs between the
+/// utilities.
+pub trait Change {
+    /// Change `path`, whose metadata is `metadata`. `follow` says whether a
+    /// symlink is to be followed or changed itself, if it can be.
+    fn apply(&mut self, path: &Path, metadata: &Metadata, follow: bool) -> io::Result<Applied>;
+
+    /// The diagnostic for failing to change `path` with `error`, such as
+    /// `changing permissions of 'f': Operation not permitted`.
+    fn failure(&self, path: &Path, error: &io::Error) -> String;
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Options {
+    /// Work on the trees under directories too (`-R`).
+    pub recursive: bool,
+    /// Which symlinks a recursive run follows: `-P`, `-H` or `-L`.
+    pub symlinks: SymlinkPolicy,
+    /// Whether a symlink operand is followed when not recursive, as chmod
+    /// always does and chown does unless `-h`.
+    pub dereference: bool,
+    pub preserve_root: bool,
+    pub verbosity: Verbosity,
+}
+
+/// Apply `change` to every file in `paths`, returning the exit status.
+pub fn change_all(paths: &[PathBuf], options: &Options, change: &mut impl Change) -> i32 {
+    let status = ExitStatus::new();
+    for path in paths {
+        if options.recursive && options.preserve_root && is_root(path) {
+            match path.as_os_str() == "/" {
+                true => show_error!("it is dangerous to operate recursively on {}", quote(path)),
+                false => show_error!(
+                    "it is dangerous to operate recursively on {} (same as '/')",
+                    quote(path)
+                ),
+            }
+            show_error!("use --no-preserve-root to override this failsafe");
+            status.fail();
+            continue;
+        }
+        change_tree(path, options, change, &status);
+    }
+    status.code()
+}
+
+fn change_tree(path: &Path, options: &Options, change: &mut impl Change, status: &ExitStatus) {
+    let silent = options.verbosity == Verbosity::Silent;
+    let (symlinks, max_depth) = match (options.recursive, options.dereference) {
+        (true, _) => (options.symlinks, usize::MAX),
+        (false, true) => (SymlinkPolicy::CommandLine, 0),
+        (false, false) => (SymlinkPolicy::Never, 0),
+    };
+    let walker = Walker::new(path).symlinks(symlinks).max_depth(max_depth);
+    for entry in walker {
+        let entry = match entry {
+            Ok(entry) if entry.kind == EntryKind::DirPost => continue,
+            Ok(entry) => entry,
+            Err(e) => {
+                status.fail();
+                if !silent {
+                    match &e.kind {
+                        WalkErrorKind::Io(error) if e.path.is_dir() => show_error!(
+                            "cannot read directory {}: {}",
+                            quote(&e.path),
+                            strerror(error)
+                        ),
+                        WalkErrorKind::Io(error) => {
+                            show_error!("cannot access {}: {}", quote(&e.path), strerror(error))
+                        }
+                        WalkErrorKind::Loop(_) => show_error!("{}", e),
+                    }
+                }
+                continue;
+            }
+        };
+        let follow = match symlinks {
+            SymlinkPolicy::Always => true,
+            SymlinkPolicy::CommandLine => entry.is_root(),
+            SymlinkPolicy::Never => false,
+        };
+        match change.apply(&entry.path, &entry.metadata, follow) {
+            Ok(applied) => {
+                let report = match options.verbosity {
+                    Verbosity::Verbose => true,
+                    Verbosity::Changes => applied.changed,
+                    Verbosity::Silent | Verbosity::Normal => false,
+                };
+                if report {
+                    println!("{}", applied.message);
+                }
+            }
+            Err(e) => {
+                status.fail();
+                if !silent {
+                    show_error!("{}", change.failure(&entry.path, &e));
+                }
+            }
+        }
+    }
+}
+
+/// Whether `path` is the root directory, by whatever name.
+fn is_root(path: &Path) -> bool {
+    let id = |path: &Path| fs::metadata(path).map(|m| (m.dev(), m.ino())).ok();
+    id(path).is_some_and(|path| id(Path::new("/")) == Some(path))
+}
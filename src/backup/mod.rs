@@ -0,0 +1,134 @@
+use std::ffi::OsString;
+use std::fs;
+use std::os::unix::ffi::{OsStrExt, OsStringExt};
+use std::path::{Path, PathBuf};
+
+use crate::envcfg;
+use crate::quote::quote;
+
+/// How existing destination files are backed up before being replaced.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BackupMode {
+    /// Never make backups.
+    None,
+    /// Append the simple suffix, `~` by default.
+    Simple,
+    /// Make numbered backups, `FILE.~N~`.
+    Numbered,
+    /// Numbered if numbered backups already exist, simple otherwise.
+    Existing,
+}
+
+/// The --backup CONTROL words with the modes they select; as with GNU, any
+/// unambiguous prefix is accepted too.
+const CONTROLS: [(&str, BackupMode); 8] = [
+    ("none", BackupMode::None),
+    ("off", BackupMode::None),
+    ("simple", BackupMode::Simple),
+    ("never", BackupMode::Simple),
+    ("existing", BackupMode::Existing),
+    ("nil", BackupMode::Existing),
+    ("numbered", BackupMode::Numbered),
+    ("t", BackupMode::Numbered),
+];
+
+impl BackupMode {
+    pub fn parse(control: &str) -> Result<Self, String> {
+        if let Some((_, mode)) = CONTROLS.iter().find(|(name, _)| *name == control) {
+            return Ok(*mode);
+        }
+        let mut matches = CONTROLS
+            .iter()
+            .filter(|(name, _)| name.starts_with(control))
+            .map(|(_, mode)| *mode);
+        match matches.next() {
+            Some(mode) if !control.is_empty() && matches.all(|m| m == mode) => Ok(mode),
+            Some(_) => Err(format!(
+                "ambiguous argument {} for 'backup type'",
+                quote(control)
+            )),
+            None => Err(format!(
+                "invalid argument {} for 'backup type'",
+                quote(control)
+            )),
+        }
+    }
+
+    /// The mode --backup[=CONTROL] selects: CONTROL if given, otherwise
+    /// $VERSION_CONTROL, otherwise existing.
+    pub fn from_option(control: Option<&str>) -> Result<Self, String> {
+        match control {
+            Some(control) => Self::parse(control),
+            None => match envcfg::version_control() {
+                Some(control) => {
+                    Self::parse(&control).map_err(|e| format!("{} (from $VERSION_CONTROL)", e))
+                }
+                None => Ok(BackupMode::Existing),
+            },
+        }
+    }
+}
+
+/// The simple backup suffix: `suffix` (from -S/--suffix) if given,
+/// otherwise $SIMPLE_BACKUP_SUFFIX, otherwise `~`. A suffix containing a
+/// slash would back files up into another directory, so it is ignored.
+pub fn suffix(suffix: Option<&str>) -> String {
+    suffix
+        .map(str::to_string)
+        .or_else(envcfg::simple_backup_suffix)
+        .filter(|s| !s.is_empty() && !s.contains('/'))
+        .unwrap_or_else(|| "~".to_string())
+}
+
+/// The highest N among the `NAME.~N~` backups of `path`, or 0 if there
+/// are none.
+fn highest_backup(path: &Path) -> u64 {
+    let Some(name) = path.file_name() else {
+        return 0;
+    };
+    let dir = match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+    let Ok(entries) = fs::read_dir(dir) else {
+        return 0;
+    };
+    let mut prefix = name.as_bytes().to_vec();
+    prefix.extend_from_slice(b".~");
+    entries
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| {
+            let entry = entry.file_name();
+            let number = entry.as_bytes().strip_prefix(prefix.as_slice())?;
+            let number = std::str::from_utf8(number.strip_suffix(b"~")?).ok()?;
+            match number.bytes().all(|b| b.is_ascii_digit()) && !number.starts_with('0') {
+                true => number.parse().ok(),
+                false => None,
+            }
+        })
+        .max()
+        .unwrap_or(0)
+}
+
+/// The name to back `path` up as, or `None` if no backup is wanted.
+pub fn backup_path(path: &Path, mode: BackupMode, suffix: &str) -> Option<PathBuf> {
+    let numbered = |n: u64| {
+        let mut name = path.as_os_str().to_os_string().into_vec();
+        name.extend_from_slice(format!(".~{}~", n).as_bytes());
+        PathBuf::from(OsString::from_vec(name))
+    };
+    let simple = || {
+        let mut name = path.as_os_str().to_os_string();
+        name.push(suffix);
+        PathBuf::from(name)
+    };
+    match mode {
+        BackupMode::None => None,
+        BackupMode::Simple => Some(simple()),
+        BackupMode::Numbered => Some(numbered(highest_backup(path) + 1)),
+        BackupMode::Existing => match highest_backup(path) {
+            0 => Some(simple()),
+            n => Some(numbered(n + 1)),
+        },
+    }
+}
@@ -0,0 +1,36 @@
+use std::env;
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+use std::path::PathBuf;
+use std::process;
+
+use clap_mangen::Man;
+use coreutils::uu::COMMANDS;
+
+/// Write `DIR/NAME.1` for every utility, DIR defaulting to `man`.
+fn main() {
+    let dir = PathBuf::from(env::args_os().nth(1).unwrap_or_else(|| "man".into()));
+    if let Err(e) = fs::create_dir_all(&dir) {
+        eprintln!("coreutils-mangen: {}: {}", dir.display(), e);
+        process::exit(1);
+    }
+    for (name, command) in COMMANDS {
+        let path = dir.join(format!("{}.1", name));
+        let written = File::create(&path).and_then(|file| {
+            let mut out = BufWriter::new(file);
+            let command = command().name(name).bin_name(name);
+            Man::new(command).render(&mut out)?;
+            out.flush()
+        });
+        if let Err(e) = written {
+            eprintln!("coreutils-mangen: {}: {}", path.display(), e);
+            process::exit(1);
+        }
+    }
+    let _ = writeln!(
+        io::stdout(),
+        "{} manual pages in {}",
+        COMMANDS.len(),
+        dir.display()
+    );
+}
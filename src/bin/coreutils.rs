@@ -0,0 +1,92 @@
+use std::env;
+use std::ffi::OsString;
+use std::io;
+use std::path::Path;
+use std::process;
+
+use coreutils::error::set_program_name;
+use coreutils::io::restore_sigpipe;
+use coreutils::uu::{self, COMMANDS, UTILITIES};
+use coreutils::version;
+
+use clap::ValueEnum;
+use clap_complete::Shell;
+
+fn usage() -> ! {
+    eprintln!("Usage: coreutils UTILITY [ARGUMENT]...");
+    eprintln!("   or: UTILITY [ARGUMENT]...  (with UTILITY a link to coreutils)");
+    eprintln!();
+    eprintln!("Available utilities:");
+    let names = UTILITIES.map(|(name, _)| name);
+    for line in names.chunks(8) {
+        eprintln!("  {}", line.join(" "));
+    }
+    process::exit(1);
+}
+
+/// Write the completions of the named utilities, or of all of them, for
+/// the shell given first.
+fn completion(args: &[OsString]) -> ! {
+    let Some(shell) = args
+        .first()
+        .and_then(|s| Shell::from_str(&s.to_string_lossy(), true).ok())
+    else {
+        let shells = Shell::value_variants().iter().map(|s| s.to_string());
+        eprintln!("Usage: coreutils --completion SHELL [UTILITY]...");
+        eprintln!("SHELL is one of: {}", shells.collect::<Vec<_>>().join(", "));
+        process::exit(1);
+    };
+    let wanted = &args[1..];
+    if let Some(unknown) = wanted
+        .iter()
+        .find(|w| !COMMANDS.iter().any(|(name, _)| *w == name))
+    {
+        eprintln!("coreutils: unknown utility '{}'", unknown.to_string_lossy());
+        process::exit(1);
+    }
+    let mut stdout = io::stdout();
+    for (name, command) in COMMANDS {
+        if wanted.is_empty() || wanted.iter().any(|w| w == name) {
+            clap_complete::generate(shell, &mut command(), name, &mut stdout);
+        }
+    }
+    process::exit(0);
+}
+
+fn main() {
+    let mut args = env::args_os().collect::<Vec<OsString>>();
+    let invoked = args
+        .first()
+        .and_then(|arg| Path::new(arg).file_name())
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default();
+
+    // Run as a link named after the utility, or as `coreutils UTILITY ...`.
+    let name = match uu::find(&invoked) {
+        Some(_) => invoked,
+        None => {
+            args.remove(0);
+            match args.first() {
+                Some(name) => name.to_string_lossy().to_string(),
+                None => usage(),
+            }
+        }
+    };
+    let Some(run) = uu::find(&name) else {
+        match name.as_str() {
+            "--help" | "-h" => usage(),
+            "--version" | "-V" => {
+                println!("coreutils {}", version::VERSION);
+                process::exit(0);
+            }
+            "--completion" => completion(&args[1..]),
+            _ => {
+                eprintln!("coreutils: unknown utility '{}'", name);
+                process::exit(1);
+            }
+        }
+    };
+    set_program_name(&name);
+    restore_sigpipe();
+    process::exit(run(args));
+}
@@ -0,0 +1,3 @@
+fn main() {
+    coreutils::uu::main(coreutils::uu::numfmt::run)
+}
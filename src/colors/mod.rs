@@ -0,0 +1,258 @@
+use std::collections::HashMap;
+use std::env;
+use std::fs::{FileType, Metadata};
+use std::os::unix::fs::{FileTypeExt, MetadataExt};
+
+/// Indicators used when LS_COLORS does not set them, as in GNU ls.
+const DEFAULTS: [(&str, &str); 17] = [
+    ("lc", "\x1b["),
+    ("rc", "m"),
+    ("rs", "0"),
+    ("di", "01;34"),
+    ("ln", "01;36"),
+    ("pi", "33"),
+    ("so", "01;35"),
+    ("bd", "01;33"),
+    ("cd", "01;33"),
+    ("ex", "01;32"),
+    ("do", "01;35"),
+    ("su", "37;41"),
+    ("sg", "30;43"),
+    ("st", "37;44"),
+    ("ow", "34;42"),
+    ("tw", "30;42"),
+    ("ca", ""),
+];
+
+/// A parsed LS_COLORS value: styles for file types and special mode bits,
+/// keyed by their two-letter indicator, and styles for name patterns.
+pub struct LsColors {
+    indicators: HashMap<String, String>,
+    patterns: Vec<(String, String)>,
+}
+
+/// Expand the escapes dircolors allows in values: `\e`, `\a`, `\n`, octal
+/// and `\xHH` escapes, and `^X` control characters.
+fn unescape(value: &str) -> String {
+    let chars = value.chars().collect::<Vec<char>>();
+    let mut out = String::new();
+    let mut i = 0;
+    while i < chars.len() {
+        match chars[i] {
+            '\\' if i + 1 < chars.len() => {
+                i += 1;
+                let c = match chars[i] {
+                    'a' => '\x07',
+                    'b' => '\x08',
+                    'e' => '\x1b',
+                    'f' => '\x0c',
+                    'n' => '\n',
+                    'r' => '\r',
+                    't' => '\t',
+                    'v' => '\x0b',
+                    '?' => '\x7f',
+                    '_' => ' ',
+                    'x' => {
+                        let digits = chars[i + 1..]
+                            .iter()
+                            .take(2)
+                            .take_while(|c| c.is_ascii_hexdigit())
+                            .collect::<String>();
+                        i += digits.len();
+                        u8::from_str_radix(&digits, 16).map_or('x', char::from)
+                    }
+                    '0'..='7' => {
+                        let digits = chars[i..]
+                            .iter()
+                            .take(3)
+                            .take_while(|c| ('0'..='7').contains(c))
+                            .collect::<String>();
+                        i += digits.len() - 1;
+                        u8::from_str_radix(&digits, 8).map_or('0', char::from)
+                    }
+                    c => c,
+                };
+                out.push(c);
+            }
+            '^' if i + 1 < chars.len() => {
+                i += 1;
+                out.push(match chars[i] {
+                    '?' => '\x7f',
+                    c => char::from(c.to_ascii_uppercase() as u8 & 0x1f),
+                });
+            }
+            c => out.push(c),
+        }
+        i += 1;
+    }
+    out
+}
+
+impl LsColors {
+    pub fn parse(spec: &str) -> Self {
+        let mut colors = LsColors {
+            indicators: DEFAULTS
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect(),
+            patterns: vec![],
+        };
+        for item in spec.split(':').filter(|item| !item.is_empty()) {
+            let Some((key, value)) = item.split_once('=') else {
+                continue;
+            };
+            let value = unescape(value);
+            match key.strip_prefix('*') {
+                Some(pattern) => colors.patterns.push((pattern.to_string(), value)),
+                None => {
+                    colors.indicators.insert(key.to_string(), value);
+                }
+            }
+        }
+        colors
+    }
+
+    /// The styles from `LS_COLORS`, or the built-in defaults when it is
+    /// unset.
+    pub fn from_env() -> Self {
+        Self::parse(&env::var("LS_COLORS").unwrap_or_default())
+    }
+
+    fn indicator(&self, key: &str) -> Option<&str> {
+        self.indicators
+            .get(key)
+            .map(String::as_str)
+            .filter(|v| !v.is_empty() && *v != "0" && *v != "00")
+    }
+
+    /// The style for a name pattern such as `*.tar`, matched as a suffix.
+    /// Case only matters when two patterns differ by nothing else.
+    fn pattern_style(&self, name: &str) -> Option<&str> {
+        if let Some((_, style)) = self
+            .patterns
+            .iter()
+            .rev()
+            .find(|(p, _)| name.ends_with(p.as_str()))
+        {
+            return Some(style);
+        }
+        let lower = name.to_lowercase();
+        self.patterns
+            .iter()
+            .rev()
+            .find(|(p, _)| lower.ends_with(&p.to_lowercase()))
+            .map(|(_, style)| style.as_str())
+    }
+
+    /// The style for a file given its `lstat` metadata; `target` is the
+    /// metadata the link resolves to, `None` for a dangling link.
+    pub fn style(
+        &self,
+        name: &str,
+        metadata: Option<&Metadata>,
+        target: Option<&Metadata>,
+    ) -> Option<&str> {
+        self.style_lazily(
+            name,
+            metadata.map(Metadata::file_type),
+            || metadata,
+            || target,
+        )
+    }
+
+    /// Whether any of the indicators `keys` is set.
+    fn any(&self, keys: &[&str]) -> bool {
+        keys.iter().any(|key| self.indicator(key).is_some())
+    }
+
+    /// The style for a file of `file_type`, as [`LsColors::style`] gives
+    /// it, but looking at its metadata only if a rule for the type depends
+    /// on mode bits or links, and at a symlink's target only if the link
+    /// is colored by it. `None` for the type is a file that could not be
+    /// looked at.
+    pub fn style_lazily<'m>(
+        &self,
+        name: &str,
+        file_type: Option<FileType>,
+        metadata: impl FnOnce() -> Option<&'m Metadata>,
+        target: impl FnOnce() -> Option<&'m Metadata>,
+    ) -> Option<&str> {
+        let Some(file_type) = file_type else {
+            return self.indicator("mi").or(self.indicator("or"));
+        };
+        let key = if file_type.is_symlink() {
+            let by_target = self.indicators.get("ln").is_some_and(|v| v == "target");
+            // Without its own style an orphan looks like any other link.
+            if !by_target && self.indicator("or").is_none() {
+                "ln"
+            } else {
+                match target() {
+                    None => "or",
+                    Some(target) if by_target => {
+                        return self.style(name, Some(target), Some(target));
+                    }
+                    Some(_) => "ln",
+                }
+            }
+        } else if file_type.is_dir() {
+            let mode = match self.any(&["tw", "ow", "st"]) {
+                true => metadata().map_or(0, Metadata::mode),
+                false => 0,
+            };
+            match (mode & libc::S_ISVTX != 0, mode & libc::S_IWOTH != 0) {
+                (true, true) if self.indicator("tw").is_some() => "tw",
+                (false, true) if self.indicator("ow").is_some() => "ow",
+                (true, false) if self.indicator("st").is_some() => "st",
+                _ => "di",
+            }
+        } else if file_type.is_fifo() {
+            "pi"
+        } else if file_type.is_socket() {
+            "so"
+        } else if file_type.is_block_device() {
+            "bd"
+        } else if file_type.is_char_device() {
+            "cd"
+        } else {
+            let metadata = match self.any(&["su", "sg", "ex", "mh"]) {
+                true => metadata(),
+                false => None,
+            };
+            let mode = metadata.map_or(0, Metadata::mode);
+            if mode & libc::S_ISUID != 0 && self.indicator("su").is_some() {
+                "su"
+            } else if mode & libc::S_ISGID != 0 && self.indicator("sg").is_some() {
+                "sg"
+            } else if mode & 0o111 != 0 && self.indicator("ex").is_some() {
+                "ex"
+            } else if metadata.is_some_and(|m| m.nlink() > 1) && self.indicator("mh").is_some() {
+                "mh"
+            } else {
+                return self.pattern_style(name).or(self.indicator("fi"));
+            }
+        };
+        self.indicator(key).or(match key {
+            "or" => self.indicator("ln"),
+            _ => None,
+        })
+    }
+
+    /// Wrap `text` in the escape sequences for `style`.
+    pub fn paint(&self, text: &str, style: Option<&str>) -> String {
+        let Some(style) = style.filter(|s| !s.is_empty()) else {
+            return text.to_string();
+        };
+        let left = self.indicators.get("lc").map_or("", String::as_str);
+        let right = self.indicators.get("rc").map_or("", String::as_str);
+        let end = match self.indicators.get("ec") {
+            Some(end) => end.clone(),
+            None => format!(
+                "{}{}{}",
+                left,
+                self.indicators.get("rs").map_or("0", String::as_str),
+                right
+            ),
+        };
+        format!("{}{}{}{}{}", left, style, right, text, end)
+    }
+}
@@ -0,0 +1,526 @@
+//! Copying files the way cp, mv across file systems and install do: the
+//! data by cloning, the kernel's in-kernel copy or a loop that leaves holes
+//! where the source is all zeros, special files and symlinks by creating
+//! new ones, and whichever attributes the caller asks to preserve.
+
+use std::collections::HashMap;
+use std::ffi::{CString, OsStr};
+use std::fmt::{self, Display};
+use std::fs::{self, File, Metadata, OpenOptions};
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::os::unix::ffi::OsStrExt;
+use std::os::unix::fs::{DirBuilderExt, FileTypeExt, MetadataExt, OpenOptionsExt, PermissionsExt};
+use std::path::{Path, PathBuf};
+
+use crate::error::strerror;
+use crate::fs::{EntryKind, SymlinkPolicy, WalkErrorKind, Walker};
+use crate::quote::quote;
+use crate::{acl, selinux, sparse, sys, xattr};
+
+/// The attributes `--preserve` can keep.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Preserve {
+    pub mode: bool,
+    pub ownership: bool,
+    pub timestamps: bool,
+    /// Copy files linked to each other as links to each other.
+    pub links: bool,
+    pub context: bool,
+    pub xattr: bool,
+}
+
+impl Preserve {
+    /// What `-a` and `--preserve=all` keep.
+    pub const ALL: Preserve = Preserve {
+        mode: true,
+        ownership: true,
+        timestamps: true,
+        links: true,
+        context: true,
+        xattr: true,
+    };
+
+    /// Turn on, or off, the attributes named in the comma-separated `list`
+    /// of a `--preserve` or `--no-preserve`.
+    pub fn apply(&mut self, list: &str, on: bool) -> Result<(), String> {
+        for word in list.split(',') {
+            let field = match word {
+                "mode" => &mut self.mode,
+                "ownership" => &mut self.ownership,
+                "timestamps" => &mut self.timestamps,
+                "links" => &mut self.links,
+                "context" => &mut self.context,
+                "xattr" => &mut self.xattr,
+                "all" => {
+                    *self = match on {
+                        true => Preserve::ALL,
+                        false => Preserve::default(),
+                    };
+                    continue;
+                }
+                _ => {
+                    return Err(format!(
+                        "invalid argument {} for '--{}preserve'",
+                        quote(word),
+                        if on { "" } else { "no-" }
+                    ))
+                }
+            };
+            *field = on;
+        }
+        Ok(())
+    }
+}
+
+/// When to share the source's data blocks rather than copy them.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum Reflink {
+    /// Clone where the file system can, copy otherwise.
+    #[default]
+    Auto,
+    /// Clone or fail.
+    Always,
+    Never,
+}
+
+/// When to leave holes in the copy.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum Sparse {
+    /// Where the source looks sparse, leave holes for its runs of zeros.
+    #[default]
+    Auto,
+    /// Leave a hole for every block of zeros.
+    Always,
+    Never,
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Options {
+    pub preserve: Preserve,
+    /// Fail, rather than carry on quietly, when ownership, extended
+    /// attributes or the security context cannot be preserved, as an
+    /// explicit `--preserve` asks. Mode and timestamps always count.
+    pub required: bool,
+    pub reflink: Reflink,
+    pub sparse: Sparse,
+    /// Copy what symbolic links point to rather than the links.
+    pub dereference: bool,
+    /// Read fifos and devices like regular files rather than creating new
+    /// ones (`--copy-contents`).
+    pub copy_contents: bool,
+}
+
+/// A failure to copy, or to preserve something while copying.
+#[derive(Debug)]
+pub struct CopyError {
+    /// What was being done, such as `cannot open 'a' for reading`.
+    pub action: String,
+    pub error: io::Error,
+}
+
+impl CopyError {
+    fn new(action: String) -> impl FnOnce(io::Error) -> CopyError {
+        move |error| CopyError { action, error }
+    }
+}
+
+impl Display for CopyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.action, strerror(&self.error))
+    }
+}
+
+fn c_path(path: &Path) -> io::Result<CString> {
+    CString::new(path.as_os_str().as_bytes()).map_err(|_| io::ErrorKind::InvalidInput.into())
+}
+
+fn check(status: libc::c_int) -> io::Result<()> {
+    match status {
+        0 => Ok(()),
+        _ => Err(io::Error::last_os_error()),
+    }
+}
+
+/// Copies files one after another, remembering what it has copied so that
+/// hard links can be kept.
+pub struct Copier {
+    options: Options,
+    /// The copy made of each multiply linked file, by device and inode.
+    links: HashMap<(u64, u64), PathBuf>,
+}
+
+impl Copier {
+    pub fn new(options: Options) -> Self {
+        Copier {
+            options,
+            links: HashMap::new(),
+        }
+    }
+
+    pub fn options(&self) -> &Options {
+        &self.options
+    }
+
+    /// Copy `from` to `to`, creating `to` or, if it is a regular file,
+    /// overwriting it; backing it up or removing it first is the caller's
+    /// business. A directory is created empty, with its attributes
+    /// preserved: see [`Copier::copy_tree`] for its contents.
+    pub fn copy(&mut self, from: &Path, to: &Path) -> Result<(), CopyError> {
+        let metadata = match self.options.dereference {
+            true => fs::metadata(from),
+            false => fs::symlink_metadata(from),
+        }
+        .map_err(CopyError::new(format!("cannot stat {}", quote(from))))?;
+        if metadata.is_dir() {
+            let added = self.create_dir(to, &metadata)?;
+            return self.finish_dir(from, to, &metadata, added);
+        }
+        self.copy_node(from, to, &metadata)
+    }
+
+    /// Copy the tree under `from` to `to`, reporting each failure to
+    /// `report` and carrying on with the rest. Returns whether everything
+    /// was copied.
+    pub fn copy_tree(&mut self, from: &Path, to: &Path, mut report: impl FnMut(CopyError)) -> bool {
+        let symlinks = match self.options.dereference {
+            true => SymlinkPolicy::Always,
+            false => SymlinkPolicy::Never,
+        };
+        let mut ok = true;
+        // What create_dir added to each directory being copied.
+        let mut added = vec![];
+        let mut fail = |e| {
+            ok = false;
+            report(e)
+        };
+        for entry in Walker::new(from).symlinks(symlinks).postorder(true) {
+            let entry = match entry {
+                Ok(entry) => entry,
+                Err(e) => {
+                    let action = format!("cannot access {}", quote(&e.path));
+                    let error = match e.kind {
+                        WalkErrorKind::Io(error) => error,
+                        WalkErrorKind::Loop(_) => io::Error::from_raw_os_error(libc::ELOOP),
+                    };
+                    fail(CopyError { action, error });
+                    continue;
+                }
+            };
+            let target = match entry.path.strip_prefix(from) {
+                Ok(rest) if !rest.as_os_str().is_empty() => to.join(rest),
+                _ => to.to_path_buf(),
+            };
+            let result = match entry.kind {
+                EntryKind::Dir => {
+                    let created = self.create_dir(&target, &entry.metadata);
+                    added.push(*created.as_ref().unwrap_or(&0));
+                    created.map(|_| ())
+                }
+                // Last, so that copying the contents does not undo the
+                // times or fail for want of write permission.
+                EntryKind::DirPost => {
+                    let bits = added.pop().unwrap_or(0);
+                    self.finish_dir(&entry.path, &target, &entry.metadata, bits)
+                }
+                EntryKind::File => self.copy_node(&entry.path, &target, &entry.metadata),
+            };
+            if let Err(e) = result {
+                fail(e);
+            }
+        }
+        ok
+    }
+
+    /// Create the directory `to` for a copy of the one `metadata`
+    /// describes, if it does not exist yet, returning the owner bits it was
+    /// given beyond the source's so that it can be filled.
+    fn create_dir(&self, to: &Path, metadata: &Metadata) -> Result<u32, CopyError> {
+        if to.is_dir() {
+            return Ok(0);
+        }
+        // With the mode preserved afterwards, nobody else may get in first.
+        let (mode, added) = match self.options.preserve.mode {
+            true => (0o700, 0),
+            false => (metadata.mode() & 0o777 | 0o700, 0o700 & !metadata.mode()),
+        };
+        fs::DirBuilder::new()
+            .mode(mode)
+            .create(to)
+            .map_err(CopyError::new(format!(
+                "cannot create directory {}",
+                quote(to)
+            )))?;
+        Ok(added)
+    }
+
+    /// Finish the copy `to` of the directory `from` once its contents are
+    /// in, taking away the owner bits [`Copier::create_dir`] added.
+    fn finish_dir(
+        &self,
+        from: &Path,
+        to: &Path,
+        metadata: &Metadata,
+        added: u32,
+    ) -> Result<(), CopyError> {
+        if added != 0 && !self.options.preserve.mode {
+            let mode = fs::metadata(to).map(|m| m.mode() & 0o7777 & !added);
+            mode.and_then(|mode| fs::set_permissions(to, fs::Permissions::from_mode(mode)))
+                .map_err(CopyError::new(format!(
+                    "setting permissions for {}",
+                    quote(to)
+                )))?;
+        }
+        self.preserve(from, to, metadata)
+    }
+
+    /// Copy anything but a directory.
+    fn copy_node(&mut self, from: &Path, to: &Path, metadata: &Metadata) -> Result<(), CopyError> {
+        let id = (metadata.dev(), metadata.ino());
+        if self.options.preserve.links && metadata.nlink() > 1 {
+            if let Some(first) = self.links.get(&id) {
+                return fs::hard_link(first, to).map_err(CopyError::new(format!(
+                    "cannot create hard link {} to {}",
+                    quote(to),
+                    quote(first)
+                )));
+            }
+        }
+
+        let kind = metadata.file_type();
+        let special = kind.is_fifo() || kind.is_char_device() || kind.is_block_device();
+        if kind.is_symlink() {
+            let target = fs::read_link(from).map_err(CopyError::new(format!(
+                "cannot read symbolic link {}",
+                quote(from)
+            )))?;
+            sys::symlink(&target, to).map_err(CopyError::new(format!(
+                "cannot create symbolic link {}",
+                quote(to)
+            )))?;
+        } else if kind.is_file() || special && self.options.copy_contents {
+            self.copy_contents(from, to, metadata)?;
+        } else {
+            make_node(to, metadata)?;
+        }
+
+        if self.options.preserve.links && metadata.nlink() > 1 {
+            self.links.insert(id, to.to_path_buf());
+        }
+        self.preserve(from, to, metadata)
+    }
+
+    fn copy_contents(&self, from: &Path, to: &Path, metadata: &Metadata) -> Result<(), CopyError> {
+        let mut source = File::open(from).map_err(CopyError::new(format!(
+            "cannot open {} for reading",
+            quote(from)
+        )))?;
+        let mut dest = OpenOptions::new()
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .mode(metadata.mode() & 0o777)
+            .open(to)
+            .map_err(CopyError::new(format!(
+                "cannot create regular file {}",
+                quote(to)
+            )))?;
+
+        if self.options.reflink != Reflink::Never {
+            match clone(&source, &dest) {
+                Ok(()) => return Ok(()),
+                Err(e) if self.options.reflink == Reflink::Always => {
+                    return Err(CopyError::new(format!(
+                        "failed to clone {} from {}",
+                        quote(to),
+                        quote(from)
+                    ))(e))
+                }
+                Err(_) => {}
+            }
+        }
+
+        let sparse = match self.options.sparse {
+            Sparse::Always => true,
+            Sparse::Never => false,
+            Sparse::Auto => sparse::looks_sparse(metadata),
+        };
+        let copied = match sparse {
+            true => copy_sparse(&mut source, &mut dest, metadata),
+            // The standard library uses copy_file_range or sendfile for
+            // this where the kernel allows.
+            false => io::copy(&mut source, &mut dest).map_err(Failure::Unknown),
+        };
+        copied.map(|_| ()).map_err(|e| match e {
+            Failure::Read(e) => CopyError::new(format!("error reading {}", quote(from)))(e),
+            Failure::Write(e) | Failure::Unknown(e) => {
+                CopyError::new(format!("error writing {}", quote(to)))(e)
+            }
+        })
+    }
+
+    /// Give `to` the attributes of `from` that are to be preserved.
+    fn preserve(&self, from: &Path, to: &Path, metadata: &Metadata) -> Result<(), CopyError> {
+        let preserve = self.options.preserve;
+        let symlink = metadata.file_type().is_symlink();
+        let required = |result: io::Result<()>, action: String| match result {
+            Err(e) if self.options.required && !xattr::is_absent(&e) => {
+                Err(CopyError::new(action)(e))
+            }
+            _ => Ok(()),
+        };
+
+        // Before the mode, since changing the owner clears set-user-ID.
+        if preserve.ownership {
+            let chown = |uid, gid| match symlink {
+                true => std::os::unix::fs::lchown(to, uid, gid),
+                false => std::os::unix::fs::chown(to, uid, gid),
+            };
+            match chown(Some(metadata.uid()), Some(metadata.gid())) {
+                Ok(()) => {}
+                // Only root may give files away; keep the group at least.
+                Err(e) if !self.options.required && e.kind() == io::ErrorKind::PermissionDenied => {
+                    let _ = chown(None, Some(metadata.gid()));
+                }
+                Err(e) => {
+                    return Err(CopyError::new(format!(
+                        "failed to preserve ownership for {}",
+                        quote(to)
+                    ))(e))
+                }
+            }
+        }
+        if preserve.xattr && !symlink {
+            // ACLs go with the mode, contexts with --preserve=context.
+            let wanted = |name: &OsStr| {
+                !name.as_bytes().starts_with(b"system.posix_acl_") && name != "security.selinux"
+            };
+            required(
+                xattr::copy(from, to, wanted),
+                format!("failed to preserve extended attributes for {}", quote(to)),
+            )?;
+        }
+        if preserve.context {
+            required(
+                selinux::copy(from, to),
+                format!("failed to set the security context of {}", quote(to)),
+            )?;
+        }
+        if preserve.mode && !symlink {
+            let action = format!("preserving permissions for {}", quote(to));
+            fs::set_permissions(to, fs::Permissions::from_mode(metadata.mode() & 0o7777))
+                .map_err(CopyError::new(action.clone()))?;
+            match acl::copy(from, to) {
+                Err(e) if !xattr::is_absent(&e) => return Err(CopyError::new(action)(e)),
+                _ => {}
+            }
+        }
+        if preserve.timestamps {
+            set_times(to, metadata, !symlink).map_err(CopyError::new(format!(
+                "preserving times for {}",
+                quote(to)
+            )))?;
+        }
+        Ok(())
+    }
+}
+
+/// Which side of a copy failed.
+enum Failure {
+    Read(io::Error),
+    Write(io::Error),
+    /// The kernel copied for us and does not say.
+    Unknown(io::Error),
+}
+
+/// Copy `source`, which `metadata` describes, to `dest`: only the data
+/// the source has, a block at a time, seeking over its holes and over
+/// blocks of zeros so that they become holes in the copy.
+fn copy_sparse(source: &mut File, dest: &mut File, metadata: &Metadata) -> Result<u64, Failure> {
+    let mut buf = vec![0; metadata.blksize().clamp(4096, 1 << 20) as usize];
+    let size = metadata.size();
+    let mut extents = Vec::new();
+    for extent in sparse::data(source, size) {
+        extents.push(extent.map_err(Failure::Read)?);
+    }
+    // A trailing hole needs the length set, as nothing is written there.
+    let mut end = size;
+    'copy: for extent in extents {
+        source
+            .seek(SeekFrom::Start(extent.start))
+            .map_err(Failure::Read)?;
+        dest.seek(SeekFrom::Start(extent.start))
+            .map_err(Failure::Write)?;
+        let mut left = extent.end - extent.start;
+        while left > 0 {
+            let want = buf.len().min(left.try_into().unwrap_or(usize::MAX));
+            let n = match source.read(&mut buf[..want]) {
+                // The file has shrunk since it was looked at.
+                Ok(0) => {
+                    end = extent.end - left;
+                    break 'copy;
+                }
+                Ok(n) => n,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => return Err(Failure::Read(e)),
+            };
+            let written = match sparse::is_zero(&buf[..n]) {
+                true => dest.seek(SeekFrom::Current(n as i64)).map(|_| ()),
+                false => dest.write_all(&buf[..n]),
+            };
+            written.map_err(Failure::Write)?;
+            left -= n as u64;
+        }
+    }
+    dest.set_len(end).map_err(Failure::Write)?;
+    Ok(end)
+}
+
+/// Make `dest` share the data blocks of `source`.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn clone(source: &File, dest: &File) -> io::Result<()> {
+    use std::os::fd::AsRawFd;
+    let status = unsafe { libc::ioctl(dest.as_raw_fd(), libc::FICLONE, source.as_raw_fd()) };
+    check(status)
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "android")))]
+fn clone(_: &File, _: &File) -> io::Result<()> {
+    Err(io::ErrorKind::Unsupported.into())
+}
+
+/// Create a fifo, device or socket at `to` like the one `metadata`
+/// describes.
+fn make_node(to: &Path, metadata: &Metadata) -> Result<(), CopyError> {
+    let kind = metadata.file_type();
+    let what = match kind.is_fifo() {
+        true => "fifo",
+        false => "special file",
+    };
+    let action = format!("cannot create {} {}", what, quote(to));
+    let path = c_path(to).map_err(CopyError::new(action.clone()))?;
+    let mode = (metadata.mode() & 0o777) as libc::mode_t;
+    let status = match kind.is_fifo() {
+        true => unsafe { libc::mkfifo(path.as_ptr(), mode) },
+        false => unsafe {
+            let kind = metadata.mode() as libc::mode_t & libc::S_IFMT;
+            libc::mknod(path.as_ptr(), kind | mode, metadata.rdev() as libc::dev_t)
+        },
+    };
+    check(status).map_err(CopyError::new(action))
+}
+
+/// Give `path`, or the symlink itself unless `follow`, the access and
+/// modification times in `metadata`.
+fn set_times(path: &Path, metadata: &Metadata, follow: bool) -> io::Result<()> {
+    let time = |seconds, nanoseconds| libc::timespec {
+        tv_sec: seconds as libc::time_t,
+        tv_nsec: nanoseconds as _,
+    };
+    let times = [
+        time(metadata.atime(), metadata.atime_nsec()),
+        time(metadata.mtime(), metadata.mtime_nsec()),
+    ];
+    let flags = if follow { 0 } else { libc::AT_SYMLINK_NOFOLLOW };
+    let path = c_path(path)?;
+    check(unsafe { libc::utimensat(libc::AT_FDCWD, path.as_ptr(), times.as_ptr(), flags) })
+}
@@ -0,0 +1,480 @@
+//! Dates as `date -d`, `touch -d` and `touch -t` take them: the free-form
+//! GNU grammar of calendar dates, times of day, zones, weekdays and
+//! relative items such as `2 hours ago` or `next friday`, seconds since the
+//! epoch as `@N`, and the `[[CC]YY]MMDDhhmm[.ss]` stamps of `touch -t`.
+
+use std::mem;
+
+use time::{Date, Month, PrimitiveDateTime, Time, UtcOffset, Weekday};
+
+use crate::timefmt::{ZonedTime, MONTHS, WEEKDAYS};
+
+#[derive(Default)]
+struct Relative {
+    years: i64,
+    months: i64,
+    days: i64,
+    seconds: i64,
+}
+
+/// A parsed date string: absolute fields that were mentioned and the
+/// relative adjustments to apply afterwards.
+#[derive(Default)]
+struct DateSpec {
+    date: Option<(Option<i32>, u8, u8)>,
+    time: Option<(u8, u8, u8, u32)>,
+    weekday: Option<(Weekday, i64)>,
+    offset: Option<UtcOffset>,
+    relative: Relative,
+}
+
+fn month_from_name(name: &str) -> Option<u8> {
+    if name.len() < 3 {
+        return None;
+    }
+    MONTHS
+        .iter()
+        .position(|m| {
+            m.to_lowercase().starts_with(name)
+                && (name.len() == 3 || m.len() == name.len() || name == "sept")
+        })
+        .map(|m| m as u8 + 1)
+}
+
+fn weekday_from_name(name: &str) -> Option<Weekday> {
+    if name.len() < 3 {
+        return None;
+    }
+    let index = WEEKDAYS.iter().position(|d| {
+        let d = d.to_lowercase();
+        d.starts_with(name) && (name.len() == 3 || d.len() == name.len())
+    })?;
+    Some(Weekday::Sunday.nth_next(index as u8))
+}
+
+fn unit_seconds(unit: &str) -> Option<(i64, bool)> {
+    let unit = unit
+        .strip_suffix('s')
+        .filter(|u| !u.is_empty())
+        .unwrap_or(unit);
+    Some(match unit {
+        "sec" | "second" => (1, false),
+        "min" | "minute" => (60, false),
+        "hour" => (3600, false),
+        "day" => (1, true),
+        "week" => (7, true),
+        "fortnight" => (14, true),
+        _ => return None,
+    })
+}
+
+fn add_relative(relative: &mut Relative, unit: &str, count: i64) -> bool {
+    let unit_name = unit
+        .strip_suffix('s')
+        .filter(|u| !u.is_empty())
+        .unwrap_or(unit);
+    let (field, scale) = match unit_name {
+        "year" => (&mut relative.years, 1),
+        "month" => (&mut relative.months, 1),
+        _ => match unit_seconds(unit) {
+            Some((days, true)) => (&mut relative.days, days),
+            Some((seconds, false)) => (&mut relative.seconds, seconds),
+            None => return false,
+        },
+    };
+    // A count too large for any date is as bad as an unknown unit.
+    match count.checked_mul(scale).and_then(|n| field.checked_add(n)) {
+        Some(total) => {
+            *field = total;
+            true
+        }
+        None => false,
+    }
+}
+
+fn parse_time_of_day(token: &str) -> Option<(u8, u8, u8, u32)> {
+    let parts = token.split(':').collect::<Vec<&str>>();
+    if parts.len() < 2 || parts.len() > 3 {
+        return None;
+    }
+    let hour = parts[0].parse::<u8>().ok()?;
+    let minute = parts[1].parse::<u8>().ok()?;
+    let (second, nanos) = match parts.get(2) {
+        Some(s) => {
+            let (whole, fraction) = s.split_once(['.', ',']).unwrap_or((s, ""));
+            let nanos = match fraction.is_empty() {
+                true => 0,
+                false => format!("{:0<9}", &fraction[..fraction.len().min(9)])
+                    .parse()
+                    .ok()?,
+            };
+            (whole.parse::<u8>().ok()?, nanos)
+        }
+        None => (0, 0),
+    };
+    (hour < 24 && minute < 60 && second < 61).then_some((hour, minute, second, nanos))
+}
+
+fn meridian((h, m, s, n): (u8, u8, u8, u32), suffix: &str) -> Option<(u8, u8, u8, u32)> {
+    if h == 0 || h > 12 {
+        return None;
+    }
+    let h = match suffix.starts_with('p') {
+        true => h % 12 + 12,
+        false => h % 12,
+    };
+    Some((h, m, s, n))
+}
+
+fn parse_offset(token: &str) -> Option<UtcOffset> {
+    let sign = match token.chars().next()? {
+        '+' => 1,
+        '-' => -1,
+        _ => return None,
+    };
+    let digits = token[1..].replace(':', "");
+    if !digits.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    let (h, m) = match digits.len() {
+        1 | 2 => (digits.parse::<i32>().ok()?, 0),
+        4 => (
+            digits[..2].parse::<i32>().ok()?,
+            digits[2..].parse::<i32>().ok()?,
+        ),
+        _ => return None,
+    };
+    UtcOffset::from_whole_seconds(sign * (h * 3600 + m * 60)).ok()
+}
+
+fn parse_date_spec(input: &str) -> Option<DateSpec> {
+    let mut spec = DateSpec::default();
+    let lowered = input.to_lowercase();
+    let mut tokens = vec![];
+    for token in lowered.split([' ', '\t', ',']).filter(|t| !t.is_empty()) {
+        // Split ISO 8601 "dateTtime" and a trailing zone designator.
+        match token.split_once('t') {
+            Some((date, time))
+                if date.len() == 10 && date.chars().nth(4) == Some('-') && !time.is_empty() =>
+            {
+                tokens.push(date.to_string());
+                tokens.push(time.to_string());
+            }
+            _ => tokens.push(token.to_string()),
+        }
+    }
+
+    let mut last_relative: Option<(String, i64)> = None;
+    let mut i = 0;
+    while i < tokens.len() {
+        let token = tokens[i].clone();
+        let next = tokens.get(i + 1).cloned();
+        i += 1;
+        match token.as_str() {
+            "now" | "today" => continue,
+            "yesterday" => spec.relative.days -= 1,
+            "tomorrow" => spec.relative.days += 1,
+            "noon" => spec.time = Some((12, 0, 0, 0)),
+            "midnight" => spec.time = Some((0, 0, 0, 0)),
+            "utc" | "gmt" | "z" | "ut" => spec.offset = Some(UtcOffset::UTC),
+            "am" | "a.m." | "pm" | "p.m." => spec.time = Some(meridian(spec.time?, &token)?),
+            "ago" => {
+                let (unit, count) = last_relative.take()?;
+                if !add_relative(&mut spec.relative, &unit, count.checked_mul(-2)?) {
+                    return None;
+                }
+            }
+            "next" | "last" | "this" => {
+                let count = match token.as_str() {
+                    "next" => 1,
+                    "last" => -1,
+                    _ => 0,
+                };
+                let next = next?;
+                i += 1;
+                if let Some(weekday) = weekday_from_name(&next) {
+                    spec.weekday = Some((weekday, count));
+                } else if add_relative(&mut spec.relative, &next, count) {
+                    last_relative = Some((next, count));
+                } else {
+                    return None;
+                }
+            }
+            _ => {
+                if let Some(weekday) = weekday_from_name(token.trim_end_matches('.')) {
+                    spec.weekday = Some((weekday, 0));
+                } else if let Some(month) = month_from_name(token.trim_end_matches('.')) {
+                    let mut day = None;
+                    let mut year = None;
+                    let numbers = tokens[i..]
+                        .iter()
+                        .take(2)
+                        .map_while(|t| t.parse::<i32>().ok());
+                    for value in numbers.collect::<Vec<i32>>() {
+                        if day.is_none() && (1..=31).contains(&value) && tokens[i].len() <= 2 {
+                            day = Some(value as u8);
+                        } else if year.is_none() {
+                            year = Some(value);
+                        } else {
+                            break;
+                        }
+                        i += 1;
+                    }
+                    // "14 oct 2026" puts the day in front of the month.
+                    let day = match (day, spec.date) {
+                        (Some(day), _) => day,
+                        (None, Some((None, 0, day))) => day,
+                        _ => 1,
+                    };
+                    spec.date = Some((year, month, day));
+                } else if let Some(clock) = token
+                    .strip_suffix("am")
+                    .or_else(|| token.strip_suffix("pm"))
+                    .filter(|c| !c.is_empty())
+                {
+                    let time = match clock.parse::<u8>() {
+                        Ok(hour) => (hour, 0, 0, 0),
+                        Err(_) => parse_time_of_day(clock)?,
+                    };
+                    spec.time = Some(meridian(time, &token[clock.len()..])?);
+                } else if let Some(time) = parse_time_of_day(token.trim_end_matches('z')) {
+                    spec.time = Some(time);
+                    if token.ends_with('z') {
+                        spec.offset = Some(UtcOffset::UTC);
+                    }
+                    let zone = token
+                        .find(['+', '-'])
+                        .and_then(|p| parse_offset(&token[p..]));
+                    if let Some((time_part, zone)) = token
+                        .find(['+', '-'])
+                        .and_then(|p| zone.map(|z| (&token[..p], z)))
+                    {
+                        spec.time = Some(parse_time_of_day(time_part)?);
+                        spec.offset = Some(zone);
+                    }
+                } else if let Some(time) = token
+                    .find(['+', '-'])
+                    .filter(|&p| p > 0)
+                    .and_then(|p| parse_time_of_day(&token[..p]).zip(parse_offset(&token[p..])))
+                {
+                    spec.time = Some(time.0);
+                    spec.offset = Some(time.1);
+                } else if token.len() == 10 && token.chars().nth(4) == Some('-') {
+                    let mut parts = token.split('-');
+                    let year = parts.next()?.parse().ok()?;
+                    let month = parts.next()?.parse().ok()?;
+                    let day = parts.next()?.parse().ok()?;
+                    spec.date = Some((Some(year), month, day));
+                } else if token.contains('/') {
+                    let parts = token.split('/').collect::<Vec<&str>>();
+                    let month = parts[0].parse().ok()?;
+                    let day = parts.get(1)?.parse().ok()?;
+                    let year = match parts.get(2) {
+                        Some(y) => {
+                            let y = y.parse::<i32>().ok()?;
+                            Some(if y < 69 {
+                                y + 2000
+                            } else if y < 100 {
+                                y + 1900
+                            } else {
+                                y
+                            })
+                        }
+                        None => None,
+                    };
+                    spec.date = Some((year, month, day));
+                } else if let Ok(count) = token.parse::<i64>() {
+                    if let Some(unit) = next.filter(|n| {
+                        matches!(n.trim_end_matches('s'), "year" | "month")
+                            || unit_seconds(n).is_some()
+                    }) {
+                        if !add_relative(&mut spec.relative, &unit, count) {
+                            return None;
+                        }
+                        last_relative = Some((unit, count));
+                        i += 1;
+                    } else if token.starts_with(['+', '-']) && spec.time.is_some() {
+                        spec.offset = Some(parse_offset(&token)?);
+                    } else if spec.date.is_none() && (1..=31).contains(&count) {
+                        // Day preceding a month name, as in "14 oct".
+                        spec.date = Some((None, 0, count as u8));
+                    } else if let Some((None, month, day)) = spec.date {
+                        spec.date = Some((Some(count as i32), month, day));
+                    } else if token.len() == 4 && spec.time.is_none() {
+                        spec.time = Some(((count / 100) as u8, (count % 100) as u8, 0, 0));
+                    } else {
+                        return None;
+                    }
+                } else if add_relative(&mut spec.relative, &token, 1) {
+                    last_relative = Some((token, 1));
+                } else if let Some(offset) = parse_offset(&token) {
+                    spec.offset = Some(offset);
+                } else {
+                    return None;
+                }
+            }
+        }
+    }
+    if let Some((_, 0, _)) = spec.date {
+        return None;
+    }
+    Some(spec)
+}
+
+/// Convert a local wall-clock time to seconds since the epoch with mktime so
+/// that the rules of TZ, including DST, apply.
+fn local_timestamp(datetime: PrimitiveDateTime) -> Option<i64> {
+    let mut tm: libc::tm = unsafe { mem::zeroed() };
+    tm.tm_year = datetime.year() - 1900;
+    tm.tm_mon = datetime.month() as i32 - 1;
+    tm.tm_mday = datetime.day() as i32;
+    tm.tm_hour = datetime.hour() as i32;
+    tm.tm_min = datetime.minute() as i32;
+    tm.tm_sec = datetime.second() as i32;
+    tm.tm_isdst = -1;
+    let seconds = unsafe { libc::mktime(&mut tm) };
+    (seconds != -1 || datetime.year() == 1969).then_some(seconds as i64)
+}
+
+/// The nanoseconds in `N[.FRACTION]` seconds, exactly, as a float would
+/// not give them for times far from the epoch.
+fn parse_epoch(text: &str) -> Option<i128> {
+    let (negative, number) = match text.strip_prefix('-') {
+        Some(number) => (true, number),
+        None => (false, text.strip_prefix('+').unwrap_or(text)),
+    };
+    let (whole, fraction) = number.split_once(['.', ',']).unwrap_or((number, ""));
+    let digits = whole.bytes().chain(fraction.bytes());
+    if whole.is_empty() && fraction.is_empty() || !digits.clone().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    let whole = match whole.is_empty() {
+        true => 0,
+        false => whole.parse::<i64>().ok()? as i128,
+    };
+    let nanos = format!("{:0<9}", &fraction[..fraction.len().min(9)])
+        .parse::<i128>()
+        .ok()?;
+    let value = whole * 1_000_000_000 + nanos;
+    Some(if negative { -value } else { value })
+}
+
+/// Resolve a `--date` string to nanoseconds since the epoch, taking the
+/// date and time it leaves out from `now`, and reading it as a time in UTC
+/// if `utc` and it names no zone itself.
+pub fn parse_date(input: &str, now: i128, utc: bool) -> Option<i128> {
+    let trimmed = input.trim();
+    if let Some(epoch) = trimmed.strip_prefix('@') {
+        return parse_epoch(epoch.trim());
+    }
+    let spec = parse_date_spec(trimmed)?;
+    let current = ZonedTime::new(now, utc || spec.offset.is_some())?;
+    let current = match spec.offset {
+        Some(offset) => current.time.to_offset(offset),
+        None => current.time,
+    };
+
+    let mut date = current.date();
+    let mut time = current.time();
+    if let Some((year, month, day)) = spec.date {
+        let month = Month::try_from(month).ok()?;
+        date = Date::from_calendar_date(year.unwrap_or(current.year()), month, day).ok()?;
+        time = Time::MIDNIGHT;
+    }
+    if let Some((weekday, count)) = spec.weekday {
+        let today = date.weekday().number_days_from_sunday() as i64;
+        let target = weekday.number_days_from_sunday() as i64;
+        let mut delta = (target - today).rem_euclid(7);
+        match count {
+            c if c > 0 => {
+                if delta == 0 {
+                    delta = 7;
+                }
+                delta += 7 * (c - 1);
+            }
+            c if c < 0 => {
+                delta -= 7;
+                if delta == 0 {
+                    delta = -7;
+                }
+                delta += 7 * (c + 1);
+            }
+            _ => {}
+        }
+        date = date.checked_add(time::Duration::days(delta))?;
+        time = Time::MIDNIGHT;
+    }
+    if let Some((h, m, s, n)) = spec.time {
+        time = Time::from_hms_nano(h, m, s.min(59), n).ok()?;
+    }
+
+    let months = (date.year() as i64 * 12 + date.month() as i64 - 1)
+        .checked_add(spec.relative.years.checked_mul(12)?)?
+        .checked_add(spec.relative.months)?;
+    let first = Date::from_calendar_date(
+        i32::try_from(months.div_euclid(12)).ok()?,
+        Month::try_from(months.rem_euclid(12) as u8 + 1).ok()?,
+        1,
+    )
+    .ok()?;
+    let days = (date.day() as i64 - 1).checked_add(spec.relative.days)?;
+    let date = first.checked_add(time::Duration::seconds(days.checked_mul(86_400)?))?;
+    let datetime = PrimitiveDateTime::new(date, time);
+
+    let seconds = match (spec.offset, utc) {
+        (Some(offset), _) => datetime.assume_offset(offset).unix_timestamp(),
+        (None, true) => datetime.assume_utc().unix_timestamp(),
+        (None, false) => local_timestamp(datetime)?,
+    };
+    Some(
+        (seconds as i128 + spec.relative.seconds as i128) * 1_000_000_000
+            + time.nanosecond() as i128,
+    )
+}
+
+/// Resolve a `touch -t` stamp, `[[CC]YY]MMDDhhmm[.ss]`, to nanoseconds
+/// since the epoch, as a local time unless `utc`. A two-digit year is in
+/// 1969 to 2068, and a missing one is the year of `now`.
+pub fn parse_touch_stamp(stamp: &str, now: i128, utc: bool) -> Option<i128> {
+    let (digits, seconds) = match stamp.split_once('.') {
+        Some((digits, seconds)) if seconds.len() == 2 => (digits, Some(seconds)),
+        Some(_) => return None,
+        None => (stamp, None),
+    };
+    let all_digits = |s: &str| s.bytes().all(|b| b.is_ascii_digit());
+    if !all_digits(digits) || !seconds.is_none_or(all_digits) {
+        return None;
+    }
+    let (year, rest) = match digits.len() {
+        8 => (None, digits),
+        10 => {
+            let year = digits[..2].parse::<i32>().ok()?;
+            (
+                Some(if year < 69 { year + 2000 } else { year + 1900 }),
+                &digits[2..],
+            )
+        }
+        12 => (Some(digits[..4].parse::<i32>().ok()?), &digits[4..]),
+        _ => return None,
+    };
+    let field = |i: usize| rest[i..i + 2].parse::<u8>().ok();
+    let year = match year {
+        Some(year) => year,
+        None => ZonedTime::new(now, utc)?.time.year(),
+    };
+    let date = Date::from_calendar_date(year, Month::try_from(field(0)?).ok()?, field(2)?).ok()?;
+    let second = seconds.map_or(Some(0), |s| s.parse::<u8>().ok())?;
+    // A leap second, which the clock does not keep, is the next minute.
+    let leap = match second {
+        60 => 1,
+        0..=59 => 0,
+        _ => return None,
+    };
+    let time = Time::from_hms(field(4)?, field(6)?, second - leap as u8).ok()?;
+    let datetime = PrimitiveDateTime::new(date, time);
+    let seconds = match utc {
+        true => datetime.assume_utc().unix_timestamp(),
+        false => local_timestamp(datetime)?,
+    };
+    Some((seconds + leap) as i128 * 1_000_000_000)
+}
@@ -0,0 +1,194 @@
+//! dd's `iflag=` and `oflag=` operands and what they need of the I/O:
+//! open flags, a buffer aligned for `direct`, and whole-block reads for
+//! `fullblock`.
+
+use std::alloc::{self, Layout};
+use std::fmt::{self, Display};
+use std::fs::File;
+use std::io::{self, Read};
+use std::ops::{Deref, DerefMut};
+use std::os::fd::AsRawFd;
+use std::ptr::NonNull;
+
+use crate::quote::quote;
+
+/// Which of the operands a flag was given in.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Side {
+    Input,
+    Output,
+}
+
+/// A flag dd does not know, or knows only on the other side.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FlagError {
+    pub side: Side,
+    pub flag: String,
+}
+
+impl Display for FlagError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let side = match self.side {
+            Side::Input => "input",
+            Side::Output => "output",
+        };
+        write!(f, "invalid {} flag: {}", side, quote(&self.flag))
+    }
+}
+
+/// The flags of one side.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Flags {
+    pub append: bool,
+    pub direct: bool,
+    pub directory: bool,
+    pub dsync: bool,
+    pub sync: bool,
+    /// Read until a block is full, rather than take what one read gives.
+    pub fullblock: bool,
+    pub nonblock: bool,
+    pub noatime: bool,
+    /// Ask for the data to be dropped from the cache.
+    pub nocache: bool,
+    pub noctty: bool,
+    pub nofollow: bool,
+    /// `count=` is in bytes rather than blocks.
+    pub count_bytes: bool,
+    /// `skip=` is in bytes rather than blocks.
+    pub skip_bytes: bool,
+    /// `seek=` is in bytes rather than blocks.
+    pub seek_bytes: bool,
+}
+
+impl Flags {
+    /// Add the comma-separated `list` of flags given for `side`. Operands
+    /// given more than once add up.
+    pub fn add(&mut self, list: &str, side: Side) -> Result<(), FlagError> {
+        for flag in list.split(',') {
+            let field = match flag {
+                "append" => &mut self.append,
+                #[cfg(any(target_os = "linux", target_os = "android", target_os = "freebsd"))]
+                "direct" => &mut self.direct,
+                "directory" => &mut self.directory,
+                "dsync" => &mut self.dsync,
+                "sync" => &mut self.sync,
+                "fullblock" if side == Side::Input => &mut self.fullblock,
+                "nonblock" => &mut self.nonblock,
+                "noatime" => &mut self.noatime,
+                "nocache" => &mut self.nocache,
+                "noctty" => &mut self.noctty,
+                "nofollow" => &mut self.nofollow,
+                "count_bytes" => &mut self.count_bytes,
+                "skip_bytes" => &mut self.skip_bytes,
+                "seek_bytes" => &mut self.seek_bytes,
+                _ => {
+                    return Err(FlagError {
+                        side,
+                        flag: flag.to_string(),
+                    })
+                }
+            };
+            *field = true;
+        }
+        Ok(())
+    }
+
+    /// The flags `open` is given for these, for
+    /// `OpenOptionsExt::custom_flags`.
+    pub fn open_flags(&self) -> libc::c_int {
+        let mut flags = 0;
+        let mut set = |on: bool, flag: libc::c_int| {
+            if on {
+                flags |= flag;
+            }
+        };
+        set(self.append, libc::O_APPEND);
+        #[cfg(any(target_os = "linux", target_os = "android", target_os = "freebsd"))]
+        set(self.direct, libc::O_DIRECT);
+        set(self.directory, libc::O_DIRECTORY);
+        set(self.dsync, libc::O_DSYNC);
+        set(self.sync, libc::O_SYNC);
+        set(self.nonblock, libc::O_NONBLOCK);
+        #[cfg(any(target_os = "linux", target_os = "android"))]
+        set(self.noatime, libc::O_NOATIME);
+        set(self.noctty, libc::O_NOCTTY);
+        set(self.nofollow, libc::O_NOFOLLOW);
+        flags
+    }
+}
+
+/// Stop using direct I/O on `file`, as dd does before writing a final
+/// block shorter than the device's, which direct I/O would refuse.
+pub fn clear_direct(file: &File) -> io::Result<()> {
+    #[cfg(any(target_os = "linux", target_os = "android", target_os = "freebsd"))]
+    unsafe {
+        let fd = file.as_raw_fd();
+        let flags = libc::fcntl(fd, libc::F_GETFL);
+        if flags == -1 || libc::fcntl(fd, libc::F_SETFL, flags & !libc::O_DIRECT) == -1 {
+            return Err(io::Error::last_os_error());
+        }
+    }
+    #[cfg(not(any(target_os = "linux", target_os = "android", target_os = "freebsd")))]
+    let _ = file.as_raw_fd();
+    Ok(())
+}
+
+/// Fill as much of `buf` as `reader` has, reading again after a short
+/// read, as `iflag=fullblock` does. Less than a full buffer means the end
+/// of the input.
+pub fn read_full(reader: &mut impl Read, buf: &mut [u8]) -> io::Result<usize> {
+    let mut filled = 0;
+    while filled < buf.len() {
+        match reader.read(&mut buf[filled..]) {
+            Ok(0) => break,
+            Ok(n) => filled += n,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(filled)
+}
+
+/// A zeroed buffer starting on a page boundary, as direct I/O needs.
+pub struct AlignedBuffer {
+    ptr: NonNull<u8>,
+    len: usize,
+    layout: Layout,
+}
+
+unsafe impl Send for AlignedBuffer {}
+
+impl AlignedBuffer {
+    pub fn new(len: usize) -> Self {
+        let page = match unsafe { libc::sysconf(libc::_SC_PAGESIZE) } {
+            size if size > 0 => size as usize,
+            _ => 4096,
+        };
+        let layout = Layout::from_size_align(len.max(1), page).expect("buffer too large");
+        let ptr = unsafe { alloc::alloc_zeroed(layout) };
+        let Some(ptr) = NonNull::new(ptr) else {
+            alloc::handle_alloc_error(layout);
+        };
+        AlignedBuffer { ptr, len, layout }
+    }
+}
+
+impl Deref for AlignedBuffer {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        unsafe { std::slice::from_raw_parts(self.ptr.as_ptr(), self.len) }
+    }
+}
+
+impl DerefMut for AlignedBuffer {
+    fn deref_mut(&mut self) -> &mut [u8] {
+        unsafe { std::slice::from_raw_parts_mut(self.ptr.as_ptr(), self.len) }
+    }
+}
+
+impl Drop for AlignedBuffer {
+    fn drop(&mut self) {
+        unsafe { alloc::dealloc(self.ptr.as_ptr(), self.layout) }
+    }
+}
@@ -0,0 +1,104 @@
+//! Reading back the lines the digest utilities print, for `--check`.
+
+use super::{check_length, Algorithm};
+
+/// One properly formatted line of a check file.
+#[derive(Debug, PartialEq, Eq)]
+pub struct CheckLine {
+    /// The algorithm and length in bits a BSD-style line names.
+    pub algorithm: Option<(Algorithm, usize)>,
+    pub digest: Vec<u8>,
+    pub name: String,
+}
+
+fn decode_hex(hex: &str) -> Option<Vec<u8>> {
+    if hex.is_empty() || !hex.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+/// Undo the escaping of names holding backslashes or newlines.
+fn unescape(name: &str) -> Option<String> {
+    let mut unescaped = String::with_capacity(name.len());
+    let mut chars = name.chars();
+    while let Some(c) = chars.next() {
+        unescaped.push(match c {
+            '\\' => match chars.next()? {
+                '\\' => '\\',
+                'n' => '\n',
+                _ => return None,
+            },
+            c => c,
+        });
+    }
+    Some(unescaped)
+}
+
+/// The algorithm a BSD-style tag such as `SHA256`, `BLAKE2b-256` or
+/// `SHA3-384` names.
+fn parse_tag(tag: &str) -> Option<(Algorithm, usize)> {
+    let (name, bits) = match tag.split_once('-') {
+        Some((name, bits)) => (name, Some(bits.parse::<usize>().ok()?)),
+        None => (tag, None),
+    };
+    let algorithm = Algorithm::NAMES
+        .iter()
+        .map(|&(_, algorithm)| algorithm)
+        .filter(|algorithm| !algorithm.is_legacy())
+        .find(|algorithm| algorithm.tag(algorithm.bits()).split('-').next() == Some(name))?;
+    match bits {
+        Some(bits) if bits > 0 && algorithm.has_length() => check_length(algorithm, bits)
+            .ok()
+            .map(|bits| (algorithm, bits)),
+        Some(_) => None,
+        // SHA-3 always names its length.
+        None if algorithm.tag(algorithm.bits()) == name => Some((algorithm, algorithm.bits())),
+        None => None,
+    }
+}
+
+/// Parse `line`, either `TAG (NAME) = DIGEST` or `DIGEST  NAME` with `*`
+/// instead of the second space for binary mode, and either optionally
+/// preceded by a backslash to say that the name is escaped.
+pub fn parse_check_line(line: &str) -> Option<CheckLine> {
+    let line = line.trim_start_matches([' ', '\t']);
+    let (escaped, line) = match line.strip_prefix('\\') {
+        Some(line) => (true, line),
+        None => (false, line),
+    };
+    let name = |name: &str| match escaped {
+        true => unescape(name),
+        false => Some(name.to_string()),
+    };
+
+    if let Some((tag, rest)) = line.split_once(" (") {
+        if let Some((file, hex)) = rest.rsplit_once(") = ") {
+            if let Some(algorithm) = parse_tag(tag) {
+                let digest = decode_hex(hex)?;
+                if digest.len() * 8 != algorithm.1 {
+                    return None;
+                }
+                return Some(CheckLine {
+                    algorithm: Some(algorithm),
+                    digest,
+                    name: name(file)?,
+                });
+            }
+        }
+    }
+
+    let (hex, rest) = line.split_once(' ')?;
+    let file = rest.strip_prefix([' ', '*'])?;
+    if file.is_empty() {
+        return None;
+    }
+    Some(CheckLine {
+        algorithm: None,
+        digest: decode_hex(hex)?,
+        name: name(file)?,
+    })
+}
@@ -0,0 +1,42 @@
+//! CRC-32C, the Castagnoli CRC of iSCSI, ext4 and Btrfs: polynomial
+//! 0x1EDC6F41, processing bits least significant first, over the data alone.
+
+use super::Digest;
+
+const TABLE: [u32; 256] = {
+    let mut table = [0; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut crc = i as u32;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = match crc & 1 {
+                0 => crc >> 1,
+                _ => crc >> 1 ^ 0x82F6_3B78,
+            };
+            bit += 1;
+        }
+        table[i] = crc;
+        i += 1;
+    }
+    table
+};
+
+/// The CRC is kept inverted, so that it starts at 0 and ends as it is
+/// printed.
+#[derive(Default)]
+pub struct Crc32c(u32);
+
+impl Digest for Crc32c {
+    fn update(&mut self, data: &[u8]) {
+        let mut crc = !self.0;
+        for &b in data {
+            crc = crc >> 8 ^ TABLE[(crc as u8 ^ b) as usize];
+        }
+        self.0 = !crc;
+    }
+
+    fn finalize(self: Box<Self>) -> Vec<u8> {
+        self.0.to_be_bytes().to_vec()
+    }
+}
@@ -0,0 +1,91 @@
+//! The checksums of `sum` and the original `cksum`, which predate the
+//! cryptographic digests and have no library of their own.
+
+use super::Digest;
+
+/// The POSIX `cksum` CRC: CRC-32 with polynomial 0x04C11DB7, processing
+/// bits most significant first, over the data and then its length.
+#[derive(Default)]
+pub struct Crc {
+    crc: u32,
+    size: u64,
+}
+
+const CRC_TABLE: [u32; 256] = {
+    let mut table = [0; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut crc = (i as u32) << 24;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = match crc & 0x8000_0000 {
+                0 => crc << 1,
+                _ => crc << 1 ^ 0x04C1_1DB7,
+            };
+            bit += 1;
+        }
+        table[i] = crc;
+        i += 1;
+    }
+    table
+};
+
+impl Crc {
+    fn feed(&mut self, data: &[u8]) {
+        for &b in data {
+            self.crc = self.crc << 8 ^ CRC_TABLE[((self.crc >> 24) as u8 ^ b) as usize];
+        }
+    }
+}
+
+impl Digest for Crc {
+    fn update(&mut self, data: &[u8]) {
+        self.feed(data);
+        self.size += data.len() as u64;
+    }
+
+    fn finalize(mut self: Box<Self>) -> Vec<u8> {
+        // The length follows, least significant byte first, in as few
+        // bytes as it takes.
+        let mut size = self.size;
+        while size > 0 {
+            self.feed(&[size as u8]);
+            size >>= 8;
+        }
+        (!self.crc).to_be_bytes().to_vec()
+    }
+}
+
+/// The BSD `sum -r` checksum: a 16-bit sum rotated right before each byte.
+#[derive(Default)]
+pub struct Bsd(u16);
+
+impl Digest for Bsd {
+    fn update(&mut self, data: &[u8]) {
+        for &b in data {
+            self.0 = self.0.rotate_right(1).wrapping_add(b as u16);
+        }
+    }
+
+    fn finalize(self: Box<Self>) -> Vec<u8> {
+        self.0.to_be_bytes().to_vec()
+    }
+}
+
+/// The System V `sum -s` checksum: the sum of the bytes folded to 16 bits.
+#[derive(Default)]
+pub struct Sysv(u32);
+
+impl Digest for Sysv {
+    fn update(&mut self, data: &[u8]) {
+        for &b in data {
+            self.0 = self.0.wrapping_add(b as u32);
+        }
+    }
+
+    fn finalize(self: Box<Self>) -> Vec<u8> {
+        let r = (self.0 & 0xffff) + (self.0 >> 16);
+        let sum = (r & 0xffff) + (r >> 16);
+        (sum as u16).to_be_bytes().to_vec()
+    }
+}
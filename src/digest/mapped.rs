@@ -0,0 +1,89 @@
+//! Hashing a large regular file by mapping it into memory, which saves
+//! copying it out a block at a time and the system call for each block.
+
+use std::fs::File;
+use std::io::{self, Seek};
+use std::os::fd::AsRawFd;
+use std::ptr;
+
+use super::{Algorithm, Sum};
+
+/// Smaller files are read, as mapping them costs more than it saves.
+const MIN_SIZE: u64 = 1 << 20;
+
+/// A file mapped read-only, unmapped when dropped.
+struct Mapping {
+    addr: *mut libc::c_void,
+    len: usize,
+}
+
+impl Mapping {
+    fn new(file: &File, len: usize) -> Option<Self> {
+        let addr = unsafe {
+            libc::mmap(
+                ptr::null_mut(),
+                len,
+                libc::PROT_READ,
+                libc::MAP_PRIVATE,
+                file.as_raw_fd(),
+                0,
+            )
+        };
+        if addr == libc::MAP_FAILED {
+            return None;
+        }
+        // Only hints: the data is wanted soon, and once.
+        unsafe {
+            libc::madvise(addr, len, libc::MADV_SEQUENTIAL);
+            libc::madvise(addr, len, libc::MADV_WILLNEED);
+        }
+        Some(Mapping { addr, len })
+    }
+
+    fn bytes(&self) -> &[u8] {
+        unsafe { std::slice::from_raw_parts(self.addr.cast(), self.len) }
+    }
+}
+
+impl Drop for Mapping {
+    fn drop(&mut self) {
+        unsafe {
+            libc::munmap(self.addr, self.len);
+        }
+    }
+}
+
+/// The sum of `file` from its start, if it is a regular file large enough
+/// to be worth mapping, has not been read from, and can be mapped. Like
+/// any reader of a mapped file, this faults if the file is truncated
+/// while it is being hashed.
+pub(super) fn sum(mut file: &File, algorithm: Algorithm, bits: usize) -> Option<io::Result<Sum>> {
+    let metadata = file.metadata().ok()?;
+    if !metadata.is_file() || metadata.len() < MIN_SIZE || file.stream_position().ok()? != 0 {
+        return None;
+    }
+    let mapping = Mapping::new(file, usize::try_from(metadata.len()).ok()?)?;
+    let mut digest = algorithm.create(bits);
+    for chunk in mapping.bytes().chunks(1 << 20) {
+        digest.update(chunk);
+    }
+    // Leave the file where a reader that had read it all would.
+    if let Err(e) = file.seek(io::SeekFrom::Start(metadata.len())) {
+        return Some(Err(e));
+    }
+    Some(Ok(Sum {
+        digest: digest.finalize(),
+        size: metadata.len(),
+    }))
+}
+
+/// Tell the kernel `file` will be read through once, so that it reads
+/// ahead further.
+pub(super) fn advise_sequential(file: &File) {
+    #[cfg(any(target_os = "linux", target_os = "android", target_os = "freebsd"))]
+    unsafe {
+        libc::posix_fadvise(file.as_raw_fd(), 0, 0, libc::POSIX_FADV_SEQUENTIAL);
+    }
+    #[cfg(not(any(target_os = "linux", target_os = "android", target_os = "freebsd")))]
+    let _ = file;
+}
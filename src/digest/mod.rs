@@ -0,0 +1,338 @@
+//! Checksums and message digests for cksum, sum, md5sum, the sha*sum
+//! family and b2sum: the algorithms behind one streaming interface, and
+//! the line formats their output and check files use. SM3, SHA-3 and
+//! CRC-32C, which only cksum offers, are each built in by a feature of the
+//! same name.
+
+mod check;
+#[cfg(feature = "crc32c")]
+mod crc32c;
+mod legacy;
+mod mapped;
+pub mod pool;
+#[cfg(feature = "sha3")]
+mod sha3;
+#[cfg(feature = "sm3")]
+mod sm3;
+
+use std::io::{self, Read};
+
+use crate::io::Input;
+
+use blake2::digest::{Update, VariableOutput};
+
+pub use check::{parse_check_line, CheckLine};
+
+/// A checksum being computed over data fed to it piece by piece.
+pub trait Digest: Send {
+    fn update(&mut self, data: &[u8]);
+
+    /// The checksum of everything fed so far, most significant byte first.
+    fn finalize(self: Box<Self>) -> Vec<u8>;
+}
+
+/// The RustCrypto implementations all share one interface.
+struct Crypto<D>(D);
+
+impl<D: sha2::Digest + Send> Digest for Crypto<D> {
+    fn update(&mut self, data: &[u8]) {
+        sha2::Digest::update(&mut self.0, data);
+    }
+
+    fn finalize(self: Box<Self>) -> Vec<u8> {
+        self.0.finalize().to_vec()
+    }
+}
+
+struct Blake2b(blake2::Blake2bVar);
+
+impl Digest for Blake2b {
+    fn update(&mut self, data: &[u8]) {
+        self.0.update(data);
+    }
+
+    fn finalize(self: Box<Self>) -> Vec<u8> {
+        self.0.finalize_boxed().to_vec()
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Algorithm {
+    Sysv,
+    Bsd,
+    Crc,
+    #[cfg(feature = "crc32c")]
+    Crc32c,
+    Md5,
+    Sha1,
+    Sha224,
+    Sha256,
+    Sha384,
+    Sha512,
+    #[cfg(feature = "sha3")]
+    Sha3,
+    Blake2b,
+    #[cfg(feature = "sm3")]
+    Sm3,
+}
+
+impl Algorithm {
+    /// Every algorithm by the name `cksum -a` knows it by.
+    pub const NAMES: &'static [(&'static str, Algorithm)] = &[
+        ("sysv", Algorithm::Sysv),
+        ("bsd", Algorithm::Bsd),
+        ("crc", Algorithm::Crc),
+        #[cfg(feature = "crc32c")]
+        ("crc32c", Algorithm::Crc32c),
+        ("md5", Algorithm::Md5),
+        ("sha1", Algorithm::Sha1),
+        ("sha224", Algorithm::Sha224),
+        ("sha256", Algorithm::Sha256),
+        ("sha384", Algorithm::Sha384),
+        ("sha512", Algorithm::Sha512),
+        #[cfg(feature = "sha3")]
+        ("sha3", Algorithm::Sha3),
+        ("blake2b", Algorithm::Blake2b),
+        #[cfg(feature = "sm3")]
+        ("sm3", Algorithm::Sm3),
+    ];
+
+    pub fn parse(name: &str) -> Option<Self> {
+        Self::NAMES
+            .iter()
+            .find(|(n, _)| *n == name)
+            .map(|(_, algorithm)| *algorithm)
+    }
+
+    pub fn name(self) -> &'static str {
+        Self::NAMES
+            .iter()
+            .find(|(_, a)| *a == self)
+            .map_or("", |(name, _)| name)
+    }
+
+    /// Whether the algorithm is one of the 16 and 32 bit sums, which are
+    /// printed in decimal with the size of the input rather than in hex.
+    pub fn is_legacy(self) -> bool {
+        match self {
+            Algorithm::Sysv | Algorithm::Bsd | Algorithm::Crc => true,
+            #[cfg(feature = "crc32c")]
+            Algorithm::Crc32c => true,
+            _ => false,
+        }
+    }
+
+    /// Whether `--length` may choose how long the digest is.
+    pub fn has_length(self) -> bool {
+        match self {
+            Algorithm::Blake2b => true,
+            #[cfg(feature = "sha3")]
+            Algorithm::Sha3 => true,
+            _ => false,
+        }
+    }
+
+    /// The length of the digest in bits, or the most it may be for BLAKE2b
+    /// and SHA-3.
+    pub fn bits(self) -> usize {
+        match self {
+            Algorithm::Sysv | Algorithm::Bsd => 16,
+            Algorithm::Crc => 32,
+            #[cfg(feature = "crc32c")]
+            Algorithm::Crc32c => 32,
+            Algorithm::Md5 => 128,
+            Algorithm::Sha1 => 160,
+            Algorithm::Sha224 => 224,
+            Algorithm::Sha256 => 256,
+            #[cfg(feature = "sm3")]
+            Algorithm::Sm3 => 256,
+            Algorithm::Sha384 => 384,
+            Algorithm::Sha512 | Algorithm::Blake2b => 512,
+            #[cfg(feature = "sha3")]
+            Algorithm::Sha3 => 512,
+        }
+    }
+
+    /// The name BSD-style `--tag` lines start with. BLAKE2b includes the
+    /// length when it is not the full 512 bits, and SHA-3 always does.
+    pub fn tag(self, bits: usize) -> String {
+        match self {
+            Algorithm::Blake2b if bits != self.bits() => format!("BLAKE2b-{}", bits),
+            Algorithm::Blake2b => "BLAKE2b".to_string(),
+            Algorithm::Crc => "CRC".to_string(),
+            #[cfg(feature = "sha3")]
+            Algorithm::Sha3 => format!("SHA3-{}", bits),
+            _ => self.name().to_ascii_uppercase(),
+        }
+    }
+
+    /// Start a digest, `bits` long where the algorithm allows a choice.
+    pub fn create(self, bits: usize) -> Box<dyn Digest> {
+        match self {
+            Algorithm::Sysv => Box::new(legacy::Sysv::default()),
+            Algorithm::Bsd => Box::new(legacy::Bsd::default()),
+            Algorithm::Crc => Box::new(legacy::Crc::default()),
+            #[cfg(feature = "crc32c")]
+            Algorithm::Crc32c => Box::new(crc32c::Crc32c::default()),
+            Algorithm::Md5 => Box::new(Crypto(md5::Md5::default())),
+            Algorithm::Sha1 => Box::new(Crypto(sha1::Sha1::default())),
+            Algorithm::Sha224 => Box::new(Crypto(sha2::Sha224::default())),
+            Algorithm::Sha256 => Box::new(Crypto(sha2::Sha256::default())),
+            Algorithm::Sha384 => Box::new(Crypto(sha2::Sha384::default())),
+            Algorithm::Sha512 => Box::new(Crypto(sha2::Sha512::default())),
+            Algorithm::Blake2b => Box::new(Blake2b(
+                blake2::Blake2bVar::new(bits / 8).expect("BLAKE2b length out of range"),
+            )),
+            #[cfg(feature = "sha3")]
+            Algorithm::Sha3 => Box::new(sha3::Sha3::new(bits)),
+            #[cfg(feature = "sm3")]
+            Algorithm::Sm3 => Box::new(sm3::Sm3::default()),
+        }
+    }
+}
+
+/// Why a `--length` was refused.
+#[derive(Debug, PartialEq, Eq)]
+pub enum LengthError {
+    Invalid,
+    NotMultipleOf8,
+    /// Longer than the algorithm's digest.
+    TooLong,
+    /// Not one of the only lengths the algorithm has.
+    Unsupported(&'static [usize]),
+}
+
+/// Parse a `--length` in bits: a multiple of 8 no longer than the
+/// algorithm allows, with 0 meaning the full length, or for SHA-3 one of
+/// its four lengths.
+pub fn parse_length(algorithm: Algorithm, length: &str) -> Result<usize, LengthError> {
+    let bits = length.parse::<usize>().map_err(|_| LengthError::Invalid)?;
+    check_length(algorithm, bits)
+}
+
+/// Whether `bits` is a length `algorithm` may be, as for [`parse_length`].
+pub fn check_length(algorithm: Algorithm, bits: usize) -> Result<usize, LengthError> {
+    #[cfg(feature = "sha3")]
+    if algorithm == Algorithm::Sha3 {
+        return match sha3::LENGTHS.contains(&bits) {
+            true => Ok(bits),
+            false => Err(LengthError::Unsupported(&sha3::LENGTHS)),
+        };
+    }
+    match bits {
+        0 => Ok(algorithm.bits()),
+        _ if bits > algorithm.bits() => Err(LengthError::TooLong),
+        _ if !bits.is_multiple_of(8) => Err(LengthError::NotMultipleOf8),
+        _ => Ok(bits),
+    }
+}
+
+/// A digest together with how much data it covers.
+pub struct Sum {
+    pub digest: Vec<u8>,
+    pub size: u64,
+}
+
+impl Sum {
+    pub fn hex(&self) -> String {
+        self.digest.iter().map(|b| format!("{:02x}", b)).collect()
+    }
+
+    /// The legacy sums' checksum and block count, as `sum` and `cksum`
+    /// print them for input named `name`.
+    pub fn legacy(&self, algorithm: Algorithm, name: Option<&str>) -> String {
+        let value = self
+            .digest
+            .iter()
+            .fold(0u64, |value, &b| value << 8 | b as u64);
+        let blocks = |size: u64| self.size.div_ceil(size);
+        let line = match algorithm {
+            Algorithm::Bsd => format!("{:05} {:5}", value, blocks(1024)),
+            Algorithm::Sysv => format!("{} {}", value, blocks(512)),
+            _ => format!("{} {}", value, self.size),
+        };
+        match name {
+            Some(name) => format!("{} {}", line, name),
+            None => line,
+        }
+    }
+}
+
+/// Feed everything `reader` holds into a new `algorithm` digest.
+pub fn sum(reader: &mut impl Read, algorithm: Algorithm, bits: usize) -> io::Result<Sum> {
+    let mut digest = algorithm.create(bits);
+    let mut buf = vec![0; 64 * 1024];
+    let mut size = 0;
+    loop {
+        match reader.read(&mut buf) {
+            Ok(0) => break,
+            Ok(n) => {
+                digest.update(&buf[..n]);
+                size += n as u64;
+            }
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(Sum {
+        digest: digest.finalize(),
+        size,
+    })
+}
+
+/// Like [`sum`] for an input, but mapping a large regular file into
+/// memory instead of reading it, and otherwise telling the kernel that the
+/// file will be read straight through.
+pub fn sum_input(input: &mut Input, algorithm: Algorithm, bits: usize) -> io::Result<Sum> {
+    if let Some(file) = input.file() {
+        if let Some(sum) = mapped::sum(file, algorithm, bits) {
+            return sum;
+        }
+        mapped::advise_sequential(file);
+    }
+    sum(input, algorithm, bits)
+}
+
+/// Escape `name` for an output line as the digest utilities do: if it
+/// holds a backslash or newline, those are written as `\\` and `\n` and
+/// the whole line is marked by a leading backslash.
+pub fn escape_name(name: &str) -> (bool, String) {
+    if !name.contains(['\\', '\n']) {
+        return (false, name.to_string());
+    }
+    (true, name.replace('\\', "\\\\").replace('\n', "\\n"))
+}
+
+/// The line a digest utility prints for `name`, either BSD-style with a
+/// tag naming the algorithm or as digest, mode character and name.
+pub fn format_line(
+    algorithm: Algorithm,
+    bits: usize,
+    sum: &Sum,
+    name: &str,
+    tag: bool,
+    binary: bool,
+    zero: bool,
+) -> String {
+    let (escaped, name) = match zero {
+        true => (false, name.to_string()),
+        false => escape_name(name),
+    };
+    let prefix = if escaped { "\\" } else { "" };
+    match tag {
+        true => format!(
+            "{}{} ({}) = {}",
+            prefix,
+            algorithm.tag(bits),
+            name,
+            sum.hex()
+        ),
+        false => format!(
+            "{}{} {}{}",
+            prefix,
+            sum.hex(),
+            if binary { '*' } else { ' ' },
+            name
+        ),
+    }
+}
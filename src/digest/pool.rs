@@ -0,0 +1,55 @@
+//! Hashing several files at once while reporting on them in the order
+//! they were named.
+
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc;
+use std::thread;
+
+/// Apply `work` to each of `items` on up to `threads` threads, handing
+/// each result to `report` in the order of `items` as soon as it and all
+/// before it are done. With one thread everything happens on this one.
+pub fn in_order<T, R>(
+    items: &[T],
+    threads: usize,
+    work: impl Fn(&T) -> R + Sync,
+    mut report: impl FnMut(&T, R),
+) where
+    T: Sync,
+    R: Send,
+{
+    let threads = threads.min(items.len());
+    if threads <= 1 {
+        for item in items {
+            report(item, work(item));
+        }
+        return;
+    }
+    let next = AtomicUsize::new(0);
+    let (sender, receiver) = mpsc::channel();
+    thread::scope(|scope| {
+        for _ in 0..threads {
+            let sender = sender.clone();
+            let (next, work) = (&next, &work);
+            scope.spawn(move || loop {
+                let i = next.fetch_add(1, Ordering::Relaxed);
+                let Some(item) = items.get(i) else {
+                    break;
+                };
+                if sender.send((i, work(item))).is_err() {
+                    break;
+                }
+            });
+        }
+        drop(sender);
+        let mut done = BTreeMap::new();
+        let mut wanted = 0;
+        for (i, result) in receiver {
+            done.insert(i, result);
+            while let Some(result) = done.remove(&wanted) {
+                report(&items[wanted], result);
+                wanted += 1;
+            }
+        }
+    });
+}
@@ -0,0 +1,136 @@
+//! SHA-3 (FIPS 202): the Keccak-f[1600] sponge, absorbing at a rate that
+//! leaves twice the digest length as capacity.
+
+use super::Digest;
+
+/// The digest lengths in bits SHA-3 is defined for.
+pub const LENGTHS: [usize; 4] = [224, 256, 384, 512];
+
+const ROUND_CONSTANTS: [u64; 24] = [
+    0x0000_0000_0000_0001,
+    0x0000_0000_0000_8082,
+    0x8000_0000_0000_808a,
+    0x8000_0000_8000_8000,
+    0x0000_0000_0000_808b,
+    0x0000_0000_8000_0001,
+    0x8000_0000_8000_8081,
+    0x8000_0000_0000_8009,
+    0x0000_0000_0000_008a,
+    0x0000_0000_0000_0088,
+    0x0000_0000_8000_8009,
+    0x0000_0000_8000_000a,
+    0x0000_0000_8000_808b,
+    0x8000_0000_0000_008b,
+    0x8000_0000_0000_8089,
+    0x8000_0000_0000_8003,
+    0x8000_0000_0000_8002,
+    0x8000_0000_0000_0080,
+    0x0000_0000_0000_800a,
+    0x8000_0000_8000_000a,
+    0x8000_0000_8000_8081,
+    0x8000_0000_0000_8080,
+    0x0000_0000_8000_0001,
+    0x8000_0000_8000_8008,
+];
+
+/// How far each lane, at x + 5y, is rotated.
+const ROTATIONS: [u32; 25] = [
+    0, 1, 62, 28, 27, 36, 44, 6, 55, 20, 3, 10, 43, 25, 39, 41, 45, 15, 21, 8, 18, 2, 61, 56, 14,
+];
+
+fn keccak(state: &mut [u64; 25]) {
+    for constant in ROUND_CONSTANTS {
+        let mut columns = [0u64; 5];
+        for (x, column) in columns.iter_mut().enumerate() {
+            *column = (0..5).fold(0, |c, y| c ^ state[x + 5 * y]);
+        }
+        for x in 0..5 {
+            let d = columns[(x + 4) % 5] ^ columns[(x + 1) % 5].rotate_left(1);
+            for y in 0..5 {
+                state[x + 5 * y] ^= d;
+            }
+        }
+        let mut moved = [0u64; 25];
+        for x in 0..5 {
+            for y in 0..5 {
+                moved[y + 5 * ((2 * x + 3 * y) % 5)] =
+                    state[x + 5 * y].rotate_left(ROTATIONS[x + 5 * y]);
+            }
+        }
+        for x in 0..5 {
+            for y in 0..5 {
+                state[x + 5 * y] =
+                    moved[x + 5 * y] ^ (!moved[(x + 1) % 5 + 5 * y] & moved[(x + 2) % 5 + 5 * y]);
+            }
+        }
+        state[0] ^= constant;
+    }
+}
+
+pub struct Sha3 {
+    state: [u64; 25],
+    /// The bytes absorbed per permutation.
+    rate: usize,
+    block: [u8; 200],
+    filled: usize,
+    bytes: usize,
+}
+
+impl Sha3 {
+    pub fn new(bits: usize) -> Self {
+        Sha3 {
+            state: [0; 25],
+            rate: 200 - 2 * bits / 8,
+            block: [0; 200],
+            filled: 0,
+            bytes: bits / 8,
+        }
+    }
+
+    fn absorb(&mut self, block: &[u8]) {
+        for (lane, bytes) in self.state.iter_mut().zip(block.chunks_exact(8)) {
+            *lane ^= u64::from_le_bytes(bytes.try_into().unwrap());
+        }
+        keccak(&mut self.state);
+    }
+}
+
+impl Digest for Sha3 {
+    fn update(&mut self, mut data: &[u8]) {
+        let rate = self.rate;
+        if self.filled > 0 {
+            let n = data.len().min(rate - self.filled);
+            self.block[self.filled..self.filled + n].copy_from_slice(&data[..n]);
+            self.filled += n;
+            data = &data[n..];
+            if self.filled < rate {
+                return;
+            }
+            let block = self.block;
+            self.absorb(&block[..rate]);
+            self.filled = 0;
+        }
+        let mut blocks = data.chunks_exact(rate);
+        for block in &mut blocks {
+            self.absorb(block);
+        }
+        let rest = blocks.remainder();
+        self.block[..rest.len()].copy_from_slice(rest);
+        self.filled = rest.len();
+    }
+
+    fn finalize(mut self: Box<Self>) -> Vec<u8> {
+        // The SHA-3 domain bits and the first padding bit, then the last.
+        let rate = self.rate;
+        self.block[self.filled..rate].fill(0);
+        self.block[self.filled] = 0x06;
+        self.block[rate - 1] |= 0x80;
+        let block = self.block;
+        self.absorb(&block[..rate]);
+        self.state
+            .iter()
+            .flat_map(|lane| lane.to_le_bytes())
+            .take(self.bytes)
+            .collect()
+    }
+}
@@ -0,0 +1,120 @@
+//! SM3, the Chinese national standard hash (GB/T 32905-2016): a 256-bit
+//! digest in the shape of SHA-256, with a different message expansion
+//! and compression function.
+
+use super::Digest;
+
+const IV: [u32; 8] = [
+    0x7380_166f,
+    0x4914_b2b9,
+    0x1724_42d7,
+    0xda8a_0600,
+    0xa96f_30bc,
+    0x1631_38aa,
+    0xe38d_ee4d,
+    0xb0fb_0e4e,
+];
+
+pub struct Sm3 {
+    state: [u32; 8],
+    block: [u8; 64],
+    filled: usize,
+    size: u64,
+}
+
+impl Default for Sm3 {
+    fn default() -> Self {
+        Sm3 {
+            state: IV,
+            block: [0; 64],
+            filled: 0,
+            size: 0,
+        }
+    }
+}
+
+fn p0(x: u32) -> u32 {
+    x ^ x.rotate_left(9) ^ x.rotate_left(17)
+}
+
+fn p1(x: u32) -> u32 {
+    x ^ x.rotate_left(15) ^ x.rotate_left(23)
+}
+
+fn compress(state: &mut [u32; 8], block: &[u8]) {
+    let mut w = [0u32; 68];
+    for (word, bytes) in w.iter_mut().zip(block.chunks_exact(4)) {
+        *word = u32::from_be_bytes(bytes.try_into().unwrap());
+    }
+    for j in 16..68 {
+        w[j] = p1(w[j - 16] ^ w[j - 9] ^ w[j - 3].rotate_left(15))
+            ^ w[j - 13].rotate_left(7)
+            ^ w[j - 6];
+    }
+
+    let [mut a, mut b, mut c, mut d, mut e, mut f, mut g, mut h] = *state;
+    for j in 0..64 {
+        let (t, ff, gg) = match j {
+            0..16 => (0x79cc_4519u32, a ^ b ^ c, e ^ f ^ g),
+            _ => (0x7a87_9d8a, (a & b) | (a & c) | (b & c), (e & f) | (!e & g)),
+        };
+        let ss1 = a
+            .rotate_left(12)
+            .wrapping_add(e)
+            .wrapping_add(t.rotate_left(j as u32 % 32))
+            .rotate_left(7);
+        let ss2 = ss1 ^ a.rotate_left(12);
+        let tt1 = ff
+            .wrapping_add(d)
+            .wrapping_add(ss2)
+            .wrapping_add(w[j] ^ w[j + 4]);
+        let tt2 = gg.wrapping_add(h).wrapping_add(ss1).wrapping_add(w[j]);
+        d = c;
+        c = b.rotate_left(9);
+        b = a;
+        a = tt1;
+        h = g;
+        g = f.rotate_left(19);
+        f = e;
+        e = p0(tt2);
+    }
+    for (word, new) in state.iter_mut().zip([a, b, c, d, e, f, g, h]) {
+        *word ^= new;
+    }
+}
+
+impl Digest for Sm3 {
+    fn update(&mut self, mut data: &[u8]) {
+        self.size += data.len() as u64;
+        if self.filled > 0 {
+            let n = data.len().min(64 - self.filled);
+            self.block[self.filled..self.filled + n].copy_from_slice(&data[..n]);
+            self.filled += n;
+            data = &data[n..];
+            if self.filled < 64 {
+                return;
+            }
+            compress(&mut self.state, &self.block);
+            self.filled = 0;
+        }
+        let mut blocks = data.chunks_exact(64);
+        for block in &mut blocks {
+            compress(&mut self.state, block);
+        }
+        let rest = blocks.remainder();
+        self.block[..rest.len()].copy_from_slice(rest);
+        self.filled = rest.len();
+    }
+
+    fn finalize(mut self: Box<Self>) -> Vec<u8> {
+        // A one bit, zeros up to eight bytes short of a block, and the
+        // length in bits.
+        let bits = self.size.wrapping_mul(8);
+        let zeros = (55 - self.filled as isize).rem_euclid(64) as usize;
+        let mut padding = vec![0x80];
+        padding.resize(1 + zeros, 0);
+        padding.extend_from_slice(&bits.to_be_bytes());
+        self.update(&padding);
+        self.state.iter().flat_map(|w| w.to_be_bytes()).collect()
+    }
+}
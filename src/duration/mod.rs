@@ -0,0 +1,27 @@
+use std::time::Duration;
+
+/// Parse a duration such as `10`, `1.5m` or `2d`: a non-negative decimal
+/// number of seconds with an optional s, m, h or d suffix. `inf` and
+/// `infinity` yield the largest representable duration.
+pub fn parse_duration(s: &str) -> Option<Duration> {
+    let (number, multiplier) = match s.char_indices().last() {
+        Some((i, 's')) => (&s[..i], 1.0),
+        Some((i, 'm')) => (&s[..i], 60.0),
+        Some((i, 'h')) => (&s[..i], 60.0 * 60.0),
+        Some((i, 'd')) => (&s[..i], 24.0 * 60.0 * 60.0),
+        _ => (s, 1.0),
+    };
+    if number.is_empty() || number.starts_with(['+', '-']) && number.len() == 1 {
+        return None;
+    }
+    let value = match number.to_ascii_lowercase().as_str() {
+        "inf" | "infinity" => f64::INFINITY,
+        n if n.chars().all(|c| c.is_ascii_digit() || c == '.') => n.parse::<f64>().ok()?,
+        _ => return None,
+    };
+    let seconds = value * multiplier;
+    if seconds.is_nan() || seconds < 0.0 {
+        return None;
+    }
+    Some(Duration::try_from_secs_f64(seconds).unwrap_or(Duration::MAX))
+}
@@ -0,0 +1,71 @@
+//! The environment variables the utilities honor, read in one place. An
+//! empty variable counts as unset, as it does for GNU, but for `TZ`; what
+//! a value means is left to the module that parses it.
+
+use std::env;
+use std::ffi::OsString;
+use std::path::PathBuf;
+
+use crate::posix::posixly_correct;
+use crate::size::{parse_block_size, SizeFormat};
+
+/// `name`'s value, if it is set, valid UTF-8 and not empty.
+fn var(name: &str) -> Option<String> {
+    env::var(name).ok().filter(|value| !value.is_empty())
+}
+
+/// A positive `COLUMNS`, the width to lay output out in when there is no
+/// terminal to ask.
+pub fn columns() -> Option<usize> {
+    var("COLUMNS")
+        .and_then(|c| c.parse::<usize>().ok())
+        .filter(|&cols| cols > 0)
+}
+
+/// `TIME_STYLE`, which ls and du take as their `--time-style` default.
+pub fn time_style() -> Option<String> {
+    var("TIME_STYLE")
+}
+
+/// `QUOTING_STYLE`, ls's `--quoting-style` default.
+pub fn quoting_style() -> Option<String> {
+    var("QUOTING_STYLE")
+}
+
+/// `VERSION_CONTROL`, the backup type when `--backup` names none.
+pub fn version_control() -> Option<String> {
+    var("VERSION_CONTROL")
+}
+
+/// `SIMPLE_BACKUP_SUFFIX`, the suffix when `-S` gives none.
+pub fn simple_backup_suffix() -> Option<String> {
+    var("SIMPLE_BACKUP_SUFFIX")
+}
+
+/// The block size a utility reports in when no option sets it: its own
+/// variable, such as `DF_BLOCK_SIZE` for `df`, then `BLOCK_SIZE`, then
+/// `BLOCKSIZE`, then 512 bytes for POSIXLY_CORRECT and 1024 otherwise. An
+/// invalid value is passed over, as GNU does.
+pub fn block_size(utility: &str) -> SizeFormat {
+    let own = format!("{}_BLOCK_SIZE", utility.to_ascii_uppercase());
+    let spec = [own.as_str(), "BLOCK_SIZE", "BLOCKSIZE"]
+        .into_iter()
+        .find_map(var);
+    spec.and_then(|spec| parse_block_size(&spec).ok())
+        .unwrap_or(match posixly_correct() {
+            true => SizeFormat::Blocks(512),
+            false => SizeFormat::Blocks(1024),
+        })
+}
+
+/// The directory for temporary files: `TMPDIR`, or `/tmp`.
+pub fn tmpdir() -> PathBuf {
+    env::var_os("TMPDIR")
+        .filter(|dir| !dir.is_empty())
+        .map_or_else(|| PathBuf::from("/tmp"), PathBuf::from)
+}
+
+/// `TZ`, the time zone rule, which need not be UTF-8 to name a file.
+pub fn tz() -> Option<OsString> {
+    env::var_os("TZ")
+}
@@ -0,0 +1,120 @@
+use std::cell::Cell;
+use std::env;
+use std::ffi::CStr;
+use std::io;
+use std::path::Path;
+use std::process;
+use std::sync::OnceLock;
+
+static PROGRAM_NAME: OnceLock<String> = OnceLock::new();
+
+/// Override the name used to prefix diagnostics, which otherwise comes
+/// from the basename of `argv[0]`.
+pub fn set_program_name(name: &str) {
+    let _ = PROGRAM_NAME.set(name.to_string());
+}
+
+pub fn program_name() -> &'static str {
+    PROGRAM_NAME.get_or_init(|| {
+        env::args_os()
+            .next()
+            .as_deref()
+            .and_then(|arg| Path::new(arg).file_name())
+            .map_or("coreutils".to_string(), |n| n.to_string_lossy().to_string())
+    })
+}
+
+/// The system's description of `errno`, as `strerror(3)` gives it.
+pub fn errno_text(errno: i32) -> String {
+    let text = unsafe { libc::strerror(errno) };
+    match text.is_null() {
+        true => format!("Unknown error {}", errno),
+        false => unsafe { CStr::from_ptr(text) }
+            .to_string_lossy()
+            .to_string(),
+    }
+}
+
+/// Describe an I/O error the way the C tools do, without the
+/// "(os error N)" suffix that its `Display` implementation adds.
+pub fn strerror(error: &io::Error) -> String {
+    match error.raw_os_error() {
+        Some(errno) => errno_text(errno),
+        None => error.to_string(),
+    }
+}
+
+/// The last OS error, described with [`strerror`].
+pub fn last_error() -> String {
+    strerror(&io::Error::last_os_error())
+}
+
+/// Collects the exit status of a run that keeps going after errors.
+///
+/// The status only ever increases, so a serious failure (2) recorded
+/// earlier is not masked by a later minor one (1).
+pub struct ExitStatus(Cell<i32>);
+
+impl ExitStatus {
+    pub fn new() -> Self {
+        ExitStatus(Cell::new(0))
+    }
+
+    /// Record a minor failure.
+    pub fn fail(&self) {
+        self.set(1);
+    }
+
+    pub fn set(&self, code: i32) {
+        self.0.set(self.0.get().max(code));
+    }
+
+    pub fn code(&self) -> i32 {
+        self.0.get()
+    }
+
+    pub fn is_success(&self) -> bool {
+        self.0.get() == 0
+    }
+
+    pub fn exit(&self) -> ! {
+        process::exit(self.0.get())
+    }
+}
+
+impl Default for ExitStatus {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Print a diagnostic to stderr, prefixed with the program name.
+#[macro_export]
+macro_rules! show_error {
+    ($($arg:tt)*) => {
+        eprintln!("{}: {}", $crate::error::program_name(), format_args!($($arg)*))
+    };
+}
+
+/// Print a diagnostic and exit with `status`.
+#[macro_export]
+macro_rules! die {
+    ($status:expr, $($arg:tt)*) => {{
+        $crate::show_error!($($arg)*);
+        ::std::process::exit($status)
+    }};
+}
+
+/// Print a diagnostic followed by a pointer to --help, then exit with
+/// `status`.
+#[macro_export]
+macro_rules! usage_error {
+    ($status:expr, $($arg:tt)*) => {{
+        $crate::show_error!($($arg)*);
+        eprintln!(
+            "Try '{} --help' for more information.",
+            $crate::error::program_name()
+        );
+        ::std::process::exit($status)
+    }};
+}
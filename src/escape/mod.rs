@@ -0,0 +1,206 @@
+//! Backslash escapes as echo -e, printf and tr read them. The three agree
+//! on the single-letter escapes but not on octal, hex and Unicode ones, so
+//! each is a [`Dialect`] of one interpreter.
+
+use std::fmt::{self, Display};
+
+use crate::locale::langinfo;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Dialect {
+    /// `echo -e`: `\0NNN` or `\NNN` octal, `\xHH`, `\c` to stop.
+    Echo,
+    /// printf's FORMAT: `\NNN` octal, `\xHH`, `\uHHHH`, `\UHHHHHHHH`.
+    Printf,
+    /// printf's `%b` arguments, where octal may also be `\0NNN` as in echo.
+    PrintfArgument,
+    /// tr's SETs: `\NNN` octal and no hex, and a backslash before any
+    /// other character stands for that character.
+    Tr,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EscapeError {
+    /// `\x`, `\u` or `\U` without enough hex digits.
+    MissingHex,
+    /// `\u` or `\U` naming a character that may not be written that way.
+    InvalidUniversal(char, u32),
+}
+
+impl Display for EscapeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            EscapeError::MissingHex => write!(f, "missing hexadecimal number in escape"),
+            EscapeError::InvalidUniversal(c, value) => write!(
+                f,
+                "invalid universal character name \\{}{:0width$x}",
+                c,
+                value,
+                width = if c == 'u' { 4 } else { 8 }
+            ),
+        }
+    }
+}
+
+/// What an escape sequence came to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Sequence {
+    /// Output was appended, from this many bytes after the backslash.
+    Consumed(usize),
+    /// `\c`: print nothing more.
+    Stop,
+}
+
+fn is_octal(b: u8) -> bool {
+    (b'0'..=b'7').contains(&b)
+}
+
+/// The value of up to `max` leading digits of `input` in `radix`, and how
+/// many there were.
+fn digits(input: &[u8], radix: u32, max: usize) -> (u32, usize) {
+    input
+        .iter()
+        .take(max)
+        .map_while(|&b| (b as char).to_digit(radix))
+        .fold((0, 0), |(value, n), digit| (value * radix + digit, n + 1))
+}
+
+/// Whether `\u` and `\U` may name `value`: not a surrogate, and not below
+/// U+00A0 but for `$`, `@` and `` ` ``, as C99 has it.
+fn is_universal(value: u32) -> bool {
+    let basic = value < 0xA0 && !matches!(value, 0x24 | 0x40 | 0x60);
+    !basic && !(0xD800..=0xDFFF).contains(&value) && value <= 0x10FFFF
+}
+
+/// Append `value` in the locale's encoding: UTF-8 where that is the
+/// charset, the escape itself elsewhere, as GNU prints what it cannot
+/// encode.
+fn push_universal(value: u32, out: &mut Vec<u8>) {
+    let utf8 = langinfo(libc::CODESET, "").eq_ignore_ascii_case("UTF-8");
+    match char::from_u32(value).filter(|_| utf8 || value < 0x80) {
+        Some(c) => out.extend_from_slice(c.encode_utf8(&mut [0; 4]).as_bytes()),
+        None if value <= 0xFFFF => out.extend(format!("\\u{:04X}", value).bytes()),
+        None => out.extend(format!("\\U{:08X}", value).bytes()),
+    }
+}
+
+/// Interpret the escape sequence `input` starts with, the backslash
+/// already taken, appending what it stands for to `out`.
+pub fn escape_sequence(
+    input: &[u8],
+    dialect: Dialect,
+    out: &mut Vec<u8>,
+) -> Result<Sequence, EscapeError> {
+    let Some(&c) = input.first() else {
+        out.push(b'\\');
+        return Ok(Sequence::Consumed(0));
+    };
+    let simple = match c {
+        b'\\' => Some(b'\\'),
+        b'a' => Some(0x07),
+        b'b' => Some(0x08),
+        b'f' => Some(0x0c),
+        b'n' => Some(b'\n'),
+        b'r' => Some(b'\r'),
+        b't' => Some(b'\t'),
+        b'v' => Some(0x0b),
+        b'e' if dialect != Dialect::Tr => Some(0x1b),
+        b'"' if matches!(dialect, Dialect::Printf | Dialect::PrintfArgument) => Some(b'"'),
+        _ => None,
+    };
+    if let Some(byte) = simple {
+        out.push(byte);
+        return Ok(Sequence::Consumed(1));
+    }
+
+    match (c, dialect) {
+        (b'c', Dialect::Echo | Dialect::Printf | Dialect::PrintfArgument) => Ok(Sequence::Stop),
+        (b'x', Dialect::Echo | Dialect::Printf | Dialect::PrintfArgument) => {
+            match digits(&input[1..], 16, 2) {
+                (_, 0) if dialect == Dialect::Echo => {
+                    out.extend_from_slice(b"\\x");
+                    Ok(Sequence::Consumed(1))
+                }
+                (_, 0) => Err(EscapeError::MissingHex),
+                (value, n) => {
+                    out.push(value as u8);
+                    Ok(Sequence::Consumed(1 + n))
+                }
+            }
+        }
+        (b'u' | b'U', Dialect::Printf | Dialect::PrintfArgument) => {
+            let width = if c == b'u' { 4 } else { 8 };
+            let (value, n) = digits(&input[1..], 16, width);
+            if n < width {
+                return Err(EscapeError::MissingHex);
+            }
+            if !is_universal(value) {
+                return Err(EscapeError::InvalidUniversal(c as char, value));
+            }
+            push_universal(value, out);
+            Ok(Sequence::Consumed(1 + n))
+        }
+        // \0NNN, where the 0 does not count towards the three digits.
+        (b'0', Dialect::Echo | Dialect::PrintfArgument) => {
+            let (value, n) = digits(&input[1..], 8, 3);
+            out.push(value as u8);
+            Ok(Sequence::Consumed(1 + n))
+        }
+        (c, Dialect::Echo | Dialect::PrintfArgument) if is_octal(c) => {
+            let (value, n) = digits(input, 8, 3);
+            out.push(value as u8);
+            Ok(Sequence::Consumed(n))
+        }
+        (c, Dialect::Printf | Dialect::Tr) if is_octal(c) => {
+            let (mut value, mut n) = digits(input, 8, 3);
+            // tr reads \400 and above as two digits and a character.
+            if dialect == Dialect::Tr && value > 0o377 {
+                (value, n) = digits(input, 8, 2);
+            }
+            out.push(value as u8);
+            Ok(Sequence::Consumed(n))
+        }
+        (_, Dialect::Tr) => {
+            let len = utf8_len(input);
+            out.extend_from_slice(&input[..len]);
+            Ok(Sequence::Consumed(len))
+        }
+        _ => {
+            out.push(b'\\');
+            Ok(Sequence::Consumed(0))
+        }
+    }
+}
+
+/// The length of the character `input` starts with, or 1 if it is not
+/// valid UTF-8, so that an escaped character is taken whole.
+fn utf8_len(input: &[u8]) -> usize {
+    let len = match input[0] {
+        0xc0..=0xdf => 2,
+        0xe0..=0xef => 3,
+        0xf0..=0xf7 => 4,
+        _ => 1,
+    };
+    match input.get(..len).map(std::str::from_utf8) {
+        Some(Ok(_)) => len,
+        _ => 1,
+    }
+}
+
+/// `input` with its escapes interpreted, and whether it ended at a `\c`.
+pub fn unescape(input: &[u8], dialect: Dialect) -> Result<(Vec<u8>, bool), EscapeError> {
+    let mut out = Vec::with_capacity(input.len());
+    let mut i = 0;
+    while i < input.len() {
+        if input[i] != b'\\' {
+            out.push(input[i]);
+            i += 1;
+            continue;
+        }
+        match escape_sequence(&input[i + 1..], dialect, &mut out)? {
+            Sequence::Consumed(n) => i += 1 + n,
+            Sequence::Stop => return Ok((out, true)),
+        }
+    }
+    Ok((out, false))
+}
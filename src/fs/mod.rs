@@ -0,0 +1,287 @@
+use std::collections::VecDeque;
+use std::fmt::Display;
+use std::fs::{self, Metadata};
+use std::io;
+use std::os::unix::fs::MetadataExt;
+use std::path::{Path, PathBuf};
+
+use crate::error::strerror;
+
+/// Which symbolic links a [`Walker`] follows, as selected by `-P`, `-H` and
+/// `-L`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum SymlinkPolicy {
+    /// Never follow symbolic links (`-P`).
+    #[default]
+    Never,
+    /// Follow symbolic links given as the root only (`-H`).
+    CommandLine,
+    /// Follow every symbolic link (`-L`).
+    Always,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum EntryKind {
+    /// Anything that is not a directory.
+    File,
+    /// A directory, reported before its contents.
+    Dir,
+    /// A directory again, reported after its contents when
+    /// [`Walker::postorder`] is enabled.
+    DirPost,
+}
+
+pub struct Entry {
+    pub path: PathBuf,
+    pub depth: usize,
+    pub kind: EntryKind,
+    pub metadata: Metadata,
+}
+
+impl Entry {
+    pub fn is_root(&self) -> bool {
+        self.depth == 0
+    }
+}
+
+pub enum WalkErrorKind {
+    Io(io::Error),
+    /// The directory is one of its own ancestors, reached through a link.
+    Loop(PathBuf),
+}
+
+pub struct WalkError {
+    pub path: PathBuf,
+    pub kind: WalkErrorKind,
+}
+
+impl Display for WalkError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.kind {
+            WalkErrorKind::Io(e) => write!(f, "{}", strerror(e)),
+            WalkErrorKind::Loop(ancestor) => write!(
+                f,
+                "File system loop detected; '{}' is part of the same file system loop as '{}'",
+                self.path.to_string_lossy(),
+                ancestor.to_string_lossy()
+            ),
+        }
+    }
+}
+
+type Filter = Box<dyn Fn(&Path) -> bool>;
+
+struct Frame {
+    path: PathBuf,
+    depth: usize,
+    id: (u64, u64),
+    metadata: Metadata,
+    children: std::vec::IntoIter<PathBuf>,
+}
+
+/// A depth-first walk of a directory tree.
+///
+/// Errors are yielded in place of the entries they concern so that callers
+/// can report them and carry on with the rest of the tree.
+pub struct Walker {
+    root: Option<PathBuf>,
+    root_dev: Option<u64>,
+    symlinks: SymlinkPolicy,
+    min_depth: usize,
+    max_depth: usize,
+    one_file_system: bool,
+    postorder: bool,
+    sort: bool,
+    filter: Option<Filter>,
+    stack: Vec<Frame>,
+    pending: VecDeque<Result<Entry, WalkError>>,
+}
+
+impl Walker {
+    pub fn new<P: AsRef<Path>>(root: P) -> Self {
+        Walker {
+            root: Some(root.as_ref().to_path_buf()),
+            root_dev: None,
+            symlinks: SymlinkPolicy::Never,
+            min_depth: 0,
+            max_depth: usize::MAX,
+            one_file_system: false,
+            postorder: false,
+            sort: false,
+            filter: None,
+            stack: vec![],
+            pending: VecDeque::new(),
+        }
+    }
+
+    pub fn symlinks(mut self, policy: SymlinkPolicy) -> Self {
+        self.symlinks = policy;
+        self
+    }
+
+    /// Do not report entries shallower than `depth`; the root is depth 0.
+    pub fn min_depth(mut self, depth: usize) -> Self {
+        self.min_depth = depth;
+        self
+    }
+
+    /// Do not descend into directories deeper than `depth`.
+    pub fn max_depth(mut self, depth: usize) -> Self {
+        self.max_depth = depth;
+        self
+    }
+
+    /// Report directories on other file systems but do not descend into
+    /// them.
+    pub fn one_file_system(mut self, yes: bool) -> Self {
+        self.one_file_system = yes;
+        self
+    }
+
+    /// Report every directory a second time once its contents are done.
+    pub fn postorder(mut self, yes: bool) -> Self {
+        self.postorder = yes;
+        self
+    }
+
+    /// Visit the entries of each directory in byte order of their names.
+    pub fn sort(mut self, yes: bool) -> Self {
+        self.sort = yes;
+        self
+    }
+
+    /// Skip, without descending, every entry below the root for which
+    /// `filter` returns false.
+    pub fn filter<F: Fn(&Path) -> bool + 'static>(mut self, filter: F) -> Self {
+        self.filter = Some(Box::new(filter));
+        self
+    }
+
+    fn error(path: PathBuf, error: io::Error) -> Result<Entry, WalkError> {
+        Err(WalkError {
+            path,
+            kind: WalkErrorKind::Io(error),
+        })
+    }
+
+    fn read_children(&mut self, path: &Path) -> Vec<PathBuf> {
+        let entries = match fs::read_dir(path) {
+            Ok(entries) => entries,
+            Err(e) => {
+                self.pending.push_back(Self::error(path.to_path_buf(), e));
+                return vec![];
+            }
+        };
+        let mut children = vec![];
+        for entry in entries {
+            match entry {
+                Ok(entry) => children.push(entry.path()),
+                Err(e) => self.pending.push_back(Self::error(path.to_path_buf(), e)),
+            }
+        }
+        if self.sort {
+            children.sort_by(|a, b| a.file_name().cmp(&b.file_name()));
+        }
+        children
+    }
+
+    fn visit(&mut self, path: PathBuf, depth: usize) -> Option<Result<Entry, WalkError>> {
+        if depth > 0 && self.filter.as_ref().is_some_and(|f| !f(&path)) {
+            return None;
+        }
+        let follow = match self.symlinks {
+            SymlinkPolicy::Always => true,
+            SymlinkPolicy::CommandLine => depth == 0,
+            SymlinkPolicy::Never => false,
+        };
+        let metadata = match follow {
+            // A dangling link is still reported, as the link itself.
+            true => path.metadata().or_else(|_| path.symlink_metadata()),
+            false => path.symlink_metadata(),
+        };
+        let metadata = match metadata {
+            Ok(metadata) => metadata,
+            Err(e) => return Some(Self::error(path, e)),
+        };
+        let root_dev = *self.root_dev.get_or_insert(metadata.dev());
+
+        if !metadata.is_dir() {
+            return (depth >= self.min_depth).then_some(Ok(Entry {
+                path,
+                depth,
+                kind: EntryKind::File,
+                metadata,
+            }));
+        }
+
+        let id = (metadata.dev(), metadata.ino());
+        if let Some(ancestor) = self.stack.iter().find(|frame| frame.id == id) {
+            return Some(Err(WalkError {
+                path,
+                kind: WalkErrorKind::Loop(ancestor.path.clone()),
+            }));
+        }
+        let descend =
+            depth < self.max_depth && !(self.one_file_system && metadata.dev() != root_dev);
+        let children = match descend {
+            true => self.read_children(&path),
+            false => vec![],
+        };
+        self.stack.push(Frame {
+            path: path.clone(),
+            depth,
+            id,
+            metadata: metadata.clone(),
+            children: children.into_iter(),
+        });
+        if depth < self.min_depth {
+            return None;
+        }
+        // Errors reading the directory follow the directory itself.
+        self.pending.push_front(Ok(Entry {
+            path,
+            depth,
+            kind: EntryKind::Dir,
+            metadata,
+        }));
+        self.pending.pop_front()
+    }
+}
+
+impl Iterator for Walker {
+    type Item = Result<Entry, WalkError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(item) = self.pending.pop_front() {
+                return Some(item);
+            }
+            if let Some(root) = self.root.take() {
+                if let Some(item) = self.visit(root, 0) {
+                    return Some(item);
+                }
+                continue;
+            }
+            let frame = self.stack.last_mut()?;
+            match frame.children.next() {
+                Some(child) => {
+                    let depth = frame.depth + 1;
+                    if let Some(item) = self.visit(child, depth) {
+                        return Some(item);
+                    }
+                }
+                None => {
+                    let frame = self.stack.pop()?;
+                    if self.postorder && frame.depth >= self.min_depth {
+                        return Some(Ok(Entry {
+                            path: frame.path,
+                            depth: frame.depth,
+                            kind: EntryKind::DirPost,
+                            metadata: frame.metadata,
+                        }));
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,219 @@
+/// Matching options, named after the flags of `fnmatch(3)`.
+#[derive(Clone, Copy, Default)]
+pub struct MatchOptions {
+    /// A leading `.` must be matched explicitly (`FNM_PERIOD`).
+    pub period: bool,
+    /// Wildcards never match `/` (`FNM_PATHNAME`).
+    pub pathname: bool,
+    /// Backslash is an ordinary character (`FNM_NOESCAPE`).
+    pub noescape: bool,
+    /// Compare letters without regard to case (`FNM_CASEFOLD`).
+    pub casefold: bool,
+}
+
+#[derive(Clone)]
+enum ClassItem {
+    Char(char),
+    Range(char, char),
+    Named(String),
+}
+
+#[derive(Clone)]
+enum Token {
+    Literal(char),
+    Any,
+    Star,
+    Class {
+        negated: bool,
+        items: Vec<ClassItem>,
+    },
+}
+
+/// A compiled shell wildcard pattern.
+#[derive(Clone)]
+pub struct Pattern {
+    tokens: Vec<Token>,
+    options: MatchOptions,
+}
+
+fn named_class(name: &str, c: char) -> bool {
+    match name {
+        "alnum" => c.is_alphanumeric(),
+        "alpha" => c.is_alphabetic(),
+        "blank" => c == ' ' || c == '\t',
+        "cntrl" => c.is_control(),
+        "digit" => c.is_ascii_digit(),
+        "graph" => !c.is_control() && !c.is_whitespace(),
+        "lower" => c.is_lowercase(),
+        "print" => !c.is_control(),
+        "punct" => c.is_ascii_punctuation(),
+        "space" => c.is_whitespace(),
+        "upper" => c.is_uppercase(),
+        "xdigit" => c.is_ascii_hexdigit(),
+        _ => false,
+    }
+}
+
+/// Parse the bracket expression starting after the `[` at `start`,
+/// returning the token and the index after the closing `]`, or `None` if
+/// the bracket is not closed and so stands for itself.
+fn parse_class(chars: &[char], start: usize, options: MatchOptions) -> Option<(Token, usize)> {
+    let mut i = start;
+    let negated = matches!(chars.get(i), Some('!') | Some('^'));
+    if negated {
+        i += 1;
+    }
+    let mut items = vec![];
+    let mut first = true;
+    loop {
+        let c = *chars.get(i)?;
+        if c == ']' && !first {
+            return Some((Token::Class { negated, items }, i + 1));
+        }
+        first = false;
+        if options.pathname && c == '/' {
+            return None;
+        }
+        if c == '[' && chars.get(i + 1) == Some(&':') {
+            let rest = &chars[i + 2..];
+            if let Some(end) = rest.windows(2).position(|w| w == [':', ']']) {
+                items.push(ClassItem::Named(rest[..end].iter().collect()));
+                i += end + 4;
+                continue;
+            }
+        }
+        let mut low = c;
+        if c == '\\' && !options.noescape {
+            i += 1;
+            low = *chars.get(i)?;
+        }
+        i += 1;
+        if chars.get(i) == Some(&'-') && chars.get(i + 1).is_some_and(|&c| c != ']') {
+            let mut high = chars[i + 1];
+            i += 2;
+            if high == '\\' && !options.noescape {
+                high = *chars.get(i)?;
+                i += 1;
+            }
+            items.push(ClassItem::Range(low, high));
+        } else {
+            items.push(ClassItem::Char(low));
+        }
+    }
+}
+
+impl Pattern {
+    pub fn new(pattern: &str, options: MatchOptions) -> Self {
+        let chars = pattern.chars().collect::<Vec<char>>();
+        let mut tokens = vec![];
+        let mut i = 0;
+        while i < chars.len() {
+            let c = chars[i];
+            i += 1;
+            let token = match c {
+                '?' => Token::Any,
+                '*' => {
+                    // Consecutive stars are equivalent to one.
+                    while chars.get(i) == Some(&'*') {
+                        i += 1;
+                    }
+                    Token::Star
+                }
+                '[' => match parse_class(&chars, i, options) {
+                    Some((token, next)) => {
+                        i = next;
+                        token
+                    }
+                    None => Token::Literal('['),
+                },
+                '\\' if !options.noescape && i < chars.len() => {
+                    i += 1;
+                    Token::Literal(chars[i - 1])
+                }
+                c => Token::Literal(c),
+            };
+            tokens.push(token);
+        }
+        Pattern { tokens, options }
+    }
+
+    fn same(&self, a: char, b: char) -> bool {
+        match self.options.casefold {
+            true => a.to_lowercase().eq(b.to_lowercase()),
+            false => a == b,
+        }
+    }
+
+    fn in_class(&self, items: &[ClassItem], c: char) -> bool {
+        let folded = |c: char| match self.options.casefold {
+            true => c.to_lowercase().next().unwrap_or(c),
+            false => c,
+        };
+        items.iter().any(|item| match item {
+            ClassItem::Char(x) => self.same(*x, c),
+            ClassItem::Range(low, high) => {
+                (*low..=*high).contains(&c) || (*low..=*high).contains(&folded(c))
+            }
+            ClassItem::Named(name) => named_class(name, c),
+        })
+    }
+
+    /// Whether a single-character token matches `c`, which sits at
+    /// `position` in the text; `leading` says whether that position is the
+    /// start of a name for the purposes of [`MatchOptions::period`].
+    fn matches_one(&self, token: &Token, c: char, leading: bool) -> bool {
+        let special = (c == '/' && self.options.pathname) || (c == '.' && leading);
+        match token {
+            Token::Literal(x) => self.same(*x, c),
+            Token::Any => !special,
+            Token::Class { negated, items } => !special && self.in_class(items, c) != *negated,
+            Token::Star => false,
+        }
+    }
+
+    pub fn matches(&self, text: &str) -> bool {
+        let text = text.chars().collect::<Vec<char>>();
+        let leading = |i: usize| {
+            self.options.period && (i == 0 || (self.options.pathname && text[i - 1] == '/'))
+        };
+        // Characters a star cannot extend over.
+        let barrier =
+            |i: usize| (self.options.pathname && text[i] == '/') || (text[i] == '.' && leading(i));
+        let (mut t, mut p) = (0, 0);
+        // Where to resume after a mismatch: the star's token index and the
+        // text position it has consumed up to.
+        let mut backtrack: Option<(usize, usize)> = None;
+        while t < text.len() {
+            match self.tokens.get(p) {
+                // A star may not stand for a leading period, even empty.
+                Some(Token::Star) if !(text[t] == '.' && leading(t)) => {
+                    backtrack = Some((p, t));
+                    p += 1;
+                    continue;
+                }
+                Some(token) if self.matches_one(token, text[t], leading(t)) => {
+                    t += 1;
+                    p += 1;
+                    continue;
+                }
+                _ => {}
+            }
+            match backtrack {
+                Some((star, consumed)) if !barrier(consumed) => {
+                    backtrack = Some((star, consumed + 1));
+                    p = star + 1;
+                    t = consumed + 1;
+                }
+                _ => return false,
+            }
+        }
+        self.tokens[p..]
+            .iter()
+            .all(|token| matches!(token, Token::Star))
+    }
+}
+
+/// Match `text` against the wildcard `pattern`, as `fnmatch(3)` does.
+pub fn fnmatch(pattern: &str, text: &str, options: MatchOptions) -> bool {
+    Pattern::new(pattern, options).matches(text)
+}
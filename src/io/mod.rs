@@ -0,0 +1,337 @@
+use std::fmt::{self, Display};
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+
+use crate::error::strerror;
+use crate::quote::quotef;
+use crate::term::stdout_is_tty;
+
+/// Let writing to a closed pipe end the program silently, as it does C
+/// programs, rather than fail every write. Rust ignores SIGPIPE before
+/// `main`, which would leave `ls | head` panicking or reporting the error.
+pub fn restore_sigpipe() {
+    #[cfg(unix)]
+    let _ = crate::signal::restore_default(libc::SIGPIPE);
+}
+
+/// The size of the output buffer, and of the blocks written through it.
+pub const OUTPUT_BUFFER: usize = 64 << 10;
+
+/// Standard output buffered as C's stdio buffers it: flushed at the end of
+/// each line on a terminal, and otherwise only when a large buffer fills,
+/// so that a tool writing many short lines to a pipe does not make a system
+/// call for each. A write of a whole buffer or more goes straight out.
+/// Anything still buffered is written when it is dropped, but only an
+/// explicit flush reports failing to.
+pub struct Output {
+    inner: BufWriter<io::StdoutLock<'static>>,
+    tty: bool,
+}
+
+impl Output {
+    pub fn new() -> Self {
+        Output {
+            inner: BufWriter::with_capacity(OUTPUT_BUFFER, io::stdout().lock()),
+            tty: stdout_is_tty(),
+        }
+    }
+}
+
+impl Default for Output {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Write for Output {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let n = self.inner.write(buf)?;
+        if self.tty && buf[..n].contains(&b'\n') {
+            self.inner.flush()?;
+        }
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// A FILE operand opened for reading, where `-` means standard input.
+pub struct Input {
+    name: String,
+    reader: Reader,
+}
+
+/// Where an [`Input`] reads from, kept apart so that copying it whole can
+/// use the kernel's copy.
+enum Reader {
+    File(BufReader<File>),
+    Stdin(BufReader<io::Stdin>),
+}
+
+impl Input {
+    pub fn open(path: &Path) -> Result<Self, InputError> {
+        if path.as_os_str() == "-" {
+            return Ok(Self::stdin());
+        }
+        match File::open(path) {
+            Ok(file) => Ok(Input {
+                name: quotef(path),
+                reader: Reader::File(BufReader::new(file)),
+            }),
+            Err(error) => Err(InputError {
+                name: quotef(path),
+                error,
+            }),
+        }
+    }
+
+    pub fn stdin() -> Self {
+        Input {
+            name: "-".to_string(),
+            reader: Reader::Stdin(BufReader::new(io::stdin())),
+        }
+    }
+
+    /// The operand, quoted if need be, for use in diagnostics.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn is_stdin(&self) -> bool {
+        matches!(self.reader, Reader::Stdin(_))
+    }
+
+    /// The file being read, if the input is one and nothing has been read
+    /// ahead from it.
+    pub fn file(&self) -> Option<&File> {
+        match &self.reader {
+            Reader::File(reader) if reader.buffer().is_empty() => Some(reader.get_ref()),
+            _ => None,
+        }
+    }
+
+    /// Copy the rest of the input to standard output unchanged, as cat
+    /// does without options. Where the kernel allows, the data goes
+    /// straight from one to the other with copy_file_range, sendfile or
+    /// splice, rather than through a buffer here.
+    pub fn copy_to_stdout(&mut self) -> io::Result<u64> {
+        let mut stdout = io::stdout().lock();
+        let buffered = self.take_buffered();
+        stdout.write_all(&buffered)?;
+        stdout.flush()?;
+        let copied = match &mut self.reader {
+            Reader::File(reader) => io::copy(reader.get_mut(), &mut stdout),
+            Reader::Stdin(reader) => io::copy(&mut reader.get_mut().lock(), &mut stdout),
+        }?;
+        Ok(buffered.len() as u64 + copied)
+    }
+
+    /// What has been read ahead but not consumed, taken out of the buffer.
+    fn take_buffered(&mut self) -> Vec<u8> {
+        let buffered = match &self.reader {
+            Reader::File(reader) => reader.buffer().to_vec(),
+            Reader::Stdin(reader) => reader.buffer().to_vec(),
+        };
+        self.consume(buffered.len());
+        buffered
+    }
+
+    /// Describe a read error without losing track of the file, as
+    /// [`InputError`] does for failing to open it.
+    pub fn error(&self, error: io::Error) -> InputError {
+        InputError {
+            name: self.name.clone(),
+            error,
+        }
+    }
+}
+
+impl Read for Input {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match &mut self.reader {
+            Reader::File(reader) => reader.read(buf),
+            Reader::Stdin(reader) => reader.read(buf),
+        }
+    }
+}
+
+impl BufRead for Input {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        match &mut self.reader {
+            Reader::File(reader) => reader.fill_buf(),
+            Reader::Stdin(reader) => reader.fill_buf(),
+        }
+    }
+
+    fn consume(&mut self, amount: usize) {
+        match &mut self.reader {
+            Reader::File(reader) => reader.consume(amount),
+            Reader::Stdin(reader) => reader.consume(amount),
+        }
+    }
+}
+
+/// A failure to open or read an input, displayed as "NAME: error".
+#[derive(Debug)]
+pub struct InputError {
+    pub name: String,
+    pub error: io::Error,
+}
+
+impl Display for InputError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.name, strerror(&self.error))
+    }
+}
+
+/// Opens each FILE operand in turn, or standard input when there are
+/// none, so that a tool can report a file it cannot open and go on with
+/// the rest.
+pub struct Inputs {
+    operands: std::vec::IntoIter<PathBuf>,
+}
+
+impl Iterator for Inputs {
+    type Item = Result<Input, InputError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.operands.next().map(|path| Input::open(&path))
+    }
+}
+
+pub fn inputs<P: AsRef<Path>>(operands: &[P]) -> Inputs {
+    let operands = match operands.is_empty() {
+        true => vec![PathBuf::from("-")],
+        false => operands.iter().map(|p| p.as_ref().to_path_buf()).collect(),
+    };
+    Inputs {
+        operands: operands.into_iter(),
+    }
+}
+
+/// The record terminator for a tool's -z/--zero-terminated option.
+pub fn terminator(zero: bool) -> u8 {
+    match zero {
+        true => b'\0',
+        false => b'\n',
+    }
+}
+
+/// A line, or NUL-terminated record, without its terminator.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Record {
+    pub bytes: Vec<u8>,
+    /// False for a final record the input ended without terminating,
+    /// which tools like head and tail reproduce as it was and sort and
+    /// uniq terminate.
+    pub terminated: bool,
+}
+
+/// The records of `reader`, split on `terminator`.
+pub struct Records<R> {
+    reader: R,
+    terminator: u8,
+}
+
+impl<R: BufRead> Records<R> {
+    pub fn new(reader: R, terminator: u8) -> Self {
+        Records { reader, terminator }
+    }
+}
+
+impl<R: BufRead> Iterator for Records<R> {
+    type Item = io::Result<Record>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut bytes = Vec::new();
+        loop {
+            match self.reader.read_until(self.terminator, &mut bytes) {
+                Ok(0) if bytes.is_empty() => return None,
+                Ok(_) => break,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => return Some(Err(e)),
+            }
+        }
+        let terminated = bytes.last() == Some(&self.terminator);
+        if terminated {
+            bytes.pop();
+        }
+        Some(Ok(Record { bytes, terminated }))
+    }
+}
+
+/// The number of times `byte` occurs in `buf`, counted a block at a time
+/// so that the compiler can compare many bytes in one instruction.
+pub fn count_byte(buf: &[u8], byte: u8) -> usize {
+    let mut chunks = buf.chunks_exact(32);
+    let mut count = 0;
+    for chunk in &mut chunks {
+        // At most 32, so the sum fits the narrow lanes.
+        count += chunk.iter().map(|&b| u8::from(b == byte)).sum::<u8>() as usize;
+    }
+    count + chunks.remainder().iter().filter(|&&b| b == byte).count()
+}
+
+/// The lines and bytes of `reader`, read in large blocks rather than a
+/// line at a time, as wc counts when it needs nothing else.
+pub fn count_lines(reader: &mut impl Read, terminator: u8) -> io::Result<(u64, u64)> {
+    let mut buf = vec![0; 256 << 10];
+    let (mut lines, mut bytes) = (0, 0);
+    loop {
+        match reader.read(&mut buf) {
+            Ok(0) => return Ok((lines, bytes)),
+            Ok(n) => {
+                lines += count_byte(&buf[..n], terminator) as u64;
+                bytes += n as u64;
+            }
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+/// Where the last `count` records of the seekable `file` start, found by
+/// reading back from its end a block at a time, as tail does for a regular
+/// file rather than reading it all. A final record the file ends without
+/// terminating counts as one.
+pub fn tail_start(file: &File, count: u64, terminator: u8) -> io::Result<u64> {
+    use std::os::unix::fs::FileExt;
+
+    let size = file.metadata()?.len();
+    if count == 0 {
+        return Ok(size);
+    }
+    let mut buf = vec![0; 64 << 10];
+    let mut end = size;
+    // The terminator ending the file ends the last record, not another.
+    let mut first = true;
+    let mut left = count;
+    while end > 0 {
+        let start = end.saturating_sub(buf.len() as u64);
+        let block = &mut buf[..(end - start) as usize];
+        file.read_exact_at(block, start)?;
+        let mut block: &[u8] = block;
+        if first {
+            first = false;
+            if block.last() == Some(&terminator) {
+                block = &block[..block.len() - 1];
+            }
+        }
+        for (i, _) in block
+            .iter()
+            .enumerate()
+            .rev()
+            .filter(|&(_, &b)| b == terminator)
+        {
+            left -= 1;
+            if left == 0 {
+                return Ok(start + i as u64 + 1);
+            }
+        }
+        end = start;
+    }
+    Ok(0)
+}
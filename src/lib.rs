@@ -1 +1,47 @@
+pub mod acl;
+pub mod atomic;
+pub mod attrs;
+pub mod backup;
+pub mod colors;
+pub mod copy;
+pub mod datetime;
+pub mod dd;
+pub mod digest;
+pub mod duration;
+pub mod envcfg;
+pub mod error;
+pub mod escape;
+pub mod fs;
+pub mod glob;
+pub mod io;
+pub mod locale;
+pub mod mode;
+pub mod posix;
+pub mod process;
+pub mod progress;
+pub mod prompt;
+pub mod quote;
+pub mod ranges;
+pub mod remove;
+pub mod selinux;
+pub mod seq;
+pub mod shuffle;
+pub mod signal;
+pub mod size;
+pub mod sort;
+pub mod sparse;
+pub mod stdbuf;
+pub mod sys;
 pub mod table;
+pub mod term;
+pub mod text;
+pub mod timefmt;
+pub mod translate;
+pub mod uname;
+pub mod usergroup;
+pub mod utmp;
+pub mod uu;
+pub mod version;
+pub mod version_cmp;
+pub mod watch;
+pub mod xattr;
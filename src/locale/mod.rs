@@ -0,0 +1,107 @@
+use std::cmp::Ordering;
+use std::ffi::{CStr, CString};
+use std::sync::OnceLock;
+
+use crate::timefmt::{MONTHS, WEEKDAYS};
+
+/// Select the locale the LC_ALL, LC_* and LANG variables name. Until a
+/// program calls this it runs in the C locale, whatever the environment
+/// says.
+pub fn init() {
+    unsafe { libc::setlocale(libc::LC_ALL, c"".as_ptr()) };
+}
+
+/// Whether `category` (e.g. `libc::LC_COLLATE`) is the C or POSIX locale.
+pub fn is_c_locale(category: libc::c_int) -> bool {
+    let locale = unsafe { libc::setlocale(category, std::ptr::null()) };
+    if locale.is_null() {
+        return true;
+    }
+    let locale = unsafe { CStr::from_ptr(locale) }.to_bytes();
+    locale == b"C" || locale == b"POSIX"
+}
+
+/// The locale's text for `item`, or `fallback` if it has none.
+pub fn langinfo(item: libc::nl_item, fallback: &str) -> String {
+    let text = unsafe { libc::nl_langinfo(item) };
+    if text.is_null() {
+        return fallback.to_string();
+    }
+    let text = unsafe { CStr::from_ptr(text) }.to_string_lossy();
+    match text.is_empty() {
+        true => fallback.to_string(),
+        false => text.to_string(),
+    }
+}
+
+/// Day and month names of the LC_TIME locale; English names in the C
+/// locale.
+pub struct Names {
+    pub weekdays: Vec<String>,
+    pub abbreviated_weekdays: Vec<String>,
+    pub months: Vec<String>,
+    pub abbreviated_months: Vec<String>,
+}
+
+pub fn names() -> &'static Names {
+    static NAMES: OnceLock<Names> = OnceLock::new();
+    NAMES.get_or_init(|| Names {
+        weekdays: (0..7)
+            .map(|i| langinfo(libc::DAY_1 + i, WEEKDAYS[i as usize]))
+            .collect(),
+        abbreviated_weekdays: (0..7)
+            .map(|i| langinfo(libc::ABDAY_1 + i, &WEEKDAYS[i as usize][..3]))
+            .collect(),
+        months: (0..12)
+            .map(|i| langinfo(libc::MON_1 + i, MONTHS[i as usize]))
+            .collect(),
+        abbreviated_months: (0..12)
+            .map(|i| langinfo(libc::ABMON_1 + i, &MONTHS[i as usize][..3]))
+            .collect(),
+    })
+}
+
+/// Whether the LC_COLLATE locale orders text by its bytes, as the C locale
+/// does, so that a tool comparing many lines, such as sort or uniq, can
+/// compare them with `<[u8]>::cmp` and skip [`collate`] altogether.
+pub fn collates_bytewise() -> bool {
+    static C_COLLATE: OnceLock<bool> = OnceLock::new();
+    *C_COLLATE.get_or_init(|| is_c_locale(libc::LC_COLLATE))
+}
+
+/// Compare `a` and `b` in the LC_COLLATE locale's order. In the C locale
+/// that is byte order, which is compared directly.
+pub fn collate(a: &[u8], b: &[u8]) -> Ordering {
+    if collates_bytewise() {
+        return a.cmp(b);
+    }
+    // strcoll stops at a NUL, so compare NUL-separated pieces in turn.
+    let mut a_parts = a.split(|&b| b == 0);
+    let mut b_parts = b.split(|&b| b == 0);
+    loop {
+        match (a_parts.next(), b_parts.next()) {
+            (None, None) => return Ordering::Equal,
+            (None, Some(_)) => return Ordering::Less,
+            (Some(_), None) => return Ordering::Greater,
+            (Some(x), Some(y)) => {
+                let (x, y) = (CString::new(x).unwrap(), CString::new(y).unwrap());
+                match unsafe { libc::strcoll(x.as_ptr(), y.as_ptr()) } {
+                    0 => continue,
+                    n if n < 0 => return Ordering::Less,
+                    _ => return Ordering::Greater,
+                }
+            }
+        }
+    }
+}
+
+/// The LC_NUMERIC locale's decimal separator, `.` in the C locale.
+pub fn decimal_point() -> String {
+    langinfo(libc::RADIXCHAR, ".")
+}
+
+/// The LC_NUMERIC locale's digit group separator, which is empty in the C
+/// locale.
+pub fn thousands_separator() -> String {
+    langinfo(libc::THOUSEP, "")
+}
@@ -0,0 +1,274 @@
+use std::fmt::{self, Display};
+
+use crate::quote::quote;
+
+/// The file type bits, with the values Unix systems share, so that modes
+/// can be made up where the platform has none.
+pub const S_IFMT: u32 = 0o170000;
+pub const S_IFSOCK: u32 = 0o140000;
+pub const S_IFLNK: u32 = 0o120000;
+pub const S_IFREG: u32 = 0o100000;
+pub const S_IFBLK: u32 = 0o060000;
+pub const S_IFDIR: u32 = 0o040000;
+pub const S_IFCHR: u32 = 0o020000;
+pub const S_IFIFO: u32 = 0o010000;
+const S_ISUID: u32 = 0o4000;
+const S_ISGID: u32 = 0o2000;
+const S_ISVTX: u32 = 0o1000;
+const S_IRWXU: u32 = 0o700;
+const S_IRWXG: u32 = 0o070;
+const S_IRWXO: u32 = 0o007;
+const READ: u32 = 0o444;
+const WRITE: u32 = 0o222;
+const EXECUTE: u32 = 0o111;
+/// The bits a mode change can touch.
+const MODE_BITS: u32 = 0o7777;
+
+/// The mode of a file, formatted as `ls -l` shows it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FileMode(pub u32);
+
+impl FileMode {
+    /// The character `ls -l` shows for the file type.
+    pub fn type_char(&self) -> char {
+        match self.0 & S_IFMT {
+            S_IFREG => '-',
+            S_IFDIR => 'd',
+            S_IFLNK => 'l',
+            S_IFBLK => 'b',
+            S_IFCHR => 'c',
+            S_IFIFO => 'p',
+            S_IFSOCK => 's',
+            _ => '?',
+        }
+    }
+
+    /// The file type in words, as diagnostics and prompts name it.
+    pub fn type_name(&self) -> &'static str {
+        match self.0 & S_IFMT {
+            S_IFREG => "regular file",
+            S_IFDIR => "directory",
+            S_IFLNK => "symbolic link",
+            S_IFBLK => "block special file",
+            S_IFCHR => "character special file",
+            S_IFIFO => "fifo",
+            S_IFSOCK => "socket",
+            _ => "weird file",
+        }
+    }
+
+    pub fn permissions(&self) -> u32 {
+        self.0 & MODE_BITS
+    }
+}
+
+impl Display for FileMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mode = self.0;
+        let bit = |mask: u32, c: char| if mode & mask != 0 { c } else { '-' };
+        // The execute position also shows set-ID and sticky bits, in
+        // upper case when the execute bit underneath is clear.
+        let special = |exec: u32, extra: u32, set: char, unset: char| match (
+            mode & exec != 0,
+            mode & extra != 0,
+        ) {
+            (true, true) => set,
+            (false, true) => unset,
+            (true, false) => 'x',
+            (false, false) => '-',
+        };
+        let chars = [
+            self.type_char(),
+            bit(0o400, 'r'),
+            bit(0o200, 'w'),
+            special(0o100, S_ISUID, 's', 'S'),
+            bit(0o040, 'r'),
+            bit(0o020, 'w'),
+            special(0o010, S_ISGID, 's', 'S'),
+            bit(0o004, 'r'),
+            bit(0o002, 'w'),
+            special(0o001, S_ISVTX, 't', 'T'),
+        ];
+        write!(f, "{}", chars.iter().collect::<String>())
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Op {
+    Add,
+    Remove,
+    Set,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Source {
+    /// The bits given by `value`.
+    Bits,
+    /// A copy of the u, g or o permissions selected by `value`.
+    Copy,
+    /// `X`: execute if the file is a directory or already executable.
+    ExecuteIfAny,
+}
+
+#[derive(Clone, Copy, Debug)]
+struct Change {
+    op: Op,
+    source: Source,
+    /// The bits the "who" letters select, or 0 for "all bits not in the
+    /// umask".
+    affected: u32,
+    value: u32,
+    /// The bits the change explicitly mentions; set-ID bits of
+    /// directories are left alone unless they are mentioned.
+    mentioned: u32,
+}
+
+/// A parsed mode expression, octal or symbolic, as chmod, mkdir -m and
+/// friends take it.
+#[derive(Clone, Debug)]
+pub struct ModeSpec(Vec<Change>);
+
+impl ModeSpec {
+    pub fn parse(spec: &str) -> Result<Self, String> {
+        let invalid = || format!("invalid mode: {}", quote(spec));
+        if !spec.is_empty() && spec.bytes().all(|b| b.is_ascii_digit()) {
+            let value = u32::from_str_radix(spec, 8).map_err(|_| invalid())?;
+            if value > MODE_BITS {
+                return Err(invalid());
+            }
+            // With fewer than five digits the set-ID bits of directories
+            // are only ever set, never cleared.
+            let mentioned = match spec.len() < 5 {
+                true => (value & (S_ISUID | S_ISGID)) | S_ISVTX | 0o777,
+                false => MODE_BITS,
+            };
+            return Ok(ModeSpec(vec![Change {
+                op: Op::Set,
+                source: Source::Bits,
+                affected: MODE_BITS,
+                value,
+                mentioned,
+            }]));
+        }
+
+        let mut changes = Vec::new();
+        for clause in spec.split(',') {
+            let mut chars = clause.chars().peekable();
+            let mut affected = 0;
+            while let Some(&c) = chars.peek() {
+                affected |= match c {
+                    'u' => S_ISUID | S_IRWXU,
+                    'g' => S_ISGID | S_IRWXG,
+                    'o' => S_ISVTX | S_IRWXO,
+                    'a' => MODE_BITS,
+                    _ => break,
+                };
+                chars.next();
+            }
+            if chars.peek().is_none() {
+                return Err(invalid());
+            }
+            while let Some(c) = chars.next() {
+                let op = match c {
+                    '+' => Op::Add,
+                    '-' => Op::Remove,
+                    '=' => Op::Set,
+                    _ => return Err(invalid()),
+                };
+                let mut source = Source::Bits;
+                let mut value = 0;
+                match chars.peek() {
+                    Some(&who @ ('u' | 'g' | 'o')) => {
+                        chars.next();
+                        source = Source::Copy;
+                        value = match who {
+                            'u' => S_IRWXU,
+                            'g' => S_IRWXG,
+                            _ => S_IRWXO,
+                        };
+                    }
+                    _ => {
+                        while let Some(&c) = chars.peek() {
+                            value |= match c {
+                                'r' => READ,
+                                'w' => WRITE,
+                                'x' => EXECUTE,
+                                'X' => {
+                                    source = Source::ExecuteIfAny;
+                                    0
+                                }
+                                's' => S_ISUID | S_ISGID,
+                                't' => S_ISVTX,
+                                _ => break,
+                            };
+                            chars.next();
+                        }
+                    }
+                }
+                changes.push(Change {
+                    op,
+                    source,
+                    affected,
+                    value,
+                    mentioned: match affected {
+                        0 => value,
+                        _ => affected & value,
+                    },
+                });
+            }
+        }
+        Ok(ModeSpec(changes))
+    }
+
+    /// Apply the changes to `mode`, the current permission bits of a file
+    /// or directory, with `umask` limiting changes that name no users.
+    pub fn apply(&self, mode: u32, is_dir: bool, umask: u32) -> u32 {
+        let mut mode = mode & MODE_BITS;
+        for change in &self.0 {
+            let omitted = match is_dir {
+                true => (S_ISUID | S_ISGID) & !change.mentioned,
+                false => 0,
+            };
+            let mut value = change.value;
+            match change.source {
+                Source::Bits => {}
+                Source::Copy => {
+                    value &= mode;
+                    value |= [READ, WRITE, EXECUTE]
+                        .into_iter()
+                        .filter(|bits| value & bits != 0)
+                        .fold(0, |all, bits| all | bits);
+                }
+                Source::ExecuteIfAny => {
+                    if is_dir || mode & EXECUTE != 0 {
+                        value |= EXECUTE;
+                    }
+                }
+            }
+            value &= match change.affected {
+                0 => !umask,
+                affected => affected,
+            } & !omitted;
+            mode = match change.op {
+                Op::Add => mode | value,
+                Op::Remove => mode & !value,
+                Op::Set => {
+                    let preserved = match change.affected {
+                        0 => 0,
+                        affected => !affected,
+                    } | omitted;
+                    (mode & preserved) | value
+                }
+            };
+        }
+        mode
+    }
+}
+
+/// The process's file mode creation mask.
+pub fn umask() -> u32 {
+    // The mask can only be read by replacing it, so put it straight back.
+    let mask = unsafe { libc::umask(0) };
+    unsafe { libc::umask(mask) };
+    mask as u32
+}
@@ -0,0 +1,86 @@
+use std::env;
+use std::ffi::OsString;
+use std::sync::OnceLock;
+
+use clap::{Arg, Command, Parser};
+
+use crate::version;
+
+/// Whether POSIXLY_CORRECT is set, asking utilities to follow POSIX where
+/// their usual behavior differs from it.
+pub fn posixly_correct() -> bool {
+    static POSIXLY_CORRECT: OnceLock<bool> = OnceLock::new();
+    *POSIXLY_CORRECT.get_or_init(|| env::var_os("POSIXLY_CORRECT").is_some())
+}
+
+/// Whether an option consumes the following argument when no value is
+/// attached to it.
+fn takes_separate_value(arg: &Arg) -> bool {
+    arg.get_action().takes_values()
+        && !arg.is_require_equals_set()
+        && arg.get_num_args().is_none_or(|n| n.min_values() > 0)
+}
+
+/// Mark the end of the options at the first operand in `args`, which
+/// start with the program name, so that later arguments that look like
+/// options are operands too, as POSIX requires.
+pub fn stop_at_first_operand(command: &Command, mut args: Vec<OsString>) -> Vec<OsString> {
+    let options = command
+        .get_arguments()
+        .filter(|a| !a.is_positional())
+        .collect::<Vec<&Arg>>();
+    let long = |name: &str| options.iter().find(|a| a.get_long() == Some(name));
+    let short = |c: char| options.iter().find(|a| a.get_short() == Some(c));
+
+    let mut i = 1;
+    while i < args.len() {
+        let Some(arg) = args[i].to_str().map(str::to_string) else {
+            break;
+        };
+        if arg == "--" {
+            return args;
+        }
+        if let Some(name) = arg.strip_prefix("--") {
+            if !name.contains('=') && long(name).is_some_and(|a| takes_separate_value(a)) {
+                i += 1;
+            }
+        } else if let Some(cluster) = arg.strip_prefix('-').filter(|c| !c.is_empty()) {
+            // The rest of a cluster after an option taking a value is the
+            // value; only a value-taking option at its end consumes the
+            // next argument.
+            for (at, c) in cluster.char_indices() {
+                if short(c).is_some_and(|a| takes_separate_value(a)) {
+                    if at + c.len_utf8() == cluster.len() {
+                        i += 1;
+                    }
+                    break;
+                }
+            }
+        } else {
+            break;
+        }
+        i += 1;
+    }
+    if i < args.len() {
+        args.insert(i, OsString::from("--"));
+    }
+    args
+}
+
+/// Parse `args` into `P`, treating everything from the first operand on as
+/// operands when POSIXLY_CORRECT is set.
+pub fn parse<P: Parser>(args: Vec<OsString>) -> P {
+    let args = match posixly_correct() {
+        true => stop_at_first_operand(&P::command(), args),
+        false => args,
+    };
+    try_parse(args).unwrap_or_else(|e| e.exit())
+}
+
+/// Parse `args` into `P` as they are, for a utility that reports errors
+/// its own way, with the version text every utility has.
+pub fn try_parse<P: Parser>(args: Vec<OsString>) -> Result<P, clap::Error> {
+    let mut command = version::stamp(P::command());
+    let mut matches = command.try_get_matches_from_mut(args)?;
+    P::from_arg_matches_mut(&mut matches).map_err(|e| e.format(&mut command))
+}
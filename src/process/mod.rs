@@ -0,0 +1,104 @@
+//! Running another program the way env, nice, nohup, timeout and friends
+//! do, with the exit statuses they share: 125 when the utility itself
+//! fails, 126 when the program cannot be invoked and 127 when it cannot be
+//! found.
+
+use std::ffi::OsStr;
+use std::io;
+use std::os::unix::process::CommandExt;
+use std::process::{Child, Command};
+
+use crate::error::strerror;
+use crate::quote::quote;
+use crate::show_error;
+use crate::signal::SigSet;
+
+pub const EXIT_CANCELED: i32 = 125;
+pub const EXIT_CANNOT_INVOKE: i32 = 126;
+pub const EXIT_ENOENT: i32 = 127;
+
+/// How a program that could not be run is reported.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Diagnostic {
+    /// `'prog': No such file or directory`, as env and nice put it.
+    Name,
+    /// `failed to run command 'prog': No such file or directory`.
+    FailedToRun,
+}
+
+/// The status for a program that could not be run because of `error`.
+pub fn failure_status(error: &io::Error) -> i32 {
+    match error.kind() {
+        io::ErrorKind::NotFound => EXIT_ENOENT,
+        _ => EXIT_CANNOT_INVOKE,
+    }
+}
+
+/// Report that `program` could not be run, returning the status to exit
+/// with.
+pub fn cannot_run(program: &OsStr, error: &io::Error, diagnostic: Diagnostic) -> i32 {
+    match diagnostic {
+        Diagnostic::Name => show_error!("{}: {}", quote(program), strerror(error)),
+        Diagnostic::FailedToRun => show_error!(
+            "failed to run command {}: {}",
+            quote(program),
+            strerror(error)
+        ),
+    }
+    failure_status(error)
+}
+
+/// Replace this process with `program`, searched for in `PATH`. This only
+/// returns if that fails, with the status to exit with.
+pub fn exec<I, S>(program: &OsStr, args: I, diagnostic: Diagnostic) -> i32
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<OsStr>,
+{
+    let error = Command::new(program).args(args).exec();
+    cannot_run(program, &error, diagnostic)
+}
+
+/// Start `command` with no signals blocked, whatever this process blocks,
+/// and in a process group of its own if `own_group`, so that it and its
+/// children can be signalled together.
+pub fn spawn(command: &mut Command, own_group: bool) -> io::Result<Child> {
+    if own_group {
+        command.process_group(0);
+    }
+    unsafe {
+        command.pre_exec(|| {
+            SigSet::empty().set_mask();
+            Ok(())
+        });
+    }
+    command.spawn()
+}
+
+/// How a child ended, from the status `waitpid` gives.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Termination {
+    Exited(i32),
+    Signaled(i32),
+}
+
+impl Termination {
+    /// None for a status that is neither, such as a stopped child's.
+    pub fn from_wait(status: libc::c_int) -> Option<Self> {
+        if libc::WIFEXITED(status) {
+            Some(Termination::Exited(libc::WEXITSTATUS(status)))
+        } else if libc::WIFSIGNALED(status) {
+            Some(Termination::Signaled(libc::WTERMSIG(status)))
+        } else {
+            None
+        }
+    }
+
+    /// The status a shell reports: the exit code, or 128 plus the signal.
+    pub fn code(&self) -> i32 {
+        match *self {
+            Termination::Exited(code) => code,
+            Termination::Signaled(signal) => 128 + signal,
+        }
+    }
+}
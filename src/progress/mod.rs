@@ -0,0 +1,233 @@
+//! Progress reports for long transfers, in dd's words: `N bytes (1.2 MB,
+//! 1.1 MiB) copied, 3 s, 400 MB/s`. A report is redrawn in place on a
+//! terminal and written as a line of its own elsewhere, at most once a
+//! second, and once more whenever SIGUSR1 asks for one.
+
+use std::io::{self, Read, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+
+use crate::signal;
+use crate::term::stderr_is_tty;
+
+const INTERVAL: Duration = Duration::from_secs(1);
+
+static REQUESTED: AtomicBool = AtomicBool::new(false);
+
+extern "C" fn request(_: libc::c_int) {
+    REQUESTED.store(true, Ordering::Relaxed);
+}
+
+/// Report on SIGUSR1 rather than being killed by it, as dd does.
+pub fn report_on_usr1() -> io::Result<()> {
+    signal::install(libc::SIGUSR1, request)
+}
+
+/// Whether SIGUSR1 has asked for a report since the last call.
+pub fn report_requested() -> bool {
+    REQUESTED.swap(false, Ordering::Relaxed)
+}
+
+/// `value` in the largest of `units`, powers of `base`, that leaves at
+/// least one, with a decimal place below ten.
+fn scaled(value: f64, base: f64, units: [&str; 8]) -> String {
+    let mut value = value;
+    let mut unit = None;
+    for name in units {
+        if value < base {
+            break;
+        }
+        value /= base;
+        unit = Some(name);
+    }
+    match (unit, value < 9.95) {
+        (None, _) => format!("{} B", value),
+        (Some(unit), true) => format!("{:.1} {}", value, unit),
+        (Some(unit), false) => format!("{:.0} {}", value, unit),
+    }
+}
+
+const SI: [&str; 8] = ["kB", "MB", "GB", "TB", "PB", "EB", "ZB", "YB"];
+const IEC: [&str; 8] = ["KiB", "MiB", "GiB", "TiB", "PiB", "EiB", "ZiB", "YiB"];
+
+/// `bytes` as dd reports them: the count, then in SI and IEC units if it
+/// comes to at least one of them.
+pub fn format_bytes(bytes: u64) -> String {
+    match bytes {
+        1 => "1 byte".to_string(),
+        0..1000 => format!("{} bytes", bytes),
+        _ => format!(
+            "{} bytes ({}, {})",
+            bytes,
+            scaled(bytes as f64, 1000.0, SI),
+            scaled(bytes as f64, 1024.0, IEC)
+        ),
+    }
+}
+
+/// A transfer rate in SI units, with a decimal place below a hundred.
+pub fn format_rate(bytes: u64, elapsed: Duration) -> String {
+    let seconds = elapsed.as_secs_f64();
+    if seconds <= 0.0 {
+        return "Infinity B/s".to_string();
+    }
+    let mut rate = bytes as f64 / seconds;
+    let mut unit = "B";
+    for name in SI {
+        if rate < 1000.0 {
+            break;
+        }
+        rate /= 1000.0;
+        unit = name;
+    }
+    match rate < 100.0 && unit != "B" {
+        true => format!("{:.1} {}/s", rate, unit),
+        false => format!("{:.0} {}/s", rate, unit),
+    }
+}
+
+/// `seconds` as `H:MM:SS`.
+fn clock(seconds: u64) -> String {
+    format!(
+        "{}:{:02}:{:02}",
+        seconds / 3600,
+        seconds / 60 % 60,
+        seconds % 60
+    )
+}
+
+/// The state of one transfer.
+pub struct Progress {
+    verb: &'static str,
+    label: Option<String>,
+    total: Option<u64>,
+    done: u64,
+    start: Instant,
+    next: Instant,
+    tty: bool,
+    /// The width of the report last drawn in place, to blank what a
+    /// shorter one leaves.
+    drawn: usize,
+}
+
+impl Progress {
+    /// A transfer starting now, whose bytes are said to be `verb`, such as
+    /// `copied`.
+    pub fn new(verb: &'static str) -> Self {
+        let start = Instant::now();
+        Progress {
+            verb,
+            label: None,
+            total: None,
+            done: 0,
+            start,
+            next: start + INTERVAL,
+            tty: stderr_is_tty(),
+            drawn: 0,
+        }
+    }
+
+    /// Put `label`, such as a file name, before each report.
+    pub fn label(mut self, label: impl Into<String>) -> Self {
+        self.label = Some(label.into());
+        self
+    }
+
+    /// The size of the whole transfer, so that reports can estimate
+    /// when it will end.
+    pub fn total(mut self, total: u64) -> Self {
+        self.total = Some(total);
+        self
+    }
+
+    pub fn done(&self) -> u64 {
+        self.done
+    }
+
+    /// The report as of now.
+    pub fn report(&self) -> String {
+        let elapsed = self.start.elapsed();
+        let mut report = format!(
+            "{}{} {}, {} s, {}",
+            self.label
+                .as_ref()
+                .map_or(String::new(), |l| format!("{}: ", l)),
+            format_bytes(self.done),
+            self.verb,
+            elapsed.as_secs(),
+            format_rate(self.done, elapsed)
+        );
+        if let Some(total) = self.total.filter(|&t| t > self.done && self.done > 0) {
+            let left = (total - self.done) as f64 * elapsed.as_secs_f64() / self.done as f64;
+            report.push_str(&format!(", ETA {}", clock(left.ceil() as u64)));
+        }
+        report
+    }
+
+    /// Count `bytes` more, reporting if a second has passed since the last
+    /// report or SIGUSR1 asked for one.
+    pub fn add(&mut self, bytes: u64) {
+        self.done += bytes;
+        let now = Instant::now();
+        if report_requested() {
+            self.print_line();
+        } else if now >= self.next {
+            self.next = now + INTERVAL;
+            self.draw();
+        }
+    }
+
+    /// Redraw the report, in place on a terminal.
+    pub fn draw(&mut self) {
+        let report = self.report();
+        let mut stderr = io::stderr().lock();
+        let _ = match self.tty {
+            true => {
+                let blank = self.drawn.saturating_sub(report.len());
+                self.drawn = report.len();
+                write!(stderr, "\r{}{:blank$}", report, "")
+            }
+            false => writeln!(stderr, "{}", report),
+        };
+    }
+
+    /// Write the report as a line of its own, below one drawn in place.
+    pub fn print_line(&mut self) {
+        self.finish();
+        let _ = writeln!(io::stderr(), "{}", self.report());
+    }
+
+    /// End a report drawn in place, so that what follows starts a line.
+    pub fn finish(&mut self) {
+        if self.drawn > 0 {
+            let _ = writeln!(io::stderr());
+            self.drawn = 0;
+        }
+    }
+}
+
+/// A reader that counts what it reads as a [`Progress`].
+pub struct Reader<R> {
+    inner: R,
+    progress: Progress,
+}
+
+impl<R: Read> Reader<R> {
+    pub fn new(inner: R, progress: Progress) -> Self {
+        Reader { inner, progress }
+    }
+
+    /// End the reports, returning the reader.
+    pub fn finish(mut self) -> R {
+        self.progress.finish();
+        self.inner
+    }
+}
+
+impl<R: Read> Read for Reader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.progress.add(n as u64);
+        Ok(n)
+    }
+}
@@ -0,0 +1,86 @@
+use std::ffi::CStr;
+use std::fmt;
+use std::io::{self, BufRead, Write};
+use std::sync::OnceLock;
+
+use regex::Regex;
+
+use crate::error::program_name;
+use crate::term::stdin_is_tty;
+
+/// How a tool that replaces or removes files asks before doing so, from
+/// its -f, -i and -n options. When more than one is given the last one
+/// wins, as [`Interactive::last`] works out.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Interactive {
+    /// -f: never ask.
+    Never,
+    /// -i: ask about every file.
+    Always,
+    /// -n: never replace an existing file, and so never ask.
+    NoClobber,
+    /// None of them: ask only about files the user cannot write, when
+    /// there is someone at a terminal to answer.
+    Default,
+}
+
+impl Interactive {
+    /// The choice given last on the command line, from each option's
+    /// position (as clap's `ArgMatches::index_of` reports it) if present.
+    pub fn last(options: &[(Option<usize>, Interactive)]) -> Self {
+        options
+            .iter()
+            .filter_map(|(index, choice)| index.map(|i| (i, *choice)))
+            .max_by_key(|(index, _)| *index)
+            .map_or(Interactive::Default, |(_, choice)| choice)
+    }
+
+    /// Whether to ask before acting on a file that is not writable.
+    pub fn asks_for_protected(&self) -> bool {
+        match self {
+            Interactive::Always => true,
+            Interactive::Default => stdin_is_tty(),
+            Interactive::Never | Interactive::NoClobber => false,
+        }
+    }
+}
+
+/// The LC_MESSAGES locale's expression for an affirmative answer.
+fn yes_expression() -> &'static Regex {
+    static YES: OnceLock<Regex> = OnceLock::new();
+    YES.get_or_init(|| {
+        let expr = unsafe { libc::nl_langinfo(libc::YESEXPR) };
+        let expr = match expr.is_null() {
+            true => None,
+            false => Some(unsafe { CStr::from_ptr(expr) }.to_string_lossy()),
+        };
+        expr.filter(|e| !e.is_empty())
+            .and_then(|e| Regex::new(&e).ok())
+            .unwrap_or_else(|| Regex::new("^[yY]").unwrap())
+    })
+}
+
+/// Whether `answer` is yes in the current locale.
+pub fn is_yes(answer: &str) -> bool {
+    yes_expression().is_match(answer)
+}
+
+/// Ask `question` on stderr, prefixed with the program name, and read the
+/// answer from stdin. Anything but yes, including end of input, is no.
+pub fn ask(question: fmt::Arguments) -> bool {
+    eprint!("{}: {} ", program_name(), question);
+    let _ = io::stderr().flush();
+    let mut answer = String::new();
+    match io::stdin().lock().read_line(&mut answer) {
+        Ok(0) | Err(_) => false,
+        Ok(_) => is_yes(answer.trim_end_matches('\n')),
+    }
+}
+
+/// Ask a yes/no question, as [`ask`] does, with `format!` arguments.
+#[macro_export]
+macro_rules! prompt {
+    ($($arg:tt)*) => {
+        $crate::prompt::ask(format_args!($($arg)*))
+    };
+}
@@ -0,0 +1,235 @@
+use std::ffi::{CStr, OsStr};
+use std::os::unix::ffi::OsStrExt;
+
+/// How names are quoted for output, as the GNU tools' `--quoting-style`
+/// names them.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum QuotingStyle {
+    /// Output the name as is.
+    Literal,
+    /// Quote for the shell when the name needs it.
+    Shell,
+    /// Always quote for the shell.
+    ShellAlways,
+    /// Like `Shell`, but write unprintable bytes as `$'...'` escapes.
+    ShellEscape,
+    /// Like `ShellAlways`, but write unprintable bytes as `$'...'` escapes.
+    ShellEscapeAlways,
+    /// Quote as a C string literal.
+    C,
+    /// Like `C`, without the surrounding quotes.
+    Escape,
+    /// Like `C`, with the locale's quotation marks.
+    Locale,
+}
+
+impl QuotingStyle {
+    pub const NAMES: [(&'static str, QuotingStyle); 8] = [
+        ("literal", QuotingStyle::Literal),
+        ("shell", QuotingStyle::Shell),
+        ("shell-always", QuotingStyle::ShellAlways),
+        ("shell-escape", QuotingStyle::ShellEscape),
+        ("shell-escape-always", QuotingStyle::ShellEscapeAlways),
+        ("c", QuotingStyle::C),
+        ("escape", QuotingStyle::Escape),
+        ("locale", QuotingStyle::Locale),
+    ];
+
+    pub fn parse(name: &str) -> Option<Self> {
+        Self::NAMES
+            .iter()
+            .find(|(n, _)| *n == name)
+            .map(|(_, style)| *style)
+    }
+}
+
+/// A piece of a name: a printable character, or a byte that is not part
+/// of one.
+enum Unit<'a> {
+    Char(&'a str),
+    Byte(u8),
+}
+
+/// Whether the LC_CTYPE locale the program selected with `setlocale`
+/// uses UTF-8; in any other locale only ASCII is printable.
+fn utf8_locale() -> bool {
+    let codeset = unsafe { libc::nl_langinfo(libc::CODESET) };
+    !codeset.is_null() && unsafe { CStr::from_ptr(codeset) }.to_bytes() == b"UTF-8"
+}
+
+fn units(bytes: &[u8]) -> Vec<Unit<'_>> {
+    let utf8 = utf8_locale();
+    let mut units = Vec::new();
+    let mut rest = bytes;
+    while let Some(&byte) = rest.first() {
+        if byte.is_ascii() {
+            units.push(match byte {
+                b' '..=b'~' => Unit::Char(std::str::from_utf8(&rest[..1]).unwrap()),
+                _ => Unit::Byte(byte),
+            });
+            rest = &rest[1..];
+            continue;
+        }
+        let valid = match std::str::from_utf8(rest) {
+            Ok(s) => s,
+            Err(e) => std::str::from_utf8(&rest[..e.valid_up_to()]).unwrap(),
+        };
+        match valid.chars().next() {
+            Some(c) if utf8 && !c.is_control() => {
+                units.push(Unit::Char(&valid[..c.len_utf8()]));
+                rest = &rest[c.len_utf8()..];
+            }
+            _ => {
+                units.push(Unit::Byte(byte));
+                rest = &rest[1..];
+            }
+        }
+    }
+    units
+}
+
+/// The backslash escape C uses for a byte.
+fn escape_byte(byte: u8) -> String {
+    match byte {
+        b'\x07' => "\\a".to_string(),
+        b'\x08' => "\\b".to_string(),
+        b'\x0c' => "\\f".to_string(),
+        b'\n' => "\\n".to_string(),
+        b'\r' => "\\r".to_string(),
+        b'\t' => "\\t".to_string(),
+        b'\x0b' => "\\v".to_string(),
+        _ => format!("\\{:03o}", byte),
+    }
+}
+
+/// Characters the shell treats specially, so that a name containing them
+/// has to be quoted; `#` and `~` only matter at the start of a word.
+fn shell_special(c: &str, first: bool) -> bool {
+    match c {
+        "#" | "~" => first,
+        _ => c.len() == 1 && "\t\n !\"$&'()*;<=>?[\\]^`|".contains(c),
+    }
+}
+
+fn quote_shell(units: &[Unit], always: bool, escape: bool) -> String {
+    let has_bytes = units.iter().any(|u| matches!(u, Unit::Byte(_)));
+    let needs_quotes = always
+        || units.is_empty()
+        || (escape && has_bytes)
+        || units.iter().enumerate().any(|(i, u)| match u {
+            Unit::Char(c) => shell_special(c, i == 0),
+            Unit::Byte(b) => b"\t\n".contains(b),
+        });
+    // Unprintable bytes are written raw unless escaping, so the quoting is
+    // built as bytes and only then made into a string.
+    let raw = |out: &mut Vec<u8>| {
+        for unit in units {
+            match unit {
+                Unit::Char(c) => out.extend_from_slice(c.as_bytes()),
+                Unit::Byte(b) => out.push(*b),
+            }
+        }
+    };
+    let mut out = Vec::new();
+    if !needs_quotes {
+        raw(&mut out);
+        return String::from_utf8_lossy(&out).to_string();
+    }
+
+    // A single quote is easier to read inside double quotes, as long as
+    // nothing else in the name is special there.
+    let chars = || {
+        units.iter().filter_map(|u| match u {
+            Unit::Char(c) => Some(*c),
+            Unit::Byte(_) => None,
+        })
+    };
+    if !(escape && has_bytes)
+        && chars().any(|c| c == "'")
+        && !chars().any(|c| c.len() == 1 && "\"$`\\!".contains(c))
+    {
+        out.push(b'"');
+        raw(&mut out);
+        out.push(b'"');
+        return String::from_utf8_lossy(&out).to_string();
+    }
+
+    out.push(b'\'');
+    let mut i = 0;
+    while i < units.len() {
+        match &units[i] {
+            Unit::Char("'") => out.extend_from_slice(b"'\\''"),
+            Unit::Char(c) => out.extend_from_slice(c.as_bytes()),
+            Unit::Byte(b) if !escape => out.push(*b),
+            Unit::Byte(_) => {
+                out.extend_from_slice(b"'$'");
+                while let Some(Unit::Byte(b)) = units.get(i) {
+                    out.extend_from_slice(escape_byte(*b).as_bytes());
+                    i += 1;
+                }
+                out.push(b'\'');
+                // Reopen the quotes only if something follows.
+                if i < units.len() {
+                    out.push(b'\'');
+                }
+                continue;
+            }
+        }
+        i += 1;
+    }
+    if !matches!(units.last(), Some(Unit::Byte(_)) if escape) {
+        out.push(b'\'');
+    }
+    String::from_utf8_lossy(&out).to_string()
+}
+
+/// Backslash-escape `units` between `open` and `close`, which are also
+/// escaped inside the name when they are ASCII quotes.
+fn quote_escaped(units: &[Unit], open: &str, close: &str, escape_space: bool) -> String {
+    let mut out = String::from(open);
+    for unit in units {
+        match unit {
+            Unit::Char("\\") => out.push_str("\\\\"),
+            Unit::Char(" ") if escape_space => out.push_str("\\ "),
+            Unit::Char(c) if *c == close && c.is_ascii() => {
+                out.push('\\');
+                out.push_str(c);
+            }
+            Unit::Char(c) => out.push_str(c),
+            Unit::Byte(b) => out.push_str(&escape_byte(*b)),
+        }
+    }
+    out.push_str(close);
+    out
+}
+
+/// Quote the raw bytes of `name` in `style`.
+pub fn quote_with(name: &OsStr, style: QuotingStyle) -> String {
+    let units = units(name.as_bytes());
+    match style {
+        QuotingStyle::Literal => name.to_string_lossy().to_string(),
+        QuotingStyle::Shell => quote_shell(&units, false, false),
+        QuotingStyle::ShellAlways => quote_shell(&units, true, false),
+        QuotingStyle::ShellEscape => quote_shell(&units, false, true),
+        QuotingStyle::ShellEscapeAlways => quote_shell(&units, true, true),
+        QuotingStyle::C => quote_escaped(&units, "\"", "\"", false),
+        QuotingStyle::Escape => quote_escaped(&units, "", "", true),
+        // Typographic quotes where the locale can show them.
+        QuotingStyle::Locale if utf8_locale() => {
+            quote_escaped(&units, "\u{2018}", "\u{2019}", false)
+        }
+        QuotingStyle::Locale => quote_escaped(&units, "'", "'", false),
+    }
+}
+
+/// Quote a name for a diagnostic, so that it is unambiguous and can be
+/// pasted back into a shell.
+pub fn quote<S: AsRef<OsStr> + ?Sized>(name: &S) -> String {
+    quote_with(name.as_ref(), QuotingStyle::ShellEscapeAlways)
+}
+
+/// Quote a file name for a diagnostic only if it needs it, as GNU does
+/// for names that start a "NAME: error" message.
+pub fn quotef<S: AsRef<OsStr> + ?Sized>(name: &S) -> String {
+    quote_with(name.as_ref(), QuotingStyle::ShellEscape)
+}
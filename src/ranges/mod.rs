@@ -0,0 +1,199 @@
+//! Lists of numbered positions such as `1,3-5,7-`, as cut's `-b`, `-c` and
+//! `-f` and numfmt's `--field` take them, kept as sorted disjoint
+//! intervals so that membership is a binary search.
+
+use std::fmt::{self, Display};
+
+use crate::quote::quote;
+
+/// What the numbers in a list count, which only changes the diagnostics.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Numbering {
+    #[default]
+    Fields,
+    /// Bytes or characters, as cut's `-b` and `-c` count them.
+    Positions,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum RangeErrorKind {
+    /// A zero, or an empty item such as the one in `1,,2`.
+    Zero,
+    /// `3-2`.
+    Decreasing,
+    /// A lone `-` where that does not mean everything.
+    NoEndpoint,
+    /// A second dash, as in `1-2-3`.
+    InvalidRange,
+    /// Something other than digits, dashes and separators, from there on.
+    InvalidValue(String),
+    /// A number that does not fit.
+    TooLarge(String),
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RangeError {
+    pub numbering: Numbering,
+    pub kind: RangeErrorKind,
+}
+
+impl Display for RangeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let fields = self.numbering == Numbering::Fields;
+        match (&self.kind, fields) {
+            (RangeErrorKind::Zero, true) => write!(f, "fields are numbered from 1"),
+            (RangeErrorKind::Zero, false) => {
+                write!(f, "byte/character positions are numbered from 1")
+            }
+            (RangeErrorKind::Decreasing, _) => write!(f, "invalid decreasing range"),
+            (RangeErrorKind::NoEndpoint, _) => write!(f, "invalid range with no endpoint: -"),
+            (RangeErrorKind::InvalidRange, true) => write!(f, "invalid field range"),
+            (RangeErrorKind::InvalidRange, false) => write!(f, "invalid byte or character range"),
+            (RangeErrorKind::InvalidValue(text), true) => {
+                write!(f, "invalid field value {}", quote(text))
+            }
+            (RangeErrorKind::InvalidValue(text), false) => {
+                write!(f, "invalid byte/character position {}", quote(text))
+            }
+            (RangeErrorKind::TooLarge(text), true) => {
+                write!(f, "field number {} is too large", quote(text))
+            }
+            (RangeErrorKind::TooLarge(text), false) => {
+                write!(f, "byte/character offset {} is too large", quote(text))
+            }
+        }
+    }
+}
+
+/// A set of positions counted from 1, open-ended ranges running to
+/// `usize::MAX`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Ranges(Vec<(usize, usize)>);
+
+impl Ranges {
+    /// Every position from 1 on.
+    pub fn all() -> Self {
+        Ranges(vec![(1, usize::MAX)])
+    }
+
+    /// Parse a list of `N`, `N-M`, `N-` and `-M` items separated by commas
+    /// or blanks. With `allow_dash`, a lone `-` means every position, as
+    /// numfmt has it.
+    pub fn parse(spec: &str, numbering: Numbering, allow_dash: bool) -> Result<Self, RangeError> {
+        let error = |kind| RangeError { numbering, kind };
+        let bytes = spec.as_bytes();
+        let mut ranges = Vec::new();
+        let mut value: usize = 0;
+        let mut initial = 1;
+        let (mut lhs, mut rhs, mut dash) = (false, false, false);
+        let mut i = 0;
+        loop {
+            match bytes.get(i) {
+                Some(b'-') => {
+                    if dash {
+                        return Err(error(RangeErrorKind::InvalidRange));
+                    }
+                    if lhs && value == 0 {
+                        return Err(error(RangeErrorKind::Zero));
+                    }
+                    dash = true;
+                    initial = if lhs { value } else { 1 };
+                    value = 0;
+                    i += 1;
+                }
+                None | Some(b',' | b' ' | b'\t') => {
+                    if dash {
+                        match (lhs, rhs) {
+                            (false, false) if allow_dash => ranges.push((1, usize::MAX)),
+                            (false, false) => return Err(error(RangeErrorKind::NoEndpoint)),
+                            (_, false) => ranges.push((initial, usize::MAX)),
+                            _ if value < initial => return Err(error(RangeErrorKind::Decreasing)),
+                            _ => ranges.push((initial, value)),
+                        }
+                    } else if value == 0 {
+                        return Err(error(RangeErrorKind::Zero));
+                    } else {
+                        ranges.push((value, value));
+                    }
+                    if i == bytes.len() {
+                        break;
+                    }
+                    (value, lhs, rhs, dash) = (0, false, false, false);
+                    i += 1;
+                }
+                Some(b) if b.is_ascii_digit() => {
+                    let start = i;
+                    while bytes.get(i).is_some_and(u8::is_ascii_digit) {
+                        // usize::MAX itself is taken for "no end".
+                        value = value
+                            .checked_mul(10)
+                            .and_then(|v| v.checked_add((bytes[i] - b'0') as usize))
+                            .filter(|&v| v != usize::MAX)
+                            .ok_or_else(|| {
+                                let end = bytes[start..]
+                                    .iter()
+                                    .position(|b| !b.is_ascii_digit())
+                                    .map_or(bytes.len(), |n| start + n);
+                                error(RangeErrorKind::TooLarge(spec[start..end].to_string()))
+                            })?;
+                        i += 1;
+                    }
+                    match dash {
+                        true => rhs = true,
+                        false => lhs = true,
+                    }
+                }
+                Some(_) => {
+                    return Err(error(RangeErrorKind::InvalidValue(spec[i..].to_string())));
+                }
+            }
+        }
+        Ok(Ranges::from_intervals(ranges))
+    }
+
+    /// The set covering `intervals`, which may overlap and come in any
+    /// order.
+    pub fn from_intervals(mut intervals: Vec<(usize, usize)>) -> Self {
+        intervals.sort_unstable();
+        let mut merged: Vec<(usize, usize)> = Vec::with_capacity(intervals.len());
+        for (start, end) in intervals {
+            match merged.last_mut() {
+                Some(last) if start <= last.1.saturating_add(1) => last.1 = last.1.max(end),
+                _ => merged.push((start, end)),
+            }
+        }
+        Ranges(merged)
+    }
+
+    pub fn contains(&self, n: usize) -> bool {
+        let i = self.0.partition_point(|&(_, end)| end < n);
+        self.0.get(i).is_some_and(|&(start, _)| start <= n)
+    }
+
+    /// Every position from 1 on that is not in the set, as cut's
+    /// `--complement` selects.
+    pub fn complement(&self) -> Self {
+        let mut gaps = Vec::new();
+        let mut next = 1;
+        for &(start, end) in &self.0 {
+            if start > next {
+                gaps.push((next, start - 1));
+            }
+            next = end.saturating_add(1);
+        }
+        if next < usize::MAX {
+            gaps.push((next, usize::MAX));
+        }
+        Ranges(gaps)
+    }
+
+    /// The disjoint intervals, in order.
+    pub fn intervals(&self) -> &[(usize, usize)] {
+        &self.0
+    }
+
+    /// The last position in the set, `usize::MAX` if it has no end.
+    pub fn max(&self) -> Option<usize> {
+        self.0.last().map(|&(_, end)| end)
+    }
+}
@@ -0,0 +1,411 @@
+//! Removing files and directory trees as rm does.
+//!
+//! Directories are opened and worked on through their descriptors, each
+//! one opened relative to its parent without following symlinks and
+//! checked to be the directory that was examined, so that swapping a
+//! directory for a symlink while the removal runs cannot send it
+//! elsewhere in the file system.
+
+use std::ffi::{CStr, CString, OsStr};
+use std::fmt::{self, Display};
+use std::io;
+use std::os::unix::ffi::OsStrExt;
+use std::path::{Path, PathBuf};
+
+use crate::error::strerror;
+use crate::mode::{FileMode, S_IFDIR, S_IFLNK, S_IFMT, S_IFREG};
+use crate::quote::quote;
+use crate::show_error;
+
+/// A file about to be, or just, removed.
+pub struct Node<'a> {
+    pub path: &'a Path,
+    pub mode: FileMode,
+    pub size: u64,
+    /// Space allocated, in 512-byte blocks.
+    pub blocks: u64,
+    /// Whether the user may write to the file itself. rm asks before
+    /// removing files it may not, symlinks aside.
+    pub writable: bool,
+}
+
+impl Node<'_> {
+    pub fn is_dir(&self) -> bool {
+        self.mode.0 & S_IFMT == S_IFDIR
+    }
+
+    pub fn is_symlink(&self) -> bool {
+        self.mode.0 & S_IFMT == S_IFLNK
+    }
+
+    /// The kind of file, as rm's prompts name it.
+    pub fn description(&self) -> &'static str {
+        match self.mode.0 & S_IFMT == S_IFREG && self.size == 0 {
+            true => "regular empty file",
+            false => self.mode.type_name(),
+        }
+    }
+}
+
+/// What the removal asks of the utility running it: whether to go ahead
+/// at each step, and what to do with what happened.
+pub trait Hooks {
+    /// Whether to go into the directory `node` to remove its contents.
+    fn descend(&mut self, _node: &Node) -> bool {
+        true
+    }
+
+    /// Whether to remove `node`; a directory is asked about once empty.
+    fn remove(&mut self, _node: &Node) -> bool {
+        true
+    }
+
+    /// `node` is gone.
+    fn removed(&mut self, _node: &Node) {}
+
+    fn error(&mut self, error: RemoveError) {
+        show_error!("{}", error);
+    }
+}
+
+/// Hooks that remove everything without asking and report errors.
+pub struct Quiet;
+
+impl Hooks for Quiet {}
+
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Options {
+    /// Remove directories and their contents (`-r`).
+    pub recursive: bool,
+    /// Remove empty directories (`-d`).
+    pub dirs: bool,
+    /// Leave alone directories on other file systems than the operand's.
+    pub one_file_system: bool,
+    /// Refuse to remove `/` recursively.
+    pub preserve_root: bool,
+}
+
+#[derive(Debug)]
+pub enum RemoveErrorKind {
+    Io(io::Error),
+    /// A directory given without `-r` or `-d`.
+    IsDirectory,
+    /// `/` itself, while [`Options::preserve_root`] is set.
+    Root,
+    /// An operand naming `.` or `..`.
+    Dot,
+    /// A directory on another file system, under
+    /// [`Options::one_file_system`].
+    OtherDevice,
+}
+
+#[derive(Debug)]
+pub struct RemoveError {
+    pub path: PathBuf,
+    pub kind: RemoveErrorKind,
+}
+
+impl Display for RemoveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let path = quote(&self.path);
+        match &self.kind {
+            RemoveErrorKind::Io(e) => write!(f, "cannot remove {}: {}", path, strerror(e)),
+            RemoveErrorKind::IsDirectory => write!(f, "cannot remove {}: Is a directory", path),
+            RemoveErrorKind::Root => {
+                write!(f, "it is dangerous to operate recursively on {}", path)
+            }
+            RemoveErrorKind::Dot => write!(
+                f,
+                "refusing to remove '.' or '..' directory: skipping {}",
+                path
+            ),
+            RemoveErrorKind::OtherDevice => {
+                write!(f, "skipping {}, since it's on a different device", path)
+            }
+        }
+    }
+}
+
+/// What `fstatat` says of a file.
+struct Stat {
+    mode: u32,
+    size: u64,
+    blocks: u64,
+    dev: u64,
+    ino: u64,
+}
+
+fn stat_at(dir: libc::c_int, name: &CStr) -> io::Result<Stat> {
+    let mut st: libc::stat = unsafe { std::mem::zeroed() };
+    match unsafe { libc::fstatat(dir, name.as_ptr(), &mut st, libc::AT_SYMLINK_NOFOLLOW) } {
+        0 => Ok(Stat {
+            mode: st.st_mode as u32,
+            size: st.st_size as u64,
+            blocks: st.st_blocks as u64,
+            dev: st.st_dev as u64,
+            ino: st.st_ino as u64,
+        }),
+        _ => Err(io::Error::last_os_error()),
+    }
+}
+
+/// An open directory, closed when dropped.
+struct Dir(*mut libc::DIR);
+
+impl Dir {
+    /// Open the directory `name` in `parent`, provided it is still the one
+    /// `expected` describes.
+    fn open(parent: libc::c_int, name: &CStr, expected: &Stat) -> io::Result<Dir> {
+        let flags = libc::O_RDONLY | libc::O_DIRECTORY | libc::O_NOFOLLOW | libc::O_CLOEXEC;
+        let fd = unsafe { libc::openat(parent, name.as_ptr(), flags) };
+        if fd < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        let mut st: libc::stat = unsafe { std::mem::zeroed() };
+        let same = unsafe { libc::fstat(fd, &mut st) } == 0
+            && st.st_dev as u64 == expected.dev
+            && st.st_ino as u64 == expected.ino;
+        let dir = match same {
+            true => unsafe { libc::fdopendir(fd) },
+            false => std::ptr::null_mut(),
+        };
+        if dir.is_null() {
+            let error = match same {
+                true => io::Error::last_os_error(),
+                // Replaced since it was examined.
+                false => io::Error::from_raw_os_error(libc::ENOENT),
+            };
+            unsafe { libc::close(fd) };
+            return Err(error);
+        }
+        Ok(Dir(dir))
+    }
+
+    fn fd(&self) -> libc::c_int {
+        unsafe { libc::dirfd(self.0) }
+    }
+
+    /// The names of the entries, but for `.` and `..`. They are all read
+    /// before any is removed, as removing entries while reading a
+    /// directory may make it skip others.
+    fn names(&self) -> io::Result<Vec<CString>> {
+        let mut names = vec![];
+        loop {
+            clear_errno();
+            let entry = unsafe { libc::readdir(self.0) };
+            if entry.is_null() {
+                return match io::Error::last_os_error() {
+                    e if e.raw_os_error() == Some(0) => Ok(names),
+                    e => Err(e),
+                };
+            }
+            let name = unsafe { CStr::from_ptr((*entry).d_name.as_ptr()) };
+            if name != c"." && name != c".." {
+                names.push(name.to_owned());
+            }
+        }
+    }
+}
+
+impl Drop for Dir {
+    fn drop(&mut self) {
+        unsafe { libc::closedir(self.0) };
+    }
+}
+
+/// Let a null from readdir be told apart from an error.
+fn clear_errno() {
+    #[cfg(any(target_os = "linux", target_os = "emscripten"))]
+    let errno = unsafe { libc::__errno_location() };
+    #[cfg(target_os = "android")]
+    let errno = unsafe { libc::__errno() };
+    #[cfg(not(any(target_os = "linux", target_os = "emscripten", target_os = "android")))]
+    let errno = unsafe { libc::__error() };
+    unsafe { *errno = 0 };
+}
+
+/// Whether `path` ends in a `.` or `..` component.
+fn is_dot(path: &Path) -> bool {
+    let bytes = path.as_os_str().as_bytes();
+    let trimmed = match bytes.iter().rposition(|&b| b != b'/') {
+        Some(end) => &bytes[..=end],
+        None => return false,
+    };
+    let last = trimmed.rsplit(|&b| b == b'/').next().unwrap_or(trimmed);
+    last == b"." || last == b".."
+}
+
+/// Removes the operands it is given one at a time, asking its [`Hooks`]
+/// before each step.
+pub struct Remover<H: Hooks> {
+    options: Options,
+    hooks: H,
+}
+
+impl<H: Hooks> Remover<H> {
+    pub fn new(options: Options, hooks: H) -> Self {
+        Remover { options, hooks }
+    }
+
+    pub fn hooks(&mut self) -> &mut H {
+        &mut self.hooks
+    }
+
+    pub fn into_hooks(self) -> H {
+        self.hooks
+    }
+
+    /// Remove `path`, and everything under it if it is a directory and
+    /// removal is recursive. Returns false if anything went wrong; files
+    /// the hooks chose to keep are not failures.
+    pub fn remove(&mut self, path: &Path) -> bool {
+        let name = match CString::new(path.as_os_str().as_bytes()) {
+            Ok(name) => name,
+            Err(_) => return self.fail(path, io::ErrorKind::InvalidInput.into()),
+        };
+        let stat = match stat_at(libc::AT_FDCWD, &name) {
+            Ok(stat) => stat,
+            Err(e) => return self.fail(path, e),
+        };
+        if stat.mode & S_IFMT == S_IFDIR {
+            if !self.options.recursive && !self.options.dirs {
+                return self.report(path, RemoveErrorKind::IsDirectory);
+            }
+            if is_dot(path) {
+                return self.report(path, RemoveErrorKind::Dot);
+            }
+            if self.options.recursive && self.options.preserve_root && is_root(&stat) {
+                return self.report(path, RemoveErrorKind::Root);
+            }
+        }
+        self.entry(libc::AT_FDCWD, &name, path, stat, None).ok
+    }
+
+    fn report(&mut self, path: &Path, kind: RemoveErrorKind) -> bool {
+        self.hooks.error(RemoveError {
+            path: path.to_path_buf(),
+            kind,
+        });
+        false
+    }
+
+    fn fail(&mut self, path: &Path, error: io::Error) -> bool {
+        self.report(path, RemoveErrorKind::Io(error))
+    }
+
+    /// Remove `name` in the directory `dir`, which is `path`, from the
+    /// tree on the device `root_dev`.
+    fn entry(
+        &mut self,
+        dir: libc::c_int,
+        name: &CStr,
+        path: &Path,
+        stat: Stat,
+        root_dev: Option<u64>,
+    ) -> Outcome {
+        let is_dir = stat.mode & S_IFMT == S_IFDIR;
+        let writable = match stat.mode & S_IFMT == S_IFLNK {
+            true => true,
+            false => unsafe {
+                libc::faccessat(dir, name.as_ptr(), libc::W_OK, libc::AT_EACCESS) == 0
+            },
+        };
+        let node = Node {
+            path,
+            mode: FileMode(stat.mode),
+            size: stat.size,
+            blocks: stat.blocks,
+            writable,
+        };
+
+        if is_dir && self.options.recursive {
+            if self.options.one_file_system && root_dev.is_some_and(|dev| dev != stat.dev) {
+                self.report(path, RemoveErrorKind::OtherDevice);
+                return Outcome::FAILED;
+            }
+            if !self.hooks.descend(&node) {
+                return Outcome::KEPT;
+            }
+            let contents = self.contents(dir, name, path, &stat);
+            if !contents.removed {
+                return contents;
+            }
+        }
+
+        if !self.hooks.remove(&node) {
+            return Outcome::KEPT;
+        }
+        let flags = if is_dir { libc::AT_REMOVEDIR } else { 0 };
+        match unsafe { libc::unlinkat(dir, name.as_ptr(), flags) } {
+            0 => {
+                self.hooks.removed(&node);
+                Outcome::REMOVED
+            }
+            _ => {
+                self.fail(path, io::Error::last_os_error());
+                Outcome::FAILED
+            }
+        }
+    }
+
+    /// Remove everything in the directory `name` in `dir`.
+    fn contents(&mut self, dir: libc::c_int, name: &CStr, path: &Path, stat: &Stat) -> Outcome {
+        let opened = Dir::open(dir, name, stat).and_then(|d| Ok((d.names()?, d)));
+        let (names, child) = match opened {
+            Ok(opened) => opened,
+            Err(e) => {
+                self.fail(path, e);
+                return Outcome::FAILED;
+            }
+        };
+        let mut outcome = Outcome::REMOVED;
+        for entry in names {
+            let entry_path = path.join(OsStr::from_bytes(entry.to_bytes()));
+            let result = match stat_at(child.fd(), &entry) {
+                Ok(entry_stat) => {
+                    self.entry(child.fd(), &entry, &entry_path, entry_stat, Some(stat.dev))
+                }
+                Err(e) if e.kind() == io::ErrorKind::NotFound => Outcome::REMOVED,
+                Err(e) => {
+                    self.fail(&entry_path, e);
+                    Outcome::FAILED
+                }
+            };
+            outcome.removed &= result.removed;
+            outcome.ok &= result.ok;
+        }
+        outcome
+    }
+}
+
+/// Whether `stat` describes the root directory.
+fn is_root(stat: &Stat) -> bool {
+    stat_at(libc::AT_FDCWD, c"/").is_ok_and(|root| root.dev == stat.dev && root.ino == stat.ino)
+}
+
+/// The result of removing one file or tree.
+#[derive(Clone, Copy)]
+struct Outcome {
+    /// Whether it is gone, so that its directory can go too.
+    removed: bool,
+    /// Whether nothing failed.
+    ok: bool,
+}
+
+impl Outcome {
+    const REMOVED: Outcome = Outcome {
+        removed: true,
+        ok: true,
+    };
+
+    /// Kept because the hooks said so.
+    const KEPT: Outcome = Outcome {
+        removed: false,
+        ok: true,
+    };
+
+    const FAILED: Outcome = Outcome {
+        removed: false,
+        ok: false,
+    };
+}
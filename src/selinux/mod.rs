@@ -0,0 +1,148 @@
+//! SELinux security contexts, which the kernel keeps in the
+//! `security.selinux` extended attribute for files and under
+//! `/proc/thread-self/attr` for processes, and checks and computes through
+//! selinuxfs.
+//!
+//! On systems without SELinux files have no context: reading one gives
+//! `None` and copying does nothing, while setting one fails.
+
+use std::fmt::{self, Display};
+use std::fs::{self, OpenOptions};
+use std::io::{self, Read, Write};
+use std::path::Path;
+use std::sync::OnceLock;
+
+use crate::xattr;
+
+const ATTRIBUTE: &str = "security.selinux";
+
+/// Where selinuxfs is mounted, if it is.
+fn selinuxfs() -> Option<&'static Path> {
+    static MOUNT: OnceLock<Option<&Path>> = OnceLock::new();
+    *MOUNT.get_or_init(|| {
+        ["/sys/fs/selinux", "/selinux"]
+            .into_iter()
+            .map(Path::new)
+            .find(|p| p.join("enforce").exists())
+    })
+}
+
+/// Whether the running kernel enforces or at least tracks SELinux policy,
+/// which it shows by mounting selinuxfs.
+pub fn is_enabled() -> bool {
+    selinuxfs().is_some()
+}
+
+fn not_enabled() -> io::Error {
+    io::Error::new(io::ErrorKind::Unsupported, "SELinux is not enabled")
+}
+
+/// A context's fields, `user:role:type:range`, where the range, which may
+/// itself hold colons, is only there under MLS policy.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Context {
+    pub user: String,
+    pub role: String,
+    pub kind: String,
+    pub range: Option<String>,
+}
+
+impl Context {
+    pub fn parse(text: &str) -> Option<Self> {
+        let mut fields = text.splitn(4, ':');
+        let (user, role, kind) = (fields.next()?, fields.next()?, fields.next()?);
+        Some(Context {
+            user: user.to_string(),
+            role: role.to_string(),
+            kind: kind.to_string(),
+            range: fields.next().map(str::to_string),
+        })
+    }
+}
+
+impl Display for Context {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}:{}", self.user, self.role, self.kind)?;
+        match &self.range {
+            Some(range) => write!(f, ":{}", range),
+            None => Ok(()),
+        }
+    }
+}
+
+/// An attribute's value without the NUL or newline the kernel ends it with.
+fn attribute_text(mut bytes: Vec<u8>) -> String {
+    while matches!(bytes.last(), Some(0 | b'\n')) {
+        bytes.pop();
+    }
+    String::from_utf8_lossy(&bytes).into_owned()
+}
+
+/// The context this process runs in.
+pub fn current() -> io::Result<String> {
+    fs::read("/proc/thread-self/attr/current")
+        .or_else(|_| fs::read("/proc/self/attr/current"))
+        .map(attribute_text)
+}
+
+/// Have the next program this process runs start in `context`.
+pub fn set_exec(context: &str) -> io::Result<()> {
+    let mut value = context.as_bytes().to_vec();
+    value.push(0);
+    fs::write("/proc/thread-self/attr/exec", &value)
+        .or_else(|_| fs::write("/proc/self/attr/exec", &value))
+}
+
+/// Whether the loaded policy knows `context`, failing with `EINVAL` if
+/// not.
+pub fn check(context: &str) -> io::Result<()> {
+    let fs = selinuxfs().ok_or_else(not_enabled)?;
+    let mut value = context.as_bytes().to_vec();
+    value.push(0);
+    fs::write(fs.join("context"), value)
+}
+
+/// The context policy gives an object of `class`, such as `process`,
+/// created by `source` from `target`, as a program run from a file gets.
+pub fn compute_create(source: &str, target: &str, class: &str) -> io::Result<String> {
+    let fs = selinuxfs().ok_or_else(not_enabled)?;
+    let index = fs::read_to_string(fs.join("class").join(class).join("index"))?;
+    let mut create = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .open(fs.join("create"))?;
+    create.write_all(format!("{} {} {}", source, target, index.trim()).as_bytes())?;
+    let mut bytes = Vec::new();
+    create.read_to_end(&mut bytes)?;
+    Ok(attribute_text(bytes))
+}
+
+/// The context of `path`, or of the symlink itself unless `follow`.
+pub fn context(path: &Path, follow: bool) -> io::Result<Option<String>> {
+    if !is_enabled() {
+        return Ok(None);
+    }
+    match xattr::get(path, ATTRIBUTE.as_ref(), follow) {
+        Ok(bytes) => Ok(Some(attribute_text(bytes))),
+        Err(e) if xattr::is_absent(&e) => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+/// Label `path` with `context`.
+pub fn set_context(path: &Path, context: &str, follow: bool) -> io::Result<()> {
+    if !is_enabled() {
+        return Err(not_enabled());
+    }
+    let mut value = context.as_bytes().to_vec();
+    value.push(0);
+    xattr::set(path, ATTRIBUTE.as_ref(), &value, follow)
+}
+
+/// Give `to` the context of `from`, as `cp --preserve=context` does.
+pub fn copy(from: &Path, to: &Path) -> io::Result<()> {
+    match context(from, true)? {
+        Some(context) => set_context(to, &context, true),
+        None => Ok(()),
+    }
+}
@@ -0,0 +1,79 @@
+//! The fast way seq counts when all it is asked for is whole numbers going
+//! up by one in the default format: adding one to the digits of the last
+//! number rather than formatting each number anew, so that numbers of any
+//! size cost the same and the output goes out in large blocks.
+
+use std::cmp::Ordering;
+use std::io::{self, Write};
+
+use crate::io::OUTPUT_BUFFER;
+
+/// `text` as the digits of a number seq can count from: decimal digits
+/// alone, without the zeros it starts with.
+fn digits(text: &str) -> Option<&[u8]> {
+    if text.is_empty() || !text.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    let trimmed = text.trim_start_matches('0');
+    Some(match trimmed.is_empty() {
+        true => b"0",
+        false => trimmed.as_bytes(),
+    })
+}
+
+fn compare(a: &[u8], b: &[u8]) -> Ordering {
+    a.len().cmp(&b.len()).then_with(|| a.cmp(b))
+}
+
+/// Add one to the decimal `number`.
+fn increment(number: &mut Vec<u8>) {
+    for digit in number.iter_mut().rev() {
+        if *digit == b'9' {
+            *digit = b'0';
+        } else {
+            *digit += 1;
+            return;
+        }
+    }
+    number.insert(0, b'1');
+}
+
+/// Write the whole numbers from `first` to `last`, none if `last` is
+/// smaller, with `separator` between them and a newline after the last.
+/// None, without writing anything, if either is not a number of the form
+/// counted this way, for seq to count some other way.
+pub fn count(
+    first: &str,
+    last: &str,
+    separator: &[u8],
+    out: &mut impl Write,
+) -> Option<io::Result<()>> {
+    let (first, last) = (digits(first)?, digits(last)?);
+    Some(count_digits(first, last, separator, out))
+}
+
+fn count_digits(
+    first: &[u8],
+    last: &[u8],
+    separator: &[u8],
+    out: &mut impl Write,
+) -> io::Result<()> {
+    if compare(first, last) == Ordering::Greater {
+        return Ok(());
+    }
+    let mut number = first.to_vec();
+    let mut buf = Vec::with_capacity(OUTPUT_BUFFER + last.len() + separator.len());
+    loop {
+        buf.extend_from_slice(&number);
+        if number == last {
+            buf.push(b'\n');
+            return out.write_all(&buf);
+        }
+        buf.extend_from_slice(separator);
+        if buf.len() >= OUTPUT_BUFFER {
+            out.write_all(&buf)?;
+            buf.clear();
+        }
+        increment(&mut number);
+    }
+}
@@ -0,0 +1,96 @@
+//! Putting lines in random order and choosing some of them at random, as
+//! shuf does, with the randomness read from the system or from a
+//! `--random-source` file. A choice of N lines from a stream is made by
+//! reservoir sampling, so that only N are ever held.
+
+use std::fs::File;
+use std::io::{self, BufReader, Read};
+use std::path::Path;
+
+/// Where random bytes come from.
+pub struct RandomSource {
+    reader: Box<dyn Read>,
+}
+
+impl RandomSource {
+    /// The system's randomness.
+    pub fn system() -> io::Result<Self> {
+        Ok(RandomSource {
+            reader: Box::new(BufReader::new(File::open("/dev/urandom")?)),
+        })
+    }
+
+    /// The bytes of `path`, which fail as unexpected end of file once they
+    /// run out.
+    pub fn from_file(path: &Path) -> io::Result<Self> {
+        Ok(RandomSource {
+            reader: Box::new(BufReader::new(File::open(path)?)),
+        })
+    }
+
+    fn next_u64(&mut self) -> io::Result<u64> {
+        let mut bytes = [0; 8];
+        self.reader.read_exact(&mut bytes)?;
+        Ok(u64::from_le_bytes(bytes))
+    }
+
+    /// A number from 0 up to but not including `n`, each as likely as the
+    /// others. `n` must not be 0.
+    pub fn below(&mut self, n: u64) -> io::Result<u64> {
+        // Values past the last whole multiple of `n` would favor the small
+        // numbers, so they are drawn again.
+        let zone = u64::MAX - (u64::MAX - n + 1) % n;
+        loop {
+            let value = self.next_u64()?;
+            if value <= zone {
+                return Ok(value % n);
+            }
+        }
+    }
+}
+
+/// Put `items` in random order.
+pub fn shuffle<T>(items: &mut [T], random: &mut RandomSource) -> io::Result<()> {
+    for i in (1..items.len()).rev() {
+        let j = random.below(i as u64 + 1)? as usize;
+        items.swap(i, j);
+    }
+    Ok(())
+}
+
+/// A random choice of up to `size` items from however many are pushed,
+/// each as likely to be chosen as the others.
+pub struct Reservoir<T> {
+    size: usize,
+    seen: u64,
+    items: Vec<T>,
+}
+
+impl<T> Reservoir<T> {
+    pub fn new(size: usize) -> Self {
+        Reservoir {
+            size,
+            seen: 0,
+            items: Vec::with_capacity(size.min(1 << 16)),
+        }
+    }
+
+    pub fn push(&mut self, item: T, random: &mut RandomSource) -> io::Result<()> {
+        self.seen += 1;
+        if self.items.len() < self.size {
+            self.items.push(item);
+        } else if self.size > 0 {
+            let i = random.below(self.seen)?;
+            if i < self.size as u64 {
+                self.items[i as usize] = item;
+            }
+        }
+        Ok(())
+    }
+
+    /// The items chosen, in random order.
+    pub fn finish(mut self, random: &mut RandomSource) -> io::Result<Vec<T>> {
+        shuffle(&mut self.items, random)?;
+        Ok(self.items)
+    }
+}
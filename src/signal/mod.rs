@@ -0,0 +1,244 @@
+//! Signal names and numbers, dispositions, masks, and delivering signals
+//! to commands the utilities run.
+
+use std::ffi::CStr;
+use std::io;
+use std::mem;
+use std::ptr;
+use std::time::Duration;
+
+/// The signals known by name, in number order, without their `SIG` prefix.
+pub const SIGNALS: [(&str, i32); 31] = [
+    ("HUP", libc::SIGHUP),
+    ("INT", libc::SIGINT),
+    ("QUIT", libc::SIGQUIT),
+    ("ILL", libc::SIGILL),
+    ("TRAP", libc::SIGTRAP),
+    ("ABRT", libc::SIGABRT),
+    ("BUS", libc::SIGBUS),
+    ("FPE", libc::SIGFPE),
+    ("KILL", libc::SIGKILL),
+    ("USR1", libc::SIGUSR1),
+    ("SEGV", libc::SIGSEGV),
+    ("USR2", libc::SIGUSR2),
+    ("PIPE", libc::SIGPIPE),
+    ("ALRM", libc::SIGALRM),
+    ("TERM", libc::SIGTERM),
+    ("STKFLT", libc::SIGSTKFLT),
+    ("CHLD", libc::SIGCHLD),
+    ("CONT", libc::SIGCONT),
+    ("STOP", libc::SIGSTOP),
+    ("TSTP", libc::SIGTSTP),
+    ("TTIN", libc::SIGTTIN),
+    ("TTOU", libc::SIGTTOU),
+    ("URG", libc::SIGURG),
+    ("XCPU", libc::SIGXCPU),
+    ("XFSZ", libc::SIGXFSZ),
+    ("VTALRM", libc::SIGVTALRM),
+    ("PROF", libc::SIGPROF),
+    ("WINCH", libc::SIGWINCH),
+    ("POLL", libc::SIGIO),
+    ("PWR", libc::SIGPWR),
+    ("SYS", libc::SIGSYS),
+];
+
+/// The signal `s` names: a number, a name from [`SIGNALS`] with or without
+/// `SIG` in any case, or a real-time signal as `RTMIN+n` or `RTMAX-n`.
+pub fn parse(s: &str) -> Option<i32> {
+    if let Ok(number) = s.parse::<i32>() {
+        return (0..=libc::SIGRTMAX()).contains(&number).then_some(number);
+    }
+    let name = s.to_ascii_uppercase();
+    let name = name.strip_prefix("SIG").unwrap_or(&name);
+    // RTMIN+n counts up from the first real-time signal, RTMAX-n down
+    // from the last one.
+    let realtime = |base: i32, offset: &str, sign: char| {
+        let offset = match offset {
+            "" => 0,
+            _ => offset.strip_prefix(sign)?.parse::<i32>().ok()?,
+        };
+        let signal = if sign == '+' {
+            base + offset
+        } else {
+            base - offset
+        };
+        (libc::SIGRTMIN()..=libc::SIGRTMAX())
+            .contains(&signal)
+            .then_some(signal)
+    };
+    if let Some(offset) = name.strip_prefix("RTMIN") {
+        return realtime(libc::SIGRTMIN(), offset, '+');
+    }
+    if let Some(offset) = name.strip_prefix("RTMAX") {
+        return realtime(libc::SIGRTMAX(), offset, '-');
+    }
+    SIGNALS
+        .iter()
+        .find(|(n, _)| *n == name)
+        .map(|(_, sig)| *sig)
+}
+
+/// The name of `signal` without `SIG`, or its number if it has none.
+pub fn name(signal: i32) -> String {
+    if let Some((name, _)) = SIGNALS.iter().find(|(_, sig)| *sig == signal) {
+        return name.to_string();
+    }
+    let (min, max) = (libc::SIGRTMIN(), libc::SIGRTMAX());
+    match signal {
+        _ if signal == min => "RTMIN".to_string(),
+        _ if signal == max => "RTMAX".to_string(),
+        _ if (min..=(min + max) / 2).contains(&signal) => format!("RTMIN+{}", signal - min),
+        _ if (min..max).contains(&signal) => format!("RTMAX-{}", max - signal),
+        _ => signal.to_string(),
+    }
+}
+
+/// Every signal with a name, in number order.
+pub fn named() -> impl Iterator<Item = i32> {
+    SIGNALS
+        .iter()
+        .map(|&(_, signal)| signal)
+        .chain(libc::SIGRTMIN()..=libc::SIGRTMAX())
+}
+
+/// What `signal` means, as strsignal describes it.
+pub fn description(signal: i32) -> String {
+    let text = unsafe { libc::strsignal(signal) };
+    match text.is_null() {
+        true => "?".to_string(),
+        false => unsafe { CStr::from_ptr(text) }
+            .to_string_lossy()
+            .into_owned(),
+    }
+}
+
+fn check(status: libc::c_int) -> io::Result<()> {
+    match status {
+        -1 => Err(io::Error::last_os_error()),
+        _ => Ok(()),
+    }
+}
+
+/// Have `signal` ignored, which also holds in programs `exec`ed later.
+pub fn ignore(signal: i32) -> io::Result<()> {
+    set_handler(signal, libc::SIG_IGN)
+}
+
+/// Give `signal` back its default effect, usually to terminate.
+pub fn restore_default(signal: i32) -> io::Result<()> {
+    set_handler(signal, libc::SIG_DFL)
+}
+
+/// Call `handler` whenever `signal` arrives. The handler may only do what
+/// is async-signal-safe, such as setting an atomic flag.
+pub fn install(signal: i32, handler: extern "C" fn(libc::c_int)) -> io::Result<()> {
+    set_handler(signal, handler as libc::sighandler_t)
+}
+
+fn set_handler(signal: i32, handler: libc::sighandler_t) -> io::Result<()> {
+    unsafe {
+        let mut action: libc::sigaction = mem::zeroed();
+        action.sa_sigaction = handler;
+        action.sa_flags = libc::SA_RESTART;
+        libc::sigemptyset(&mut action.sa_mask);
+        check(libc::sigaction(signal, &action, ptr::null_mut()))
+    }
+}
+
+/// Whether `signal` is being ignored, as `nohup` and shells running
+/// commands in the background leave it.
+pub fn is_ignored(signal: i32) -> bool {
+    unsafe {
+        let mut action: libc::sigaction = mem::zeroed();
+        libc::sigaction(signal, ptr::null(), &mut action) == 0
+            && action.sa_sigaction == libc::SIG_IGN
+    }
+}
+
+/// A set of signals, to block or wait for.
+#[derive(Clone, Copy)]
+pub struct SigSet(libc::sigset_t);
+
+impl SigSet {
+    pub fn empty() -> Self {
+        unsafe {
+            let mut set: libc::sigset_t = mem::zeroed();
+            libc::sigemptyset(&mut set);
+            SigSet(set)
+        }
+    }
+
+    pub fn of(signals: &[i32]) -> Self {
+        let mut set = Self::empty();
+        for &signal in signals {
+            set.add(signal);
+        }
+        set
+    }
+
+    pub fn add(&mut self, signal: i32) {
+        unsafe { libc::sigaddset(&mut self.0, signal) };
+    }
+
+    /// Hold these signals pending until unblocked or waited for.
+    pub fn block(&self) {
+        self.mask(libc::SIG_BLOCK);
+    }
+
+    pub fn unblock(&self) {
+        self.mask(libc::SIG_UNBLOCK);
+    }
+
+    /// Block exactly these signals; a child does this with an empty set
+    /// before `exec` so it does not inherit what its parent blocked.
+    pub fn set_mask(&self) {
+        self.mask(libc::SIG_SETMASK);
+    }
+
+    fn mask(&self, how: libc::c_int) {
+        unsafe { libc::pthread_sigmask(how, &self.0, ptr::null_mut()) };
+    }
+
+    /// Take one of these signals, which must be blocked, once it is
+    /// pending. Fails with [`io::ErrorKind::WouldBlock`] if `timeout` passes
+    /// first, or [`io::ErrorKind::Interrupted`] if another signal comes.
+    pub fn wait(&self, timeout: Option<Duration>) -> io::Result<i32> {
+        let received = match timeout {
+            Some(timeout) => {
+                let timespec = libc::timespec {
+                    tv_sec: timeout.as_secs() as libc::time_t,
+                    tv_nsec: timeout.subsec_nanos() as libc::c_long,
+                };
+                unsafe { libc::sigtimedwait(&self.0, ptr::null_mut(), &timespec) }
+            }
+            None => unsafe { libc::sigwaitinfo(&self.0, ptr::null_mut()) },
+        };
+        match received {
+            -1 => Err(io::Error::last_os_error()),
+            signal => Ok(signal),
+        }
+    }
+}
+
+/// Send `signal` to the process `pid`, or to the process group `-pid`.
+pub fn send(pid: libc::pid_t, signal: i32) -> io::Result<()> {
+    check(unsafe { libc::kill(pid, signal) })
+}
+
+/// Pass `signal` on to a command, as the process `pid` or the group
+/// `-pid`, and wake it should it be stopped so that it can act on it.
+pub fn forward(pid: libc::pid_t, signal: i32) -> io::Result<()> {
+    send(pid, signal)?;
+    if signal != libc::SIGKILL && signal != libc::SIGCONT {
+        send(pid, libc::SIGCONT)?;
+    }
+    Ok(())
+}
+
+/// Terminate by `signal` as a child did, so that whoever waits for us sees
+/// the same status. Returns only if the signal does not terminate.
+pub fn raise_default(signal: i32) {
+    let _ = restore_default(signal);
+    SigSet::of(&[signal]).unblock();
+    unsafe { libc::raise(signal) };
+}
@@ -0,0 +1,206 @@
+use crate::error::errno_text;
+use crate::quote::quote;
+
+/// How a byte count is shown: as is, in units of a block size, or scaled
+/// to a human-readable unit.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SizeFormat {
+    Bytes,
+    Blocks(u64),
+    /// Powers of 1024 with K, M, G... suffixes (`-h`).
+    Binary,
+    /// Powers of 1000 with k, M, G... suffixes (`--si`).
+    Decimal,
+}
+
+const SUFFIXES: [char; 8] = ['K', 'M', 'G', 'T', 'P', 'E', 'Z', 'Y'];
+
+fn div_ceil(n: u128, d: u128) -> u128 {
+    n.div_ceil(d)
+}
+
+/// Scale `size` to the largest unit that keeps it at or above one, rounding
+/// up as GNU does: one decimal place below ten, whole numbers above.
+pub fn human_size(size: u64, base: u64) -> String {
+    let base = base as u128;
+    let size = size as u128;
+    if size < base {
+        return size.to_string();
+    }
+    let mut exponent = 1;
+    while exponent < SUFFIXES.len() && size >= base.pow(exponent as u32 + 1) {
+        exponent += 1;
+    }
+    let suffix = |exponent: usize| match (base, exponent) {
+        (1000, 1) => 'k',
+        _ => SUFFIXES[exponent - 1],
+    };
+    let unit = base.pow(exponent as u32);
+    let tenths = div_ceil(size * 10, unit);
+    if tenths < 100 {
+        return format!("{}.{}{}", tenths / 10, tenths % 10, suffix(exponent));
+    }
+    let whole = div_ceil(size, unit);
+    if whole >= base && exponent < SUFFIXES.len() {
+        return format!("1.0{}", suffix(exponent + 1));
+    }
+    format!("{}{}", whole, suffix(exponent))
+}
+
+pub fn format_size(size: u64, format: SizeFormat) -> String {
+    match format {
+        SizeFormat::Bytes => size.to_string(),
+        SizeFormat::Blocks(block) => div_ceil(size as u128, block.max(1) as u128).to_string(),
+        SizeFormat::Binary => human_size(size, 1024),
+        SizeFormat::Decimal => human_size(size, 1000),
+    }
+}
+
+/// Why a SIZE was refused.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ParseSizeError {
+    Invalid,
+    /// Too large for 64 bits.
+    Overflow,
+}
+
+impl ParseSizeError {
+    /// The diagnostic for `text`, which was given as a `what` such as
+    /// `number of bytes`.
+    pub fn message(&self, what: &str, text: &str) -> String {
+        match self {
+            ParseSizeError::Invalid => format!("invalid {}: {}", what, quote(text)),
+            ParseSizeError::Overflow => format!(
+                "invalid {}: {}: {}",
+                what,
+                quote(text),
+                errno_text(libc::EOVERFLOW)
+            ),
+        }
+    }
+}
+
+/// The power of the base a suffix letter stands for.
+fn power(letter: char) -> Option<u32> {
+    Some(match letter {
+        'k' | 'K' => 1,
+        'm' | 'M' => 2,
+        'g' | 'G' => 3,
+        't' | 'T' => 4,
+        'P' => 5,
+        'E' => 6,
+        'Z' => 7,
+        'Y' => 8,
+        'R' => 9,
+        'Q' => 10,
+        _ => return None,
+    })
+}
+
+/// What `suffix` multiplies a number by: `b` 512, `B` 1024, and for the
+/// other letters powers of 1024 (`K`, `KiB`) or of 1000 (`KB`, `KD`). dd
+/// also knows `c` for bytes and `w` for two-byte words.
+fn multiplier(suffix: &str, dd: bool) -> Result<u64, ParseSizeError> {
+    let mut chars = suffix.chars();
+    let Some(letter) = chars.next() else {
+        return Ok(1);
+    };
+    match (letter, chars.as_str()) {
+        ('b', "") => Ok(512),
+        ('B', "") => Ok(1024),
+        ('c', "") if dd => Ok(1),
+        ('w', "") if dd => Ok(2),
+        (letter, rest) => {
+            let power = power(letter).ok_or(ParseSizeError::Invalid)?;
+            let base: u64 = match rest {
+                "" | "iB" => 1024,
+                "B" | "D" => 1000,
+                _ => return Err(ParseSizeError::Invalid),
+            };
+            base.checked_pow(power).ok_or(ParseSizeError::Overflow)
+        }
+    }
+}
+
+/// One number with an optional suffix; a suffix alone counts one unit.
+fn parse_factor(text: &str, dd: bool) -> Result<u64, ParseSizeError> {
+    let end = text
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(text.len());
+    let (digits, suffix) = text.split_at(end);
+    let multiplier = multiplier(suffix, dd)?;
+    let number = match digits {
+        "" if suffix.is_empty() => return Err(ParseSizeError::Invalid),
+        "" => 1,
+        digits => digits
+            .parse::<u64>()
+            .map_err(|_| ParseSizeError::Overflow)?,
+    };
+    number
+        .checked_mul(multiplier)
+        .ok_or(ParseSizeError::Overflow)
+}
+
+/// Parse a SIZE as head, tail, split and truncate take it: a decimal
+/// number with an optional suffix such as `K`, `MiB` or `GB`.
+pub fn parse_size(text: &str) -> Result<u64, ParseSizeError> {
+    let text = text.trim_start();
+    parse_factor(text.strip_prefix('+').unwrap_or(text), false)
+}
+
+/// Parse a size as dd's operands take it, which may also use the `c` and
+/// `w` suffixes and be a product such as `2x512`.
+pub fn parse_dd_size(text: &str) -> Result<u64, ParseSizeError> {
+    text.split('x').try_fold(1u64, |product, factor| {
+        let factor = parse_factor(factor, true)?;
+        product.checked_mul(factor).ok_or(ParseSizeError::Overflow)
+    })
+}
+
+/// Parse a `--block-size` or `BLOCK_SIZE` value: a SIZE, or
+/// `human-readable` or `si`. A leading `'`, which asks for digit grouping,
+/// is allowed and ignored.
+pub fn parse_block_size(text: &str) -> Result<SizeFormat, ParseSizeError> {
+    let text = text.strip_prefix('\'').unwrap_or(text);
+    match text {
+        "human-readable" => Ok(SizeFormat::Binary),
+        "si" => Ok(SizeFormat::Decimal),
+        text => match parse_size(text)? {
+            0 => Err(ParseSizeError::Invalid),
+            size => Ok(SizeFormat::Blocks(size)),
+        },
+    }
+}
+
+/// Parse sort's `--buffer-size`: a SIZE in kibibytes unless it says
+/// otherwise, where `b` means bytes and `%` a share of physical memory.
+pub fn parse_buffer_size(text: &str) -> Result<u64, ParseSizeError> {
+    if let Some(percent) = text.strip_suffix('%') {
+        if percent.is_empty() || !percent.bytes().all(|b| b.is_ascii_digit()) {
+            return Err(ParseSizeError::Invalid);
+        }
+        let percent: u128 = percent.parse().map_err(|_| ParseSizeError::Overflow)?;
+        if percent > 100 {
+            return Err(ParseSizeError::Overflow);
+        }
+        return Ok((physical_memory() as u128 * percent / 100) as u64);
+    }
+    match text.strip_suffix('b') {
+        Some(digits) if !digits.is_empty() && digits.bytes().all(|b| b.is_ascii_digit()) => {
+            digits.parse().map_err(|_| ParseSizeError::Overflow)
+        }
+        Some(_) => Err(ParseSizeError::Invalid),
+        None if text.ends_with(|c: char| c.is_ascii_digit()) => parse_size(&format!("{}K", text)),
+        None => parse_size(text),
+    }
+}
+
+/// The bytes of memory the machine has, or a guess if it cannot say.
+fn physical_memory() -> u64 {
+    let pages = unsafe { libc::sysconf(libc::_SC_PHYS_PAGES) };
+    let size = unsafe { libc::sysconf(libc::_SC_PAGESIZE) };
+    match (u64::try_from(pages), u64::try_from(size)) {
+        (Ok(pages), Ok(size)) if pages > 0 && size > 0 => pages.saturating_mul(size),
+        _ => 1 << 30,
+    }
+}
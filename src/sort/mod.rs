@@ -0,0 +1,314 @@
+//! Sorting more records than fit in memory, as sort does with a large
+//! input: records are gathered up to a memory budget, sorted on several
+//! threads, and spilled to temporary files as sorted runs, which are merged
+//! at the end at most sixteen at a time, as GNU sort does by default.
+
+use std::cmp::Ordering;
+use std::ffi::{CString, OsString};
+use std::fmt::{self, Display};
+use std::fs::{self, File};
+use std::io::{self, BufReader, BufWriter, Seek, SeekFrom, Write};
+use std::mem;
+use std::ops::Range;
+use std::os::fd::FromRawFd;
+use std::os::unix::ffi::OsStringExt;
+use std::path::PathBuf;
+use std::thread;
+
+use crate::error::strerror;
+use crate::io::Records;
+use crate::quote::quote;
+
+/// The budget when none is given: enough that most inputs never reach a
+/// temporary file.
+pub const DEFAULT_BUDGET: usize = 64 << 20;
+
+/// The most runs merged at once, so that a huge input does not run out of
+/// file descriptors.
+const MERGE: usize = 16;
+
+/// Fewer records than this per thread are not worth a thread.
+const MIN_PER_THREAD: usize = 1 << 12;
+
+/// What a record costs beyond its bytes.
+const OVERHEAD: usize = mem::size_of::<Vec<u8>>();
+
+/// A failure to use a temporary file, displayed as sort reports it.
+#[derive(Debug)]
+pub struct SortError {
+    /// What was being done, such as `cannot create temporary file in '/tmp'`.
+    pub action: String,
+    pub error: io::Error,
+}
+
+impl Display for SortError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.action, strerror(&self.error))
+    }
+}
+
+fn failed(action: String) -> impl FnOnce(io::Error) -> SortError {
+    move |error| SortError { action, error }
+}
+
+/// A sorted run that did not fit in memory.
+struct Run {
+    file: File,
+    path: PathBuf,
+}
+
+/// Records being sorted with `compare`, which must be a total order for
+/// the output to be sorted. Records that compare equal keep their input
+/// order.
+pub struct Sorter<F> {
+    compare: F,
+    budget: usize,
+    threads: usize,
+    tmpdirs: Vec<PathBuf>,
+    terminator: u8,
+    records: Vec<Vec<u8>>,
+    used: usize,
+    runs: Vec<Run>,
+    /// The number of temporary files made, to spread them over `tmpdirs`.
+    spilled: usize,
+}
+
+impl<F> Sorter<F>
+where
+    F: Fn(&[u8], &[u8]) -> Ordering + Sync,
+{
+    pub fn new(compare: F) -> Self {
+        Sorter {
+            compare,
+            budget: DEFAULT_BUDGET,
+            threads: 1,
+            tmpdirs: Vec::new(),
+            terminator: b'\n',
+            records: Vec::new(),
+            used: 0,
+            runs: Vec::new(),
+            spilled: 0,
+        }
+    }
+
+    /// Keep no more than about `bytes` of records in memory (`-S`).
+    pub fn budget(mut self, bytes: usize) -> Self {
+        self.budget = bytes;
+        self
+    }
+
+    /// Sort on up to `threads` threads (`--parallel`).
+    pub fn threads(mut self, threads: usize) -> Self {
+        self.threads = threads.max(1);
+        self
+    }
+
+    /// Put temporary files in `dirs` in turn (`-T`), rather than in
+    /// `TMPDIR` or `/tmp`.
+    pub fn tmpdirs(mut self, dirs: Vec<PathBuf>) -> Self {
+        self.tmpdirs = dirs;
+        self
+    }
+
+    /// End the records in temporary files with `terminator` (`-z`).
+    pub fn terminator(mut self, terminator: u8) -> Self {
+        self.terminator = terminator;
+        self
+    }
+
+    /// Add a record, without its terminator, spilling what has been
+    /// gathered to a temporary file if it is over the budget.
+    pub fn push(&mut self, record: Vec<u8>) -> Result<(), SortError> {
+        self.used += record.len() + OVERHEAD;
+        self.records.push(record);
+        if self.used > self.budget {
+            self.spill()?;
+        }
+        Ok(())
+    }
+
+    /// Hand every record to `output` in order.
+    pub fn finish(
+        mut self,
+        mut output: impl FnMut(&[u8]) -> io::Result<()>,
+    ) -> Result<(), SortError> {
+        let records = self.sorted();
+        if self.runs.is_empty() {
+            for record in records {
+                output(&record).map_err(failed("write failed".to_string()))?;
+            }
+            return Ok(());
+        }
+        // What is still in memory is the last run, and counts toward the
+        // number merged at once.
+        while self.runs.len() >= MERGE {
+            let merged = self.merge_runs(0..MERGE)?;
+            self.runs.insert(0, merged);
+        }
+        let runs = mem::take(&mut self.runs);
+        let mut sources = Vec::with_capacity(runs.len() + 1);
+        for run in runs {
+            sources.push(self.read_run(run)?);
+        }
+        sources.push(Source::Memory(records.into_iter()));
+        merge(sources, &self.compare, |record| {
+            output(&record).map_err(failed("write failed".to_string()))
+        })
+    }
+
+    /// The records gathered so far, sorted, leaving none.
+    fn sorted(&mut self) -> Vec<Vec<u8>> {
+        self.used = 0;
+        let mut records = mem::take(&mut self.records);
+        let threads = self.threads.min(records.len() / MIN_PER_THREAD).max(1);
+        if threads == 1 {
+            records.sort_by(|a, b| (self.compare)(a, b));
+            return records;
+        }
+        let per_thread = records.len().div_ceil(threads);
+        let mut parts = Vec::with_capacity(threads);
+        while records.len() > per_thread {
+            parts.push(records.split_off(records.len() - per_thread));
+        }
+        parts.push(records);
+        parts.reverse();
+        let compare = &self.compare;
+        thread::scope(|scope| {
+            for part in &mut parts {
+                scope.spawn(move || part.sort_by(|a, b| compare(a, b)));
+            }
+        });
+        let total = parts.iter().map(Vec::len).sum();
+        let mut records = Vec::with_capacity(total);
+        let sources = parts
+            .into_iter()
+            .map(|part| Source::Memory(part.into_iter()))
+            .collect();
+        // Nothing here can fail.
+        let _ = merge(sources, compare, |record| {
+            records.push(record);
+            Ok(())
+        });
+        records
+    }
+
+    /// Write what has been gathered to a temporary file as a sorted run.
+    fn spill(&mut self) -> Result<(), SortError> {
+        let records = self.sorted();
+        let run = self.write_run(vec![Source::Memory(records.into_iter())])?;
+        self.runs.push(run);
+        Ok(())
+    }
+
+    /// Merge the runs in `range` into one run.
+    fn merge_runs(&mut self, range: Range<usize>) -> Result<Run, SortError> {
+        let mut sources = Vec::with_capacity(range.len());
+        for run in self.runs.drain(range).collect::<Vec<_>>() {
+            sources.push(self.read_run(run)?);
+        }
+        self.write_run(sources)
+    }
+
+    /// Merge `sources` into a new temporary file, ready to be read back.
+    fn write_run(&mut self, sources: Vec<Source>) -> Result<Run, SortError> {
+        let mut run = self.create_run()?;
+        let action = format!("write failed: {}", quote(&run.path));
+        let write_failed = |error| SortError {
+            action: action.clone(),
+            error,
+        };
+        let mut writer = BufWriter::new(&run.file);
+        merge(sources, &self.compare, |record| {
+            writer
+                .write_all(&record)
+                .and_then(|_| writer.write_all(&[self.terminator]))
+                .map_err(write_failed)
+        })?;
+        writer.flush().map_err(write_failed)?;
+        drop(writer);
+        run.file.seek(SeekFrom::Start(0)).map_err(write_failed)?;
+        Ok(run)
+    }
+
+    fn read_run(&self, run: Run) -> Result<Source, SortError> {
+        Ok(Source::Run(
+            Records::new(BufReader::new(run.file), self.terminator),
+            run.path,
+        ))
+    }
+
+    /// A new temporary file, in the next of the directories. The file is
+    /// removed at once, so that nothing is left behind if sort is killed.
+    fn create_run(&mut self) -> Result<Run, SortError> {
+        let dir = match self.tmpdirs.is_empty() {
+            true => crate::envcfg::tmpdir(),
+            false => self.tmpdirs[self.spilled % self.tmpdirs.len()].clone(),
+        };
+        self.spilled += 1;
+        let cannot_create = failed(format!("cannot create temporary file in {}", quote(&dir)));
+        let template = match CString::new(dir.join("sortXXXXXX").into_os_string().into_vec()) {
+            Ok(template) => template,
+            Err(_) => return Err(cannot_create(io::ErrorKind::InvalidInput.into())),
+        };
+        let mut template = template.into_bytes_with_nul();
+        let fd = unsafe { libc::mkstemp(template.as_mut_ptr().cast()) };
+        if fd < 0 {
+            return Err(cannot_create(io::Error::last_os_error()));
+        }
+        template.pop();
+        let path = PathBuf::from(OsString::from_vec(template));
+        let file = unsafe { File::from_raw_fd(fd) };
+        let _ = fs::remove_file(&path);
+        Ok(Run { file, path })
+    }
+}
+
+/// Sorted records to be merged.
+enum Source {
+    Memory(std::vec::IntoIter<Vec<u8>>),
+    Run(Records<BufReader<File>>, PathBuf),
+}
+
+impl Source {
+    fn next(&mut self) -> Result<Option<Vec<u8>>, SortError> {
+        match self {
+            Source::Memory(records) => Ok(records.next()),
+            Source::Run(records, path) => match records.next() {
+                None => Ok(None),
+                Some(Ok(record)) => Ok(Some(record.bytes)),
+                Some(Err(e)) => Err(failed(format!("read failed: {}", quote(&*path)))(e)),
+            },
+        }
+    }
+}
+
+/// Hand the records of all of `sources` to `output` in order, taking the
+/// record from the earliest source when several compare equal.
+fn merge<F>(
+    sources: Vec<Source>,
+    compare: &F,
+    mut output: impl FnMut(Vec<u8>) -> Result<(), SortError>,
+) -> Result<(), SortError>
+where
+    F: Fn(&[u8], &[u8]) -> Ordering,
+{
+    let mut heads = Vec::with_capacity(sources.len());
+    for mut source in sources {
+        if let Some(record) = source.next()? {
+            heads.push((record, source));
+        }
+    }
+    while !heads.is_empty() {
+        let mut least = 0;
+        for i in 1..heads.len() {
+            if compare(&heads[i].0, &heads[least].0) == Ordering::Less {
+                least = i;
+            }
+        }
+        match heads[least].1.next()? {
+            Some(record) => output(mem::replace(&mut heads[least].0, record))?,
+            None => output(heads.remove(least).0)?,
+        }
+    }
+    Ok(())
+}
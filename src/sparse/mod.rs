@@ -0,0 +1,88 @@
+//! Finding where a file's data is, so that a copy can skip its holes
+//! rather than read them as zeros and write them out again. The kernel
+//! says with SEEK_DATA and SEEK_HOLE where it can; elsewhere the whole file
+//! counts as data.
+
+use std::fs::{File, Metadata};
+use std::io;
+use std::ops::Range;
+use std::os::unix::fs::MetadataExt;
+
+/// Whether `metadata` describes a regular file with fewer blocks than its
+/// size needs, and so with holes, as far as one can tell without looking.
+pub fn looks_sparse(metadata: &Metadata) -> bool {
+    metadata.is_file() && metadata.blocks() * 512 < metadata.size()
+}
+
+/// Whether `buf` is all zeros, as a block that can be left a hole is.
+pub fn is_zero(buf: &[u8]) -> bool {
+    buf.iter().all(|&b| b == 0)
+}
+
+/// The runs of data in the first `size` bytes of `file`, in order. This
+/// moves the file's offset.
+pub fn data(file: &File, size: u64) -> Extents<'_> {
+    Extents {
+        file,
+        offset: 0,
+        size,
+    }
+}
+
+pub struct Extents<'a> {
+    file: &'a File,
+    offset: u64,
+    size: u64,
+}
+
+#[cfg(any(target_os = "linux", target_os = "android", target_os = "freebsd"))]
+fn seek(file: &File, offset: u64, whence: libc::c_int) -> io::Result<u64> {
+    use std::os::fd::AsRawFd;
+    match unsafe { libc::lseek(file.as_raw_fd(), offset as libc::off_t, whence) } {
+        -1 => Err(io::Error::last_os_error()),
+        offset => Ok(offset as u64),
+    }
+}
+
+#[cfg(any(target_os = "linux", target_os = "android", target_os = "freebsd"))]
+const SEEK_DATA: libc::c_int = libc::SEEK_DATA;
+#[cfg(any(target_os = "linux", target_os = "android", target_os = "freebsd"))]
+const SEEK_HOLE: libc::c_int = libc::SEEK_HOLE;
+
+#[cfg(not(any(target_os = "linux", target_os = "android", target_os = "freebsd")))]
+fn seek(_: &File, _: u64, _: libc::c_int) -> io::Result<u64> {
+    Err(io::Error::from_raw_os_error(libc::EINVAL))
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "android", target_os = "freebsd")))]
+const SEEK_DATA: libc::c_int = 3;
+#[cfg(not(any(target_os = "linux", target_os = "android", target_os = "freebsd")))]
+const SEEK_HOLE: libc::c_int = 4;
+
+impl Iterator for Extents<'_> {
+    type Item = io::Result<Range<u64>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.offset >= self.size {
+            return None;
+        }
+        let offset = std::mem::replace(&mut self.offset, self.size);
+        let start = match seek(self.file, offset, SEEK_DATA) {
+            Ok(start) => start,
+            // No data from here on.
+            Err(e) if e.raw_os_error() == Some(libc::ENXIO) => return None,
+            // No way to tell, so all of it may be data.
+            Err(e) if e.raw_os_error() == Some(libc::EINVAL) => return Some(Ok(offset..self.size)),
+            Err(e) => return Some(Err(e)),
+        };
+        if start >= self.size {
+            return None;
+        }
+        let end = match seek(self.file, start, SEEK_HOLE) {
+            Ok(end) => end.clamp(start + 1, self.size),
+            Err(e) => return Some(Err(e)),
+        };
+        self.offset = end;
+        Some(Ok(start..end))
+    }
+}
@@ -0,0 +1,102 @@
+//! The library stdbuf preloads into the command it runs, which sets the
+//! buffering of the standard streams from `_STDBUF_I`, `_STDBUF_O` and
+//! `_STDBUF_E` before `main` starts, as GNU's libstdbuf does. build.rs
+//! compiles it on its own, without the standard library, so that it is
+//! small and loads into any program; it is not a module of the crate.
+
+#![no_std]
+
+use core::ffi::{c_char, c_int, c_void, CStr};
+use core::panic::PanicInfo;
+
+#[repr(C)]
+struct File {
+    _private: [u8; 0],
+}
+
+const IOFBF: c_int = 0;
+const IOLBF: c_int = 1;
+const IONBF: c_int = 2;
+
+#[link(name = "c")]
+extern "C" {
+    static mut stdin: *mut File;
+    static mut stdout: *mut File;
+    static mut stderr: *mut File;
+    fn getenv(name: *const c_char) -> *const c_char;
+    fn setvbuf(stream: *mut File, buf: *mut c_char, mode: c_int, size: usize) -> c_int;
+    fn malloc(size: usize) -> *mut c_void;
+    fn free(ptr: *mut c_void);
+    fn write(fd: c_int, buf: *const c_void, count: usize) -> isize;
+}
+
+#[panic_handler]
+fn panic(_: &PanicInfo) -> ! {
+    loop {}
+}
+
+/// Write a diagnostic to standard error, which may be unbuffered by now.
+fn complain(parts: &[&[u8]]) {
+    for part in parts {
+        unsafe { write(2, part.as_ptr().cast(), part.len()) };
+    }
+}
+
+/// Give `stream` the buffering `mode` asks for: `0` for none, `L` for
+/// lines, or a buffer of that many bytes.
+fn apply(stream: *mut File, name: &[u8], mode: &CStr) {
+    let text = mode.to_bytes();
+    let (mode, size) = match text.first() {
+        Some(b'0') => (IONBF, 0),
+        Some(b'L') => (IOLBF, 0),
+        _ => {
+            let size = text.iter().try_fold(0usize, |size, &b| match b {
+                b'0'..=b'9' => size.checked_mul(10)?.checked_add((b - b'0') as usize),
+                _ => None,
+            });
+            match size {
+                Some(size) if size > 0 => (IOFBF, size),
+                _ => {
+                    complain(&[b"invalid buffering mode ", text, b" for ", name, b"\n"]);
+                    return;
+                }
+            }
+        }
+    };
+    let buf = match size {
+        0 => core::ptr::null_mut(),
+        size => unsafe { malloc(size) },
+    };
+    if size > 0 && buf.is_null() {
+        complain(&[b"failed to allocate a ", text, b" byte stdio buffer\n"]);
+        return;
+    }
+    if unsafe { setvbuf(stream, buf.cast(), mode, size) } != 0 {
+        complain(&[b"could not set buffering of ", name, b" to mode ", text, b"\n"]);
+        unsafe { free(buf) };
+    }
+}
+
+fn mode(name: &CStr) -> Option<&'static CStr> {
+    let value = unsafe { getenv(name.as_ptr()) };
+    (!value.is_null()).then(|| unsafe { CStr::from_ptr(value) })
+}
+
+extern "C" fn init() {
+    unsafe {
+        if let Some(mode) = mode(c"_STDBUF_E") {
+            apply(stderr, b"stderr", mode);
+        }
+        if let Some(mode) = mode(c"_STDBUF_I") {
+            apply(stdin, b"stdin", mode);
+        }
+        if let Some(mode) = mode(c"_STDBUF_O") {
+            apply(stdout, b"stdout", mode);
+        }
+    }
+}
+
+/// Run as the library is loaded, before the program's own code.
+#[used]
+#[link_section = ".init_array"]
+static INIT: extern "C" fn() = init;
@@ -0,0 +1,85 @@
+//! What stdbuf needs to change the buffering of another program's
+//! standard streams: the modes it takes, and the library, preloaded into
+//! the program, that applies them. The library is built with the crate and
+//! carried in the binary, and is written out to a directory of the user's
+//! own the first time it is needed.
+
+use std::fmt::{self, Display};
+use std::fs::{self, DirBuilder};
+use std::io::{self, Write};
+use std::os::unix::fs::{DirBuilderExt, MetadataExt};
+use std::path::PathBuf;
+
+use crate::atomic::{AtomicFile, Durability};
+use crate::size::{parse_size, ParseSizeError};
+
+/// The library, empty where the system cannot preload one.
+static LIBRARY: &[u8] = include_bytes!(concat!(env!("OUT_DIR"), "/libstdbuf.so"));
+
+/// How a stream is to be buffered.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Mode {
+    /// A line at a time.
+    Line,
+    /// In blocks of this many bytes, or not at all for 0.
+    Size(u64),
+}
+
+impl Mode {
+    /// Parse a MODE: `L`, or a SIZE with one of the suffixes `K`, `MB`,
+    /// `GiB` and so on, where 0 means unbuffered.
+    pub fn parse(text: &str) -> Result<Self, ParseSizeError> {
+        if text == "L" {
+            return Ok(Mode::Line);
+        }
+        let number = text.trim_start();
+        let number = number.strip_prefix('+').unwrap_or(number);
+        match number
+            .trim_start_matches(|c: char| c.is_ascii_digit())
+            .chars()
+            .next()
+        {
+            Some(c) if !"EGkKMPQRTYZ".contains(c) => Err(ParseSizeError::Invalid),
+            _ => parse_size(number).map(Mode::Size),
+        }
+    }
+}
+
+/// The mode as the library reads it from the environment.
+impl Display for Mode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Mode::Line => write!(f, "L"),
+            Mode::Size(size) => write!(f, "{}", size),
+        }
+    }
+}
+
+/// The path of the library to preload, written out if it is not there
+/// yet. The directory it goes in is the user's alone, so that no one else
+/// can change what gets preloaded.
+pub fn library() -> io::Result<PathBuf> {
+    if LIBRARY.is_empty() {
+        return Err(io::ErrorKind::Unsupported.into());
+    }
+    let uid = unsafe { libc::geteuid() };
+    let dir = crate::envcfg::tmpdir().join(format!("coreutils-stdbuf-{}", uid));
+    match DirBuilder::new().mode(0o700).create(&dir) {
+        Err(e) if e.kind() != io::ErrorKind::AlreadyExists => return Err(e),
+        _ => {}
+    }
+    let metadata = fs::symlink_metadata(&dir)?;
+    if !metadata.is_dir() || metadata.uid() != uid || metadata.mode() & 0o077 != 0 {
+        return Err(io::ErrorKind::PermissionDenied.into());
+    }
+
+    // Named for its contents, so that a build with another library never
+    // uses this one.
+    let path = dir.join(format!("libstdbuf-{}.so", env!("COREUTILS_STDBUF_HASH")));
+    if !path.is_file() {
+        let mut file = AtomicFile::create(&path, Durability::None)?;
+        file.file().write_all(LIBRARY)?;
+        file.commit()?;
+    }
+    Ok(path)
+}
@@ -0,0 +1,95 @@
+//! The platform-specific parts of the file utilities: metadata in Unix
+//! terms, owner names and symlinks, so that the tools themselves need not
+//! use `std::os::unix` directly.
+
+use std::fs::Metadata;
+use std::io;
+use std::path::Path;
+use std::time::SystemTime;
+
+#[cfg(unix)]
+mod unix;
+#[cfg(windows)]
+mod windows;
+
+#[cfg(unix)]
+use unix as platform;
+#[cfg(windows)]
+use windows as platform;
+
+/// The metadata a listing shows, as Unix describes it. Elsewhere the mode
+/// is made up from the file type and attributes, and the fields with no
+/// counterpart are 0, or 1 for the link count.
+#[derive(Clone, Copy, Debug)]
+pub struct FileInfo {
+    pub mode: u32,
+    pub uid: u32,
+    pub gid: u32,
+    pub nlink: u64,
+    pub size: u64,
+    /// Space allocated, in 512-byte blocks.
+    pub blocks: u64,
+    /// The preferred I/O block size.
+    pub block_size: u64,
+    pub dev: u64,
+    pub inode: u64,
+    pub accessed: Option<SystemTime>,
+    pub modified: Option<SystemTime>,
+    /// The last status change; the last write where there is no such time.
+    pub changed: Option<SystemTime>,
+    /// Creation time, where the platform and file system record it.
+    pub born: Option<SystemTime>,
+    /// The BSD file flags (`chflags`), 0 elsewhere.
+    pub flags: u32,
+}
+
+impl FileInfo {
+    /// Describe `metadata`, which is that of `path`.
+    pub fn new(path: &Path, metadata: &Metadata) -> Self {
+        platform::file_info(path, metadata)
+    }
+}
+
+/// The name of the user owning `path`, if there is one.
+pub fn owner_name(path: &Path, info: &FileInfo) -> Option<String> {
+    platform::owner_name(path, info)
+}
+
+/// The name of the group owning `path`, if there is one.
+pub fn group_name(path: &Path, info: &FileInfo) -> Option<String> {
+    platform::group_name(path, info)
+}
+
+/// BSD file flags and the names `chflags` and `ls -lo` give them.
+const FLAG_NAMES: [(u32, &str); 11] = [
+    (0x0000_0001, "nodump"),
+    (0x0000_0002, "uchg"),
+    (0x0000_0004, "uappnd"),
+    (0x0000_0008, "opaque"),
+    (0x0000_0020, "compressed"),
+    (0x0000_8000, "hidden"),
+    (0x0001_0000, "arch"),
+    (0x0002_0000, "schg"),
+    (0x0004_0000, "sappnd"),
+    (0x0008_0000, "restricted"),
+    (0x0010_0000, "sunlnk"),
+];
+
+/// The file flags as a comma-separated list of names, or `-` if none are
+/// set.
+pub fn flag_names(flags: u32) -> String {
+    let names = FLAG_NAMES
+        .iter()
+        .filter(|(bit, _)| flags & bit != 0)
+        .map(|(_, name)| *name)
+        .collect::<Vec<&str>>();
+    match names.is_empty() {
+        true => "-".to_string(),
+        false => names.join(","),
+    }
+}
+
+/// Create a symbolic link at `link` pointing to `target`.
+pub fn symlink(target: &Path, link: &Path) -> io::Result<()> {
+    platform::symlink(target, link)
+}
@@ -0,0 +1,88 @@
+use std::fs::Metadata;
+use std::io;
+use std::os::unix::fs::{self, MetadataExt};
+use std::path::Path;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use super::FileInfo;
+use crate::usergroup;
+
+/// The BSDs and macOS keep `chflags` flags in the stat structure.
+#[cfg(any(target_os = "macos", target_os = "ios"))]
+fn flags(metadata: &Metadata) -> u32 {
+    std::os::darwin::fs::MetadataExt::st_flags(metadata)
+}
+
+#[cfg(target_os = "freebsd")]
+fn flags(metadata: &Metadata) -> u32 {
+    std::os::freebsd::fs::MetadataExt::st_flags(metadata)
+}
+
+#[cfg(target_os = "netbsd")]
+fn flags(metadata: &Metadata) -> u32 {
+    std::os::netbsd::fs::MetadataExt::st_flags(metadata)
+}
+
+#[cfg(target_os = "openbsd")]
+fn flags(metadata: &Metadata) -> u32 {
+    std::os::openbsd::fs::MetadataExt::st_flags(metadata)
+}
+
+#[cfg(target_os = "dragonfly")]
+fn flags(metadata: &Metadata) -> u32 {
+    std::os::dragonfly::fs::MetadataExt::st_flags(metadata)
+}
+
+#[cfg(not(any(
+    target_os = "macos",
+    target_os = "ios",
+    target_os = "freebsd",
+    target_os = "netbsd",
+    target_os = "openbsd",
+    target_os = "dragonfly"
+)))]
+fn flags(_metadata: &Metadata) -> u32 {
+    0
+}
+
+fn changed(metadata: &Metadata) -> Option<SystemTime> {
+    let seconds = Duration::from_secs(metadata.ctime().unsigned_abs());
+    match metadata.ctime() >= 0 {
+        true => {
+            UNIX_EPOCH.checked_add(seconds + Duration::from_nanos(metadata.ctime_nsec() as u64))
+        }
+        false => UNIX_EPOCH.checked_sub(seconds),
+    }
+}
+
+pub fn file_info(_path: &Path, metadata: &Metadata) -> FileInfo {
+    FileInfo {
+        mode: metadata.mode(),
+        uid: metadata.uid(),
+        gid: metadata.gid(),
+        nlink: metadata.nlink(),
+        size: metadata.size(),
+        blocks: metadata.blocks(),
+        block_size: metadata.blksize(),
+        dev: metadata.dev(),
+        inode: metadata.ino(),
+        accessed: metadata.accessed().ok(),
+        modified: metadata.modified().ok(),
+        changed: changed(metadata),
+        // statx on Linux, st_birthtime on the BSDs and macOS.
+        born: metadata.created().ok(),
+        flags: flags(metadata),
+    }
+}
+
+pub fn owner_name(_path: &Path, info: &FileInfo) -> Option<String> {
+    usergroup::user_name(info.uid)
+}
+
+pub fn group_name(_path: &Path, info: &FileInfo) -> Option<String> {
+    usergroup::group_name(info.gid)
+}
+
+pub fn symlink(target: &Path, link: &Path) -> io::Result<()> {
+    fs::symlink(target, link)
+}
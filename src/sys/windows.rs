@@ -0,0 +1,126 @@
+use std::ffi::OsString;
+use std::fs::Metadata;
+use std::io;
+use std::os::windows::ffi::{OsStrExt, OsStringExt};
+use std::os::windows::fs::{self, MetadataExt};
+use std::path::Path;
+use std::ptr;
+
+use windows_sys::Win32::Foundation::{LocalFree, ERROR_SUCCESS};
+use windows_sys::Win32::Security::Authorization::{GetNamedSecurityInfoW, SE_FILE_OBJECT};
+use windows_sys::Win32::Security::{
+    LookupAccountSidW, GROUP_SECURITY_INFORMATION, OWNER_SECURITY_INFORMATION, PSID,
+};
+use windows_sys::Win32::Storage::FileSystem::FILE_ATTRIBUTE_READONLY;
+
+use super::FileInfo;
+use crate::mode::{S_IFDIR, S_IFLNK, S_IFREG};
+
+/// Extensions Windows runs directly, shown as executable.
+const EXECUTABLE: [&str; 4] = ["exe", "com", "bat", "cmd"];
+
+pub fn file_info(path: &Path, metadata: &Metadata) -> FileInfo {
+    let kind = metadata.file_type();
+    let executable = path
+        .extension()
+        .is_some_and(|e| EXECUTABLE.iter().any(|x| e.eq_ignore_ascii_case(x)));
+    let mut mode = if kind.is_symlink() {
+        S_IFLNK | 0o777
+    } else if kind.is_dir() {
+        S_IFDIR | 0o755
+    } else if executable {
+        S_IFREG | 0o755
+    } else {
+        S_IFREG | 0o644
+    };
+    if metadata.file_attributes() & FILE_ATTRIBUTE_READONLY != 0 && !kind.is_symlink() {
+        mode &= !0o222;
+    }
+    FileInfo {
+        mode,
+        uid: 0,
+        gid: 0,
+        nlink: 1,
+        size: metadata.file_size(),
+        blocks: metadata.file_size().div_ceil(512),
+        block_size: 4096,
+        dev: 0,
+        inode: 0,
+        accessed: metadata.accessed().ok(),
+        modified: metadata.modified().ok(),
+        changed: metadata.modified().ok(),
+        born: metadata.created().ok(),
+        flags: 0,
+    }
+}
+
+/// Look up the account name of the owner or primary group in the
+/// security descriptor of `path`.
+fn account_name(path: &Path, owner: bool) -> Option<String> {
+    let wide = path
+        .as_os_str()
+        .encode_wide()
+        .chain(Some(0))
+        .collect::<Vec<u16>>();
+    let mut owner_sid: PSID = ptr::null_mut();
+    let mut group_sid: PSID = ptr::null_mut();
+    let mut descriptor = ptr::null_mut();
+    let status = unsafe {
+        GetNamedSecurityInfoW(
+            wide.as_ptr(),
+            SE_FILE_OBJECT,
+            OWNER_SECURITY_INFORMATION | GROUP_SECURITY_INFORMATION,
+            &mut owner_sid,
+            &mut group_sid,
+            ptr::null_mut(),
+            ptr::null_mut(),
+            &mut descriptor,
+        )
+    };
+    if status != ERROR_SUCCESS {
+        return None;
+    }
+    let sid = if owner { owner_sid } else { group_sid };
+    let mut name = [0u16; 256];
+    let mut domain = [0u16; 256];
+    let (mut name_len, mut domain_len) = (name.len() as u32, domain.len() as u32);
+    let mut kind = 0;
+    let found = unsafe {
+        LookupAccountSidW(
+            ptr::null(),
+            sid,
+            name.as_mut_ptr(),
+            &mut name_len,
+            domain.as_mut_ptr(),
+            &mut domain_len,
+            &mut kind,
+        )
+    };
+    unsafe { LocalFree(descriptor) };
+    (found != 0).then(|| {
+        OsString::from_wide(&name[..name_len as usize])
+            .to_string_lossy()
+            .to_string()
+    })
+}
+
+pub fn owner_name(path: &Path, _info: &FileInfo) -> Option<String> {
+    account_name(path, true)
+}
+
+pub fn group_name(path: &Path, _info: &FileInfo) -> Option<String> {
+    account_name(path, false)
+}
+
+/// Windows distinguishes links to directories from links to files, so
+/// the link follows whatever the target is now.
+pub fn symlink(target: &Path, link: &Path) -> io::Result<()> {
+    let resolved = match link.parent() {
+        Some(parent) => parent.join(target),
+        None => target.to_path_buf(),
+    };
+    match resolved.is_dir() {
+        true => fs::symlink_dir(target, link),
+        false => fs::symlink_file(target, link),
+    }
+}
@@ -17,6 +17,24 @@ impl TableColumn {
     }
 }
 
+/// What a table cell must be: shown, and able to say how wide it shows
+/// without being formatted to find out.
+pub trait Cell: Display {
+    fn width(&self) -> usize;
+}
+
+impl Cell for String {
+    fn width(&self) -> usize {
+        self.len()
+    }
+}
+
+impl Cell for &str {
+    fn width(&self) -> usize {
+        self.len()
+    }
+}
+
 pub struct TableRow<T, const N: usize> {
     cells: [T; N],
 }
@@ -38,20 +56,25 @@ impl<T, const N: usize> Table<T, N> {
     }
 }
 
-impl<T: Display, const N: usize> Display for Table<T, N> {
+impl<T: Cell, const N: usize> Display for Table<T, N> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let column_sizes = self
             .rows
             .iter()
             .fold(vec![0; self.columns.len()], |mut res, r| {
-                for col in 0..self.columns.len() {
-                    res[col] = cmp::max(res[col], format!("{}", r.cells[col]).len());
+                for (col, size) in res.iter_mut().enumerate() {
+                    *size = cmp::max(*size, r.cells[col].width());
                 }
                 res
             });
 
         for row in self.rows.iter() {
-            for (col, _) in column_sizes.iter().enumerate().take(self.columns.len()) {
+            for (col, _) in column_sizes
+                .iter()
+                .enumerate()
+                .take(self.columns.len())
+                .filter(|(_, &size)| size > 0)
+            {
                 match self.columns[col].alignment {
                     ColumnAlignment::Left => {
                         write!(f, "{:<width$} ", row.cells[col], width = column_sizes[col])?
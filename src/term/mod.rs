@@ -0,0 +1,44 @@
+use std::mem;
+use std::os::unix::io::RawFd;
+
+use crate::envcfg;
+
+pub fn isatty(fd: RawFd) -> bool {
+    unsafe { libc::isatty(fd) == 1 }
+}
+
+pub fn stdin_is_tty() -> bool {
+    isatty(libc::STDIN_FILENO)
+}
+
+pub fn stdout_is_tty() -> bool {
+    isatty(libc::STDOUT_FILENO)
+}
+
+pub fn stderr_is_tty() -> bool {
+    isatty(libc::STDERR_FILENO)
+}
+
+/// Rows and columns of the terminal open on `fd`, if it is one and the
+/// kernel knows its size.
+pub fn window_size(fd: RawFd) -> Option<(u16, u16)> {
+    let mut size: libc::winsize = unsafe { mem::zeroed() };
+    match unsafe { libc::ioctl(fd, libc::TIOCGWINSZ, &mut size) } {
+        0 => Some((size.ws_row, size.ws_col)),
+        _ => None,
+    }
+}
+
+/// The width to lay output out in: the width of the terminal on stdout,
+/// otherwise a positive `COLUMNS`.
+pub fn width() -> Option<usize> {
+    window_size(libc::STDOUT_FILENO)
+        .map(|(_, cols)| cols as usize)
+        .filter(|&cols| cols > 0)
+        .or_else(envcfg::columns)
+}
+
+/// [`width`], or 80 when there is nothing to go by.
+pub fn width_or_default() -> usize {
+    width().unwrap_or(80)
+}
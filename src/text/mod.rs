@@ -0,0 +1,208 @@
+//! Laying text out in columns: how wide it shows, expanding tabs, folding
+//! long lines, finding paragraphs and filling them, as fmt, fold, pr and
+//! `ls -m` do. Widths are in terminal columns, so that wide characters
+//! count twice and combining ones not at all.
+
+use unicode_width::UnicodeWidthChar;
+
+/// The columns `c` takes up; control characters take none.
+pub fn char_width(c: char) -> usize {
+    c.width().unwrap_or(0)
+}
+
+/// The columns `text` takes up, if it contains no tabs or other controls
+/// but for SGR escape sequences such as colored names have, which take
+/// none.
+pub fn width(text: &str) -> usize {
+    let mut width = 0;
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\x1b' => {
+                let _ = chars.by_ref().find(|&c| c == 'm');
+            }
+            c => width += char_width(c),
+        }
+    }
+    width
+}
+
+/// The column after `c` written at `column`, with tab stops every `tab`
+/// columns, a backspace going back one and a carriage return to the start.
+pub fn advance(column: usize, c: char, tab: usize) -> usize {
+    match c {
+        '\t' => column + tab - column % tab,
+        '\x08' => column.saturating_sub(1),
+        '\r' => 0,
+        c => column + char_width(c),
+    }
+}
+
+/// `line` with its tabs replaced by the spaces that reach the next stop.
+pub fn expand_tabs(line: &str, tab: usize) -> String {
+    let mut out = String::with_capacity(line.len());
+    let mut column = 0;
+    for c in line.chars() {
+        let next = advance(column, c, tab);
+        match c {
+            '\t' => out.extend(std::iter::repeat_n(' ', next - column)),
+            c => out.push(c),
+        }
+        column = next;
+    }
+    out
+}
+
+/// `line` cut into pieces no wider than `width` columns, each but the last
+/// ending after its last blank if `at_blanks` and it has one, as fold
+/// does. A character wider than `width` is a piece of its own.
+pub fn fold(line: &str, width: usize, at_blanks: bool) -> Vec<&str> {
+    let mut pieces = Vec::new();
+    let mut start = 0;
+    let mut column = 0;
+    // The end of the last blank in the current piece.
+    let mut blank = None;
+    let mut chars = line.char_indices().peekable();
+    while let Some(&(i, c)) = chars.peek() {
+        let next = advance(column, c, 8);
+        if next > width && i > start {
+            let end = match blank {
+                Some(end) if at_blanks => end,
+                _ => i,
+            };
+            pieces.push(&line[start..end]);
+            start = end;
+            blank = None;
+            column = line[start..i]
+                .chars()
+                .fold(0, |column, c| advance(column, c, 8));
+            continue;
+        }
+        if c == ' ' || c == '\t' {
+            blank = Some(i + c.len_utf8());
+        }
+        column = next;
+        chars.next();
+    }
+    if start < line.len() || pieces.is_empty() {
+        pieces.push(&line[start..]);
+    }
+    pieces
+}
+
+/// Part of a text as fmt sees it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Block<'a> {
+    /// A line that is left as it is: blank, or without the prefix.
+    Verbatim(&'a str),
+    Paragraph(Paragraph<'a>),
+}
+
+/// Lines to be filled together, with the indentation of the first and of
+/// the others, which fmt keeps.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Paragraph<'a> {
+    pub first_indent: &'a str,
+    pub indent: &'a str,
+    pub words: Vec<&'a str>,
+}
+
+fn indentation(line: &str) -> &str {
+    &line[..line.len() - line.trim_start_matches([' ', '\t']).len()]
+}
+
+/// `lines` split into paragraphs, which end at a blank line or where the
+/// indentation changes, though the first line of a paragraph may be
+/// indented differently from the rest. Only lines starting with `prefix`
+/// take part, the prefix left out of the indentation.
+pub fn paragraphs<'a>(lines: &[&'a str], prefix: &str) -> Vec<Block<'a>> {
+    let mut blocks = Vec::new();
+    let mut current: Option<(Paragraph<'a>, usize)> = None;
+    for &line in lines {
+        let body = line
+            .strip_prefix(prefix)
+            .filter(|body| !body.trim().is_empty());
+        let Some(body) = body else {
+            blocks.extend(current.take().map(|(p, _)| Block::Paragraph(p)));
+            blocks.push(Block::Verbatim(line));
+            continue;
+        };
+        let indent = indentation(body);
+        let words = body.split_whitespace();
+        match &mut current {
+            Some((paragraph, count)) if *count == 1 || paragraph.indent == indent => {
+                paragraph.indent = indent;
+                paragraph.words.extend(words);
+                *count += 1;
+            }
+            _ => {
+                blocks.extend(current.take().map(|(p, _)| Block::Paragraph(p)));
+                let paragraph = Paragraph {
+                    first_indent: indent,
+                    indent,
+                    words: words.collect(),
+                };
+                current = Some((paragraph, 1));
+            }
+        }
+    }
+    blocks.extend(current.map(|(p, _)| Block::Paragraph(p)));
+    blocks
+}
+
+/// `words` filled greedily into lines no wider than `width` where they
+/// fit, the first line starting with `first_indent` and the rest with
+/// `indent`.
+pub fn fill(words: &[&str], width: usize, first_indent: &str, indent: &str) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut line = expand_tabs(first_indent, 8);
+    let mut empty = true;
+    for word in words {
+        let needed = width_of(&line) + usize::from(!empty) + self::width(word);
+        if !empty && needed > width {
+            lines.push(std::mem::replace(&mut line, expand_tabs(indent, 8)));
+            empty = true;
+        }
+        if !empty {
+            line.push(' ');
+        }
+        line.push_str(word);
+        empty = false;
+    }
+    if !empty {
+        lines.push(line);
+    }
+    lines
+}
+
+fn width_of(line: &str) -> usize {
+    line.chars().fold(0, |column, c| advance(column, c, 8))
+}
+
+/// `items` joined by `separator` and a space, a line broken after a
+/// separator wherever the next item would reach `width`, as `ls -m` lists
+/// names. None for `width` means never break.
+pub fn join_wrapped(items: &[String], separator: char, width: Option<usize>) -> String {
+    let mut out = String::new();
+    let mut column = 0;
+    for (i, item) in items.iter().enumerate() {
+        let len = self::width(item);
+        if i > 0 {
+            out.push(separator);
+            match width {
+                Some(width) if column + len + 2 >= width => {
+                    out.push('\n');
+                    column = 0;
+                }
+                _ => {
+                    out.push(' ');
+                    column += 2;
+                }
+            }
+        }
+        out.push_str(item);
+        column += len;
+    }
+    out.push('\n');
+    out
+}
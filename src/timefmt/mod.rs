@@ -0,0 +1,336 @@
+use std::ffi::CStr;
+use std::mem;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use time::{OffsetDateTime, UtcOffset};
+
+use crate::envcfg;
+use crate::locale::{is_c_locale, names};
+
+pub const WEEKDAYS: [&str; 7] = [
+    "Sunday",
+    "Monday",
+    "Tuesday",
+    "Wednesday",
+    "Thursday",
+    "Friday",
+    "Saturday",
+];
+
+pub const MONTHS: [&str; 12] = [
+    "January",
+    "February",
+    "March",
+    "April",
+    "May",
+    "June",
+    "July",
+    "August",
+    "September",
+    "October",
+    "November",
+    "December",
+];
+
+/// `time` in the local time zone TZ selects, with the offset in force
+/// then, or in UTC if it cannot be had.
+pub fn local_time(time: OffsetDateTime) -> OffsetDateTime {
+    ZonedTime::new(time.unix_timestamp_nanos(), false).map_or(time, |zoned| zoned.time)
+}
+
+/// A point in time together with the offset and zone name it is shown in.
+pub struct ZonedTime {
+    pub time: OffsetDateTime,
+    pub zone: String,
+}
+
+impl ZonedTime {
+    /// The time `nanos` after the epoch, in UTC or in the zone TZ selects.
+    pub fn new(nanos: i128, utc: bool) -> Option<Self> {
+        let utc_time = OffsetDateTime::from_unix_timestamp_nanos(nanos).ok()?;
+        if utc {
+            return Some(ZonedTime {
+                time: utc_time,
+                zone: "UTC".to_string(),
+            });
+        }
+        let seconds = utc_time.unix_timestamp() as libc::time_t;
+        let mut tm: libc::tm = unsafe { mem::zeroed() };
+        if unsafe { libc::localtime_r(&seconds, &mut tm) }.is_null() {
+            return Some(ZonedTime {
+                time: utc_time,
+                zone: "UTC".to_string(),
+            });
+        }
+        let offset = UtcOffset::from_whole_seconds(tm.tm_gmtoff as i32).unwrap_or(UtcOffset::UTC);
+        let zone = match tm.tm_zone.is_null() {
+            true => String::new(),
+            false => unsafe { CStr::from_ptr(tm.tm_zone) }
+                .to_string_lossy()
+                .to_string(),
+        };
+        Some(ZonedTime {
+            time: utc_time.to_offset(offset),
+            zone,
+        })
+    }
+
+    pub fn from_system_time(time: SystemTime) -> Option<Self> {
+        let nanos = match time.duration_since(UNIX_EPOCH) {
+            Ok(d) => d.as_nanos() as i128,
+            Err(e) => -(e.duration().as_nanos() as i128),
+        };
+        Self::new(nanos, false)
+    }
+
+    pub fn unix_timestamp_nanos(&self) -> i128 {
+        self.time.unix_timestamp_nanos()
+    }
+}
+
+struct Spec {
+    pad: Option<char>,
+    width: Option<usize>,
+    upper: bool,
+    swap_case: bool,
+}
+
+fn number(value: i64, default_width: usize, default_pad: char, spec: &Spec) -> String {
+    let digits = value.unsigned_abs().to_string();
+    let sign = if value < 0 { "-" } else { "" };
+    let width = spec.width.unwrap_or(default_width);
+    match spec.pad.unwrap_or(default_pad) {
+        '-' => format!("{}{}", sign, digits),
+        '_' | ' ' => format!("{:>w$}", format!("{}{}", sign, digits), w = width),
+        _ => format!(
+            "{}{:0>w$}",
+            sign,
+            digits,
+            w = width.saturating_sub(sign.len())
+        ),
+    }
+}
+
+fn offset_string(offset: UtcOffset, colons: usize) -> String {
+    let seconds = offset.whole_seconds();
+    let sign = if seconds < 0 { '-' } else { '+' };
+    let seconds = seconds.unsigned_abs();
+    let (h, m, s) = (seconds / 3600, (seconds % 3600) / 60, seconds % 60);
+    match colons {
+        0 => format!("{}{:02}{:02}", sign, h, m),
+        1 => format!("{}{:02}:{:02}", sign, h, m),
+        2 => format!("{}{:02}:{:02}:{:02}", sign, h, m, s),
+        _ if s != 0 => format!("{}{:02}:{:02}:{:02}", sign, h, m, s),
+        _ if m != 0 => format!("{}{:02}:{:02}", sign, h, m),
+        _ => format!("{}{:02}", sign, h),
+    }
+}
+
+/// Render `format` with the strftime conversions GNU date supports,
+/// including the `-`, `_`, `0`, `^` and `#` flags and field widths.
+pub fn strftime(format: &str, zoned: &ZonedTime) -> String {
+    let t = &zoned.time;
+    let names = names();
+    let chars = format.chars().collect::<Vec<char>>();
+    let mut out = String::new();
+    let mut i = 0;
+    while i < chars.len() {
+        if chars[i] != '%' || i + 1 == chars.len() {
+            out.push(chars[i]);
+            i += 1;
+            continue;
+        }
+        let start = i;
+        i += 1;
+        let mut spec = Spec {
+            pad: None,
+            width: None,
+            upper: false,
+            swap_case: false,
+        };
+        while let Some(&flag) = chars.get(i) {
+            match flag {
+                '-' | '_' | '0' => spec.pad = Some(flag),
+                '^' => spec.upper = true,
+                '#' => spec.swap_case = true,
+                _ => break,
+            }
+            i += 1;
+        }
+        let width_start = i;
+        while chars.get(i).is_some_and(|c| c.is_ascii_digit()) {
+            i += 1;
+        }
+        if i > width_start {
+            spec.width = chars[width_start..i]
+                .iter()
+                .collect::<String>()
+                .parse()
+                .ok();
+        }
+        let mut colons = 0;
+        while chars.get(i) == Some(&':') {
+            colons += 1;
+            i += 1;
+        }
+        let Some(&conversion) = chars.get(i) else {
+            out.extend(&chars[start..]);
+            break;
+        };
+        i += 1;
+
+        let weekday = t.weekday().number_days_from_sunday() as i64;
+        let yday = t.ordinal() as i64 - 1;
+        let hour12 = match t.hour() % 12 {
+            0 => 12,
+            h => h as i64,
+        };
+        let text = |s: &str| -> (String, bool) { (s.to_string(), true) };
+        let (mut value, is_text) = match conversion {
+            '%' => text("%"),
+            'a' => text(&names.abbreviated_weekdays[weekday as usize]),
+            'A' => text(&names.weekdays[weekday as usize]),
+            'b' | 'h' => text(&names.abbreviated_months[t.month() as usize - 1]),
+            'B' => text(&names.months[t.month() as usize - 1]),
+            'c' => text(&strftime("%a %b %e %H:%M:%S %Y", zoned)),
+            'C' => (number(t.year() as i64 / 100, 2, '0', &spec), false),
+            'd' => (number(t.day() as i64, 2, '0', &spec), false),
+            'D' | 'x' => text(&strftime("%m/%d/%y", zoned)),
+            'e' => (number(t.day() as i64, 2, '_', &spec), false),
+            'F' => text(&strftime("%Y-%m-%d", zoned)),
+            'g' => (
+                number(t.to_iso_week_date().0 as i64 % 100, 2, '0', &spec),
+                false,
+            ),
+            'G' => (number(t.to_iso_week_date().0 as i64, 4, '0', &spec), false),
+            'H' => (number(t.hour() as i64, 2, '0', &spec), false),
+            'I' => (number(hour12, 2, '0', &spec), false),
+            'j' => (number(t.ordinal() as i64, 3, '0', &spec), false),
+            'k' => (number(t.hour() as i64, 2, '_', &spec), false),
+            'l' => (number(hour12, 2, '_', &spec), false),
+            'm' => (number(t.month() as i64, 2, '0', &spec), false),
+            'M' => (number(t.minute() as i64, 2, '0', &spec), false),
+            'n' => text("\n"),
+            'N' => {
+                let digits = format!("{:09}", t.nanosecond());
+                let value = match spec.width {
+                    Some(w) if w < 9 => digits[..w].to_string(),
+                    Some(w) => format!("{:0<w$}", digits),
+                    None => digits,
+                };
+                (value, false)
+            }
+            'p' => text(if t.hour() < 12 { "AM" } else { "PM" }),
+            'P' => text(if t.hour() < 12 { "am" } else { "pm" }),
+            'q' => (number((t.month() as i64 - 1) / 3 + 1, 1, '0', &spec), false),
+            'r' => text(&strftime("%I:%M:%S %p", zoned)),
+            'R' => text(&strftime("%H:%M", zoned)),
+            's' => (number(t.unix_timestamp(), 1, '0', &spec), false),
+            'S' => (number(t.second() as i64, 2, '0', &spec), false),
+            't' => text("\t"),
+            'T' | 'X' => text(&strftime("%H:%M:%S", zoned)),
+            'u' => (
+                number(t.weekday().number_from_monday() as i64, 1, '0', &spec),
+                false,
+            ),
+            'U' => (number((yday + 7 - weekday) / 7, 2, '0', &spec), false),
+            'V' => (number(t.iso_week() as i64, 2, '0', &spec), false),
+            'w' => (number(weekday, 1, '0', &spec), false),
+            'W' => (
+                number((yday + 7 - (weekday + 6) % 7) / 7, 2, '0', &spec),
+                false,
+            ),
+            'y' => (number(t.year() as i64 % 100, 2, '0', &spec), false),
+            'Y' => (number(t.year() as i64, 1, '0', &spec), false),
+            'z' => text(&offset_string(t.offset(), colons)),
+            'Z' => text(&zoned.zone),
+            _ => {
+                out.extend(&chars[start..i]);
+                continue;
+            }
+        };
+        if is_text {
+            if let Some(width) = spec.width {
+                value = match spec.pad {
+                    Some('0') => format!("{:0>width$}", value),
+                    Some('-') => value,
+                    _ => format!("{:>width$}", value),
+                };
+            }
+        }
+        if spec.upper {
+            value = value.to_uppercase();
+        } else if spec.swap_case {
+            value = match value.chars().any(|c| c.is_lowercase()) {
+                true => value.to_uppercase(),
+                false => value.to_lowercase(),
+            };
+        }
+        out.push_str(&value);
+    }
+    out
+}
+
+/// Roughly six months, the age beyond which ls shows the year instead of
+/// the time of day.
+const RECENT_SECONDS: i128 = 31_556_952 / 2;
+
+/// A pair of strftime formats for recent and older timestamps, as chosen by
+/// `--time-style` or `TIME_STYLE`.
+pub struct TimeStyle {
+    pub recent: String,
+    pub old: String,
+}
+
+impl TimeStyle {
+    /// Parse a style keyword (`full-iso`, `long-iso`, `iso`, `locale`, each
+    /// optionally prefixed with `posix-`) or `+FORMAT`, where a newline
+    /// separates the format for old files from the one for recent files.
+    pub fn parse(style: &str) -> Result<Self, String> {
+        let both = |format: &str| TimeStyle {
+            recent: format.to_string(),
+            old: format.to_string(),
+        };
+        if let Some(format) = style.strip_prefix('+') {
+            return Ok(match format.split_once('\n') {
+                Some((old, recent)) => TimeStyle {
+                    recent: recent.to_string(),
+                    old: old.to_string(),
+                },
+                None => both(format),
+            });
+        }
+        // In the C locale the posix- variants fall back to the locale style.
+        let style = match style.strip_prefix("posix-") {
+            Some(_) if is_c_locale(libc::LC_TIME) => "locale",
+            Some(style) => style,
+            None => style,
+        };
+        match style {
+            "full-iso" => Ok(both("%Y-%m-%d %H:%M:%S.%N %z")),
+            "long-iso" => Ok(both("%Y-%m-%d %H:%M")),
+            "iso" => Ok(TimeStyle {
+                recent: "%m-%d %H:%M".to_string(),
+                old: "%Y-%m-%d ".to_string(),
+            }),
+            "locale" => Ok(TimeStyle {
+                recent: "%b %e %H:%M".to_string(),
+                old: "%b %e  %Y".to_string(),
+            }),
+            _ => Err(format!("invalid argument '{}' for 'time style'", style)),
+        }
+    }
+
+    /// The style named by `TIME_STYLE`, or the locale style.
+    pub fn from_env() -> Result<Self, String> {
+        Self::parse(&envcfg::time_style().unwrap_or_else(|| "locale".to_string()))
+    }
+
+    /// Format `time`, using the recent format if it is no more than six
+    /// months before `now` and not in the future.
+    pub fn format(&self, time: &ZonedTime, now: i128) -> String {
+        let age = now - time.unix_timestamp_nanos();
+        let recent = (0..=RECENT_SECONDS * 1_000_000_000).contains(&age);
+        strftime(if recent { &self.recent } else { &self.old }, time)
+    }
+}
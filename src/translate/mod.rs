@@ -0,0 +1,128 @@
+//! What tr does once its SETs are expanded to bytes: translating,
+//! deleting and squeezing them through tables of all 256 bytes, so that
+//! the input is handled a block at a time and never decoded.
+
+use std::io::{self, Read, Write};
+
+/// The bytes not in `set`, in order, as `-c` makes SET1.
+pub fn complement(set: &[u8]) -> Vec<u8> {
+    let mut present = [false; 256];
+    for &b in set {
+        present[b as usize] = true;
+    }
+    (0..=255).filter(|&b| !present[b as usize]).collect()
+}
+
+/// A table of what each byte becomes.
+pub struct Translator {
+    map: [u8; 256],
+    delete: [bool; 256],
+    squeeze: [bool; 256],
+    /// The last byte written, which a squeezed byte is not repeated after.
+    last: Option<u8>,
+}
+
+impl Default for Translator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Translator {
+    /// A table that leaves every byte as it is.
+    pub fn new() -> Self {
+        Translator {
+            map: std::array::from_fn(|b| b as u8),
+            delete: [false; 256],
+            squeeze: [false; 256],
+            last: None,
+        }
+    }
+
+    /// Translate each byte of `from` to the byte in the same place in
+    /// `to`, which is stretched to length by repeating its last byte, as
+    /// GNU does, unless `truncate` asks for `from` to be cut to its length
+    /// instead (`-t`). Of the bytes a repeated byte in `from` is given,
+    /// the last wins.
+    pub fn translate(mut self, from: &[u8], to: &[u8], truncate: bool) -> Self {
+        let Some(&fill) = to.last() else {
+            return self;
+        };
+        let from = match truncate {
+            true => &from[..from.len().min(to.len())],
+            false => from,
+        };
+        for (i, &b) in from.iter().enumerate() {
+            self.map[b as usize] = to.get(i).copied().unwrap_or(fill);
+        }
+        self
+    }
+
+    /// Delete the bytes of `set` (`-d`).
+    pub fn delete(mut self, set: &[u8]) -> Self {
+        for &b in set {
+            self.delete[b as usize] = true;
+        }
+        self
+    }
+
+    /// Write a run of any byte of `set` once (`-s`), looking at the bytes
+    /// after they are translated.
+    pub fn squeeze(mut self, set: &[u8]) -> Self {
+        for &b in set {
+            self.squeeze[b as usize] = true;
+        }
+        self
+    }
+
+    /// Whether the table only maps bytes to bytes, so that a block can be
+    /// changed where it is.
+    fn maps_only(&self) -> bool {
+        !self.delete.contains(&true) && !self.squeeze.contains(&true)
+    }
+
+    /// Append what `input` becomes to `output`. A squeezed run may carry
+    /// on from the end of the previous input.
+    pub fn process(&mut self, input: &[u8], output: &mut Vec<u8>) {
+        output.reserve(input.len());
+        for &b in input {
+            if self.delete[b as usize] {
+                continue;
+            }
+            let b = self.map[b as usize];
+            if self.squeeze[b as usize] && self.last == Some(b) {
+                continue;
+            }
+            output.push(b);
+            self.last = Some(b);
+        }
+    }
+
+    /// Translate everything `reader` holds to `writer`.
+    pub fn copy(&mut self, reader: &mut impl Read, writer: &mut impl Write) -> io::Result<()> {
+        let maps_only = self.maps_only();
+        let mut buf = vec![0; 128 << 10];
+        let mut output = Vec::new();
+        loop {
+            let n = match reader.read(&mut buf) {
+                Ok(0) => return Ok(()),
+                Ok(n) => n,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e),
+            };
+            match maps_only {
+                true => {
+                    for b in &mut buf[..n] {
+                        *b = self.map[*b as usize];
+                    }
+                    writer.write_all(&buf[..n])?;
+                }
+                false => {
+                    output.clear();
+                    self.process(&buf[..n], &mut output);
+                    writer.write_all(&output)?;
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,48 @@
+use std::ffi::CStr;
+use std::io;
+use std::mem::MaybeUninit;
+
+pub struct UtsName {
+    pub sysname: String,
+    pub nodename: String,
+    pub release: String,
+    pub version: String,
+    pub machine: String,
+}
+
+fn field(chars: &[libc::c_char]) -> String {
+    unsafe { CStr::from_ptr(chars.as_ptr()) }
+        .to_string_lossy()
+        .to_string()
+}
+
+/// Query the kernel for its identification via uname(2).
+pub fn uname() -> io::Result<UtsName> {
+    let mut name = MaybeUninit::<libc::utsname>::uninit();
+    if unsafe { libc::uname(name.as_mut_ptr()) } != 0 {
+        return Err(io::Error::last_os_error());
+    }
+    let name = unsafe { name.assume_init() };
+    Ok(UtsName {
+        sysname: field(&name.sysname),
+        nodename: field(&name.nodename),
+        release: field(&name.release),
+        version: field(&name.version),
+        machine: field(&name.machine),
+    })
+}
+
+/// The operating system name as GNU uname -o reports it.
+pub fn operating_system() -> &'static str {
+    if cfg!(target_os = "linux") && cfg!(target_env = "gnu") {
+        "GNU/Linux"
+    } else if cfg!(target_os = "linux") {
+        "Linux"
+    } else if cfg!(target_os = "macos") {
+        "Darwin"
+    } else if cfg!(target_os = "freebsd") {
+        "FreeBSD"
+    } else {
+        std::env::consts::OS
+    }
+}
@@ -0,0 +1,92 @@
+use std::collections::HashMap;
+use std::ffi::{CString, OsStr};
+use std::hash::Hash;
+use std::os::unix::ffi::OsStrExt;
+use std::sync::{Mutex, OnceLock};
+
+use users::{get_group_by_gid, get_group_by_name, get_user_by_name, get_user_by_uid};
+
+/// A lookup table that remembers misses as well as hits, since a listing
+/// full of files owned by a deleted user would otherwise query the
+/// database for every one of them.
+struct Cache<K, V>(OnceLock<Mutex<HashMap<K, Option<V>>>>);
+
+impl<K: Eq + Hash, V: Clone> Cache<K, V> {
+    const fn new() -> Self {
+        Cache(OnceLock::new())
+    }
+
+    fn get(&self, key: K, lookup: impl FnOnce(&K) -> Option<V>) -> Option<V> {
+        let mut map = self
+            .0
+            .get_or_init(Default::default)
+            .lock()
+            .unwrap_or_else(|e| e.into_inner());
+        map.entry(key).or_insert_with_key(|k| lookup(k)).clone()
+    }
+}
+
+static USER_NAMES: Cache<u32, String> = Cache::new();
+static GROUP_NAMES: Cache<u32, String> = Cache::new();
+static USER_IDS: Cache<String, u32> = Cache::new();
+static GROUP_IDS: Cache<String, u32> = Cache::new();
+
+/// The login name of `uid`, if the user database has one.
+pub fn user_name(uid: u32) -> Option<String> {
+    USER_NAMES.get(uid, |&uid| {
+        get_user_by_uid(uid).map(|u| u.name().to_string_lossy().to_string())
+    })
+}
+
+/// The name of `gid`, if the group database has one.
+pub fn group_name(gid: u32) -> Option<String> {
+    GROUP_NAMES.get(gid, |&gid| {
+        get_group_by_gid(gid).map(|g| g.name().to_string_lossy().to_string())
+    })
+}
+
+/// The uid `spec` names: a user name, or failing that a numeric ID, as
+/// chown accepts.
+pub fn user_id(spec: &str) -> Option<u32> {
+    USER_IDS.get(spec.to_string(), |spec| {
+        get_user_by_name(spec)
+            .map(|u| u.uid())
+            .or_else(|| spec.parse().ok())
+    })
+}
+
+/// The gid `spec` names: a group name, or failing that a numeric ID, as
+/// chgrp accepts.
+pub fn group_id(spec: &str) -> Option<u32> {
+    GROUP_IDS.get(spec.to_string(), |spec| {
+        get_group_by_name(spec)
+            .map(|g| g.gid())
+            .or_else(|| spec.parse().ok())
+    })
+}
+
+/// The groups `name` belongs to, with `gid` among them, as the group
+/// database lists them.
+pub fn user_groups(name: &OsStr, gid: u32) -> Vec<u32> {
+    let name = CString::new(name.as_bytes()).unwrap();
+    let mut count: libc::c_int = 64;
+    loop {
+        let mut groups = vec![0; count as usize];
+        let previous = count;
+        let res = unsafe {
+            libc::getgrouplist(
+                name.as_ptr(),
+                gid as _,
+                groups.as_mut_ptr() as _,
+                &mut count,
+            )
+        };
+        if res >= 0 {
+            groups.truncate(count as usize);
+            return groups;
+        }
+        if count <= previous {
+            count = previous * 2;
+        }
+    }
+}
@@ -0,0 +1,122 @@
+use std::ffi::CString;
+use std::fs::{self, Metadata};
+use std::os::unix::ffi::OsStrExt;
+use std::path::Path;
+
+use time::OffsetDateTime;
+
+pub const UTMP_FILE: &str = "/var/run/utmp";
+pub const WTMP_FILE: &str = "/var/log/wtmp";
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum RecordType {
+    Empty,
+    RunLevel,
+    BootTime,
+    NewTime,
+    OldTime,
+    InitProcess,
+    LoginProcess,
+    UserProcess,
+    DeadProcess,
+    Accounting,
+}
+
+impl From<libc::c_short> for RecordType {
+    fn from(value: libc::c_short) -> Self {
+        match value {
+            libc::RUN_LVL => RecordType::RunLevel,
+            libc::BOOT_TIME => RecordType::BootTime,
+            libc::NEW_TIME => RecordType::NewTime,
+            libc::OLD_TIME => RecordType::OldTime,
+            libc::INIT_PROCESS => RecordType::InitProcess,
+            libc::LOGIN_PROCESS => RecordType::LoginProcess,
+            libc::USER_PROCESS => RecordType::UserProcess,
+            libc::DEAD_PROCESS => RecordType::DeadProcess,
+            libc::ACCOUNTING => RecordType::Accounting,
+            _ => RecordType::Empty,
+        }
+    }
+}
+
+pub struct UtmpRecord {
+    pub record_type: RecordType,
+    pub pid: i32,
+    pub line: String,
+    pub id: String,
+    pub user: String,
+    pub host: String,
+    pub time: OffsetDateTime,
+    pub exit_status: (i16, i16),
+}
+
+impl UtmpRecord {
+    /// A record describing a user currently logged in.
+    pub fn is_user_process(&self) -> bool {
+        self.record_type == RecordType::UserProcess && !self.user.is_empty()
+    }
+
+    /// Metadata of the terminal device the login is attached to.
+    pub fn tty_metadata(&self) -> Option<Metadata> {
+        fs::metadata(format!("/dev/{}", self.line)).ok()
+    }
+
+    /// Whether the process that owns this login still exists.
+    pub fn is_alive(&self) -> bool {
+        self.pid <= 0
+            || unsafe { libc::kill(self.pid, 0) } == 0
+            || std::io::Error::last_os_error().raw_os_error() == Some(libc::EPERM)
+    }
+}
+
+fn field(chars: &[libc::c_char]) -> String {
+    let bytes = chars
+        .iter()
+        .map(|&c| c as u8)
+        .take_while(|&c| c != 0)
+        .collect::<Vec<u8>>();
+    String::from_utf8_lossy(&bytes).to_string()
+}
+
+impl From<&libc::utmpx> for UtmpRecord {
+    fn from(entry: &libc::utmpx) -> Self {
+        let nanos =
+            entry.ut_tv.tv_sec as i128 * 1_000_000_000 + entry.ut_tv.tv_usec as i128 * 1_000;
+        UtmpRecord {
+            record_type: entry.ut_type.into(),
+            pid: entry.ut_pid,
+            line: field(&entry.ut_line),
+            id: field(&entry.ut_id),
+            user: field(&entry.ut_user),
+            host: field(&entry.ut_host),
+            time: OffsetDateTime::from_unix_timestamp_nanos(nanos)
+                .unwrap_or(OffsetDateTime::UNIX_EPOCH),
+            exit_status: (entry.ut_exit.e_termination, entry.ut_exit.e_exit),
+        }
+    }
+}
+
+/// Read every record of a utmp/wtmp style file. A missing or unreadable file
+/// yields no records, matching the behaviour of the C library.
+pub fn read_utmp(path: &Path) -> Vec<UtmpRecord> {
+    let Ok(path) = CString::new(path.as_os_str().as_bytes()) else {
+        return vec![];
+    };
+    let mut records = vec![];
+    unsafe {
+        if libc::utmpxname(path.as_ptr()) != 0 {
+            return records;
+        }
+        libc::setutxent();
+        loop {
+            let entry = libc::getutxent();
+            if entry.is_null() {
+                break;
+            }
+            records.push(UtmpRecord::from(&*entry));
+        }
+        libc::endutxent();
+        libc::utmpxname(c"/var/run/utmp".as_ptr());
+    }
+    records
+}
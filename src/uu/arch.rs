@@ -0,0 +1,31 @@
+use std::ffi::OsString;
+use std::io::{self, Write};
+
+use crate::error::strerror;
+use crate::posix;
+use crate::show_error;
+use crate::uname::uname;
+use clap::{CommandFactory, Parser};
+
+#[derive(Parser)]
+#[command(version, about = "print machine hardware name (same as uname -m)", long_about = None)]
+struct Cli {}
+
+pub fn command() -> clap::Command {
+    Cli::command()
+}
+
+pub fn run(args: Vec<OsString>) -> i32 {
+    posix::parse::<Cli>(args);
+    let name = match uname() {
+        Ok(name) => name,
+        Err(e) => {
+            show_error!("cannot get system name: {}", strerror(&e));
+            return 1;
+        }
+    };
+    if writeln!(io::stdout(), "{}", name.machine).is_err() {
+        return 1;
+    }
+    0
+}
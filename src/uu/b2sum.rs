@@ -0,0 +1,27 @@
+use std::ffi::OsString;
+
+use crate::digest::Algorithm;
+use crate::posix;
+use crate::uu::hashsum::{self, Job, Options};
+use clap::{CommandFactory, Parser};
+
+#[derive(Parser)]
+#[command(version, about = "print or check BLAKE2b (512-bit) checksums", long_about = None)]
+struct Cli {
+    /// digest length in bits; must not exceed the maximum for the blake2 algorithm and must be a multiple of 8
+    #[arg(short, long, value_name = "BITS")]
+    length: Option<String>,
+
+    #[command(flatten)]
+    options: Options,
+}
+
+pub fn command() -> clap::Command {
+    Cli::command()
+}
+
+pub fn run(args: Vec<OsString>) -> i32 {
+    let cli = posix::parse::<Cli>(args);
+    let bits = hashsum::length(Algorithm::Blake2b, cli.length.as_deref());
+    Job::new(Algorithm::Blake2b, bits, cli.options).run()
+}
@@ -0,0 +1,306 @@
+use std::ffi::OsString;
+use std::fs::Metadata;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::attrs::{change_all, Applied, Change, Options, Verbosity};
+use crate::error::strerror;
+use crate::fs::SymlinkPolicy;
+use crate::posix;
+use crate::quote::quote;
+use crate::selinux::{self, Context};
+use crate::{die, usage_error};
+use clap::{ArgAction, CommandFactory, Parser};
+
+#[derive(Parser)]
+#[command(
+    version,
+    about = "change the SELinux security context of each FILE to CONTEXT",
+    override_usage = "chcon [OPTION]... CONTEXT FILE...\n       \
+        chcon [OPTION]... [-u USER] [-r ROLE] [-l RANGE] [-t TYPE] FILE...\n       \
+        chcon [OPTION]... --reference=RFILE FILE...",
+    long_about = None,
+    disable_help_flag(true)
+)]
+struct Cli {
+    /// [CONTEXT] FILE...
+    args: Vec<OsString>,
+
+    #[arg(long, action(ArgAction::Help))]
+    help: Option<bool>,
+
+    /// affect the referent of each symbolic link (this is the default), rather than the symbolic link itself
+    #[arg(long, overrides_with = "no_dereference")]
+    dereference: bool,
+
+    /// affect symbolic links instead of any referenced file
+    #[arg(short = 'h', long, overrides_with = "dereference")]
+    no_dereference: bool,
+
+    /// set user USER in the target security context
+    #[arg(short, long, value_name = "USER")]
+    user: Option<String>,
+
+    /// set role ROLE in the target security context
+    #[arg(short, long, value_name = "ROLE")]
+    role: Option<String>,
+
+    /// set type TYPE in the target security context
+    #[arg(short = 't', long = "type", value_name = "TYPE")]
+    kind: Option<String>,
+
+    /// set range RANGE in the target security context
+    #[arg(short = 'l', long, value_name = "RANGE")]
+    range: Option<String>,
+
+    /// do not treat '/' specially (the default)
+    #[arg(long, overrides_with = "preserve_root")]
+    no_preserve_root: bool,
+
+    /// fail to operate recursively on '/'
+    #[arg(long, overrides_with = "no_preserve_root")]
+    preserve_root: bool,
+
+    /// use RFILE's security context rather than specifying a CONTEXT value
+    #[arg(long, value_name = "RFILE")]
+    reference: Option<PathBuf>,
+
+    /// operate on files and directories recursively
+    #[arg(short = 'R', long)]
+    recursive: bool,
+
+    /// output a diagnostic for every file processed
+    #[arg(short, long)]
+    verbose: bool,
+
+    /// if a command line argument is a symbolic link to a directory, traverse it
+    #[arg(short = 'H', overrides_with_all = ["follow_all", "follow_none"])]
+    follow_args: bool,
+
+    /// traverse every symbolic link to a directory encountered
+    #[arg(short = 'L', overrides_with_all = ["follow_args", "follow_none"])]
+    follow_all: bool,
+
+    /// do not traverse any symbolic links (default)
+    #[arg(short = 'P', overrides_with_all = ["follow_args", "follow_all"])]
+    follow_none: bool,
+}
+
+/// The fields of a file's context `-u`, `-r`, `-t` and `-l` replace.
+struct Components {
+    user: Option<String>,
+    role: Option<String>,
+    kind: Option<String>,
+    range: Option<String>,
+}
+
+impl Components {
+    /// `context` with these fields in place of its own, or the name and
+    /// value of a field that cannot go in a context.
+    fn apply(&self, context: &str) -> Result<String, (&'static str, String)> {
+        let mut context = Context::parse(context).unwrap_or(Context {
+            user: String::new(),
+            role: String::new(),
+            kind: String::new(),
+            range: None,
+        });
+        let fields = [
+            ("user", &self.user, &mut context.user),
+            ("role", &self.role, &mut context.role),
+            ("type", &self.kind, &mut context.kind),
+        ];
+        for (name, value, field) in fields {
+            if let Some(value) = value {
+                if value.contains(':') {
+                    return Err((name, value.clone()));
+                }
+                *field = value.clone();
+            }
+        }
+        if let Some(range) = &self.range {
+            context.range = Some(range.clone());
+        }
+        Ok(context.to_string())
+    }
+}
+
+/// The context each file is given.
+enum Target {
+    Whole(String),
+    Components(Components),
+}
+
+/// Why a file was not relabeled, for [`Change::failure`].
+enum Failure {
+    Read,
+    Unlabeled,
+    Component(&'static str, String),
+    Write(String),
+}
+
+struct Relabel {
+    target: Target,
+    failed: Option<Failure>,
+}
+
+impl Change for Relabel {
+    fn apply(&mut self, path: &Path, _: &Metadata, follow: bool) -> io::Result<Applied> {
+        let applied = Applied {
+            changed: true,
+            message: format!("changing security context of {}", quote(path)),
+        };
+        let current = match &self.target {
+            Target::Whole(_) => selinux::context(path, follow).ok().flatten(),
+            Target::Components(_) => match selinux::context(path, follow) {
+                Ok(current) => current,
+                Err(e) => {
+                    self.failed = Some(Failure::Read);
+                    return Err(e);
+                }
+            },
+        };
+        let context = match &self.target {
+            Target::Whole(context) => context.clone(),
+            Target::Components(components) => {
+                let Some(current) = &current else {
+                    self.failed = Some(Failure::Unlabeled);
+                    return Err(io::ErrorKind::InvalidData.into());
+                };
+                match components.apply(current) {
+                    Ok(context) => context,
+                    Err((name, value)) => {
+                        self.failed = Some(Failure::Component(name, value));
+                        return Err(io::ErrorKind::InvalidInput.into());
+                    }
+                }
+            }
+        };
+        if current.as_ref() == Some(&context) {
+            return Ok(applied);
+        }
+        if let Err(e) = selinux::set_context(path, &context, follow) {
+            self.failed = Some(Failure::Write(context));
+            return Err(e);
+        }
+        Ok(applied)
+    }
+
+    fn failure(&self, path: &Path, error: &io::Error) -> String {
+        match &self.failed {
+            Some(Failure::Read) => format!(
+                "failed to get security context of {}: {}",
+                quote(path),
+                strerror(error)
+            ),
+            Some(Failure::Unlabeled) => format!(
+                "can't apply partial context to unlabeled file {}",
+                quote(path)
+            ),
+            Some(Failure::Component(name, value)) => format!(
+                "failed to set {} security context component to {}",
+                name,
+                quote(value)
+            ),
+            Some(Failure::Write(context)) => format!(
+                "failed to change context of {} to {}: {}",
+                quote(path),
+                quote(context.as_str()),
+                strerror(error)
+            ),
+            None => format!("{}: {}", quote(path), strerror(error)),
+        }
+    }
+}
+
+pub fn command() -> clap::Command {
+    Cli::command()
+}
+
+pub fn run(args: Vec<OsString>) -> i32 {
+    let cli = posix::parse::<Cli>(args);
+    let components = Components {
+        user: cli.user,
+        role: cli.role,
+        kind: cli.kind,
+        range: cli.range,
+    };
+    let partial = components.user.is_some()
+        || components.role.is_some()
+        || components.kind.is_some()
+        || components.range.is_some();
+
+    let mut operands = cli.args.into_iter();
+    let needed = match cli.reference.is_some() || partial {
+        true => 1,
+        false => 2,
+    };
+    if operands.len() < needed {
+        match operands.next_back() {
+            Some(last) => usage_error!(1, "missing operand after {}", quote(&last)),
+            None => usage_error!(1, "missing operand"),
+        }
+    }
+    let target = match (&cli.reference, partial) {
+        (Some(reference), _) => match selinux::context(reference, true) {
+            Ok(Some(_)) if partial => {
+                usage_error!(1, "conflicting security context specifiers given")
+            }
+            Ok(Some(context)) => Target::Whole(context),
+            Ok(None) => die!(
+                1,
+                "failed to get security context of {}: {}",
+                quote(reference),
+                strerror(&io::Error::from_raw_os_error(libc::ENODATA))
+            ),
+            Err(e) => die!(
+                1,
+                "failed to get security context of {}: {}",
+                quote(reference),
+                strerror(&e)
+            ),
+        },
+        (None, true) => Target::Components(components),
+        (None, false) => {
+            let context = operands.next().unwrap().to_string_lossy().into_owned();
+            if selinux::is_enabled() {
+                if let Err(e) = selinux::check(&context) {
+                    die!(1, "invalid context: {}: {}", quote(&context), strerror(&e));
+                }
+            }
+            Target::Whole(context)
+        }
+    };
+
+    let symlinks = match (cli.follow_args, cli.follow_all) {
+        (true, _) => SymlinkPolicy::CommandLine,
+        (_, true) => SymlinkPolicy::Always,
+        _ => SymlinkPolicy::Never,
+    };
+    if cli.recursive {
+        match symlinks {
+            SymlinkPolicy::Never if cli.dereference => {
+                die!(1, "-R --dereference requires either -H or -L")
+            }
+            SymlinkPolicy::CommandLine | SymlinkPolicy::Always if cli.no_dereference => {
+                die!(1, "-R -h requires -P")
+            }
+            _ => {}
+        }
+    }
+    let options = Options {
+        recursive: cli.recursive,
+        symlinks,
+        dereference: !cli.no_dereference,
+        preserve_root: cli.preserve_root,
+        verbosity: match cli.verbose {
+            true => Verbosity::Verbose,
+            false => Verbosity::Normal,
+        },
+    };
+    let paths = operands.map(PathBuf::from).collect::<Vec<_>>();
+    let mut relabel = Relabel {
+        target,
+        failed: None,
+    };
+    change_all(&paths, &options, &mut relabel)
+}
@@ -0,0 +1,194 @@
+use std::env;
+use std::ffi::{CString, OsStr, OsString};
+use std::fs;
+use std::io;
+use std::os::unix::ffi::OsStrExt;
+use std::os::unix::fs::MetadataExt;
+use std::path::Path;
+use std::process;
+
+use crate::error::strerror;
+use crate::posix;
+use crate::process::{exec, Diagnostic, EXIT_CANCELED};
+use crate::quote::quote;
+use crate::usergroup::{group_id, user_groups, user_id};
+use crate::{die, usage_error};
+use clap::{CommandFactory, Parser};
+use users::get_user_by_uid;
+
+#[derive(Parser)]
+#[command(
+    version,
+    about = "run COMMAND with root directory set to NEWROOT",
+    after_help = "If no command is given, run '\"$SHELL\" -i' (default: '/bin/sh -i').",
+    long_about = None
+)]
+struct Cli {
+    /// specify supplementary groups as g1,g2,..,gN
+    #[arg(long, value_name = "G_LIST")]
+    groups: Option<String>,
+
+    /// specify user and group (ID or name) to use
+    #[arg(long, value_name = "USER:GROUP")]
+    userspec: Option<String>,
+
+    /// do not change working directory to '/'
+    #[arg(long)]
+    skip_chdir: bool,
+
+    newroot: Option<OsString>,
+
+    /// [COMMAND [ARG]...]
+    #[arg(trailing_var_arg = true)]
+    command: Vec<OsString>,
+}
+
+/// The user and group `--userspec` names, either of which may be left out.
+fn parse_userspec(spec: &str) -> (Option<u32>, Option<u32>) {
+    let (user, group) = match spec.split_once(':') {
+        Some((user, group)) => (user, group),
+        None => (spec, ""),
+    };
+    let uid = match user {
+        "" => None,
+        user => Some(user_id(user).unwrap_or_else(|| die!(EXIT_CANCELED, "invalid user"))),
+    };
+    let gid = match group {
+        "" => None,
+        group => Some(group_id(group).unwrap_or_else(|| die!(EXIT_CANCELED, "invalid group"))),
+    };
+    (uid, gid)
+}
+
+/// The groups of a `--groups` list, where empty entries are ignored.
+fn parse_groups(list: &str) -> Vec<u32> {
+    list.split(',')
+        .filter(|group| !group.is_empty())
+        .map(|group| {
+            group_id(group).unwrap_or_else(|| die!(EXIT_CANCELED, "invalid group {}", quote(group)))
+        })
+        .collect()
+}
+
+/// Whether `path` is the root directory already.
+fn is_root(path: &Path) -> bool {
+    match (fs::metadata(path), fs::metadata("/")) {
+        (Ok(path), Ok(root)) => (path.dev(), path.ino()) == (root.dev(), root.ino()),
+        _ => false,
+    }
+}
+
+fn chroot(path: &OsStr) -> io::Result<()> {
+    let path =
+        CString::new(path.as_bytes()).map_err(|_| io::Error::from_raw_os_error(libc::ENOENT))?;
+    match unsafe { libc::chroot(path.as_ptr()) } {
+        0 => Ok(()),
+        _ => Err(io::Error::last_os_error()),
+    }
+}
+
+/// Take on the given identity, the supplementary groups first since
+/// setting them needs the privileges that setting the user ID gives up.
+fn set_ids(uid: Option<u32>, gid: Option<u32>, groups: Option<Vec<u32>>) {
+    if let Some(groups) = groups {
+        if unsafe { libc::setgroups(groups.len() as _, groups.as_ptr()) } != 0 {
+            let e = io::Error::last_os_error();
+            die!(
+                EXIT_CANCELED,
+                "failed to set supplemental groups: {}",
+                strerror(&e)
+            );
+        }
+    }
+    if let Some(gid) = gid {
+        if unsafe { libc::setgid(gid) } != 0 {
+            let e = io::Error::last_os_error();
+            die!(EXIT_CANCELED, "failed to set group-ID: {}", strerror(&e));
+        }
+    }
+    if let Some(uid) = uid {
+        if unsafe { libc::setuid(uid) } != 0 {
+            let e = io::Error::last_os_error();
+            die!(EXIT_CANCELED, "failed to set user-ID: {}", strerror(&e));
+        }
+    }
+}
+
+pub fn command() -> clap::Command {
+    Cli::command()
+}
+
+pub fn run(args: Vec<OsString>) -> i32 {
+    let cli = posix::try_parse::<Cli>(args).unwrap_or_else(|e| {
+        if e.use_stderr() {
+            let _ = e.print();
+            process::exit(EXIT_CANCELED);
+        }
+        e.exit()
+    });
+    let Some(newroot) = cli.newroot else {
+        usage_error!(EXIT_CANCELED, "missing operand");
+    };
+
+    // Names are looked up in the old root, whose databases are the ones
+    // the caller knows.
+    let (uid, mut gid) = cli.userspec.as_deref().map_or((None, None), parse_userspec);
+    let mut name = None;
+    if let Some(uid) = uid {
+        if cli.groups.is_none() || gid.is_none() {
+            match get_user_by_uid(uid) {
+                Some(user) => {
+                    gid.get_or_insert(user.primary_group_id());
+                    name = Some(user.name().to_os_string());
+                }
+                None if gid.is_none() => {
+                    die!(EXIT_CANCELED, "no group specified for unknown uid: {}", uid)
+                }
+                None => {}
+            }
+        }
+    }
+    let groups = match (&cli.groups, &name, gid) {
+        (Some(list), _, _) => Some(parse_groups(list)),
+        (None, Some(name), Some(gid)) => Some(user_groups(name, gid)),
+        // A user without an entry keeps no groups of the caller's.
+        (None, _, _) => uid.map(|_| Vec::new()),
+    };
+
+    let root = is_root(Path::new(&newroot));
+    if cli.skip_chdir && !root {
+        usage_error!(
+            EXIT_CANCELED,
+            "option --skip-chdir only permitted if NEWROOT is old '/'"
+        );
+    }
+    if !root {
+        if let Err(e) = chroot(&newroot) {
+            die!(
+                EXIT_CANCELED,
+                "cannot change root directory to {}: {}",
+                quote(&newroot),
+                strerror(&e)
+            );
+        }
+    }
+    if !cli.skip_chdir {
+        if let Err(e) = env::set_current_dir("/") {
+            die!(
+                EXIT_CANCELED,
+                "cannot chdir to root directory: {}",
+                strerror(&e)
+            );
+        }
+    }
+    set_ids(uid, gid, groups);
+
+    let mut command = cli.command.into_iter();
+    match command.next() {
+        Some(program) => exec(&program, command, Diagnostic::FailedToRun),
+        None => {
+            let shell = env::var_os("SHELL").unwrap_or_else(|| "/bin/sh".into());
+            exec(&shell, ["-i"], Diagnostic::FailedToRun)
+        }
+    }
+}
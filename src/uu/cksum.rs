@@ -0,0 +1,113 @@
+use std::ffi::OsString;
+use std::path::PathBuf;
+
+use crate::die;
+use crate::digest::Algorithm;
+use crate::posix;
+use crate::uu::hashsum::{self, Job, Threads, Verify};
+use clap::{CommandFactory, Parser};
+
+/// The algorithms `--length` is allowed with, and those `--check` is not,
+/// as the messages refusing them list them.
+#[cfg(not(feature = "sha3"))]
+const WITH_LENGTH: &str = "blake2b";
+#[cfg(feature = "sha3")]
+const WITH_LENGTH: &str = "{blake2b,sha3}";
+#[cfg(not(feature = "crc32c"))]
+const LEGACY: &str = "{bsd,sysv,crc}";
+#[cfg(feature = "crc32c")]
+const LEGACY: &str = "{bsd,sysv,crc,crc32c}";
+
+fn parse_algorithm(name: &str) -> Result<Algorithm, String> {
+    Algorithm::parse(name).ok_or_else(|| {
+        let valid = Algorithm::NAMES.iter().map(|(n, _)| *n);
+        format!(
+            "valid arguments are: {}",
+            valid.collect::<Vec<_>>().join(", ")
+        )
+    })
+}
+
+#[derive(Parser)]
+#[command(version, about = "print or verify checksums; by default use the 32 bit CRC algorithm", long_about = None)]
+struct Cli {
+    /// select the digest type to use: sysv, bsd, crc, md5, sha1, sha224, sha256, sha384, sha512, blake2b, and crc32c, sha3 and sm3 where built in
+    #[arg(short, long, value_name = "TYPE", value_parser = parse_algorithm)]
+    algorithm: Option<Algorithm>,
+
+    /// read checksums from the FILEs and check them
+    #[arg(short, long)]
+    check: bool,
+
+    /// digest length in bits; must not exceed the maximum for the blake2 algorithm and must be a multiple of 8, or be 224, 256, 384 or 512 for sha3
+    #[arg(short, long, value_name = "BITS")]
+    length: Option<String>,
+
+    /// create a BSD-style checksum (the default)
+    #[arg(long, overrides_with = "untagged")]
+    tag: bool,
+
+    /// create a reversed style checksum, without digest type
+    #[arg(long, overrides_with = "tag")]
+    untagged: bool,
+
+    /// end each output line with NUL, not newline, and disable file name escaping
+    #[arg(short, long)]
+    zero: bool,
+
+    #[command(flatten)]
+    verify: Verify,
+
+    #[command(flatten)]
+    threads: Threads,
+
+    #[arg(value_name = "FILE")]
+    files: Vec<PathBuf>,
+}
+
+pub fn command() -> clap::Command {
+    Cli::command()
+}
+
+pub fn run(args: Vec<OsString>) -> i32 {
+    let cli = posix::parse::<Cli>(args);
+    if cli.length.is_some() && !cli.algorithm.is_some_and(Algorithm::has_length) {
+        die!(
+            1,
+            "--length is only supported with --algorithm={}",
+            WITH_LENGTH
+        );
+    }
+    if cli.check && cli.algorithm.is_some_and(Algorithm::is_legacy) {
+        die!(1, "--check is not supported with --algorithm={}", LEGACY);
+    }
+    // Check lines name their own SHA-3 length.
+    #[cfg(feature = "sha3")]
+    if cli.algorithm == Some(Algorithm::Sha3) && cli.length.is_none() && !cli.check {
+        die!(
+            1,
+            "--algorithm=sha3 requires specifying --length 224, 256, 384, or 512"
+        );
+    }
+    let algorithm = match (cli.check, cli.algorithm) {
+        (_, Some(algorithm)) => Some(algorithm),
+        (true, None) => None,
+        (false, None) => Some(Algorithm::Crc),
+    };
+    let bits = match algorithm {
+        Some(algorithm) => hashsum::length(algorithm, cli.length.as_deref()),
+        None => 0,
+    };
+    Job {
+        algorithm,
+        bits,
+        check: cli.check,
+        tag: !cli.untagged,
+        binary: false,
+        zero: cli.zero,
+        verify: cli.verify,
+        threads: cli.threads.count(),
+        files: cli.files,
+    }
+    .run()
+}
@@ -0,0 +1,201 @@
+use std::ffi::OsString;
+use std::fs;
+use std::io::{self, BufRead, Write};
+use std::path::PathBuf;
+use std::process;
+use std::time::UNIX_EPOCH;
+
+use crate::datetime::parse_date;
+use crate::error::{last_error, strerror, ExitStatus};
+use crate::io::Input;
+use crate::locale;
+use crate::posix;
+use crate::timefmt::{strftime, ZonedTime};
+use crate::{die, show_error};
+use clap::{ArgAction, CommandFactory, Parser, ValueEnum};
+use time::OffsetDateTime;
+
+#[derive(Clone, Copy, ValueEnum)]
+enum IsoPrecision {
+    Date,
+    Hours,
+    Minutes,
+    Seconds,
+    Ns,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum RfcPrecision {
+    Date,
+    Seconds,
+    Ns,
+}
+
+#[derive(Parser)]
+#[command(version, about = "print or set the system date and time", long_about = None)]
+struct Cli {
+    /// +FORMAT
+    format: Option<String>,
+
+    /// display time described by STRING, not 'now'
+    #[arg(short, long, value_name = "STRING")]
+    date: Option<String>,
+
+    /// like --date; once for each line of DATEFILE
+    #[arg(short, long, value_name = "DATEFILE")]
+    file: Option<PathBuf>,
+
+    /// output date/time in ISO 8601 format
+    #[arg(short = 'I', long = "iso-8601", value_name = "FMT", value_enum, num_args = 0..=1, default_missing_value = "date", require_equals = true)]
+    iso_8601: Option<IsoPrecision>,
+
+    /// output date and time in RFC 5322 format
+    #[arg(short = 'R', long, action(ArgAction::SetTrue))]
+    rfc_email: bool,
+
+    /// output date/time in RFC 3339 format
+    #[arg(long = "rfc-3339", value_name = "FMT", value_enum)]
+    rfc_3339: Option<RfcPrecision>,
+
+    /// display the last modification time of FILE
+    #[arg(short, long, value_name = "FILE")]
+    reference: Option<PathBuf>,
+
+    /// set time described by STRING
+    #[arg(short, long, value_name = "STRING")]
+    set: Option<String>,
+
+    /// print or set Coordinated Universal Time (UTC)
+    #[arg(short, long, visible_alias = "universal")]
+    utc: bool,
+}
+
+fn output_format(cli: &Cli) -> String {
+    if let Some(format) = &cli.format {
+        match format.strip_prefix('+') {
+            Some(format) => return format.to_string(),
+            None => die!(1, "invalid date '{}'", format),
+        }
+    }
+    if let Some(precision) = cli.iso_8601 {
+        return match precision {
+            IsoPrecision::Date => "%Y-%m-%d",
+            IsoPrecision::Hours => "%Y-%m-%dT%H%:z",
+            IsoPrecision::Minutes => "%Y-%m-%dT%H:%M%:z",
+            IsoPrecision::Seconds => "%Y-%m-%dT%H:%M:%S%:z",
+            IsoPrecision::Ns => "%Y-%m-%dT%H:%M:%S,%N%:z",
+        }
+        .to_string();
+    }
+    if let Some(precision) = cli.rfc_3339 {
+        return match precision {
+            RfcPrecision::Date => "%Y-%m-%d",
+            RfcPrecision::Seconds => "%Y-%m-%d %H:%M:%S%:z",
+            RfcPrecision::Ns => "%Y-%m-%d %H:%M:%S.%N%:z",
+        }
+        .to_string();
+    }
+    if cli.rfc_email {
+        return "%a, %d %b %Y %H:%M:%S %z".to_string();
+    }
+    "%a %b %e %H:%M:%S %Z %Y".to_string()
+}
+
+/// GNU only accepts the -I precision attached to the option, so rewrite
+/// `-Iseconds` into its long form before clap sees it.
+fn normalize_args(args: impl Iterator<Item = OsString>) -> Vec<OsString> {
+    args.map(
+        |arg| match arg.to_str().and_then(|a| a.strip_prefix("-I")) {
+            Some(precision) if !precision.is_empty() => format!("--iso-8601={}", precision).into(),
+            _ => arg,
+        },
+    )
+    .collect()
+}
+
+fn now() -> i128 {
+    OffsetDateTime::now_utc().unix_timestamp_nanos()
+}
+
+pub fn command() -> clap::Command {
+    Cli::command()
+}
+
+pub fn run(args: Vec<OsString>) -> i32 {
+    locale::init();
+    let cli = posix::parse::<Cli>(normalize_args(args.into_iter()));
+    let format = output_format(&cli);
+    let exclusive = [
+        cli.date.is_some(),
+        cli.file.is_some(),
+        cli.reference.is_some(),
+        cli.set.is_some(),
+    ];
+    if exclusive.iter().filter(|&&e| e).count() > 1 {
+        die!(
+            1,
+            "the options to specify dates for printing are mutually exclusive"
+        );
+    }
+    if cli.utc {
+        std::env::set_var("TZ", "UTC0");
+    }
+
+    let mut stdout = io::stdout().lock();
+    let mut print = |nanos: i128| {
+        let line = strftime(
+            &format,
+            &ZonedTime::new(nanos, cli.utc).unwrap_or_else(|| die!(1, "time out of range")),
+        );
+        if writeln!(stdout, "{}", line).is_err() {
+            process::exit(1);
+        }
+    };
+
+    if let Some(file) = &cli.file {
+        let reader = Input::open(file).unwrap_or_else(|e| die!(1, "{}", e));
+        let status = ExitStatus::new();
+        for line in reader.lines().map_while(Result::ok) {
+            match parse_date(&line, now(), cli.utc) {
+                Some(nanos) => print(nanos),
+                None => {
+                    show_error!("invalid date '{}'", line);
+                    status.fail();
+                }
+            }
+        }
+        if !status.is_success() {
+            status.exit();
+        }
+        return 0;
+    }
+
+    let nanos = if let Some(date) = &cli.date {
+        parse_date(date, now(), cli.utc).unwrap_or_else(|| die!(1, "invalid date '{}'", date))
+    } else if let Some(reference) = &cli.reference {
+        let modified = fs::metadata(reference)
+            .and_then(|m| m.modified())
+            .unwrap_or_else(|e| die!(1, "{}: {}", reference.to_string_lossy(), strerror(&e)));
+        match modified.duration_since(UNIX_EPOCH) {
+            Ok(d) => d.as_nanos() as i128,
+            Err(e) => -(e.duration().as_nanos() as i128),
+        }
+    } else if let Some(set) = &cli.set {
+        let nanos =
+            parse_date(set, now(), cli.utc).unwrap_or_else(|| die!(1, "invalid date '{}'", set));
+        let timespec = libc::timespec {
+            tv_sec: nanos.div_euclid(1_000_000_000) as libc::time_t,
+            tv_nsec: nanos.rem_euclid(1_000_000_000) as libc::c_long,
+        };
+        if unsafe { libc::clock_settime(libc::CLOCK_REALTIME, &timespec) } != 0 {
+            show_error!("cannot set date: {}", last_error());
+            print(nanos);
+            return 1;
+        }
+        nanos
+    } else {
+        now()
+    };
+    print(nanos);
+    0
+}
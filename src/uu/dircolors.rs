@@ -0,0 +1,460 @@
+use std::env;
+use std::ffi::OsString;
+use std::io::{self, Read, Write};
+use std::path::PathBuf;
+
+use crate::glob::{fnmatch, MatchOptions};
+use crate::io::Input;
+use crate::posix;
+use crate::{die, show_error, usage_error};
+use clap::{ArgAction, CommandFactory, Parser};
+
+#[derive(Parser)]
+#[command(version, about = "output commands to set the LS_COLORS environment variable", long_about = None)]
+struct Cli {
+    file: Option<PathBuf>,
+
+    /// output Bourne shell code to set LS_COLORS
+    #[arg(short = 'b', long = "sh", visible_alias = "bourne-shell")]
+    bourne_shell: bool,
+
+    /// output C shell code to set LS_COLORS
+    #[arg(
+        short = 'c',
+        long = "csh",
+        visible_alias = "c-shell",
+        conflicts_with = "bourne_shell"
+    )]
+    c_shell: bool,
+
+    /// output defaults
+    #[arg(short, long, action(ArgAction::SetTrue))]
+    print_database: bool,
+
+    /// output fully escaped colors for display
+    #[arg(long)]
+    print_ls_colors: bool,
+}
+
+const DEFAULT_DATABASE: &str = "\
+# Configuration file for dircolors, which sets LS_COLORS for ls --color.
+#
+# Lines are KEYWORD VALUE; anything after a '#' is a comment.
+# TERM and COLORTERM lines take glob patterns and restrict the entries
+# that follow them to matching terminals.
+
+COLORTERM ?*
+TERM Eterm
+TERM ansi
+TERM *color*
+TERM con[0-9]*x[0-9]*
+TERM cons25
+TERM console
+TERM cygwin
+TERM *direct*
+TERM dtterm
+TERM gnome
+TERM hurd
+TERM jfbterm
+TERM konsole
+TERM kterm
+TERM linux
+TERM linux-c
+TERM mlterm
+TERM putty
+TERM rxvt*
+TERM screen*
+TERM st
+TERM terminator
+TERM tmux*
+TERM vt100
+TERM xterm*
+
+# Attribute codes: 00=none 01=bold 04=underscore 05=blink 07=reverse 08=concealed
+# Foreground: 30=black 31=red 32=green 33=yellow 34=blue 35=magenta 36=cyan 37=white
+# Background: 40=black 41=red 42=green 43=yellow 44=blue 45=magenta 46=cyan 47=white
+#NORMAL 00 # no color code at all
+#FILE 00 # regular file
+RESET 0
+DIR 01;34
+LINK 01;36
+MULTIHARDLINK 00
+FIFO 40;33
+SOCK 01;35
+DOOR 01;35
+BLK 40;33;01
+CHR 40;33;01
+ORPHAN 40;31;01
+MISSING 00
+SETUID 37;41
+SETGID 30;43
+CAPABILITY 00
+STICKY_OTHER_WRITABLE 30;42
+OTHER_WRITABLE 34;42
+STICKY 37;44
+EXEC 01;32
+
+# archives and compressed files
+.tar 01;31
+.tgz 01;31
+.arc 01;31
+.arj 01;31
+.taz 01;31
+.lha 01;31
+.lz4 01;31
+.lzh 01;31
+.lzma 01;31
+.tlz 01;31
+.txz 01;31
+.tzo 01;31
+.t7z 01;31
+.zip 01;31
+.z 01;31
+.dz 01;31
+.gz 01;31
+.lrz 01;31
+.lz 01;31
+.lzo 01;31
+.xz 01;31
+.zst 01;31
+.tzst 01;31
+.bz2 01;31
+.bz 01;31
+.tbz 01;31
+.tbz2 01;31
+.tz 01;31
+.deb 01;31
+.rpm 01;31
+.jar 01;31
+.war 01;31
+.ear 01;31
+.sar 01;31
+.rar 01;31
+.alz 01;31
+.ace 01;31
+.zoo 01;31
+.cpio 01;31
+.7z 01;31
+.rz 01;31
+.cab 01;31
+.wim 01;31
+.swm 01;31
+.dwm 01;31
+.esd 01;31
+
+# images and video
+.avif 01;35
+.jpg 01;35
+.jpeg 01;35
+.mjpg 01;35
+.mjpeg 01;35
+.gif 01;35
+.bmp 01;35
+.pbm 01;35
+.pgm 01;35
+.ppm 01;35
+.tga 01;35
+.xbm 01;35
+.xpm 01;35
+.tif 01;35
+.tiff 01;35
+.png 01;35
+.svg 01;35
+.svgz 01;35
+.mng 01;35
+.pcx 01;35
+.mov 01;35
+.mpg 01;35
+.mpeg 01;35
+.m2v 01;35
+.mkv 01;35
+.webm 01;35
+.webp 01;35
+.ogm 01;35
+.mp4 01;35
+.m4v 01;35
+.mp4v 01;35
+.vob 01;35
+.qt 01;35
+.nuv 01;35
+.wmv 01;35
+.asf 01;35
+.rm 01;35
+.rmvb 01;35
+.flc 01;35
+.avi 01;35
+.fli 01;35
+.flv 01;35
+.gl 01;35
+.dl 01;35
+.xcf 01;35
+.xwd 01;35
+.yuv 01;35
+.cgm 01;35
+.emf 01;35
+.ogv 01;35
+.ogx 01;35
+
+# audio
+.aac 00;36
+.au 00;36
+.flac 00;36
+.m4a 00;36
+.mid 00;36
+.midi 00;36
+.mka 00;36
+.mp3 00;36
+.mpc 00;36
+.ogg 00;36
+.ra 00;36
+.wav 00;36
+.oga 00;36
+.opus 00;36
+.spx 00;36
+.xspf 00;36
+
+# backups and temporary files
+*~ 00;90
+*# 00;90
+.bak 00;90
+.old 00;90
+.orig 00;90
+.part 00;90
+.rej 00;90
+.swp 00;90
+.tmp 00;90
+.dpkg-dist 00;90
+.dpkg-old 00;90
+.ucf-dist 00;90
+.ucf-new 00;90
+.ucf-old 00;90
+.rpmnew 00;90
+.rpmorig 00;90
+.rpmsave 00;90
+";
+
+const KEYWORDS: [(&str, &str); 36] = [
+    ("NORMAL", "no"),
+    ("NORM", "no"),
+    ("FILE", "fi"),
+    ("RESET", "rs"),
+    ("DIR", "di"),
+    ("LNK", "ln"),
+    ("LINK", "ln"),
+    ("SYMLINK", "ln"),
+    ("ORPHAN", "or"),
+    ("MISSING", "mi"),
+    ("FIFO", "pi"),
+    ("PIPE", "pi"),
+    ("SOCK", "so"),
+    ("BLK", "bd"),
+    ("BLOCK", "bd"),
+    ("CHR", "cd"),
+    ("CHAR", "cd"),
+    ("DOOR", "do"),
+    ("EXEC", "ex"),
+    ("LEFT", "lc"),
+    ("LEFTCODE", "lc"),
+    ("RIGHT", "rc"),
+    ("RIGHTCODE", "rc"),
+    ("END", "ec"),
+    ("ENDCODE", "ec"),
+    ("SUID", "su"),
+    ("SETUID", "su"),
+    ("SGID", "sg"),
+    ("SETGID", "sg"),
+    ("STICKY", "st"),
+    ("OTHER_WRITABLE", "ow"),
+    ("OWR", "ow"),
+    ("STICKY_OTHER_WRITABLE", "tw"),
+    ("OWT", "tw"),
+    ("CAPABILITY", "ca"),
+    ("MULTIHARDLINK", "mh"),
+];
+
+fn keyword_code(keyword: &str) -> Option<&'static str> {
+    KEYWORDS
+        .iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case(keyword))
+        .map(|(_, code)| *code)
+}
+
+/// Strip a trailing comment: a `#` at the start of the line or preceded by
+/// whitespace.
+fn strip_comment(line: &str) -> &str {
+    let bytes = line.as_bytes();
+    for (i, &b) in bytes.iter().enumerate() {
+        if b == b'#' && (i == 0 || bytes[i - 1].is_ascii_whitespace()) {
+            return &line[..i];
+        }
+    }
+    line
+}
+
+/// Translate a dircolors database into LS_COLORS entries, keeping only
+/// the entries whose TERM or COLORTERM block matches this terminal.
+fn parse_database(content: &str, source: &str) -> Result<Vec<(String, String)>, Vec<String>> {
+    let term = env::var("TERM")
+        .ok()
+        .filter(|t| !t.is_empty())
+        .unwrap_or("none".to_string());
+    let colorterm = env::var("COLORTERM").unwrap_or_default();
+    let mut entries = vec![];
+    let mut errors = vec![];
+    let mut in_terms = false;
+    let mut matched: Option<bool> = None;
+
+    for (number, line) in content.lines().enumerate() {
+        let line = strip_comment(line).trim();
+        if line.is_empty() {
+            continue;
+        }
+        let (keyword, value) = match line.split_once(char::is_whitespace) {
+            Some((keyword, value)) => (keyword, value.trim()),
+            None => {
+                errors.push(format!(
+                    "{}:{}: invalid line;  missing second token",
+                    source,
+                    number + 1
+                ));
+                continue;
+            }
+        };
+        if keyword.eq_ignore_ascii_case("TERM") || keyword.eq_ignore_ascii_case("COLORTERM") {
+            if !in_terms {
+                matched = Some(false);
+                in_terms = true;
+            }
+            let target = match keyword.eq_ignore_ascii_case("TERM") {
+                true => &term,
+                false => &colorterm,
+            };
+            if fnmatch(value, target, MatchOptions::default()) {
+                matched = Some(true);
+            }
+            continue;
+        }
+        in_terms = false;
+        if matched == Some(false) {
+            continue;
+        }
+        if ["OPTIONS", "COLOR", "EIGHTBIT"]
+            .iter()
+            .any(|k| k.eq_ignore_ascii_case(keyword))
+        {
+            continue;
+        }
+        let key = if keyword.starts_with('.') {
+            format!("*{}", keyword)
+        } else if keyword.starts_with('*') {
+            keyword.to_string()
+        } else if let Some(code) = keyword_code(keyword) {
+            code.to_string()
+        } else {
+            errors.push(format!(
+                "{}:{}: unrecognized keyword {}",
+                source,
+                number + 1,
+                keyword
+            ));
+            continue;
+        };
+        entries.push((key, value.to_string()));
+    }
+
+    match errors.is_empty() {
+        true => Ok(entries),
+        false => Err(errors),
+    }
+}
+
+pub fn command() -> clap::Command {
+    Cli::command()
+}
+
+pub fn run(args: Vec<OsString>) -> i32 {
+    let cli = posix::parse::<Cli>(args);
+    if cli.print_database || cli.print_ls_colors {
+        if cli.bourne_shell || cli.c_shell {
+            usage_error!(1, "the options to output non shell syntax,\nand to select a shell syntax are mutually exclusive");
+        }
+        if cli.print_database && cli.print_ls_colors {
+            usage_error!(
+                1,
+                "options --print-database and --print-ls-colors are mutually exclusive"
+            );
+        }
+    }
+    if cli.print_database {
+        if let Some(file) = &cli.file {
+            usage_error!(
+                1,
+                "extra operand '{}'\nfile operands cannot be combined with --print-database (-p)",
+                file.to_string_lossy()
+            );
+        }
+        if io::stdout().write_all(DEFAULT_DATABASE.as_bytes()).is_err() {
+            return 1;
+        }
+        return 0;
+    }
+
+    let csh = match (cli.bourne_shell, cli.c_shell) {
+        (true, _) => false,
+        (_, true) => true,
+        _ if cli.print_ls_colors => false,
+        _ => match env::var("SHELL") {
+            Ok(shell) if !shell.is_empty() => {
+                let name = shell.rsplit('/').next().unwrap_or(&shell);
+                name == "csh" || name == "tcsh"
+            }
+            _ => die!(
+                1,
+                "no SHELL environment variable, and no shell type option given"
+            ),
+        },
+    };
+
+    let (content, source) = match &cli.file {
+        None => (DEFAULT_DATABASE.to_string(), String::new()),
+        Some(file) => {
+            let mut input = Input::open(file).unwrap_or_else(|e| die!(1, "{}", e));
+            let mut bytes = Vec::new();
+            if let Err(e) = input.read_to_end(&mut bytes) {
+                die!(1, "{}", input.error(e));
+            }
+            let name = input.name().to_string();
+            (String::from_utf8_lossy(&bytes).to_string(), name)
+        }
+    };
+
+    let entries = match parse_database(&content, &source) {
+        Ok(entries) => entries,
+        Err(errors) => {
+            for error in errors {
+                show_error!("{}", error);
+            }
+            return 1;
+        }
+    };
+
+    let output = if cli.print_ls_colors {
+        entries
+            .iter()
+            .map(|(key, value)| format!("\x1b[{}m{}\t{}\x1b[0m\n", value, key, value))
+            .collect::<String>()
+    } else {
+        let colors = entries
+            .iter()
+            .map(|(key, value)| format!("{}={}:", key, value))
+            .collect::<String>();
+        match csh {
+            true => format!("setenv LS_COLORS '{}'\n", colors),
+            false => format!("LS_COLORS='{}';\nexport LS_COLORS\n", colors),
+        }
+    };
+    if io::stdout().write_all(output.as_bytes()).is_err() {
+        return 1;
+    }
+    0
+}
@@ -0,0 +1,272 @@
+use std::env;
+use std::ffi::{OsStr, OsString};
+use std::io::{self, Write};
+use std::os::unix::ffi::{OsStrExt, OsStringExt};
+use std::path::PathBuf;
+use std::process;
+
+use crate::die;
+use crate::error::strerror;
+use crate::posix;
+use crate::process::{exec, Diagnostic, EXIT_CANCELED};
+use crate::quote::quote;
+use clap::{CommandFactory, Parser};
+
+#[derive(Parser)]
+#[command(version, about = "run a program in a modified environment", long_about = None)]
+struct Cli {
+    /// start with an empty environment
+    #[arg(short, long)]
+    ignore_environment: bool,
+
+    /// end each output line with NUL, not newline
+    #[arg(short = '0', long)]
+    null: bool,
+
+    /// remove variable from the environment
+    #[arg(short, long, value_name = "NAME")]
+    unset: Vec<OsString>,
+
+    /// change working directory to DIR
+    #[arg(short = 'C', long, value_name = "DIR")]
+    chdir: Option<PathBuf>,
+
+    /// process and split S into separate arguments; used to pass multiple arguments on shebang lines
+    #[arg(short = 'S', long, value_name = "S")]
+    split_string: Option<OsString>,
+
+    /// [-] [NAME=VALUE]... [COMMAND [ARG]...]
+    #[arg(trailing_var_arg = true)]
+    args: Vec<OsString>,
+}
+
+/// Split a -S string the way a shell would, honouring quotes, backslash
+/// escapes, `#` comments and `${VAR}` expansion.
+fn split_string(s: &[u8]) -> Vec<OsString> {
+    let mut args = vec![];
+    let mut current: Option<Vec<u8>> = None;
+    let mut i = 0;
+    let mut single = false;
+    let mut double = false;
+    while i < s.len() {
+        let c = s[i];
+        i += 1;
+        if single {
+            match c {
+                b'\'' => single = false,
+                b'\\' if matches!(s.get(i), Some(b'\\') | Some(b'\'')) => {
+                    current.get_or_insert_with(Vec::new).push(s[i]);
+                    i += 1;
+                }
+                _ => current.get_or_insert_with(Vec::new).push(c),
+            }
+            continue;
+        }
+        match c {
+            b'\'' if !double => {
+                single = true;
+                current.get_or_insert_with(Vec::new);
+            }
+            b'"' => {
+                double = !double;
+                current.get_or_insert_with(Vec::new);
+            }
+            b' ' | b'\t' | b'\n' | b'\x0b' | b'\x0c' | b'\r' if !double => {
+                if let Some(arg) = current.take() {
+                    args.push(OsString::from_vec(arg));
+                }
+            }
+            b'#' if !double && current.is_none() => break,
+            b'\\' => {
+                let Some(&next) = s.get(i) else {
+                    die!(EXIT_CANCELED, "invalid backslash at end of string in -S");
+                };
+                i += 1;
+                let escaped = match next {
+                    b'c' => break,
+                    b'_' if !double => {
+                        if let Some(arg) = current.take() {
+                            args.push(OsString::from_vec(arg));
+                        }
+                        continue;
+                    }
+                    b'_' => b' ',
+                    b'f' => b'\x0c',
+                    b'n' => b'\n',
+                    b'r' => b'\r',
+                    b't' => b'\t',
+                    b'v' => b'\x0b',
+                    b'#' | b'$' | b'\\' | b'"' | b'\'' => next,
+                    _ => die!(
+                        EXIT_CANCELED,
+                        "invalid sequence '\\{}' in -S",
+                        char::from(next)
+                    ),
+                };
+                current.get_or_insert_with(Vec::new).push(escaped);
+            }
+            b'$' if s.get(i) == Some(&b'{') => {
+                let Some(end) = s[i..].iter().position(|&b| b == b'}') else {
+                    die!(
+                        EXIT_CANCELED,
+                        "only ${{VARNAME}} expansion is supported, error at: ${{"
+                    );
+                };
+                let name = OsStr::from_bytes(&s[i + 1..i + end]);
+                let value = env::var_os(name).unwrap_or_default();
+                current
+                    .get_or_insert_with(Vec::new)
+                    .extend_from_slice(value.as_bytes());
+                i += end + 1;
+            }
+            _ => current.get_or_insert_with(Vec::new).push(c),
+        }
+    }
+    if single || double {
+        die!(
+            EXIT_CANCELED,
+            "no terminating quote in -S string: '{}'",
+            String::from_utf8_lossy(s)
+        );
+    }
+    if let Some(arg) = current {
+        args.push(OsString::from_vec(arg));
+    }
+    args
+}
+
+/// Expand any -S/--split-string options in place so that the split
+/// arguments are themselves parsed as options and operands.
+fn expand_split_strings(raw: Vec<OsString>) -> Vec<OsString> {
+    let mut args = vec![];
+    let mut rest = raw.into_iter();
+    if let Some(program) = rest.next() {
+        args.push(program);
+    }
+    let mut pending: Vec<OsString> = rest.collect();
+    pending.reverse();
+    while let Some(arg) = pending.pop() {
+        let bytes = arg.as_bytes();
+        let value = if bytes == b"-S" || bytes == b"--split-string" {
+            match pending.pop() {
+                Some(value) => Some(value),
+                None => {
+                    args.push(arg);
+                    continue;
+                }
+            }
+        } else if let Some(value) = bytes.strip_prefix(b"--split-string=") {
+            Some(OsStr::from_bytes(value).to_os_string())
+        } else if bytes.starts_with(b"-S") && !bytes.starts_with(b"--") {
+            Some(OsStr::from_bytes(&bytes[2..]).to_os_string())
+        } else {
+            None
+        };
+        match value {
+            Some(value) => {
+                pending.extend(split_string(value.as_bytes()).into_iter().rev());
+            }
+            None => {
+                let operand = bytes == b"-" || bytes == b"--" || !bytes.starts_with(b"-");
+                let takes_value = matches!(bytes, b"-u" | b"-C" | b"--unset" | b"--chdir");
+                args.push(arg);
+                if operand {
+                    args.extend(pending.into_iter().rev());
+                    break;
+                }
+                if takes_value {
+                    if let Some(value) = pending.pop() {
+                        args.push(value);
+                    }
+                }
+            }
+        }
+    }
+    args
+}
+
+pub fn command() -> clap::Command {
+    Cli::command()
+}
+
+pub fn run(args: Vec<OsString>) -> i32 {
+    let cli = posix::try_parse::<Cli>(expand_split_strings(args)).unwrap_or_else(|e| {
+        if e.use_stderr() {
+            let _ = e.print();
+            process::exit(EXIT_CANCELED);
+        }
+        e.exit()
+    });
+
+    let mut operands = cli.args.into_iter().peekable();
+    let mut ignore_environment = cli.ignore_environment;
+    if operands.peek().is_some_and(|a| a == "-") {
+        ignore_environment = true;
+        operands.next();
+    }
+
+    if ignore_environment {
+        for (name, _) in env::vars_os() {
+            env::remove_var(name);
+        }
+    }
+    for name in cli.unset {
+        if name.is_empty() || name.as_bytes().contains(&b'=') {
+            die!(
+                EXIT_CANCELED,
+                "cannot unset '{}': Invalid argument",
+                name.to_string_lossy()
+            );
+        }
+        env::remove_var(name);
+    }
+    while let Some(assignment) = operands.next_if(|a| a.as_bytes().contains(&b'=')) {
+        let bytes = assignment.as_bytes();
+        let split = bytes.iter().position(|&b| b == b'=').unwrap();
+        if split == 0 {
+            die!(
+                EXIT_CANCELED,
+                "cannot set '{}': Invalid argument",
+                assignment.to_string_lossy()
+            );
+        }
+        env::set_var(
+            OsStr::from_bytes(&bytes[..split]),
+            OsStr::from_bytes(&bytes[split + 1..]),
+        );
+    }
+
+    let Some(command) = operands.next() else {
+        if cli.chdir.is_some() {
+            die!(EXIT_CANCELED, "must specify command with --chdir (-C)");
+        }
+        let terminator = if cli.null { b'\0' } else { b'\n' };
+        let mut stdout = io::stdout().lock();
+        for (name, value) in env::vars_os() {
+            let mut line = name.into_vec();
+            line.push(b'=');
+            line.extend_from_slice(value.as_bytes());
+            line.push(terminator);
+            if stdout.write_all(&line).is_err() {
+                return EXIT_CANCELED;
+            }
+        }
+        return 0;
+    };
+
+    if cli.null {
+        die!(EXIT_CANCELED, "cannot specify --null (-0) with command");
+    }
+    if let Some(dir) = cli.chdir {
+        if let Err(e) = env::set_current_dir(&dir) {
+            die!(
+                EXIT_CANCELED,
+                "cannot change directory to {}: {}",
+                quote(&dir),
+                strerror(&e)
+            );
+        }
+    }
+
+    exec(&command, operands, Diagnostic::Name)
+}
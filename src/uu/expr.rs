@@ -0,0 +1,385 @@
+use std::cmp::Ordering;
+use std::ffi::OsString;
+
+use crate::die;
+use crate::version;
+use clap::{CommandFactory, Parser};
+use regex::Regex;
+
+const EXIT_INVALID: i32 = 2;
+const EXIT_FAILURE: i32 = 3;
+
+#[derive(Parser)]
+#[command(name = "expr", version, about = "evaluate expressions", long_about = None)]
+struct Cli {}
+
+fn syntax_error(message: String) -> ! {
+    die!(EXIT_INVALID, "syntax error: {}", message)
+}
+
+#[derive(Clone)]
+enum Value {
+    Integer(i128),
+    Str(String),
+}
+
+fn looks_like_integer(s: &str) -> bool {
+    let digits = s.strip_prefix('-').unwrap_or(s);
+    !digits.is_empty() && digits.bytes().all(|b| b.is_ascii_digit())
+}
+
+impl Value {
+    fn integer(&self) -> Option<i128> {
+        match self {
+            Value::Integer(i) => Some(*i),
+            Value::Str(s) if looks_like_integer(s) => Some(
+                s.parse()
+                    .unwrap_or_else(|_| die!(EXIT_INVALID, "integer is too large")),
+            ),
+            Value::Str(_) => None,
+        }
+    }
+
+    fn string(&self) -> String {
+        match self {
+            Value::Integer(i) => i.to_string(),
+            Value::Str(s) => s.clone(),
+        }
+    }
+
+    /// Null values are the empty string and any spelling of zero.
+    fn is_null(&self) -> bool {
+        match self {
+            Value::Integer(i) => *i == 0,
+            Value::Str(s) => {
+                let digits = s.strip_prefix('-').unwrap_or(s);
+                s.is_empty() || (!digits.is_empty() && digits.bytes().all(|b| b == b'0'))
+            }
+        }
+    }
+}
+
+/// Translate a POSIX basic regular expression into the syntax of the regex
+/// crate, anchored at the start as expr requires.
+fn translate_bre(pattern: &str) -> String {
+    let mut out = String::from("^(?s:");
+    let chars = pattern.chars().collect::<Vec<char>>();
+    let mut i = 0;
+    let mut at_start = true;
+    while i < chars.len() {
+        let c = chars[i];
+        i += 1;
+        let was_start = at_start;
+        at_start = false;
+        match c {
+            '\\' if i < chars.len() => {
+                let next = chars[i];
+                i += 1;
+                match next {
+                    '(' => {
+                        out.push('(');
+                        at_start = true;
+                    }
+                    ')' => out.push(')'),
+                    '{' => out.push('{'),
+                    '}' => out.push('}'),
+                    '|' => {
+                        out.push('|');
+                        at_start = true;
+                    }
+                    '+' | '?' => out.push(next),
+                    '1'..='9' => die!(EXIT_FAILURE, "back-references are not supported"),
+                    _ => out.push_str(&regex::escape(&next.to_string())),
+                }
+            }
+            '*' if was_start => out.push_str("\\*"),
+            '^' if was_start => out.push_str("\\^"),
+            '[' => {
+                out.push('[');
+                if chars.get(i) == Some(&'^') {
+                    out.push('^');
+                    i += 1;
+                }
+                if chars.get(i) == Some(&']') {
+                    out.push_str("\\]");
+                    i += 1;
+                }
+                while i < chars.len() && chars[i] != ']' {
+                    let c = chars[i];
+                    if c == '[' && matches!(chars.get(i + 1), Some(':') | Some('=') | Some('.')) {
+                        let delimiter = chars[i + 1];
+                        let end = (i + 2..chars.len().saturating_sub(1))
+                            .find(|&j| chars[j] == delimiter && chars[j + 1] == ']')
+                            .unwrap_or_else(|| {
+                                die!(EXIT_FAILURE, "Unmatched [, [^, [:, [., or [=")
+                            });
+                        let name = chars[i + 2..end].iter().collect::<String>();
+                        match delimiter {
+                            ':' => out.push_str(&format!("[:{}:]", name)),
+                            _ => out.push_str(&regex::escape(&name)),
+                        }
+                        i = end + 2;
+                        continue;
+                    }
+                    if matches!(c, '\\' | '[' | '&' | '~') {
+                        out.push('\\');
+                    }
+                    out.push(c);
+                    i += 1;
+                }
+                if i >= chars.len() {
+                    die!(EXIT_FAILURE, "Unmatched [, [^, [:, [., or [=");
+                }
+                out.push(']');
+                i += 1;
+            }
+            '(' | ')' | '{' | '}' | '+' | '?' | '|' => {
+                out.push('\\');
+                out.push(c);
+            }
+            '$' if i < chars.len() && !(chars[i] == '\\' && chars.get(i + 1) == Some(&')')) => {
+                out.push_str("\\$")
+            }
+            _ => out.push(c),
+        }
+    }
+    out.push(')');
+    out
+}
+
+fn regex_match(string: &Value, pattern: &Value) -> Value {
+    let pattern = pattern.string();
+    let regex = Regex::new(&translate_bre(&pattern))
+        .unwrap_or_else(|e| die!(EXIT_FAILURE, "invalid regular expression: {}", e));
+    let string = string.string();
+    match regex.captures(&string) {
+        Some(captures) => match regex.captures_len() > 1 {
+            true => Value::Str(captures.get(1).map_or("", |m| m.as_str()).to_string()),
+            false => Value::Integer(captures[0].chars().count() as i128),
+        },
+        None => match regex.captures_len() > 1 {
+            true => Value::Str(String::new()),
+            false => Value::Integer(0),
+        },
+    }
+}
+
+struct ExprParser {
+    args: Vec<String>,
+    pos: usize,
+}
+
+impl ExprParser {
+    fn peek(&self) -> Option<&str> {
+        self.args.get(self.pos).map(|a| a.as_str())
+    }
+
+    fn accept(&mut self, tokens: &[&str]) -> Option<String> {
+        let token = self.peek().filter(|t| tokens.contains(t))?.to_string();
+        self.pos += 1;
+        Some(token)
+    }
+
+    fn next_operand(&mut self) -> String {
+        match self.args.get(self.pos) {
+            Some(arg) => {
+                self.pos += 1;
+                arg.clone()
+            }
+            None => syntax_error(format!(
+                "missing argument after '{}'",
+                self.args[self.pos - 1]
+            )),
+        }
+    }
+
+    fn or(&mut self) -> Value {
+        let mut left = self.and();
+        while self.accept(&["|"]).is_some() {
+            let right = self.and();
+            if left.is_null() {
+                left = match right.is_null() {
+                    true => Value::Integer(0),
+                    false => right,
+                };
+            }
+        }
+        left
+    }
+
+    fn and(&mut self) -> Value {
+        let mut left = self.comparison();
+        while self.accept(&["&"]).is_some() {
+            let right = self.comparison();
+            if left.is_null() || right.is_null() {
+                left = Value::Integer(0);
+            }
+        }
+        left
+    }
+
+    fn comparison(&mut self) -> Value {
+        let mut left = self.additive();
+        while let Some(op) = self.accept(&["<", "<=", "=", "==", "!=", ">=", ">"]) {
+            let right = self.additive();
+            let ordering = match (left.integer(), right.integer()) {
+                (Some(l), Some(r)) => l.cmp(&r),
+                _ => left.string().cmp(&right.string()),
+            };
+            let result = match op.as_str() {
+                "<" => ordering == Ordering::Less,
+                "<=" => ordering != Ordering::Greater,
+                "=" | "==" => ordering == Ordering::Equal,
+                "!=" => ordering != Ordering::Equal,
+                ">=" => ordering != Ordering::Less,
+                _ => ordering == Ordering::Greater,
+            };
+            left = Value::Integer(result as i128);
+        }
+        left
+    }
+
+    fn arithmetic(op: &str, left: &Value, right: &Value) -> Value {
+        let (Some(l), Some(r)) = (left.integer(), right.integer()) else {
+            die!(EXIT_INVALID, "non-integer argument");
+        };
+        if (op == "/" || op == "%") && r == 0 {
+            die!(EXIT_INVALID, "division by zero");
+        }
+        let result = match op {
+            "+" => l.checked_add(r),
+            "-" => l.checked_sub(r),
+            "*" => l.checked_mul(r),
+            "/" => l.checked_div(r),
+            _ => l.checked_rem(r),
+        };
+        Value::Integer(result.unwrap_or_else(|| die!(EXIT_INVALID, "result is too large")))
+    }
+
+    fn additive(&mut self) -> Value {
+        let mut left = self.multiplicative();
+        while let Some(op) = self.accept(&["+", "-"]) {
+            let right = self.multiplicative();
+            left = Self::arithmetic(&op, &left, &right);
+        }
+        left
+    }
+
+    fn multiplicative(&mut self) -> Value {
+        let mut left = self.matching();
+        while let Some(op) = self.accept(&["*", "/", "%"]) {
+            let right = self.matching();
+            left = Self::arithmetic(&op, &left, &right);
+        }
+        left
+    }
+
+    fn matching(&mut self) -> Value {
+        let mut left = self.primary();
+        while self.accept(&[":"]).is_some() {
+            let right = self.primary();
+            left = regex_match(&left, &right);
+        }
+        left
+    }
+
+    fn primary(&mut self) -> Value {
+        let Some(token) = self.peek().map(|t| t.to_string()) else {
+            match self.pos {
+                0 => die!(EXIT_INVALID, "missing operand"),
+                _ => syntax_error(format!(
+                    "missing argument after '{}'",
+                    self.args[self.pos - 1]
+                )),
+            }
+        };
+        self.pos += 1;
+        match token.as_str() {
+            "+" => Value::Str(self.next_operand()),
+            "(" => {
+                let value = self.or();
+                if self.accept(&[")"]).is_none() {
+                    match self.peek() {
+                        Some(t) => syntax_error(format!(
+                            "expecting ')' after '{}', but found '{}'",
+                            self.args[self.pos - 1],
+                            t
+                        )),
+                        None => syntax_error(format!(
+                            "expecting ')' after '{}'",
+                            self.args[self.pos - 1]
+                        )),
+                    }
+                }
+                value
+            }
+            ")" => syntax_error("unexpected ')'".to_string()),
+            "length" => Value::Integer(self.primary().string().chars().count() as i128),
+            "match" => {
+                let string = self.primary();
+                let pattern = self.primary();
+                regex_match(&string, &pattern)
+            }
+            "index" => {
+                let string = self.primary().string();
+                let chars = self.primary().string();
+                let position = string
+                    .chars()
+                    .position(|c| chars.contains(c))
+                    .map_or(0, |p| p + 1);
+                Value::Integer(position as i128)
+            }
+            "substr" => {
+                let string = self.primary().string();
+                let position = self.primary().integer();
+                let length = self.primary().integer();
+                match (position, length) {
+                    (Some(p), Some(l)) if p >= 1 && l >= 1 => Value::Str(
+                        string
+                            .chars()
+                            .skip((p - 1).min(usize::MAX as i128) as usize)
+                            .take(l.min(usize::MAX as i128) as usize)
+                            .collect(),
+                    ),
+                    _ => Value::Str(String::new()),
+                }
+            }
+            _ => Value::Str(token),
+        }
+    }
+}
+
+pub fn command() -> clap::Command {
+    Cli::command()
+}
+
+pub fn run(args: Vec<OsString>) -> i32 {
+    let mut args = args
+        .into_iter()
+        .skip(1)
+        .map(|a| a.to_string_lossy().to_string())
+        .collect::<Vec<String>>();
+    if args.len() == 1 && args[0] == "--help" {
+        let _ = Cli::command().print_help();
+        return 0;
+    }
+    if args.len() == 1 && args[0] == "--version" {
+        print!("{}", version::stamp(Cli::command()).render_version());
+        return 0;
+    }
+    if args.first().is_some_and(|a| a == "--") {
+        args.remove(0);
+    }
+
+    let mut parser = ExprParser { args, pos: 0 };
+    let value = parser.or();
+    if let Some(extra) = parser.peek() {
+        syntax_error(format!("unexpected argument '{}'", extra));
+    }
+    println!("{}", value.string());
+    if value.is_null() {
+        1
+    } else {
+        0
+    }
+}
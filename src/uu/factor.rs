@@ -0,0 +1,244 @@
+use std::ffi::OsString;
+use std::io::{self, BufRead, Write};
+use std::process;
+
+use crate::error::ExitStatus;
+use crate::io::Output;
+use crate::posix;
+use crate::show_error;
+use clap::{ArgAction, CommandFactory, Parser};
+
+#[derive(Parser)]
+#[command(version, about = "print the prime factors of each specified integer NUMBER", long_about = None, disable_help_flag(true))]
+struct Cli {
+    numbers: Vec<String>,
+
+    #[arg(long, action(ArgAction::Help))]
+    help: Option<bool>,
+
+    /// print repeated factors in form p^e unless e is 1
+    #[arg(short = 'h', long)]
+    exponents: bool,
+}
+
+fn mul_mod(a: u128, b: u128, m: u128) -> u128 {
+    if m <= u64::MAX as u128 {
+        return (a * b) % m;
+    }
+    // Double-and-add keeps intermediate values below 2m for moduli that do
+    // not fit in 64 bits.
+    let (mut a, mut b, mut result) = (a % m, b % m, 0u128);
+    while b > 0 {
+        if b & 1 == 1 {
+            result = add_mod(result, a, m);
+        }
+        a = add_mod(a, a, m);
+        b >>= 1;
+    }
+    result
+}
+
+fn add_mod(a: u128, b: u128, m: u128) -> u128 {
+    match a.checked_add(b) {
+        Some(sum) => sum % m,
+        None => a.wrapping_add(b).wrapping_sub(m),
+    }
+}
+
+fn pow_mod(mut base: u128, mut exponent: u128, m: u128) -> u128 {
+    let mut result = 1 % m;
+    base %= m;
+    while exponent > 0 {
+        if exponent & 1 == 1 {
+            result = mul_mod(result, base, m);
+        }
+        base = mul_mod(base, base, m);
+        exponent >>= 1;
+    }
+    result
+}
+
+fn gcd(mut a: u128, mut b: u128) -> u128 {
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a
+}
+
+const SMALL_PRIMES: [u128; 20] = [
+    2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37, 41, 43, 47, 53, 59, 61, 67, 71,
+];
+
+/// Miller–Rabin; the first twelve prime bases are deterministic for every
+/// 64-bit input, the wider set makes errors vanishingly unlikely beyond that.
+fn is_prime(n: u128) -> bool {
+    if n < 2 {
+        return false;
+    }
+    for p in SMALL_PRIMES {
+        if n.is_multiple_of(p) {
+            return n == p;
+        }
+    }
+    let mut d = n - 1;
+    let mut s = 0;
+    while d.is_multiple_of(2) {
+        d /= 2;
+        s += 1;
+    }
+    let bases: &[u128] = match n <= u64::MAX as u128 {
+        true => &SMALL_PRIMES[..12],
+        false => &SMALL_PRIMES,
+    };
+    'witness: for &a in bases {
+        let mut x = pow_mod(a, d, n);
+        if x == 1 || x == n - 1 {
+            continue;
+        }
+        for _ in 1..s {
+            x = mul_mod(x, x, n);
+            if x == n - 1 {
+                continue 'witness;
+            }
+        }
+        return false;
+    }
+    true
+}
+
+/// Find a non-trivial divisor of the composite n with Brent's variant of
+/// Pollard's rho.
+fn pollard_rho(n: u128) -> u128 {
+    if n.is_multiple_of(2) {
+        return 2;
+    }
+    let mut c = 1;
+    loop {
+        let f = |x: u128| add_mod(mul_mod(x, x, n), c, n);
+        let (mut x, mut y, mut q, mut g) = (2u128, 2u128, 1u128, 1u128);
+        let mut ys = y;
+        let mut r = 1;
+        while g == 1 {
+            x = y;
+            for _ in 0..r {
+                y = f(y);
+            }
+            let mut k = 0;
+            while k < r && g == 1 {
+                ys = y;
+                for _ in 0..128.min(r - k) {
+                    y = f(y);
+                    q = mul_mod(q, x.abs_diff(y), n);
+                }
+                g = gcd(q, n);
+                k += 128;
+            }
+            r *= 2;
+        }
+        if g == n {
+            loop {
+                ys = f(ys);
+                g = gcd(x.abs_diff(ys), n);
+                if g > 1 {
+                    break;
+                }
+            }
+        }
+        if g != n {
+            return g;
+        }
+        c += 1;
+    }
+}
+
+fn factor(mut n: u128) -> Vec<u128> {
+    let mut factors = vec![];
+    if n < 2 {
+        return factors;
+    }
+    for p in SMALL_PRIMES {
+        while n.is_multiple_of(p) {
+            factors.push(p);
+            n /= p;
+        }
+    }
+    let mut pending = vec![n];
+    while let Some(n) = pending.pop() {
+        if n == 1 {
+            continue;
+        }
+        if is_prime(n) {
+            factors.push(n);
+            continue;
+        }
+        let divisor = pollard_rho(n);
+        pending.push(divisor);
+        pending.push(n / divisor);
+    }
+    factors.sort();
+    factors
+}
+
+fn format_factors(number: &str, exponents: bool) -> Result<String, String> {
+    let digits = number.trim().strip_prefix('+').unwrap_or(number.trim());
+    if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+        return Err(format!("'{}' is not a valid positive integer", number));
+    }
+    let n = digits
+        .parse::<u128>()
+        .map_err(|_| format!("'{}' is too large", number))?;
+    let mut line = format!("{}:", n);
+    let factors = factor(n);
+    let mut i = 0;
+    while i < factors.len() {
+        let count = factors[i..]
+            .iter()
+            .take_while(|&&f| f == factors[i])
+            .count();
+        match exponents && count > 1 {
+            true => line.push_str(&format!(" {}^{}", factors[i], count)),
+            false => line.push_str(&format!(" {}", factors[i]).repeat(count)),
+        }
+        i += count;
+    }
+    line.push('\n');
+    Ok(line)
+}
+
+pub fn command() -> clap::Command {
+    Cli::command()
+}
+
+pub fn run(args: Vec<OsString>) -> i32 {
+    let cli = posix::parse::<Cli>(args);
+    let mut stdout = Output::new();
+    let status = ExitStatus::new();
+    let mut process_number = |number: &str| {
+        let output = match format_factors(number, cli.exponents) {
+            Ok(output) => output,
+            Err(message) => {
+                show_error!("{}", message);
+                status.fail();
+                return;
+            }
+        };
+        if stdout.write_all(output.as_bytes()).is_err() {
+            process::exit(1);
+        }
+    };
+
+    if cli.numbers.is_empty() {
+        for line in io::stdin().lock().lines() {
+            let Ok(line) = line else {
+                break;
+            };
+            line.split_whitespace().for_each(&mut process_number);
+        }
+    } else {
+        cli.numbers.iter().for_each(|n| process_number(n));
+    }
+    if stdout.flush().is_err() {
+        process::exit(1);
+    }
+    status.code()
+}
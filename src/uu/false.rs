@@ -0,0 +1,27 @@
+use std::ffi::OsString;
+
+use crate::version;
+use clap::{CommandFactory, Parser};
+
+const EXIT_STATUS: i32 = 1;
+
+#[derive(Parser)]
+#[command(name = "false", version, about = "do nothing, unsuccessfully", long_about = None)]
+struct Cli {}
+
+pub fn command() -> clap::Command {
+    Cli::command()
+}
+
+pub fn run(args: Vec<OsString>) -> i32 {
+    // Every operand is ignored; only a lone --help or --version is honoured.
+    let args = args.into_iter().skip(1).collect::<Vec<_>>();
+    if args.len() == 1 {
+        if args[0] == "--help" {
+            let _ = Cli::command().print_help();
+        } else if args[0] == "--version" {
+            print!("{}", version::stamp(Cli::command()).render_version());
+        }
+    }
+    EXIT_STATUS
+}
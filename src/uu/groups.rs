@@ -0,0 +1,84 @@
+use std::ffi::OsString;
+use std::io::{self, Write};
+
+use crate::error::ExitStatus;
+use crate::posix;
+use crate::show_error;
+use crate::usergroup::{group_name, user_groups};
+use clap::{CommandFactory, Parser};
+use users::get_user_by_name;
+
+#[derive(Parser)]
+#[command(version, about = "print the groups a user is in", long_about = None)]
+struct Cli {
+    users: Vec<OsString>,
+}
+
+fn process_groups() -> Vec<u32> {
+    let count = unsafe { libc::getgroups(0, std::ptr::null_mut()) };
+    if count <= 0 {
+        return vec![];
+    }
+    let mut groups = vec![0; count as usize];
+    let count = unsafe { libc::getgroups(count, groups.as_mut_ptr()) };
+    groups.truncate(count.max(0) as usize);
+    groups
+}
+
+/// Render a group list with the primary groups first and duplicates removed,
+/// reporting any gid that has no entry in the group database.
+fn format_groups(primary: &[u32], groups: Vec<u32>, status: &ExitStatus) -> String {
+    let mut ordered = primary.to_vec();
+    ordered.dedup();
+    for gid in groups {
+        if !ordered.contains(&gid) {
+            ordered.push(gid);
+        }
+    }
+    ordered
+        .into_iter()
+        .map(|gid| match group_name(gid) {
+            Some(name) => name,
+            None => {
+                show_error!("cannot find name for group ID {}", gid);
+                status.fail();
+                gid.to_string()
+            }
+        })
+        .collect::<Vec<String>>()
+        .join(" ")
+}
+
+pub fn command() -> clap::Command {
+    Cli::command()
+}
+
+pub fn run(args: Vec<OsString>) -> i32 {
+    let cli = posix::parse::<Cli>(args);
+    let status = ExitStatus::new();
+    let mut output = String::new();
+
+    if cli.users.is_empty() {
+        let primary = [users::get_current_gid(), users::get_effective_gid()];
+        output.push_str(&format_groups(&primary, process_groups(), &status));
+        output.push('\n');
+    }
+    for name in &cli.users {
+        let Some(user) = get_user_by_name(name) else {
+            show_error!("'{}': no such user", name.to_string_lossy());
+            status.fail();
+            continue;
+        };
+        let gid = user.primary_group_id();
+        output.push_str(&format!(
+            "{} : {}\n",
+            name.to_string_lossy(),
+            format_groups(&[gid], user_groups(name, gid), &status)
+        ));
+    }
+
+    if io::stdout().write_all(output.as_bytes()).is_err() || !status.is_success() {
+        return 1;
+    }
+    0
+}
@@ -0,0 +1,430 @@
+//! What cksum, md5sum, the sha*sum family and b2sum share: printing the
+//! digest of each FILE, or verifying the digests listed in check files.
+
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::thread;
+
+use clap::builder::RangedU64ValueParser;
+use clap::Args;
+
+use crate::digest::{
+    self, format_line, parse_check_line, pool, Algorithm, CheckLine, LengthError, Sum,
+};
+use crate::error::{strerror, ExitStatus};
+use crate::io::{Input, InputError, Records};
+use crate::progress::{self, Progress};
+use crate::quote::quote;
+use crate::{die, show_error, usage_error};
+
+/// The options that only matter when verifying. Of --quiet, --status and
+/// --warn the last given wins, as in GNU.
+#[derive(Args)]
+pub struct Verify {
+    /// don't fail or report status for missing files
+    #[arg(long)]
+    ignore_missing: bool,
+
+    /// don't print OK for each successfully verified file
+    #[arg(long, overrides_with_all = ["status", "warn"])]
+    quiet: bool,
+
+    /// don't output anything, status code shows success
+    #[arg(long, overrides_with_all = ["quiet", "warn"])]
+    status: bool,
+
+    /// exit non-zero for improperly formatted checksum lines
+    #[arg(long)]
+    strict: bool,
+
+    /// warn about improperly formatted checksum lines
+    #[arg(short, long, overrides_with_all = ["quiet", "status"])]
+    warn: bool,
+
+    /// report progress through each file on standard error, and on SIGUSR1
+    #[arg(long)]
+    progress: bool,
+}
+
+/// The options of md5sum and the other single-algorithm utilities.
+#[derive(Args)]
+pub struct Options {
+    /// read in binary mode
+    #[arg(short, long, overrides_with = "text")]
+    binary: bool,
+
+    /// read checksums from the FILEs and check them
+    #[arg(short, long)]
+    check: bool,
+
+    /// create a BSD-style checksum
+    #[arg(long)]
+    tag: bool,
+
+    /// read in text mode (default)
+    #[arg(short, long, overrides_with = "binary")]
+    text: bool,
+
+    /// end each output line with NUL, not newline, and disable file name escaping
+    #[arg(short, long)]
+    zero: bool,
+
+    #[command(flatten)]
+    verify: Verify,
+
+    #[command(flatten)]
+    threads: Threads,
+
+    #[arg(value_name = "FILE")]
+    files: Vec<PathBuf>,
+}
+
+/// How many files are hashed at once.
+#[derive(Args)]
+pub struct Threads {
+    /// hash up to N files at once; the default is one per processor
+    #[arg(long, value_name = "N", value_parser = RangedU64ValueParser::<usize>::new().range(1..))]
+    threads: Option<usize>,
+}
+
+impl Threads {
+    pub fn count(&self) -> usize {
+        self.threads
+            .unwrap_or_else(|| thread::available_parallelism().map_or(1, usize::from))
+    }
+}
+
+/// A digest utility's run, whichever utility's options it came from.
+pub struct Job {
+    /// None for cksum, which learns the algorithm from each check line.
+    pub algorithm: Option<Algorithm>,
+    pub bits: usize,
+    pub check: bool,
+    pub tag: bool,
+    pub binary: bool,
+    pub zero: bool,
+    pub verify: Verify,
+    pub threads: usize,
+    pub files: Vec<PathBuf>,
+}
+
+impl Job {
+    pub fn new(algorithm: Algorithm, bits: usize, options: Options) -> Self {
+        if options.check && options.tag {
+            usage_error!(
+                1,
+                "the --tag option is meaningless when verifying checksums"
+            );
+        }
+        if options.check && options.binary {
+            usage_error!(
+                1,
+                "the --binary and --text options are meaningless when verifying checksums"
+            );
+        }
+        Job {
+            algorithm: Some(algorithm),
+            bits,
+            check: options.check,
+            tag: options.tag,
+            binary: options.binary,
+            zero: options.zero,
+            verify: options.verify,
+            threads: options.threads.count(),
+            files: options.files,
+        }
+    }
+
+    pub fn run(self) -> i32 {
+        let status = ExitStatus::new();
+        if self.check && self.verify.progress {
+            let _ = progress::report_on_usr1();
+        }
+        let files = match self.files.is_empty() {
+            true => vec![PathBuf::from("-")],
+            false => self.files.clone(),
+        };
+        if self.check {
+            for path in &files {
+                match Input::open(path) {
+                    Ok(input) => self.check_file(input, &status),
+                    Err(e) => {
+                        show_error!("{}", e);
+                        status.fail();
+                    }
+                }
+            }
+            return status.code();
+        }
+        // Standard input can only be read by one of them.
+        let threads = match files.iter().any(|path| path.as_os_str() == "-") {
+            true => 1,
+            false => self.threads,
+        };
+        let algorithm = self.algorithm.unwrap_or(Algorithm::Crc);
+        let sum = |path: &PathBuf| {
+            let mut input = Input::open(path)?;
+            digest::sum_input(&mut input, algorithm, self.bits).map_err(|e| input.error(e))
+        };
+        pool::in_order(&files, threads, sum, |path, sum| match sum {
+            Ok(sum) => self.print_sum(&sum, &path.to_string_lossy()),
+            Err(e) => {
+                show_error!("{}", e);
+                status.fail();
+            }
+        });
+        status.code()
+    }
+
+    fn print_sum(&self, sum: &Sum, name: &str) {
+        let algorithm = self.algorithm.unwrap_or(Algorithm::Crc);
+        let line = match algorithm.is_legacy() {
+            true => sum.legacy(algorithm, Some(name).filter(|_| !self.files.is_empty())),
+            false => format_line(
+                algorithm,
+                self.bits,
+                sum,
+                name,
+                self.tag,
+                self.binary,
+                self.zero,
+            ),
+        };
+        let end = if self.zero { '\0' } else { '\n' };
+        if write!(io::stdout(), "{}{}", line, end).is_err() {
+            die!(1, "write error");
+        }
+    }
+
+    /// Verify every line of the check file `input`, hashing the files it
+    /// lists on as many threads as allowed.
+    fn check_file(&self, input: Input, status: &ExitStatus) {
+        let display = match input.is_stdin() {
+            true => quote("standard input"),
+            false => input.name().to_string(),
+        };
+        let mut lines = Vec::new();
+        let mut read_error = None;
+        for record in Records::new(input, b'\n') {
+            let record = match record {
+                Ok(record) => record,
+                Err(e) => {
+                    read_error = Some(e);
+                    break;
+                }
+            };
+            let text = String::from_utf8_lossy(&record.bytes);
+            lines.push(parse_check_line(&text).and_then(|line| {
+                let (algorithm, bits) = self.line_algorithm(&line)?;
+                Some((line, algorithm, bits))
+            }));
+        }
+
+        let listed = lines.iter().flatten();
+        let formatted = listed.clone().count();
+        // Progress reports and standard input are for one file at a time.
+        let threads =
+            match self.verify.progress || listed.clone().any(|(line, ..)| line.name == "-") {
+                true => 1,
+                false => self.threads,
+            };
+        let mut counts = Counts::default();
+        let numbered = lines.into_iter().enumerate().collect::<Vec<_>>();
+        let sum = |(_, line): &(usize, Option<(CheckLine, Algorithm, usize)>)| {
+            line.as_ref()
+                .map(|(line, algorithm, bits)| self.sum_listed(line, *algorithm, *bits))
+        };
+        pool::in_order(&numbered, threads, sum, |(number, line), sum| {
+            match (line, sum) {
+                (Some((line, ..)), Some(sum)) => self.verify_line(line, sum, &mut counts),
+                _ => {
+                    counts.malformed += 1;
+                    if self.verify.warn {
+                        let kind = self.algorithm.map(|a| a.tag(a.bits()) + " ");
+                        show_error!(
+                            "{}: {}: improperly formatted {}checksum line",
+                            display,
+                            number + 1,
+                            kind.unwrap_or_default()
+                        );
+                    }
+                }
+            }
+        });
+        if let Some(e) = read_error {
+            show_error!("{}: {}", display, strerror(&e));
+            status.fail();
+            return;
+        }
+
+        if formatted == 0 {
+            show_error!("{}: no properly formatted checksum lines found", display);
+            status.fail();
+            return;
+        }
+        if !self.verify.status {
+            counts.warn();
+        }
+        if self.verify.ignore_missing && counts.verified == 0 {
+            show_error!("{}: no file was verified", display);
+            status.fail();
+        }
+        if counts.failed > 0 || counts.unreadable > 0 || self.verify.strict && counts.malformed > 0
+        {
+            status.fail();
+        }
+    }
+
+    /// The algorithm and length `line` is to be checked with, if it can be
+    /// checked here at all.
+    fn line_algorithm(&self, line: &CheckLine) -> Option<(Algorithm, usize)> {
+        let bits = line.digest.len() * 8;
+        match (line.algorithm, self.algorithm) {
+            (Some(named), None) => Some(named),
+            (Some(named), Some(algorithm)) => (named.0 == algorithm).then_some(named),
+            (None, None) => None,
+            (None, Some(algorithm)) if algorithm.has_length() && self.bits == algorithm.bits() => {
+                digest::check_length(algorithm, bits)
+                    .ok()
+                    .map(|bits| (algorithm, bits))
+            }
+            (None, Some(algorithm)) => (bits == self.bits).then_some((algorithm, bits)),
+        }
+    }
+
+    /// The sum of the file `line` lists.
+    fn sum_listed(
+        &self,
+        line: &CheckLine,
+        algorithm: Algorithm,
+        bits: usize,
+    ) -> Result<Sum, InputError> {
+        let (escaped, shown) = digest::escape_name(&line.name);
+        let mut input = Input::open(Path::new(&line.name))?;
+        let sum = match self.verify.progress {
+            true => {
+                let shown = format!("{}{}", if escaped { "\\" } else { "" }, shown);
+                let mut report = Progress::new("read").label(shown);
+                if let Some(metadata) = fs::metadata(&line.name).ok().filter(|m| m.is_file()) {
+                    report = report.total(metadata.len());
+                }
+                let mut reader = progress::Reader::new(&mut input, report);
+                let sum = digest::sum(&mut reader, algorithm, bits);
+                reader.finish();
+                sum
+            }
+            false => digest::sum_input(&mut input, algorithm, bits),
+        };
+        sum.map_err(|e| input.error(e))
+    }
+
+    /// Report on the file `line` lists, whose sum came to `result`.
+    fn verify_line(&self, line: &CheckLine, result: Result<Sum, InputError>, counts: &mut Counts) {
+        let (escaped, shown) = digest::escape_name(&line.name);
+        let shown = format!("{}{}", if escaped { "\\" } else { "" }, shown);
+        let message = match result {
+            Err(e) if self.verify.ignore_missing && e.error.kind() == io::ErrorKind::NotFound => {
+                return;
+            }
+            Err(e) => {
+                show_error!("{}", e);
+                counts.unreadable += 1;
+                "FAILED open or read"
+            }
+            Ok(sum) if sum.digest == line.digest => {
+                counts.verified += 1;
+                if self.verify.quiet {
+                    return;
+                }
+                "OK"
+            }
+            Ok(_) => {
+                counts.verified += 1;
+                counts.failed += 1;
+                "FAILED"
+            }
+        };
+        if !self.verify.status {
+            println!("{}: {}", shown, message);
+        }
+    }
+}
+
+/// What became of the lines of one check file.
+#[derive(Default)]
+struct Counts {
+    verified: usize,
+    failed: usize,
+    unreadable: usize,
+    malformed: usize,
+}
+
+impl Counts {
+    fn warn(&self) {
+        let plural = |n: usize, one: &str, many: &str| match n {
+            1 => format!("1 {}", one),
+            n => format!("{} {}", n, many),
+        };
+        if self.malformed > 0 {
+            show_error!(
+                "WARNING: {}",
+                plural(
+                    self.malformed,
+                    "line is improperly formatted",
+                    "lines are improperly formatted"
+                )
+            );
+        }
+        if self.unreadable > 0 {
+            show_error!(
+                "WARNING: {}",
+                plural(
+                    self.unreadable,
+                    "listed file could not be read",
+                    "listed files could not be read"
+                )
+            );
+        }
+        if self.failed > 0 {
+            show_error!(
+                "WARNING: {}",
+                plural(
+                    self.failed,
+                    "computed checksum did NOT match",
+                    "computed checksums did NOT match"
+                )
+            );
+        }
+    }
+}
+
+/// Parse `--length` for `algorithm`, or exit saying why it is wrong.
+pub fn length(algorithm: Algorithm, length: Option<&str>) -> usize {
+    let Some(length) = length else {
+        return algorithm.bits();
+    };
+    digest::parse_length(algorithm, length).unwrap_or_else(|e| {
+        show_error!("invalid length: {}", quote(length));
+        match e {
+            LengthError::Invalid => {}
+            LengthError::NotMultipleOf8 => show_error!("length is not a multiple of 8"),
+            LengthError::TooLong => show_error!(
+                "maximum digest length for {} is {} bits",
+                quote(&algorithm.tag(algorithm.bits())),
+                algorithm.bits()
+            ),
+            LengthError::Unsupported(lengths) => {
+                let (last, rest) = lengths.split_last().unwrap();
+                let rest = rest.iter().map(|bits| format!("{}, ", bits));
+                show_error!(
+                    "digest length for {} must be {}or {}",
+                    quote(&algorithm.name().to_ascii_uppercase()),
+                    rest.collect::<String>(),
+                    last
+                )
+            }
+        }
+        std::process::exit(1)
+    })
+}
@@ -0,0 +1,60 @@
+use std::ffi::{CString, OsString};
+use std::fs;
+use std::io::{self, Write};
+use std::ptr;
+
+use crate::posix;
+use crate::uname::uname;
+use clap::{CommandFactory, Parser};
+
+#[derive(Parser)]
+#[command(version, about = "print the numeric identifier (in hexadecimal) for the current host", long_about = None)]
+struct Cli {}
+
+/// The host's identifier: the one recorded in /etc/hostid, or else one
+/// made from the host's IPv4 address with its halves swapped, as the C
+/// library's gethostid does. A host with neither is 0.
+fn hostid() -> u32 {
+    if let Ok(id) = fs::read("/etc/hostid") {
+        if let Ok(id) = <[u8; 4]>::try_from(&id[..id.len().min(4)]) {
+            return u32::from_ne_bytes(id);
+        }
+    }
+    address().map_or(0, |address| address.rotate_left(16))
+}
+
+/// The first IPv4 address the host's name resolves to, as it lies in
+/// memory.
+fn address() -> Option<u32> {
+    let name = CString::new(uname().ok()?.nodename).ok()?;
+    let mut hints: libc::addrinfo = unsafe { std::mem::zeroed() };
+    hints.ai_family = libc::AF_INET;
+    let mut result = ptr::null_mut();
+    if unsafe { libc::getaddrinfo(name.as_ptr(), ptr::null(), &hints, &mut result) } != 0 {
+        return None;
+    }
+    let address = unsafe {
+        match (*result).ai_addr.is_null() {
+            true => None,
+            false => Some(
+                (*((*result).ai_addr as *const libc::sockaddr_in))
+                    .sin_addr
+                    .s_addr,
+            ),
+        }
+    };
+    unsafe { libc::freeaddrinfo(result) };
+    address
+}
+
+pub fn command() -> clap::Command {
+    Cli::command()
+}
+
+pub fn run(args: Vec<OsString>) -> i32 {
+    posix::parse::<Cli>(args);
+    if writeln!(io::stdout(), "{:08x}", hostid()).is_err() {
+        return 1;
+    }
+    0
+}
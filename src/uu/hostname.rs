@@ -0,0 +1,97 @@
+use std::ffi::{CStr, CString, OsString};
+use std::io::{self, Write};
+use std::os::unix::ffi::OsStrExt;
+use std::ptr;
+
+use crate::error::{last_error, strerror};
+use crate::posix;
+use crate::show_error;
+use clap::{CommandFactory, Parser};
+
+#[derive(Parser)]
+#[command(version, about = "show or set the system's host name", long_about = None)]
+struct Cli {
+    name: Option<OsString>,
+
+    /// short host name, up to the first dot
+    #[arg(short, long, conflicts_with = "fqdn")]
+    short: bool,
+
+    /// long host name (FQDN)
+    #[arg(short, long, visible_alias = "long")]
+    fqdn: bool,
+}
+
+fn hostname() -> io::Result<String> {
+    let mut buffer = vec![0u8; 256];
+    if unsafe { libc::gethostname(buffer.as_mut_ptr() as *mut libc::c_char, buffer.len()) } != 0 {
+        return Err(io::Error::last_os_error());
+    }
+    let end = buffer.iter().position(|&b| b == 0).unwrap_or(buffer.len());
+    Ok(String::from_utf8_lossy(&buffer[..end]).to_string())
+}
+
+/// Resolve the canonical name of a host through the resolver.
+fn canonical_name(host: &str) -> Option<String> {
+    let host = CString::new(host).ok()?;
+    let mut hints: libc::addrinfo = unsafe { std::mem::zeroed() };
+    hints.ai_flags = libc::AI_CANONNAME;
+    let mut result = ptr::null_mut();
+    if unsafe { libc::getaddrinfo(host.as_ptr(), ptr::null(), &hints, &mut result) } != 0 {
+        return None;
+    }
+    let name = unsafe {
+        match (*result).ai_canonname.is_null() {
+            true => None,
+            false => Some(
+                CStr::from_ptr((*result).ai_canonname)
+                    .to_string_lossy()
+                    .to_string(),
+            ),
+        }
+    };
+    unsafe { libc::freeaddrinfo(result) };
+    name
+}
+
+pub fn command() -> clap::Command {
+    Cli::command()
+}
+
+pub fn run(args: Vec<OsString>) -> i32 {
+    let cli = posix::parse::<Cli>(args);
+
+    if let Some(name) = cli.name {
+        let bytes = name.as_bytes();
+        if unsafe { libc::sethostname(bytes.as_ptr() as *const libc::c_char, bytes.len() as _) }
+            != 0
+        {
+            show_error!(
+                "cannot set name to '{}': {}",
+                name.to_string_lossy(),
+                last_error()
+            );
+            return 1;
+        }
+        return 0;
+    }
+
+    let name = match hostname() {
+        Ok(name) => name,
+        Err(e) => {
+            show_error!("cannot determine hostname: {}", strerror(&e));
+            return 1;
+        }
+    };
+    let name = if cli.short {
+        name.split('.').next().unwrap_or_default().to_string()
+    } else if cli.fqdn {
+        canonical_name(&name).unwrap_or(name)
+    } else {
+        name
+    };
+    if writeln!(io::stdout(), "{}", name).is_err() {
+        return 1;
+    }
+    0
+}
@@ -0,0 +1,256 @@
+use std::ffi::OsString;
+use std::fs;
+use std::io::{self, Write};
+use std::path::Path;
+
+use crate::error::ExitStatus;
+use crate::posix;
+use crate::usergroup::{group_name, user_groups, user_name};
+use crate::{show_error, usage_error};
+use clap::{CommandFactory, Parser};
+use users::{get_user_by_name, get_user_by_uid};
+
+#[derive(Parser)]
+#[command(version, about = "print real and effective user and group IDs", long_about = None)]
+struct Cli {
+    users: Vec<OsString>,
+
+    /// ignore, for compatibility with other versions
+    #[arg(short = 'a')]
+    ignored: bool,
+
+    /// print only the security context of the process
+    #[arg(short = 'Z', long)]
+    context: bool,
+
+    /// print only the effective group ID
+    #[arg(short, long)]
+    group: bool,
+
+    /// print all group IDs
+    #[arg(short = 'G', long)]
+    groups: bool,
+
+    /// print a name instead of a number, for -u,-g,-G
+    #[arg(short, long)]
+    name: bool,
+
+    /// print the real ID instead of the effective ID, with -u,-g,-G
+    #[arg(short, long)]
+    real: bool,
+
+    /// print only the effective user ID
+    #[arg(short, long)]
+    user: bool,
+
+    /// delimit entries with NUL characters, not whitespace
+    #[arg(short, long)]
+    zero: bool,
+}
+
+struct Ids {
+    ruid: u32,
+    euid: u32,
+    rgid: u32,
+    egid: u32,
+    groups: Vec<u32>,
+    for_process: bool,
+}
+
+fn process_groups() -> Vec<u32> {
+    let count = unsafe { libc::getgroups(0, std::ptr::null_mut()) };
+    if count <= 0 {
+        return vec![];
+    }
+    let mut groups = vec![0; count as usize];
+    let count = unsafe { libc::getgroups(count, groups.as_mut_ptr()) };
+    groups.truncate(count.max(0) as usize);
+    groups
+}
+
+impl Ids {
+    fn current() -> Self {
+        Ids {
+            ruid: users::get_current_uid(),
+            euid: users::get_effective_uid(),
+            rgid: users::get_current_gid(),
+            egid: users::get_effective_gid(),
+            groups: process_groups(),
+            for_process: true,
+        }
+    }
+
+    fn for_user(name: &OsString) -> Option<Self> {
+        let user = get_user_by_name(name).or_else(|| {
+            name.to_str()
+                .and_then(|n| n.parse().ok())
+                .and_then(get_user_by_uid)
+        })?;
+        Some(Ids {
+            ruid: user.uid(),
+            euid: user.uid(),
+            rgid: user.primary_group_id(),
+            egid: user.primary_group_id(),
+            groups: user_groups(user.name(), user.primary_group_id()),
+            for_process: false,
+        })
+    }
+
+    /// All group ids in display order, the primary group first and without
+    /// duplicates.
+    fn all_groups(&self) -> Vec<u32> {
+        let mut groups = vec![self.rgid];
+        if self.egid != self.rgid {
+            groups.push(self.egid);
+        }
+        for gid in &self.groups {
+            if !groups.contains(gid) {
+                groups.push(*gid);
+            }
+        }
+        groups
+    }
+}
+
+fn selinux_context() -> Option<String> {
+    if !Path::new("/sys/fs/selinux/enforce").exists() {
+        return None;
+    }
+    fs::read_to_string("/proc/self/attr/current")
+        .ok()
+        .map(|c| c.trim_end_matches(['\0', '\n']).to_string())
+        .filter(|c| !c.is_empty())
+}
+
+fn with_name(id: u32, name: Option<String>) -> String {
+    match name {
+        Some(name) => format!("{}({})", id, name),
+        None => id.to_string(),
+    }
+}
+
+pub fn command() -> clap::Command {
+    Cli::command()
+}
+
+pub fn run(args: Vec<OsString>) -> i32 {
+    let cli = posix::parse::<Cli>(args);
+
+    let selected = [cli.user, cli.group, cli.groups, cli.context]
+        .into_iter()
+        .filter(|&s| s)
+        .count();
+    if selected > 1 {
+        usage_error!(1, "cannot print \"only\" of more than one choice");
+    }
+    if selected == 0 && (cli.name || cli.real) {
+        usage_error!(1, "cannot print only names or real IDs in default format");
+    }
+    if selected == 0 && cli.zero {
+        usage_error!(1, "option --zero not permitted in default format");
+    }
+    if cli.context && !cli.users.is_empty() {
+        usage_error!(1, "cannot print security context when user specified");
+    }
+
+    let status = ExitStatus::new();
+    let delimiter = if cli.zero { "\0" } else { " " };
+    let terminator = if cli.zero { "\0" } else { "\n" };
+    let lookup_name = |kind: &str, id: u32, name: Option<String>| match name {
+        Some(name) => name,
+        None => {
+            show_error!("cannot find name for {} ID {}", kind, id);
+            status.fail();
+            id.to_string()
+        }
+    };
+
+    let targets = match cli.users.is_empty() {
+        true => vec![Some(Ids::current())],
+        false => cli
+            .users
+            .iter()
+            .map(|name| {
+                let ids = Ids::for_user(name);
+                if ids.is_none() {
+                    show_error!("'{}': no such user", name.to_string_lossy());
+                }
+                ids
+            })
+            .collect(),
+    };
+
+    let mut output = String::new();
+    for ids in targets {
+        let Some(ids) = ids else {
+            status.fail();
+            continue;
+        };
+        if cli.context {
+            match selinux_context() {
+                Some(context) => output.push_str(&context),
+                None => {
+                    show_error!("--context (-Z) works only on an SELinux-enabled kernel");
+                    return 1;
+                }
+            }
+        } else if cli.user {
+            let uid = if cli.real { ids.ruid } else { ids.euid };
+            output.push_str(&match cli.name {
+                true => lookup_name("user", uid, user_name(uid)),
+                false => uid.to_string(),
+            });
+        } else if cli.group {
+            let gid = if cli.real { ids.rgid } else { ids.egid };
+            output.push_str(&match cli.name {
+                true => lookup_name("group", gid, group_name(gid)),
+                false => gid.to_string(),
+            });
+        } else if cli.groups {
+            let groups = ids
+                .all_groups()
+                .into_iter()
+                .map(|gid| match cli.name {
+                    true => lookup_name("group", gid, group_name(gid)),
+                    false => gid.to_string(),
+                })
+                .collect::<Vec<String>>();
+            output.push_str(&groups.join(delimiter));
+        } else {
+            output.push_str(&format!(
+                "uid={} gid={}",
+                with_name(ids.ruid, user_name(ids.ruid)),
+                with_name(ids.rgid, group_name(ids.rgid))
+            ));
+            if ids.euid != ids.ruid {
+                output.push_str(&format!(
+                    " euid={}",
+                    with_name(ids.euid, user_name(ids.euid))
+                ));
+            }
+            if ids.egid != ids.rgid {
+                output.push_str(&format!(
+                    " egid={}",
+                    with_name(ids.egid, group_name(ids.egid))
+                ));
+            }
+            let groups = ids
+                .all_groups()
+                .into_iter()
+                .map(|gid| with_name(gid, group_name(gid)))
+                .collect::<Vec<String>>();
+            output.push_str(&format!(" groups={}", groups.join(",")));
+            if ids.for_process {
+                if let Some(context) = selinux_context() {
+                    output.push_str(&format!(" context={}", context));
+                }
+            }
+        }
+        output.push_str(terminator);
+    }
+
+    if io::stdout().write_all(output.as_bytes()).is_err() {
+        return 1;
+    }
+    status.code()
+}
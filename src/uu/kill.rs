@@ -0,0 +1,247 @@
+use std::ffi::OsString;
+use std::io::{self, Write};
+
+use crate::error::{strerror, ExitStatus};
+use crate::quote::quote;
+use crate::signal;
+use crate::version;
+use crate::{show_error, usage_error};
+use clap::{CommandFactory, Parser};
+
+const AFTER_HELP: &str = "\
+SIGNAL may be a signal name like 'HUP', or a signal number like '1',
+or the exit status of a process terminated by a signal.
+PID is an integer; if negative it identifies a process group.";
+
+/// Send signals to processes, or list signals. The arguments are parsed by
+/// hand, since `-SIGNAL` and negative PIDs look like options; this
+/// describes them for --help, completions and manual pages.
+#[derive(Parser)]
+#[command(
+    version,
+    about = "send signals to processes, or list signals",
+    override_usage = "kill [-s SIGNAL | -SIGNAL] PID...\n       \
+        kill -l [SIGNAL]...\n       \
+        kill -t [SIGNAL]...",
+    after_help = AFTER_HELP,
+    long_about = None
+)]
+struct Cli {
+    /// specify the name or number of the signal to be sent
+    #[arg(short, long, value_name = "SIGNAL")]
+    signal: Option<String>,
+
+    /// list signal names, or convert signal names to/from numbers
+    #[arg(short, long)]
+    list: bool,
+
+    /// print a table of signal information
+    #[arg(short, long)]
+    table: bool,
+
+    /// PID... or SIGNAL...
+    #[arg(allow_hyphen_values = true)]
+    operands: Vec<String>,
+}
+
+/// The signal an operand names: a name, with or without `SIG`, or a
+/// number, which may be the exit status of a process the signal killed.
+/// 0 is `EXIT`.
+fn operand_signal(operand: &str) -> Option<i32> {
+    let signal = match operand.starts_with(|c: char| c.is_ascii_digit()) {
+        true => {
+            let number = operand.parse::<i32>().ok()?;
+            number & if number >= 0xff { 0xff } else { 0x7f }
+        }
+        false => match operand.to_ascii_uppercase().as_str() {
+            "EXIT" | "SIGEXIT" => 0,
+            name => signal::parse(name)?,
+        },
+    };
+    (signal == 0 || signal::named().any(|s| s == signal)).then_some(signal)
+}
+
+fn signal_name(signal: i32) -> String {
+    match signal {
+        0 => "EXIT".to_string(),
+        signal => signal::name(signal),
+    }
+}
+
+fn parse_signal(operand: &str) -> i32 {
+    operand_signal(operand).unwrap_or_else(|| usage_error!(1, "{}: invalid signal", quote(operand)))
+}
+
+/// Print every signal, or those of `operands`, as names or numbers, or as
+/// a table with their descriptions.
+fn list(operands: &[String], table: bool) -> i32 {
+    let status = ExitStatus::new();
+    let name_width = signal::named()
+        .map(|s| signal_name(s).len())
+        .max()
+        .unwrap_or(0);
+    let number_width = libc::SIGRTMAX().to_string().len();
+    let mut out = io::stdout().lock();
+    let mut print = |signal: i32, by_name: bool| {
+        let name = signal_name(signal);
+        let written = match (table, by_name) {
+            (true, _) => writeln!(
+                out,
+                "{:>number_width$} {:<name_width$} {}",
+                signal,
+                name,
+                signal::description(signal)
+            ),
+            // A name is turned into its number, and a number into its name.
+            (false, true) => writeln!(out, "{}", signal),
+            (false, false) => writeln!(out, "{}", name),
+        };
+        if written.is_err() {
+            status.fail();
+        }
+    };
+    if operands.is_empty() {
+        signal::named().for_each(|signal| print(signal, false));
+    }
+    for operand in operands {
+        match operand_signal(operand) {
+            Some(signal) => print(signal, !operand.starts_with(|c: char| c.is_ascii_digit())),
+            None => {
+                show_error!("{}: invalid signal", quote(operand));
+                status.fail();
+            }
+        }
+    }
+    status.code()
+}
+
+/// Send `signal` to each process, or process group for a negative PID.
+fn send(signal: i32, pids: &[String]) -> i32 {
+    let status = ExitStatus::new();
+    for operand in pids {
+        let text = operand.trim_start();
+        let pid = text
+            .strip_prefix('+')
+            .unwrap_or(text)
+            .parse::<libc::pid_t>()
+            .ok()
+            .filter(|_| !text.starts_with("+-"));
+        let Some(pid) = pid else {
+            show_error!("{}: invalid process id", quote(operand));
+            status.fail();
+            continue;
+        };
+        if let Err(e) = signal::send(pid, signal) {
+            show_error!("{}: {}", quote(operand), strerror(&e));
+            status.fail();
+        }
+    }
+    status.code()
+}
+
+pub fn command() -> clap::Command {
+    Cli::command()
+}
+
+pub fn run(args: Vec<OsString>) -> i32 {
+    let args = args
+        .into_iter()
+        .skip(1)
+        .map(|a| a.to_string_lossy().into_owned())
+        .collect::<Vec<String>>();
+
+    let mut signal = None;
+    let mut listing = None;
+    let mut set_signal = |operand: &str| {
+        if signal.is_some() {
+            usage_error!(1, "{}: multiple signals specified", quote(operand));
+        }
+        signal = Some(parse_signal(operand));
+    };
+    let mut set_listing = |table: bool| {
+        if listing.is_some() {
+            usage_error!(1, "multiple -l or -t options specified");
+        }
+        listing = Some(table);
+    };
+    let mut operands = Vec::new();
+    let mut args = args.into_iter().enumerate();
+    while let Some((index, arg)) = args.next() {
+        if arg == "--" {
+            operands.extend(args.by_ref().map(|(_, arg)| arg));
+            break;
+        }
+        if let Some(long) = arg.strip_prefix("--") {
+            let (name, value) = match long.split_once('=') {
+                Some((name, value)) => (name, Some(value.to_string())),
+                None => (long, None),
+            };
+            let matches = ["help", "list", "signal", "table", "version"]
+                .into_iter()
+                .filter(|option| option.starts_with(name))
+                .collect::<Vec<_>>();
+            match (matches.as_slice(), value) {
+                (["help"], None) => {
+                    let _ = Cli::command().print_help();
+                    return 0;
+                }
+                (["version"], None) => {
+                    print!("{}", version::stamp(Cli::command()).render_version());
+                    return 0;
+                }
+                (["list"], None) => set_listing(false),
+                (["table"], None) => set_listing(true),
+                (["signal"], Some(value)) => set_signal(&value),
+                (["signal"], None) => match args.next() {
+                    Some((_, value)) => set_signal(&value),
+                    None => usage_error!(1, "option '--signal' requires an argument"),
+                },
+                ([option], Some(_)) => {
+                    usage_error!(1, "option '--{}' doesn't allow an argument", option)
+                }
+                ([], _) => usage_error!(1, "unrecognized option '{}'", arg),
+                _ => usage_error!(1, "option '{}' is ambiguous", arg),
+            }
+            continue;
+        }
+        let Some(short) = arg.strip_prefix('-').filter(|s| !s.is_empty()) else {
+            operands.push(arg);
+            continue;
+        };
+        match short.chars().next() {
+            // -9 is a signal only as the first argument; later it is a
+            // process group.
+            Some(c) if c.is_ascii_digit() && index != 0 => operands.push(arg),
+            Some(c) if c.is_ascii_digit() || c.is_ascii_uppercase() => set_signal(short),
+            _ => {
+                for (i, c) in short.char_indices() {
+                    match c {
+                        'l' => set_listing(false),
+                        't' | 'L' => set_listing(true),
+                        's' | 'n' => {
+                            let value = match &short[i + 1..] {
+                                "" => match args.next() {
+                                    Some((_, value)) => value,
+                                    None => {
+                                        usage_error!(1, "option requires an argument -- '{}'", c)
+                                    }
+                                },
+                                rest => rest.to_string(),
+                            };
+                            set_signal(&value);
+                            break;
+                        }
+                        c => usage_error!(1, "invalid option -- '{}'", c),
+                    }
+                }
+            }
+        }
+    }
+
+    match (listing, signal) {
+        (Some(_), Some(_)) => usage_error!(1, "cannot combine signal with -l or -t"),
+        (Some(table), None) => list(&operands, table),
+        (None, _) if operands.is_empty() => usage_error!(1, "no process ID specified"),
+        (None, signal) => send(signal.unwrap_or(libc::SIGTERM), &operands),
+    }
+}
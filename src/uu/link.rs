@@ -0,0 +1,36 @@
+use std::ffi::{CString, OsString};
+use std::os::unix::ffi::OsStrExt;
+use std::path::PathBuf;
+
+use crate::error::last_error;
+use crate::posix;
+use crate::quote::quote;
+use crate::show_error;
+use clap::{CommandFactory, Parser};
+
+#[derive(Parser)]
+#[command(version, about = "call the link function to create a link named FILE2 to an existing FILE1", long_about = None)]
+struct Cli {
+    file1: PathBuf,
+    file2: PathBuf,
+}
+
+pub fn command() -> clap::Command {
+    Cli::command()
+}
+
+pub fn run(args: Vec<OsString>) -> i32 {
+    let cli = posix::parse::<Cli>(args);
+    let from = CString::new(cli.file1.as_os_str().as_bytes()).unwrap();
+    let to = CString::new(cli.file2.as_os_str().as_bytes()).unwrap();
+    if unsafe { libc::link(from.as_ptr(), to.as_ptr()) } != 0 {
+        show_error!(
+            "cannot create link {} to {}: {}",
+            quote(&cli.file2),
+            quote(&cli.file1),
+            last_error()
+        );
+        return 1;
+    }
+    0
+}
@@ -0,0 +1,58 @@
+use std::ffi::{CStr, OsString};
+use std::io::{self, Write};
+use std::path::Path;
+
+use crate::posix;
+use crate::show_error;
+use crate::utmp::{read_utmp, UTMP_FILE};
+use clap::{CommandFactory, Parser};
+
+#[derive(Parser)]
+#[command(version, about = "print user's login name", long_about = None)]
+struct Cli {}
+
+/// Find the login that owns the terminal on one of the standard streams by
+/// its utmp entry.
+fn utmp_login() -> Option<String> {
+    let tty = (0..3).find_map(|fd| {
+        let name = unsafe { libc::ttyname(fd) };
+        match name.is_null() {
+            true => None,
+            false => Some(
+                unsafe { CStr::from_ptr(name) }
+                    .to_string_lossy()
+                    .to_string(),
+            ),
+        }
+    })?;
+    let line = tty.strip_prefix("/dev/").unwrap_or(&tty).to_string();
+    read_utmp(Path::new(UTMP_FILE))
+        .into_iter()
+        .find(|record| record.is_user_process() && record.line == line)
+        .map(|record| record.user)
+}
+
+pub fn command() -> clap::Command {
+    Cli::command()
+}
+
+pub fn run(args: Vec<OsString>) -> i32 {
+    posix::parse::<Cli>(args);
+    let login = unsafe { libc::getlogin() };
+    let name = match login.is_null() {
+        true => utmp_login(),
+        false => Some(
+            unsafe { CStr::from_ptr(login) }
+                .to_string_lossy()
+                .to_string(),
+        ),
+    };
+    let Some(name) = name else {
+        show_error!("no login name");
+        return 1;
+    };
+    if writeln!(io::stdout(), "{}", name).is_err() {
+        return 1;
+    }
+    0
+}
@@ -0,0 +1,645 @@
+use std::cell::OnceCell;
+use std::cmp::Ordering;
+use std::ffi::{OsStr, OsString};
+use std::fs::{self, DirEntry, FileType, Metadata};
+use std::os::unix::ffi::OsStrExt;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use std::time::SystemTime;
+
+use crate::acl;
+use crate::colors::LsColors;
+use crate::envcfg;
+use crate::error::{strerror, ExitStatus};
+use crate::fs::{EntryKind, SymlinkPolicy, WalkErrorKind, Walker};
+use crate::glob::{MatchOptions, Pattern};
+use crate::locale::{self, collate};
+use crate::mode::FileMode;
+use crate::posix;
+use crate::quote::{quote, quote_with, QuotingStyle};
+use crate::selinux;
+use crate::size::{format_size, SizeFormat};
+use crate::sys::{self, FileInfo};
+use crate::table::{ColumnAlignment, Table, TableColumn, TableRow};
+use crate::term::{stdout_is_tty, width_or_default};
+use crate::text::join_wrapped;
+use crate::timefmt::{TimeStyle, ZonedTime};
+use crate::version_cmp::version_cmp;
+use crate::{show_error, usage_error};
+use clap::{ArgAction, CommandFactory, Parser, ValueEnum};
+
+#[derive(Clone, Copy, ValueEnum)]
+enum When {
+    Always,
+    Auto,
+    Never,
+}
+
+/// The timestamp --time selects.
+#[derive(Clone, Copy, ValueEnum)]
+enum TimeField {
+    /// last access time
+    #[value(aliases = ["atime", "use"])]
+    Access,
+    /// last status change time
+    #[value(aliases = ["ctime", "status"])]
+    Change,
+    /// last modification time
+    #[value(alias = "mtime")]
+    Modification,
+    /// file creation time
+    #[value(alias = "creation")]
+    Birth,
+}
+
+/// The styles to color names with, when coloring.
+static COLORS: OnceLock<Option<LsColors>> = OnceLock::new();
+
+/// How names are quoted in the listing.
+static QUOTING_STYLE: OnceLock<QuotingStyle> = OnceLock::new();
+
+/// The resolved --time-style, and the time listings are relative to.
+static TIME_STYLE: OnceLock<(TimeStyle, i128)> = OnceLock::new();
+
+#[derive(Parser)]
+#[command(version, about = "list directory contents", long_about = None, disable_help_flag(true))]
+struct Cli {
+    path: Option<Vec<PathBuf>>,
+
+    #[arg(short = '?', long, action(ArgAction::Help))]
+    help: Option<bool>,
+
+    /// do not ignore entries starting with .
+    #[arg(short, long)]
+    all: bool,
+
+    /// use a long listing format
+    #[arg(short, overrides_with = "commas")]
+    long: bool,
+
+    /// fill width with a comma separated list of entries
+    #[arg(short = 'm', overrides_with = "long")]
+    commas: bool,
+
+    /// make the output human readable
+    #[arg(short, long)]
+    human_readable: bool,
+
+    /// likewise, but use powers of 1000 not 1024
+    #[arg(long)]
+    si: bool,
+
+    /// In directories, ignore files that end with ‘~’
+    #[arg(short = 'B', long)]
+    ignore_backups: bool,
+
+    /// do not list implied entries matching shell PATTERN
+    #[arg(short = 'I', long, value_name = "PATTERN")]
+    ignore: Vec<String>,
+
+    /// do not list implied entries matching shell PATTERN (overridden by -a)
+    #[arg(long, value_name = "PATTERN")]
+    hide: Vec<String>,
+
+    /// List just the names of directories
+    #[arg(short, long)]
+    directory: bool,
+
+    /// color the output WHEN
+    #[arg(long, value_name = "WHEN", value_enum, num_args = 0..=1, default_missing_value = "always", require_equals = true)]
+    color: Option<When>,
+
+    /// like -l --time-style=full-iso
+    #[arg(long)]
+    full_time: bool,
+
+    /// select which timestamp is shown with -l
+    #[arg(long, value_name = "WORD", value_enum)]
+    time: Option<TimeField>,
+
+    /// with -l: show time of last access
+    #[arg(short = 'u')]
+    access_time: bool,
+
+    /// with -l: show time of last status change
+    #[arg(short = 'c')]
+    change_time: bool,
+
+    /// time/date format with -l; see TIME_STYLE below
+    #[arg(long, value_name = "TIME_STYLE")]
+    time_style: Option<String>,
+
+    /// print C-style escapes for nongraphic characters
+    #[arg(short = 'b', long)]
+    escape: bool,
+
+    /// print entry names without quoting
+    #[arg(short = 'N', long)]
+    literal: bool,
+
+    /// enclose entry names in double quotes
+    #[arg(short = 'Q', long)]
+    quote_name: bool,
+
+    /// use quoting style WORD for entry names: literal, locale, shell, shell-always, shell-escape, shell-escape-always, c, escape
+    #[arg(long, value_name = "WORD", value_parser = parse_quoting_style)]
+    quoting_style: Option<QuotingStyle>,
+
+    /// list subdirectories recursively
+    #[arg(short = 'R', long)]
+    recursive: bool,
+
+    /// natural sort of (version) numbers within text
+    #[arg(short = 'v')]
+    version_sort: bool,
+
+    /// do not sort; list entries in directory order
+    #[arg(short = 'U')]
+    unsorted: bool,
+
+    /// list all entries in directory order
+    #[arg(short = 'f')]
+    all_unsorted: bool,
+
+    /// group directories before files
+    #[arg(long)]
+    group_directories_first: bool,
+
+    /// print any security context of each file
+    #[arg(short = 'Z', long)]
+    context: bool,
+}
+
+fn parse_quoting_style(name: &str) -> Result<QuotingStyle, String> {
+    QuotingStyle::parse(name).ok_or_else(|| {
+        let valid = QuotingStyle::NAMES.map(|(n, _)| n).join(", ");
+        format!("valid arguments are: {}", valid)
+    })
+}
+
+/// The quoting style from the options, then $QUOTING_STYLE, defaulting to
+/// shell-escape on a terminal and literal otherwise.
+fn quoting_style(cli: &Cli) -> QuotingStyle {
+    if let Some(style) = cli.quoting_style {
+        return style;
+    }
+    if cli.escape {
+        return QuotingStyle::Escape;
+    }
+    if cli.quote_name {
+        return QuotingStyle::C;
+    }
+    if cli.literal {
+        return QuotingStyle::Literal;
+    }
+    if let Some(name) = envcfg::quoting_style() {
+        match QuotingStyle::parse(&name) {
+            Some(style) => return style,
+            None => show_error!(
+                "ignoring invalid value of environment variable QUOTING_STYLE: {}",
+                quote(&name)
+            ),
+        }
+    }
+    match stdout_is_tty() {
+        true => QuotingStyle::ShellEscape,
+        false => QuotingStyle::Literal,
+    }
+}
+
+fn quote_name(name: &OsStr) -> String {
+    quote_with(name, *QUOTING_STYLE.get().unwrap_or(&QuotingStyle::Literal))
+}
+
+struct LSFile<'a> {
+    path: PathBuf,
+    cli: &'a Cli,
+    /// The directory entry the file was read from, through which its
+    /// metadata is had relative to the directory.
+    entry: Option<DirEntry>,
+    /// The type of the file, which a directory entry usually tells without
+    /// a stat, or None if it could not be had.
+    file_type: Option<FileType>,
+    /// The file's own metadata, from a single lstat the first time it is
+    /// wanted, or None if it could not be had.
+    metadata: OnceCell<Option<Metadata>>,
+    /// What a symlink points to, looked up the first time it is wanted.
+    target: OnceCell<Option<Metadata>>,
+}
+
+impl<'a> LSFile<'a> {
+    fn new(path: PathBuf, cli: &'a Cli) -> Self {
+        let metadata = path.symlink_metadata().ok();
+        LSFile {
+            path,
+            cli,
+            entry: None,
+            file_type: metadata.as_ref().map(Metadata::file_type),
+            metadata: OnceCell::from(metadata),
+            target: OnceCell::new(),
+        }
+    }
+
+    fn from_entry(entry: DirEntry, cli: &'a Cli) -> Self {
+        LSFile {
+            path: entry.path(),
+            cli,
+            file_type: entry.file_type().ok(),
+            entry: Some(entry),
+            metadata: OnceCell::new(),
+            target: OnceCell::new(),
+        }
+    }
+
+    fn metadata(&self) -> Option<&Metadata> {
+        self.metadata
+            .get_or_init(|| match &self.entry {
+                Some(entry) => entry.metadata().ok(),
+                None => self.path.symlink_metadata().ok(),
+            })
+            .as_ref()
+    }
+
+    /// The metadata of the file itself or, for a symlink, of what it
+    /// points to.
+    fn target(&self) -> Option<&Metadata> {
+        match self.is_symlink() {
+            true => self
+                .target
+                .get_or_init(|| self.path.metadata().ok())
+                .as_ref(),
+            false => self.metadata(),
+        }
+    }
+
+    fn is_dir(&self) -> bool {
+        match self.file_type {
+            Some(file_type) if !file_type.is_symlink() => file_type.is_dir(),
+            _ => self.target().is_some_and(Metadata::is_dir),
+        }
+    }
+
+    fn name(&self) -> &OsStr {
+        self.path.file_name().unwrap_or_default()
+    }
+
+    /// The name as listed, quoted and colored as the options ask.
+    fn display_name(&self) -> String {
+        let name = self.name();
+        let colors = COLORS.get().and_then(Option::as_ref);
+        let painted = match colors {
+            Some(colors) => colors.paint(
+                &quote_name(name),
+                colors.style_lazily(
+                    &name.to_string_lossy(),
+                    self.file_type,
+                    || self.metadata(),
+                    || self.target(),
+                ),
+            ),
+            None => quote_name(name),
+        };
+        if !self.is_symlink() {
+            return painted;
+        }
+        match fs::read_link(&self.path) {
+            Ok(link) => {
+                let quoted = quote_name(link.as_os_str());
+                let link = link.to_string_lossy();
+                let painted_link = match (colors, self.target()) {
+                    (Some(colors), Some(target)) => {
+                        colors.paint(&quoted, colors.style(&link, Some(target), Some(target)))
+                    }
+                    (Some(colors), None) => colors.paint(&quoted, colors.style(&link, None, None)),
+                    (None, _) => quoted,
+                };
+                format!("{} -> {}", painted, painted_link)
+            }
+            _ => painted,
+        }
+    }
+
+    fn is_symlink(&self) -> bool {
+        self.file_type.is_some_and(|t| t.is_symlink())
+    }
+
+    fn info(&self) -> Option<FileInfo> {
+        self.metadata()
+            .map(|metadata| FileInfo::new(&self.path, metadata))
+    }
+
+    /// What follows the mode with -l: `+` if the file has an access
+    /// control list.
+    fn mode_suffix(&self) -> &'static str {
+        match acl::has_extended(&self.path) {
+            true => "+",
+            false => "",
+        }
+    }
+
+    /// The SELinux context for -Z, or `?` if the file has none.
+    fn context(&self) -> String {
+        selinux::context(&self.path, false)
+            .ok()
+            .flatten()
+            .unwrap_or_else(|| "?".to_string())
+    }
+
+    /// The timestamp the listing shows.
+    fn time(&self) -> Option<ZonedTime> {
+        let info = self.info()?;
+        let field = match (self.cli.time, self.cli.access_time, self.cli.change_time) {
+            (Some(field), _, _) => field,
+            (None, true, _) => TimeField::Access,
+            (None, _, true) => TimeField::Change,
+            (None, false, false) => TimeField::Modification,
+        };
+        ZonedTime::from_system_time(match field {
+            TimeField::Access => info.accessed?,
+            TimeField::Change => info.changed?,
+            TimeField::Modification => info.modified?,
+            TimeField::Birth => info.born?,
+        })
+    }
+}
+
+impl<'a> Eq for LSFile<'a> {}
+
+impl<'a> PartialEq for LSFile<'a> {
+    fn eq(&self, other: &Self) -> bool {
+        self.path == other.path
+    }
+}
+
+impl<'a> PartialOrd for LSFile<'a> {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<'a> Ord for LSFile<'a> {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        if self.cli.group_directories_first {
+            if self.is_dir() && !other.is_dir() {
+                return Ordering::Less;
+            } else if other.is_dir() && !self.is_dir() {
+                return Ordering::Greater;
+            }
+        }
+        let (a, b) = (self.name().as_bytes(), other.name().as_bytes());
+        match self.cli.version_sort {
+            true => version_cmp(a, b),
+            false => collate(a, b),
+        }
+        .then_with(|| a.cmp(b))
+    }
+}
+
+impl<'a> From<LSFile<'a>> for TableRow<String, 8> {
+    fn from(val: LSFile<'a>) -> Self {
+        let size_format = if val.cli.si {
+            SizeFormat::Decimal
+        } else if val.cli.human_readable {
+            SizeFormat::Binary
+        } else {
+            SizeFormat::Bytes
+        };
+        let info = val.info();
+        let unknown = || "?".to_string();
+        TableRow::new([
+            info.map_or_else(unknown, |i| {
+                format!("{}{}", FileMode(i.mode), val.mode_suffix())
+            }),
+            info.map_or_else(unknown, |i| i.nlink.to_string()),
+            info.map_or_else(unknown, |i| {
+                sys::owner_name(&val.path, &i).unwrap_or_else(|| i.uid.to_string())
+            }),
+            info.map_or_else(unknown, |i| {
+                sys::group_name(&val.path, &i).unwrap_or_else(|| i.gid.to_string())
+            }),
+            match val.cli.context {
+                true => val.context(),
+                false => String::new(),
+            },
+            format_size(info.map_or(0, |i| i.size), size_format),
+            match (val.time(), TIME_STYLE.get()) {
+                (Some(time), Some((style, now))) => style.format(&time, *now),
+                _ => "?".to_string(),
+            },
+            val.display_name(),
+        ])
+    }
+}
+
+/// The --ignore patterns, and the --hide ones unless -a overrides them.
+fn ignore_patterns(cli: &Cli) -> Vec<Pattern> {
+    let options = MatchOptions {
+        period: true,
+        ..Default::default()
+    };
+    cli.ignore
+        .iter()
+        .chain(cli.hide.iter().filter(|_| !cli.all))
+        .map(|pattern| Pattern::new(pattern, options))
+        .collect()
+}
+
+fn is_ignored(path: &Path, patterns: &[Pattern]) -> bool {
+    path.file_name()
+        .is_some_and(|name| is_ignored_name(name, patterns))
+}
+
+fn is_ignored_name(name: &OsStr, patterns: &[Pattern]) -> bool {
+    patterns.iter().any(|p| p.matches(&name.to_string_lossy()))
+}
+
+fn output_path(path: PathBuf, cli: &Cli, index: usize, status: &ExitStatus) {
+    let ignore = ignore_patterns(cli);
+    if let Err(e) = path.symlink_metadata() {
+        show_error!("cannot access {}: {}", quote(&path), strerror(&e));
+        status.set(2);
+        return;
+    }
+    // Names are weeded out before anything is looked up about them.
+    let wanted = |name: &OsStr| {
+        (cli.all || !name.as_bytes().starts_with(b"."))
+            && (!cli.ignore_backups || !name.as_bytes().ends_with(b"~"))
+            && !is_ignored_name(name, &ignore)
+    };
+    let entries = match path.is_dir() {
+        true => match path.read_dir() {
+            Ok(entries) => Some(entries),
+            Err(e) => {
+                show_error!("cannot open directory {}: {}", quote(&path), strerror(&e));
+                status.set(match index {
+                    0 => 2,
+                    _ => 1,
+                });
+                return;
+            }
+        },
+        false => None,
+    };
+    if cli.long {
+        if entries.is_some() {
+            if index > 0 {
+                println!();
+            }
+            println!("{}", quote_name(path.as_os_str()));
+            println!(
+                "total {}",
+                path.metadata()
+                    .map_or(0, |m| FileInfo::new(&path, &m).block_size)
+            );
+        }
+    } else if cli.recursive && entries.is_some() {
+        if index > 0 {
+            println!();
+        }
+        println!("{}:", quote_name(path.as_os_str()));
+    }
+    let operand = (entries.is_none() && wanted(path.file_name().unwrap_or_default()))
+        .then(|| LSFile::new(path.clone(), cli));
+    let files = entries
+        .into_iter()
+        .flatten()
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| wanted(&entry.file_name()))
+        .map(|entry| LSFile::from_entry(entry, cli))
+        .chain(operand)
+        // TODO: figure out how the ls version works, this doesn't quite match
+        .filter(|file| !cli.directory || file.is_dir());
+    // Unsorted names in the short format go out as they are read, so that
+    // a huge directory is never held in memory.
+    if cli.unsorted && !cli.long && !cli.commas {
+        files.for_each(|file| print_short(&file, cli));
+        println!();
+        return;
+    }
+    let mut paths = files.collect::<Vec<LSFile>>();
+    if !cli.unsorted {
+        paths.sort();
+    }
+    if cli.long {
+        let table = Table::new(
+            paths
+                .into_iter()
+                .map(LSFile::into)
+                .collect::<Vec<TableRow<String, 8>>>(),
+            [
+                TableColumn::new(ColumnAlignment::Left),
+                TableColumn::new(ColumnAlignment::Left),
+                TableColumn::new(ColumnAlignment::Left),
+                TableColumn::new(ColumnAlignment::Left),
+                TableColumn::new(ColumnAlignment::Left),
+                TableColumn::new(ColumnAlignment::Right),
+                TableColumn::new(ColumnAlignment::Left),
+                TableColumn::new(ColumnAlignment::Left),
+            ],
+        );
+        print!("{}", table)
+    } else if cli.commas {
+        let names = paths
+            .into_iter()
+            .map(|path| match cli.context {
+                true => format!("{} {}", path.context(), path.display_name()),
+                false => path.display_name(),
+            })
+            .collect::<Vec<String>>();
+        print!("{}", join_wrapped(&names, ',', Some(width_or_default())));
+    } else {
+        paths.iter().for_each(|file| print_short(file, cli));
+        println!()
+    }
+}
+
+/// Print `file` as the short format lists it, before the next on the line.
+fn print_short(file: &LSFile, cli: &Cli) {
+    if cli.context {
+        print!("{} ", file.context());
+    }
+    print!("{} ", file.display_name());
+}
+
+/// List `path` and every directory below it, each directory before its
+/// subdirectories.
+fn output_recursive(path: PathBuf, cli: &Cli, index: &mut usize, status: &ExitStatus) {
+    let all = cli.all;
+    let ignore = ignore_patterns(cli);
+    let walker = Walker::new(&path)
+        .symlinks(SymlinkPolicy::CommandLine)
+        .sort(true)
+        .filter(move |p| {
+            (all || !p
+                .file_name()
+                .is_some_and(|n| n.as_encoded_bytes().starts_with(b".")))
+                && !is_ignored(p, &ignore)
+        });
+    for entry in walker {
+        match entry {
+            Ok(entry) if entry.kind == EntryKind::Dir => {
+                output_path(entry.path, cli, *index, status);
+                *index += 1;
+            }
+            Ok(_) => {}
+            Err(e) => {
+                status.fail();
+                match e.kind {
+                    WalkErrorKind::Loop(_) => {
+                        show_error!("{}: not listing already-listed directory", quote(&e.path))
+                    }
+                    WalkErrorKind::Io(_) => {
+                        show_error!("cannot open directory {}: {}", quote(&e.path), e)
+                    }
+                }
+            }
+        }
+    }
+}
+
+pub fn command() -> clap::Command {
+    Cli::command()
+}
+
+pub fn run(args: Vec<OsString>) -> i32 {
+    let mut cli = posix::parse::<Cli>(args);
+    if cli.full_time {
+        cli.long = true;
+        cli.time_style = Some("full-iso".to_string());
+    }
+    if cli.all_unsorted {
+        (cli.all, cli.unsorted, cli.long, cli.color) = (true, true, false, None);
+    }
+    locale::init();
+    let style = match &cli.time_style {
+        Some(style) => TimeStyle::parse(style),
+        None => TimeStyle::from_env(),
+    }
+    .unwrap_or_else(|e| usage_error!(2, "{}", e));
+    let now =
+        ZonedTime::from_system_time(SystemTime::now()).map_or(0, |t| t.unix_timestamp_nanos());
+    let _ = TIME_STYLE.set((style, now));
+    let color = match cli.color.unwrap_or(When::Never) {
+        When::Always => true,
+        When::Auto => stdout_is_tty(),
+        When::Never => false,
+    };
+    let _ = QUOTING_STYLE.set(quoting_style(&cli));
+    let _ = COLORS.set(color.then(LsColors::from_env));
+    let status = ExitStatus::new();
+    let mut index = 0;
+    for path in cli
+        .path
+        .clone()
+        .unwrap_or(vec![Path::new(".").to_path_buf()])
+    {
+        match cli.recursive && !cli.directory && path.is_dir() {
+            true => output_recursive(path, &cli, &mut index, &status),
+            false => {
+                output_path(path, &cli, index, &status);
+                index += 1;
+            }
+        }
+    }
+    status.code()
+}
@@ -0,0 +1,22 @@
+use std::ffi::OsString;
+
+use crate::digest::Algorithm;
+use crate::posix;
+use crate::uu::hashsum::{Job, Options};
+use clap::{CommandFactory, Parser};
+
+#[derive(Parser)]
+#[command(version, about = "print or check MD5 (128-bit) checksums", long_about = None)]
+struct Cli {
+    #[command(flatten)]
+    options: Options,
+}
+
+pub fn command() -> clap::Command {
+    Cli::command()
+}
+
+pub fn run(args: Vec<OsString>) -> i32 {
+    let cli = posix::parse::<Cli>(args);
+    Job::new(Algorithm::Md5, Algorithm::Md5.bits(), cli.options).run()
+}
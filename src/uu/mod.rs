@@ -0,0 +1,186 @@
+//! The utilities, each as a `run` function taking the full argument
+//! list (program name first) and returning the exit status, so that they
+//! can be built as separate binaries or dispatched from one.
+//!
+//! They write to the process's own standard output and error and may exit
+//! on a fatal error rather than return, as the binaries they back do, so a
+//! program embedding one should run it in a child process to capture its
+//! output or outlive its failures.
+
+use std::ffi::OsString;
+use std::process;
+
+use crate::io::restore_sigpipe;
+
+pub mod arch;
+pub mod b2sum;
+pub mod chcon;
+pub mod chroot;
+pub mod cksum;
+pub mod date;
+pub mod dircolors;
+pub mod env;
+pub mod expr;
+pub mod factor;
+pub mod r#false;
+pub mod groups;
+mod hashsum;
+pub mod hostid;
+pub mod hostname;
+pub mod id;
+pub mod kill;
+pub mod link;
+pub mod logname;
+pub mod ls;
+pub mod md5sum;
+pub mod nice;
+pub mod nohup;
+pub mod nproc;
+pub mod numfmt;
+pub mod pathchk;
+pub mod pinky;
+pub mod printenv;
+pub mod runcon;
+pub mod sha1sum;
+pub mod sha224sum;
+pub mod sha256sum;
+pub mod sha384sum;
+pub mod sha512sum;
+pub mod sleep;
+pub mod stdbuf;
+pub mod stty;
+pub mod tee;
+pub mod test;
+pub mod timeout;
+pub mod r#true;
+pub mod tty;
+pub mod uname;
+pub mod unlink;
+pub mod uptime;
+pub mod users;
+pub mod who;
+pub mod whoami;
+pub mod yes;
+
+pub type Utility = fn(Vec<OsString>) -> i32;
+
+/// Every utility by the name it is invoked as.
+pub const UTILITIES: [(&str, Utility); 49] = [
+    ("[", test::run),
+    ("arch", arch::run),
+    ("b2sum", b2sum::run),
+    ("chcon", chcon::run),
+    ("chroot", chroot::run),
+    ("cksum", cksum::run),
+    ("date", date::run),
+    ("dircolors", dircolors::run),
+    ("env", env::run),
+    ("expr", expr::run),
+    ("factor", factor::run),
+    ("false", r#false::run),
+    ("groups", groups::run),
+    ("hostid", hostid::run),
+    ("hostname", hostname::run),
+    ("id", id::run),
+    ("kill", kill::run),
+    ("link", link::run),
+    ("logname", logname::run),
+    ("ls", ls::run),
+    ("md5sum", md5sum::run),
+    ("nice", nice::run),
+    ("nohup", nohup::run),
+    ("nproc", nproc::run),
+    ("numfmt", numfmt::run),
+    ("pathchk", pathchk::run),
+    ("pinky", pinky::run),
+    ("printenv", printenv::run),
+    ("runcon", runcon::run),
+    ("sha1sum", sha1sum::run),
+    ("sha224sum", sha224sum::run),
+    ("sha256sum", sha256sum::run),
+    ("sha384sum", sha384sum::run),
+    ("sha512sum", sha512sum::run),
+    ("sleep", sleep::run),
+    ("stdbuf", stdbuf::run),
+    ("stty", stty::run),
+    ("tee", tee::run),
+    ("test", test::run),
+    ("timeout", timeout::run),
+    ("true", r#true::run),
+    ("tty", tty::run),
+    ("uname", uname::run),
+    ("unlink", unlink::run),
+    ("uptime", uptime::run),
+    ("users", users::run),
+    ("who", who::run),
+    ("whoami", whoami::run),
+    ("yes", yes::run),
+];
+
+pub type Definition = fn() -> clap::Command;
+
+/// The command-line interface of every utility, for generating
+/// completions and manual pages. `[` is left out as it is `test`.
+pub const COMMANDS: [(&str, Definition); 48] = [
+    ("arch", arch::command),
+    ("b2sum", b2sum::command),
+    ("chcon", chcon::command),
+    ("chroot", chroot::command),
+    ("cksum", cksum::command),
+    ("date", date::command),
+    ("dircolors", dircolors::command),
+    ("env", env::command),
+    ("expr", expr::command),
+    ("factor", factor::command),
+    ("false", r#false::command),
+    ("groups", groups::command),
+    ("hostid", hostid::command),
+    ("hostname", hostname::command),
+    ("id", id::command),
+    ("kill", kill::command),
+    ("link", link::command),
+    ("logname", logname::command),
+    ("ls", ls::command),
+    ("md5sum", md5sum::command),
+    ("nice", nice::command),
+    ("nohup", nohup::command),
+    ("nproc", nproc::command),
+    ("numfmt", numfmt::command),
+    ("pathchk", pathchk::command),
+    ("pinky", pinky::command),
+    ("printenv", printenv::command),
+    ("runcon", runcon::command),
+    ("sha1sum", sha1sum::command),
+    ("sha224sum", sha224sum::command),
+    ("sha256sum", sha256sum::command),
+    ("sha384sum", sha384sum::command),
+    ("sha512sum", sha512sum::command),
+    ("sleep", sleep::command),
+    ("stdbuf", stdbuf::command),
+    ("stty", stty::command),
+    ("tee", tee::command),
+    ("test", test::command),
+    ("timeout", timeout::command),
+    ("true", r#true::command),
+    ("tty", tty::command),
+    ("uname", uname::command),
+    ("unlink", unlink::command),
+    ("uptime", uptime::command),
+    ("users", users::command),
+    ("who", who::command),
+    ("whoami", whoami::command),
+    ("yes", yes::command),
+];
+
+pub fn find(name: &str) -> Option<Utility> {
+    UTILITIES
+        .iter()
+        .find(|(n, _)| *n == name)
+        .map(|(_, run)| *run)
+}
+
+/// Run `utility` as the whole program, exiting with its status.
+pub fn main(utility: Utility) -> ! {
+    restore_sigpipe();
+    process::exit(utility(std::env::args_os().collect()))
+}
@@ -0,0 +1,83 @@
+use std::ffi::OsString;
+use std::io::{self, Write};
+use std::process;
+
+use crate::error::strerror;
+use crate::posix;
+use crate::process::{exec, Diagnostic, EXIT_CANCELED};
+use crate::{die, show_error};
+use clap::{CommandFactory, Parser};
+
+#[derive(Parser)]
+#[command(version, about = "run a program with modified scheduling priority", long_about = None)]
+struct Cli {
+    /// add integer N to the niceness (default 10)
+    #[arg(short = 'n', long, value_name = "N", allow_hyphen_values = true)]
+    adjustment: Option<String>,
+
+    /// [COMMAND [ARG]...]
+    #[arg(trailing_var_arg = true)]
+    command: Vec<OsString>,
+}
+
+/// Rewrite the obsolete `nice -N` / `nice --N` adjustment syntax into `-n N`.
+fn normalize_args(mut args: Vec<OsString>) -> Vec<OsString> {
+    if let Some(first) = args.get(1).and_then(|a| a.to_str()) {
+        let adjustment = first.strip_prefix('-').filter(|rest| {
+            let digits = rest.strip_prefix(['-', '+']).unwrap_or(rest);
+            !digits.is_empty() && digits.chars().all(|c| c.is_ascii_digit())
+        });
+        if let Some(adjustment) = adjustment.map(|a| a.to_string()) {
+            args.splice(1..2, ["-n".into(), adjustment.into()]);
+        }
+    }
+    args
+}
+
+fn niceness() -> i32 {
+    unsafe { libc::getpriority(libc::PRIO_PROCESS, 0) }
+}
+
+pub fn command() -> clap::Command {
+    Cli::command()
+}
+
+pub fn run(args: Vec<OsString>) -> i32 {
+    let cli = posix::try_parse::<Cli>(normalize_args(args)).unwrap_or_else(|e| {
+        if e.use_stderr() {
+            let _ = e.print();
+            process::exit(EXIT_CANCELED);
+        }
+        e.exit()
+    });
+
+    let adjustment = match &cli.adjustment {
+        Some(n) => n
+            .trim()
+            .parse::<i64>()
+            .unwrap_or_else(|_| die!(EXIT_CANCELED, "invalid adjustment '{}'", n)),
+        None => 10,
+    };
+
+    let mut command = cli.command.into_iter();
+    let Some(program) = command.next() else {
+        if cli.adjustment.is_some() {
+            die!(EXIT_CANCELED, "a command must be given with an adjustment");
+        }
+        if writeln!(io::stdout(), "{}", niceness()).is_err() {
+            return EXIT_CANCELED;
+        }
+        return 0;
+    };
+
+    let target = (niceness() as i64 + adjustment).clamp(-20, 19) as i32;
+    if unsafe { libc::setpriority(libc::PRIO_PROCESS, 0, target) } != 0 {
+        let err = io::Error::last_os_error();
+        show_error!("cannot set niceness: {}", strerror(&err));
+        if !matches!(err.raw_os_error(), Some(libc::EPERM) | Some(libc::EACCES)) {
+            return EXIT_CANCELED;
+        }
+    }
+
+    exec(&program, command, Diagnostic::Name)
+}
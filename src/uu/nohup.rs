@@ -0,0 +1,113 @@
+use std::env;
+use std::ffi::OsString;
+use std::fs::{File, OpenOptions};
+use std::io;
+use std::os::fd::AsRawFd;
+use std::os::unix::fs::OpenOptionsExt;
+use std::path::PathBuf;
+use std::process;
+
+use crate::error::strerror;
+use crate::posix;
+use crate::process::{exec, Diagnostic, EXIT_CANCELED, EXIT_ENOENT};
+use crate::quote::quote;
+use crate::show_error;
+use crate::signal;
+use crate::term::{stderr_is_tty, stdin_is_tty, stdout_is_tty};
+use clap::{CommandFactory, Parser};
+
+#[derive(Parser)]
+#[command(version, about = "run a command immune to hangups, with output to a non-tty", long_about = None)]
+struct Cli {
+    /// COMMAND [ARG]...
+    #[arg(trailing_var_arg = true, required = true)]
+    command: Vec<OsString>,
+}
+
+fn exit_internal_failure() -> i32 {
+    match env::var_os("POSIXLY_CORRECT") {
+        Some(_) => EXIT_ENOENT,
+        None => EXIT_CANCELED,
+    }
+}
+
+fn open_nohup_out() -> Result<(File, String), (String, io::Error)> {
+    let open = |path: &PathBuf| {
+        OpenOptions::new()
+            .create(true)
+            .append(true)
+            .mode(0o600)
+            .open(path)
+    };
+    let local = PathBuf::from("nohup.out");
+    let local_error = match open(&local) {
+        Ok(file) => return Ok((file, "nohup.out".to_string())),
+        Err(e) => e,
+    };
+    let Some(home) = env::var_os("HOME") else {
+        return Err(("nohup.out".to_string(), local_error));
+    };
+    let home = PathBuf::from(home).join("nohup.out");
+    match open(&home) {
+        Ok(file) => Ok((file, home.to_string_lossy().to_string())),
+        Err(e) => Err((home.to_string_lossy().to_string(), e)),
+    }
+}
+
+pub fn command() -> clap::Command {
+    Cli::command()
+}
+
+pub fn run(args: Vec<OsString>) -> i32 {
+    let cli = posix::try_parse::<Cli>(args).unwrap_or_else(|e| {
+        if e.use_stderr() {
+            let _ = e.print();
+            process::exit(exit_internal_failure());
+        }
+        e.exit()
+    });
+
+    let ignoring_input = stdin_is_tty();
+    let redirecting_stdout = stdout_is_tty();
+    let redirecting_stderr = stderr_is_tty();
+
+    if ignoring_input {
+        if let Ok(null) = File::options().write(true).open("/dev/null") {
+            unsafe { libc::dup2(null.as_raw_fd(), libc::STDIN_FILENO) };
+        }
+    }
+
+    let mut output_name = None;
+    if redirecting_stdout {
+        match open_nohup_out() {
+            Ok((file, name)) => {
+                unsafe { libc::dup2(file.as_raw_fd(), libc::STDOUT_FILENO) };
+                output_name = Some(name);
+            }
+            Err((name, e)) => {
+                show_error!("failed to open {}: {}", quote(&name), strerror(&e));
+                return exit_internal_failure();
+            }
+        }
+    }
+
+    match (ignoring_input, &output_name, redirecting_stderr) {
+        (true, Some(name), _) => {
+            show_error!("ignoring input and appending output to {}", quote(name))
+        }
+        (false, Some(name), _) => show_error!("appending output to {}", quote(name)),
+        (true, None, true) => show_error!("ignoring input and redirecting stderr to stdout"),
+        (true, None, false) => show_error!("ignoring input"),
+        (false, None, true) => show_error!("redirecting stderr to stdout"),
+        (false, None, false) => {}
+    }
+
+    if redirecting_stderr {
+        unsafe { libc::dup2(libc::STDOUT_FILENO, libc::STDERR_FILENO) };
+    }
+    let _ = signal::ignore(libc::SIGHUP);
+
+    let mut command = cli.command.into_iter();
+    let program = command.next().unwrap();
+    exec(&program, command, Diagnostic::FailedToRun)
+}
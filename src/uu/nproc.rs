@@ -0,0 +1,73 @@
+use std::env;
+use std::ffi::OsString;
+use std::io::{self, Write};
+use std::mem;
+
+use crate::posix;
+use clap::{CommandFactory, Parser};
+
+#[derive(Parser)]
+#[command(version, about = "print the number of processing units available", long_about = None)]
+struct Cli {
+    /// print the number of installed processors
+    #[arg(long)]
+    all: bool,
+
+    /// if possible, exclude N processing units
+    #[arg(long, value_name = "N")]
+    ignore: Option<usize>,
+}
+
+fn installed_processors() -> usize {
+    let count = unsafe { libc::sysconf(libc::_SC_NPROCESSORS_CONF) };
+    count.max(1) as usize
+}
+
+/// Processors this process may run on, honouring its CPU affinity mask.
+fn available_processors() -> usize {
+    let mut set: libc::cpu_set_t = unsafe { mem::zeroed() };
+    if unsafe { libc::sched_getaffinity(0, mem::size_of::<libc::cpu_set_t>(), &mut set) } == 0 {
+        let count = unsafe { libc::CPU_COUNT(&set) };
+        if count > 0 {
+            return count as usize;
+        }
+    }
+    let count = unsafe { libc::sysconf(libc::_SC_NPROCESSORS_ONLN) };
+    count.max(1) as usize
+}
+
+/// Parse an OpenMP thread count variable; OMP_NUM_THREADS may hold a
+/// comma separated list of which only the first entry applies here.
+fn omp_threads(name: &str) -> Option<usize> {
+    let value = env::var(name).ok()?;
+    value
+        .split(',')
+        .next()?
+        .trim()
+        .parse::<usize>()
+        .ok()
+        .filter(|&n| n > 0)
+}
+
+pub fn command() -> clap::Command {
+    Cli::command()
+}
+
+pub fn run(args: Vec<OsString>) -> i32 {
+    let cli = posix::parse::<Cli>(args);
+    let mut count = match cli.all {
+        true => installed_processors(),
+        false => {
+            let mut count = omp_threads("OMP_NUM_THREADS").unwrap_or_else(available_processors);
+            if let Some(limit) = omp_threads("OMP_THREAD_LIMIT") {
+                count = count.min(limit);
+            }
+            count
+        }
+    };
+    count = count.saturating_sub(cli.ignore.unwrap_or(0)).max(1);
+    if writeln!(io::stdout(), "{}", count).is_err() {
+        return 1;
+    }
+    0
+}
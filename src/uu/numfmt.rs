@@ -0,0 +1,507 @@
+use std::ffi::OsString;
+use std::io::{self, Write};
+use std::process;
+
+use crate::io::{terminator, Records};
+use crate::locale::{self, thousands_separator};
+use crate::posix;
+use crate::ranges::{Numbering, Ranges};
+use crate::{die, show_error, usage_error};
+use clap::{CommandFactory, Parser, ValueEnum};
+
+const EXIT_FAILURE: i32 = 2;
+
+#[derive(Clone, Copy, PartialEq, ValueEnum)]
+enum Unit {
+    None,
+    Auto,
+    Si,
+    Iec,
+    #[value(name = "iec-i")]
+    IecI,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum RoundMethod {
+    Up,
+    Down,
+    FromZero,
+    TowardsZero,
+    Nearest,
+}
+
+#[derive(Clone, Copy, PartialEq, ValueEnum)]
+enum InvalidMode {
+    Abort,
+    Fail,
+    Warn,
+    Ignore,
+}
+
+#[derive(Parser)]
+#[command(version, about = "convert numbers from/to human-readable strings", long_about = None)]
+struct Cli {
+    numbers: Vec<String>,
+
+    /// use X instead of whitespace for field delimiter
+    #[arg(short, long, value_name = "X")]
+    delimiter: Option<char>,
+
+    /// replace the numbers in these input fields; see FIELDS below
+    #[arg(long, value_name = "FIELDS", default_value = "1")]
+    field: String,
+
+    /// use printf style floating-point FORMAT
+    #[arg(long, value_name = "FORMAT")]
+    format: Option<String>,
+
+    /// auto-scale input numbers to UNITs
+    #[arg(long, value_name = "UNIT", value_enum, default_value = "none")]
+    from: Unit,
+
+    /// specify the input unit size
+    #[arg(long, value_name = "N", default_value_t = 1)]
+    from_unit: u64,
+
+    /// use locale-defined grouping of digits, e.g. 1,000,000
+    #[arg(long)]
+    grouping: bool,
+
+    /// print (without converting) the first N header lines
+    #[arg(long, value_name = "N", num_args = 0..=1, default_missing_value = "1")]
+    header: Option<usize>,
+
+    /// failure mode for invalid numbers
+    #[arg(long, value_name = "MODE", value_enum, default_value = "abort")]
+    invalid: InvalidMode,
+
+    /// pad the output to N characters; positive N will right-align, negative N will left-align
+    #[arg(long, value_name = "N", allow_hyphen_values = true)]
+    padding: Option<i64>,
+
+    /// use METHOD for rounding when scaling
+    #[arg(long, value_name = "METHOD", value_enum, default_value = "from-zero")]
+    round: RoundMethod,
+
+    /// add SUFFIX to output numbers, and accept optional SUFFIX in input numbers
+    #[arg(long, value_name = "SUFFIX")]
+    suffix: Option<String>,
+
+    /// auto-scale output numbers to UNITs
+    #[arg(long, value_name = "UNIT", value_enum, default_value = "none")]
+    to: Unit,
+
+    /// the output unit size
+    #[arg(long, value_name = "N", default_value_t = 1)]
+    to_unit: u64,
+
+    /// line delimiter is NUL, not newline
+    #[arg(short, long)]
+    zero_terminated: bool,
+}
+
+const SUFFIXES: [char; 10] = ['K', 'M', 'G', 'T', 'P', 'E', 'Z', 'Y', 'R', 'Q'];
+
+/// A parsed --format: the text around the conversion and its modifiers.
+struct Format {
+    prefix: String,
+    suffix: String,
+    left_align: bool,
+    zero_pad: bool,
+    grouping: bool,
+    width: usize,
+    precision: Option<usize>,
+}
+
+fn parse_format(format: &str) -> Format {
+    let invalid = || die!(1, "invalid format '{}'", format);
+    let mut start = None;
+    let bytes = format.as_bytes();
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' {
+            if bytes.get(i + 1) == Some(&b'%') {
+                i += 2;
+                continue;
+            }
+            start = Some(i);
+            break;
+        }
+        i += 1;
+    }
+    let Some(start) = start else {
+        die!(1, "format '{}' has no % directive", format);
+    };
+    let mut spec = Format {
+        prefix: format[..start].replace("%%", "%"),
+        suffix: String::new(),
+        left_align: false,
+        zero_pad: false,
+        grouping: false,
+        width: 0,
+        precision: None,
+    };
+    let mut i = start + 1;
+    while let Some(&flag) = bytes.get(i) {
+        match flag {
+            b'-' => spec.left_align = true,
+            b'0' => spec.zero_pad = true,
+            b'\'' => spec.grouping = true,
+            b' ' | b'+' => {}
+            _ => break,
+        }
+        i += 1;
+    }
+    let width_start = i;
+    while bytes.get(i).is_some_and(|b| b.is_ascii_digit()) {
+        i += 1;
+    }
+    spec.width = format[width_start..i].parse().unwrap_or(0);
+    if bytes.get(i) == Some(&b'.') {
+        i += 1;
+        let precision_start = i;
+        while bytes.get(i).is_some_and(|b| b.is_ascii_digit()) {
+            i += 1;
+        }
+        spec.precision = Some(format[precision_start..i].parse().unwrap_or(0));
+    }
+    if bytes.get(i) != Some(&b'f') {
+        invalid();
+    }
+    spec.suffix = format[i + 1..].replace("%%", "%");
+    if spec.suffix.contains('%') {
+        die!(1, "format '{}' has too many % directives", format);
+    }
+    spec
+}
+
+fn round(value: f64, method: RoundMethod) -> f64 {
+    match method {
+        RoundMethod::Up => value.ceil(),
+        RoundMethod::Down => value.floor(),
+        RoundMethod::FromZero => match value < 0.0 {
+            true => value.floor(),
+            false => value.ceil(),
+        },
+        RoundMethod::TowardsZero => value.trunc(),
+        RoundMethod::Nearest => value.round(),
+    }
+}
+
+fn group_digits(number: &str) -> String {
+    let (sign, rest) = match number.strip_prefix('-') {
+        Some(rest) => ("-", rest),
+        None => ("", number),
+    };
+    let (integer, fraction) = match rest.find('.') {
+        Some(dot) => (&rest[..dot], &rest[dot..]),
+        None => (rest, ""),
+    };
+    // The C locale has no digit grouping.
+    let separator = thousands_separator();
+    let mut grouped = String::new();
+    for (i, c) in integer.chars().enumerate() {
+        if i > 0 && (integer.len() - i) % 3 == 0 {
+            grouped.push_str(&separator);
+        }
+        grouped.push(c);
+    }
+    format!("{}{}{}", sign, grouped, fraction)
+}
+
+struct Converter {
+    from: Unit,
+    from_unit: f64,
+    to: Unit,
+    to_unit: f64,
+    round: RoundMethod,
+    suffix: Option<String>,
+    padding: Option<i64>,
+    format: Option<Format>,
+    grouping: bool,
+}
+
+impl Converter {
+    /// Parse an input number, returning its value and the number of
+    /// fractional digits it was written with.
+    fn parse(&self, field: &str) -> Result<(f64, usize), String> {
+        let mut text = field;
+        if let Some(suffix) = &self.suffix {
+            text = text.strip_suffix(suffix.as_str()).unwrap_or(text);
+        }
+        let end = text
+            .char_indices()
+            .find(|&(i, c)| !(c.is_ascii_digit() || c == '.' || (i == 0 && c == '-')))
+            .map_or(text.len(), |(i, _)| i);
+        let (number, unit) = text.split_at(end);
+        let value = number
+            .parse::<f64>()
+            .map_err(|_| format!("invalid number: '{}'", field))?;
+        let precision = number.find('.').map_or(0, |dot| number.len() - dot - 1);
+        if unit.is_empty() {
+            return Ok((value * self.from_unit, precision));
+        }
+
+        let mut chars = unit.chars();
+        let letter = chars.next().unwrap();
+        let power = SUFFIXES
+            .iter()
+            .position(|&s| s == letter.to_ascii_uppercase() && (letter != 'k' || s == 'K'))
+            .ok_or_else(|| format!("invalid suffix in input: '{}'", field))?
+            as i32
+            + 1;
+        let binary = chars.as_str() == "i";
+        if !chars.as_str().is_empty() && !binary {
+            return Err(format!("invalid suffix in input: '{}'", field));
+        }
+        let base = match (self.from, binary) {
+            (Unit::None, _) => {
+                return Err(format!(
+                    "rejecting suffix in input: '{}' (consider using --from)",
+                    field
+                ))
+            }
+            (Unit::Si, false) => 1000.0,
+            (Unit::Iec, false) => 1024.0,
+            (Unit::IecI, true) | (Unit::Auto, true) => 1024.0,
+            (Unit::Auto, false) => 1000.0,
+            _ => return Err(format!("invalid suffix in input: '{}'", field)),
+        };
+        Ok((value * f64::powi(base, power) * self.from_unit, 0))
+    }
+
+    fn render(&self, value: f64, input_precision: usize) -> String {
+        let value = value / self.to_unit;
+        let user_precision = self.format.as_ref().and_then(|f| f.precision);
+        let base = match self.to {
+            Unit::Si => 1000.0,
+            Unit::Iec | Unit::IecI => 1024.0,
+            _ => 0.0,
+        };
+
+        let mut number = if base == 0.0 {
+            let precision = user_precision.unwrap_or(match self.from {
+                Unit::None => input_precision,
+                _ => 0,
+            });
+            let scale = 10f64.powi(precision as i32);
+            format!("{:.*}", precision, round(value * scale, self.round) / scale)
+        } else {
+            let mut power = 0;
+            let mut scaled = value;
+            while scaled.abs() >= base && power < SUFFIXES.len() {
+                scaled /= base;
+                power += 1;
+            }
+            let precision = match user_precision {
+                Some(p) => p,
+                None if power > 0 && scaled.abs() < 10.0 => 1,
+                None => 0,
+            };
+            let scale = 10f64.powi(precision as i32);
+            scaled = round(scaled * scale, self.round) / scale;
+            if scaled.abs() >= base && power < SUFFIXES.len() {
+                scaled /= base;
+                power += 1;
+            }
+            let precision = match user_precision {
+                None if scaled.abs() >= 10.0 || power == 0 => 0,
+                _ => precision,
+            };
+            let mut number = format!("{:.*}", precision, scaled);
+            if power > 0 {
+                number.push(SUFFIXES[power - 1]);
+                if self.to == Unit::IecI {
+                    number.push('i');
+                }
+            }
+            number
+        };
+
+        if self.grouping || self.format.as_ref().is_some_and(|f| f.grouping) {
+            number = group_digits(&number);
+        }
+        if let Some(suffix) = &self.suffix {
+            number.push_str(suffix);
+        }
+        match &self.format {
+            Some(format) => {
+                let width = format.width;
+                let padded = if format.left_align {
+                    format!("{:<width$}", number)
+                } else if format.zero_pad && width > number.len() {
+                    let (sign, digits) = match number.strip_prefix('-') {
+                        Some(digits) => ("-", digits),
+                        None => ("", number.as_str()),
+                    };
+                    format!("{}{:0>w$}", sign, digits, w = width - sign.len())
+                } else {
+                    format!("{:>width$}", number)
+                };
+                format!("{}{}{}", format.prefix, padded, format.suffix)
+            }
+            None => number,
+        }
+    }
+
+    fn pad(&self, number: String, auto_width: usize) -> String {
+        match self.padding {
+            Some(width) if width < 0 => {
+                format!("{:<w$}", number, w = width.unsigned_abs() as usize)
+            }
+            Some(width) => format!("{:>w$}", number, w = width as usize),
+            None => format!("{:>w$}", number, w = auto_width),
+        }
+    }
+
+    fn convert(&self, field: &str, auto_width: usize) -> Result<String, String> {
+        let (value, precision) = self.parse(field)?;
+        Ok(self.pad(self.render(value, precision), auto_width))
+    }
+}
+
+struct Numfmt {
+    converter: Converter,
+    fields: Ranges,
+    delimiter: Option<char>,
+    invalid: InvalidMode,
+    failed: bool,
+}
+
+impl Numfmt {
+    fn selected(&self, field: usize) -> bool {
+        self.fields.contains(field)
+    }
+
+    fn convert_field(&mut self, field: &str, auto_width: usize) -> String {
+        match self.converter.convert(field, auto_width) {
+            Ok(converted) => converted,
+            Err(message) => {
+                match self.invalid {
+                    InvalidMode::Abort => {
+                        show_error!("{}", message);
+                        process::exit(EXIT_FAILURE);
+                    }
+                    InvalidMode::Fail => {
+                        show_error!("{}", message);
+                        self.failed = true;
+                    }
+                    InvalidMode::Warn => show_error!("{}", message),
+                    InvalidMode::Ignore => {}
+                }
+                format!("{:>w$}", field, w = auto_width)
+            }
+        }
+    }
+
+    fn process_line(&mut self, line: &str) -> String {
+        let mut output = String::new();
+        match self.delimiter {
+            Some(delimiter) => {
+                for (i, field) in line.split(delimiter).enumerate() {
+                    if i > 0 {
+                        output.push(delimiter);
+                    }
+                    match self.selected(i + 1) {
+                        true => output.push_str(&self.convert_field(field, 0)),
+                        false => output.push_str(field),
+                    }
+                }
+            }
+            None => {
+                // Each field is its leading blanks plus the following
+                // non-blank run; a converted field keeps its original width.
+                let mut rest = line;
+                let mut index = 0;
+                while !rest.is_empty() {
+                    index += 1;
+                    let blanks = rest.len() - rest.trim_start_matches([' ', '\t']).len();
+                    let end = rest[blanks..]
+                        .find([' ', '\t'])
+                        .map_or(rest.len(), |e| e + blanks);
+                    let (field, remainder) = rest.split_at(end);
+                    match self.selected(index) && end > blanks {
+                        true => {
+                            let width = if blanks > 0 { field.len() } else { 0 };
+                            output.push_str(&self.convert_field(&field[blanks..], width));
+                        }
+                        false => output.push_str(field),
+                    }
+                    rest = remainder;
+                }
+            }
+        }
+        output
+    }
+}
+
+pub fn command() -> clap::Command {
+    Cli::command()
+}
+
+pub fn run(args: Vec<OsString>) -> i32 {
+    locale::init();
+    let cli = posix::parse::<Cli>(args);
+    if cli.padding == Some(0) {
+        die!(1, "invalid padding value '0'");
+    }
+    if cli.to == Unit::Auto {
+        die!(1, "invalid argument 'auto' for '--to'");
+    }
+    let mut numfmt = Numfmt {
+        converter: Converter {
+            from: cli.from,
+            from_unit: cli.from_unit as f64,
+            to: cli.to,
+            to_unit: cli.to_unit as f64,
+            round: cli.round,
+            suffix: cli.suffix,
+            padding: cli.padding,
+            format: cli.format.as_deref().map(parse_format),
+            grouping: cli.grouping,
+        },
+        fields: Ranges::parse(&cli.field, Numbering::Fields, true)
+            .unwrap_or_else(|e| usage_error!(1, "{}", e)),
+        delimiter: cli.delimiter,
+        invalid: cli.invalid,
+        failed: false,
+    };
+
+    let terminator = terminator(cli.zero_terminated);
+    let mut stdout = io::stdout().lock();
+    let mut write = |record: &str| {
+        let mut bytes = record.as_bytes().to_vec();
+        bytes.push(terminator);
+        if stdout.write_all(&bytes).is_err() {
+            process::exit(EXIT_FAILURE);
+        }
+    };
+
+    if !cli.numbers.is_empty() {
+        if cli.header.is_some() {
+            die!(1, "--header ignored with command-line input");
+        }
+        for number in &cli.numbers {
+            let converted = numfmt.convert_field(number, 0);
+            write(&converted);
+        }
+    } else {
+        let mut header = cli.header.unwrap_or(0);
+        for record in Records::new(io::stdin().lock(), terminator) {
+            let record = record.unwrap_or_else(|e| die!(1, "error reading input: {}", e));
+            let line = String::from_utf8_lossy(&record.bytes).to_string();
+            if header > 0 {
+                header -= 1;
+                write(&line);
+                continue;
+            }
+            let converted = numfmt.process_line(&line);
+            write(&converted);
+        }
+    }
+
+    if numfmt.failed {
+        return EXIT_FAILURE;
+    }
+    0
+}
@@ -0,0 +1,195 @@
+use std::ffi::{CString, OsStr, OsString};
+use std::fs;
+use std::io;
+use std::os::unix::ffi::OsStrExt;
+
+use crate::error::{strerror, ExitStatus};
+use crate::locale::{self, langinfo};
+use crate::posix;
+use crate::quote::{quote, quote_with, quotef, QuotingStyle};
+use crate::{show_error, usage_error};
+use clap::{CommandFactory, Parser};
+
+/// The longest file name, with its terminating null, and file name
+/// component every POSIX system allows.
+const POSIX_PATH_MAX: usize = 256;
+const POSIX_NAME_MAX: usize = 14;
+
+#[derive(Parser)]
+#[command(
+    version,
+    about = "diagnose invalid or non-portable file names",
+    long_about = None
+)]
+struct Cli {
+    /// check for most POSIX systems
+    #[arg(short)]
+    p: bool,
+
+    /// check for empty names and leading "-"
+    #[arg(short = 'P')]
+    leading: bool,
+
+    /// check for all POSIX systems (equivalent to -p -P)
+    #[arg(long)]
+    portability: bool,
+
+    #[arg(value_name = "NAME")]
+    names: Vec<OsString>,
+}
+
+/// The system's limit on `name` for `dir`, or None if there is none.
+fn pathconf(dir: &[u8], name: libc::c_int) -> io::Result<Option<usize>> {
+    let dir = CString::new(dir).map_err(|_| io::Error::from_raw_os_error(libc::ENOENT))?;
+    unsafe { *libc::__errno_location() = 0 };
+    match unsafe { libc::pathconf(dir.as_ptr(), name) } {
+        -1 => match io::Error::last_os_error() {
+            e if e.raw_os_error() == Some(0) => Ok(None),
+            e => Err(e),
+        },
+        limit => Ok(Some(limit as usize)),
+    }
+}
+
+/// The first character of `name` outside the portable file name
+/// character set, as many bytes as the locale's character takes.
+fn nonportable(name: &[u8]) -> Option<&[u8]> {
+    let start = name
+        .iter()
+        .position(|b| !b.is_ascii_alphanumeric() && !b"/._-".contains(b))?;
+    let rest = &name[start..];
+    let utf8 = langinfo(libc::CODESET, "").eq_ignore_ascii_case("UTF-8");
+    let len = match utf8 {
+        true => {
+            let window = &rest[..rest.len().min(4)];
+            let valid = match std::str::from_utf8(window) {
+                Ok(text) => text,
+                Err(e) => std::str::from_utf8(&window[..e.valid_up_to()]).unwrap(),
+            };
+            valid.chars().next().map_or(1, char::len_utf8)
+        }
+        false => 1,
+    };
+    Some(&rest[..len])
+}
+
+/// Check one file name, printing why it is not valid if it is not.
+fn check(name: &OsStr, portable: bool, leading: bool) -> bool {
+    let bytes = name.as_bytes();
+    if leading && bytes.split(|&b| b == b'/').any(|c| c.starts_with(b"-")) {
+        show_error!("leading '-' in a component of file name {}", quote(name));
+        return false;
+    }
+    if (portable || leading) && bytes.is_empty() {
+        show_error!("empty file name");
+        return false;
+    }
+
+    let mut exists = false;
+    if portable {
+        if let Some(c) = nonportable(bytes) {
+            show_error!(
+                "nonportable character {} in file name {}",
+                quote_with(OsStr::from_bytes(c), QuotingStyle::Locale),
+                quote(name)
+            );
+            return false;
+        }
+    } else {
+        match fs::symlink_metadata(name) {
+            Ok(_) => exists = true,
+            Err(e) if e.kind() != io::ErrorKind::NotFound || bytes.is_empty() => {
+                show_error!("{}: {}", quotef(name), strerror(&e));
+                return false;
+            }
+            Err(_) => {}
+        }
+    }
+
+    // The system has already accepted the name of a file that exists, and
+    // a short name is within every system's limits.
+    if portable || (!exists && POSIX_PATH_MAX <= bytes.len()) {
+        let max = match portable {
+            true => Some(POSIX_PATH_MAX),
+            false => {
+                let dir: &[u8] = if bytes.starts_with(b"/") { b"/" } else { b"." };
+                pathconf(dir, libc::_PC_PATH_MAX).ok().flatten()
+            }
+        };
+        if let Some(max) = max.filter(|&max| max <= bytes.len()) {
+            show_error!(
+                "limit {} exceeded by length {} of file name {}",
+                max - 1,
+                bytes.len(),
+                quote(name)
+            );
+            return false;
+        }
+    }
+
+    let too_long = bytes
+        .split(|&b| b == b'/')
+        .any(|c| c.len() > POSIX_NAME_MAX);
+    if portable || (!exists && too_long) {
+        let mut limit = Some(POSIX_NAME_MAX);
+        let mut start = 0;
+        while start < bytes.len() {
+            let len = bytes[start..]
+                .iter()
+                .position(|&b| b == b'/')
+                .unwrap_or(bytes.len() - start);
+            if len == 0 {
+                start += 1;
+                continue;
+            }
+            if !portable {
+                let dir: &[u8] = match start {
+                    0 => b".",
+                    _ => &bytes[..start],
+                };
+                match pathconf(dir, libc::_PC_NAME_MAX) {
+                    Ok(max) => limit = max,
+                    // The directory's parent is the best guess for a
+                    // directory that is not there.
+                    Err(e) if e.raw_os_error() == Some(libc::ENOENT) => {}
+                    Err(e) => {
+                        show_error!("{}: {}", quotef(OsStr::from_bytes(dir)), strerror(&e));
+                        return false;
+                    }
+                }
+            }
+            if let Some(max) = limit.filter(|&max| max < len) {
+                show_error!(
+                    "limit {} exceeded by length {} of file name component {}",
+                    max,
+                    len,
+                    quote(OsStr::from_bytes(&bytes[start..start + len]))
+                );
+                return false;
+            }
+            start += len;
+        }
+    }
+    true
+}
+
+pub fn command() -> clap::Command {
+    Cli::command()
+}
+
+pub fn run(args: Vec<OsString>) -> i32 {
+    locale::init();
+    let cli = posix::parse::<Cli>(args);
+    if cli.names.is_empty() {
+        usage_error!(1, "missing operand");
+    }
+    let portable = cli.p || cli.portability;
+    let leading = cli.leading || cli.portability;
+    let status = ExitStatus::new();
+    for name in &cli.names {
+        if !check(name, portable, leading) {
+            status.fail();
+        }
+    }
+    status.code()
+}
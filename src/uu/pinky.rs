@@ -0,0 +1,227 @@
+use std::ffi::{CStr, CString, OsString};
+use std::fs;
+use std::io::{self, Write};
+use std::os::unix::fs::MetadataExt;
+use std::path::Path;
+
+use crate::posix;
+use crate::table::{ColumnAlignment, Table, TableColumn, TableRow};
+use crate::timefmt::local_time;
+use crate::utmp::{read_utmp, UtmpRecord, UTMP_FILE};
+use clap::{ArgAction, CommandFactory, Parser};
+use time::macros::format_description;
+use time::OffsetDateTime;
+use users::get_user_by_name;
+use users::os::unix::UserExt;
+
+#[derive(Parser)]
+#[command(version, about = "lightweight finger", long_about = None, disable_help_flag(true))]
+struct Cli {
+    users: Vec<OsString>,
+
+    #[arg(long, action(ArgAction::Help))]
+    help: Option<bool>,
+
+    /// produce long format output for the specified USERs
+    #[arg(short)]
+    long: bool,
+
+    /// omit the user's home directory and shell in long format
+    #[arg(short = 'b')]
+    omit_home: bool,
+
+    /// omit the user's project file in long format
+    #[arg(short = 'h')]
+    omit_project: bool,
+
+    /// omit the user's plan file in long format
+    #[arg(short = 'p')]
+    omit_plan: bool,
+
+    /// do short format output, this is the default
+    #[arg(short)]
+    short: bool,
+
+    /// omit the line of column headings in short format
+    #[arg(short = 'f')]
+    omit_heading: bool,
+
+    /// omit the user's full name in short format
+    #[arg(short = 'w')]
+    omit_name: bool,
+
+    /// omit the user's full name and remote host in short format
+    #[arg(short = 'i')]
+    omit_name_host: bool,
+
+    /// omit the user's full name, remote host and idle time in short format
+    #[arg(short = 'q')]
+    omit_name_host_idle: bool,
+}
+
+/// The user's real name from the GECOS field, with `&` standing for the
+/// capitalized login name as BSD finger does.
+fn full_name(login: &str) -> Option<String> {
+    let login_c = CString::new(login).ok()?;
+    let passwd = unsafe { libc::getpwnam(login_c.as_ptr()) };
+    if passwd.is_null() || unsafe { (*passwd).pw_gecos.is_null() } {
+        return None;
+    }
+    let gecos = unsafe { CStr::from_ptr((*passwd).pw_gecos) }
+        .to_string_lossy()
+        .to_string();
+    let name = gecos.split(',').next().unwrap_or("");
+    let mut capitalized = login.chars();
+    let capitalized = match capitalized.next() {
+        Some(first) => first.to_uppercase().chain(capitalized).collect(),
+        None => String::new(),
+    };
+    Some(name.replace('&', &capitalized))
+}
+
+fn idle_string(record: &UtmpRecord, metadata: &Option<fs::Metadata>) -> String {
+    let Some(metadata) = metadata else {
+        return "?????".to_string();
+    };
+    let idle = OffsetDateTime::now_utc().unix_timestamp() - metadata.atime();
+    if idle < 60 || record.line.is_empty() {
+        String::new()
+    } else if idle < 24 * 60 * 60 {
+        format!("{:02}:{:02}", idle / 3600, (idle % 3600) / 60)
+    } else {
+        format!("{}d", idle / (24 * 60 * 60))
+    }
+}
+
+fn short_format(cli: &Cli) -> String {
+    let show_name = !(cli.omit_name || cli.omit_name_host || cli.omit_name_host_idle);
+    let show_host = !(cli.omit_name_host || cli.omit_name_host_idle);
+    let show_idle = !cli.omit_name_host_idle;
+
+    let mut rows = vec![];
+    if !cli.omit_heading {
+        rows.push(TableRow::new([
+            "Login".to_string(),
+            match show_name {
+                true => "Name".to_string(),
+                false => String::new(),
+            },
+            " TTY".to_string(),
+            match show_idle {
+                true => "Idle".to_string(),
+                false => String::new(),
+            },
+            "When".to_string(),
+            match show_host {
+                true => "Where".to_string(),
+                false => String::new(),
+            },
+        ]));
+    }
+
+    for record in read_utmp(Path::new(UTMP_FILE)) {
+        if !record.is_user_process() {
+            continue;
+        }
+        if !cli.users.is_empty() && !cli.users.iter().any(|u| *u == *record.user) {
+            continue;
+        }
+        let metadata = record.tty_metadata();
+        let mesg = match &metadata {
+            Some(m) if m.mode() & libc::S_IWGRP != 0 => " ",
+            Some(_) => "*",
+            None => "?",
+        };
+        rows.push(TableRow::new([
+            record.user.clone(),
+            match show_name {
+                true => full_name(&record.user).unwrap_or("???".to_string()),
+                false => String::new(),
+            },
+            format!("{}{}", mesg, record.line),
+            match show_idle {
+                true => idle_string(&record, &metadata),
+                false => String::new(),
+            },
+            local_time(record.time)
+                .format(format_description!("[year]-[month]-[day] [hour]:[minute]"))
+                .unwrap_or_default(),
+            match show_host {
+                true => record.host.clone(),
+                false => String::new(),
+            },
+        ]));
+    }
+
+    let table = Table::new(
+        rows,
+        [
+            TableColumn::new(ColumnAlignment::Left),
+            TableColumn::new(ColumnAlignment::Left),
+            TableColumn::new(ColumnAlignment::Left),
+            TableColumn::new(ColumnAlignment::Right),
+            TableColumn::new(ColumnAlignment::Left),
+            TableColumn::new(ColumnAlignment::Left),
+        ],
+    );
+    format!("{}", table)
+}
+
+fn long_format(cli: &Cli) -> String {
+    let mut output = String::new();
+    for name in &cli.users {
+        let login = name.to_string_lossy().to_string();
+        output.push_str(&format!("Login name: {:<28}In real life:  ", login));
+        let Some(user) = get_user_by_name(name) else {
+            output.push_str(" ???\n");
+            continue;
+        };
+        output.push_str(&full_name(&login).unwrap_or_default());
+        output.push('\n');
+
+        let home = user.home_dir().to_path_buf();
+        if !cli.omit_home {
+            output.push_str(&format!(
+                "Directory: {:<29}Shell:  {}\n",
+                home.to_string_lossy(),
+                user.shell().to_string_lossy()
+            ));
+        }
+        if !cli.omit_project {
+            if let Ok(project) = fs::read_to_string(home.join(".project")) {
+                output.push_str("Project: ");
+                output.push_str(&project);
+                if !project.ends_with('\n') {
+                    output.push('\n');
+                }
+            }
+        }
+        if !cli.omit_plan {
+            if let Ok(plan) = fs::read_to_string(home.join(".plan")) {
+                output.push_str("Plan:\n");
+                output.push_str(&plan);
+                if !plan.ends_with('\n') {
+                    output.push('\n');
+                }
+            }
+        }
+        output.push('\n');
+    }
+    output
+}
+
+pub fn command() -> clap::Command {
+    Cli::command()
+}
+
+pub fn run(args: Vec<OsString>) -> i32 {
+    let cli = posix::parse::<Cli>(args);
+    let output = match cli.long {
+        true => long_format(&cli),
+        false => short_format(&cli),
+    };
+    if io::stdout().write_all(output.as_bytes()).is_err() {
+        return 1;
+    }
+    0
+}
@@ -0,0 +1,60 @@
+use std::env;
+use std::ffi::OsString;
+use std::io::{self, Write};
+use std::os::unix::ffi::{OsStrExt, OsStringExt};
+
+use crate::posix;
+use clap::{CommandFactory, Parser};
+
+#[derive(Parser)]
+#[command(version, about = "print all or part of environment", long_about = None)]
+struct Cli {
+    variables: Vec<OsString>,
+
+    /// end each output line with NUL, not newline
+    #[arg(short = '0', long)]
+    null: bool,
+}
+
+pub fn command() -> clap::Command {
+    Cli::command()
+}
+
+pub fn run(args: Vec<OsString>) -> i32 {
+    let cli = posix::parse::<Cli>(args);
+    let terminator = if cli.null { b'\0' } else { b'\n' };
+    let mut stdout = io::stdout().lock();
+    let mut output = vec![];
+
+    let mut found_all = true;
+    if cli.variables.is_empty() {
+        for (name, value) in env::vars_os() {
+            output.extend_from_slice(name.as_bytes());
+            output.push(b'=');
+            output.extend_from_slice(value.as_bytes());
+            output.push(terminator);
+        }
+    } else {
+        for name in cli.variables {
+            match env::var_os(&name) {
+                Some(value) if !name.as_bytes().contains(&b'=') => {
+                    output.extend(value.into_vec());
+                    output.push(terminator);
+                }
+                _ => found_all = false,
+            }
+        }
+    }
+
+    if stdout
+        .write_all(&output)
+        .and_then(|_| stdout.flush())
+        .is_err()
+    {
+        return 2;
+    }
+    if !found_all {
+        return 1;
+    }
+    0
+}
@@ -0,0 +1,146 @@
+use std::env;
+use std::ffi::OsString;
+use std::io::{self, Write};
+use std::path::PathBuf;
+use std::process;
+
+use crate::error::strerror;
+use crate::posix;
+use crate::process::{exec, Diagnostic};
+use crate::quote::quote;
+use crate::selinux::{self, Context};
+use crate::{die, usage_error};
+use clap::{CommandFactory, Parser};
+
+#[derive(Parser)]
+#[command(
+    version,
+    about = "run a program in a different SELinux security context",
+    after_help = "With neither CONTEXT nor COMMAND, print the current security context.",
+    override_usage = "runcon CONTEXT COMMAND [args]\n       \
+        runcon [ -c ] [-u USER] [-r ROLE] [-t TYPE] [-l RANGE] COMMAND [args]",
+    long_about = None
+)]
+struct Cli {
+    /// compute process transition context before modifying
+    #[arg(short, long)]
+    compute: bool,
+
+    /// type (for same role as parent)
+    #[arg(short = 't', long = "type", value_name = "TYPE")]
+    kind: Option<String>,
+
+    /// user identity
+    #[arg(short, long, value_name = "USER")]
+    user: Option<String>,
+
+    /// role
+    #[arg(short, long, value_name = "ROLE")]
+    role: Option<String>,
+
+    /// levelrange
+    #[arg(short = 'l', long, value_name = "RANGE")]
+    range: Option<String>,
+
+    /// [CONTEXT] COMMAND [args]
+    #[arg(trailing_var_arg = true)]
+    args: Vec<OsString>,
+}
+
+fn current() -> String {
+    selinux::current()
+        .unwrap_or_else(|e| die!(1, "failed to get current context: {}", strerror(&e)))
+}
+
+/// The context a program run from `program` starts in, by the policy's
+/// rules for moving from this process's context.
+fn transition(program: &OsString) -> String {
+    let from = current();
+    let path = which(program).unwrap_or_else(|| program.into());
+    let file = selinux::context(&path, true)
+        .ok()
+        .flatten()
+        .unwrap_or_else(|| die!(1, "failed to get security context of {}", quote(&path)));
+    selinux::compute_create(&from, &file, "process")
+        .unwrap_or_else(|e| die!(1, "failed to compute a new context: {}", strerror(&e)))
+}
+
+/// Where `PATH` finds `program`, which is itself if it has a slash.
+fn which(program: &OsString) -> Option<PathBuf> {
+    if program.to_string_lossy().contains('/') {
+        return None;
+    }
+    let path = env::var_os("PATH")?;
+    env::split_paths(&path)
+        .map(|dir| dir.join(program))
+        .find(|candidate| candidate.is_file())
+}
+
+pub fn command() -> clap::Command {
+    Cli::command()
+}
+
+pub fn run(args: Vec<OsString>) -> i32 {
+    let cli = posix::parse::<Cli>(args);
+    let modified = cli.compute
+        || cli.kind.is_some()
+        || cli.user.is_some()
+        || cli.role.is_some()
+        || cli.range.is_some();
+
+    let mut args = cli.args.into_iter();
+    if args.len() == 0 {
+        if writeln!(io::stdout(), "{}", current()).is_err() {
+            process::exit(1);
+        }
+        return 0;
+    }
+    let context = match modified {
+        true => None,
+        false => args.next().map(|c| c.to_string_lossy().into_owned()),
+    };
+    let Some(program) = args.next() else {
+        usage_error!(1, "no command specified");
+    };
+    if !selinux::is_enabled() {
+        die!(1, "runcon may be used only on a SELinux kernel");
+    }
+
+    let base = match context {
+        Some(context) => context,
+        None if cli.compute => transition(&program),
+        None => current(),
+    };
+    let Some(mut context) = Context::parse(&base) else {
+        die!(1, "failed to create security context: {}", quote(&base));
+    };
+    let fields = [
+        ("user", cli.user, &mut context.user),
+        ("role", cli.role, &mut context.role),
+        ("type", cli.kind, &mut context.kind),
+    ];
+    for (name, value, field) in fields {
+        if let Some(value) = value {
+            if value.contains(':') {
+                die!(1, "failed to set new {}: {}", name, quote(&value));
+            }
+            *field = value;
+        }
+    }
+    if cli.range.is_some() {
+        context.range = cli.range;
+    }
+    let context = context.to_string();
+    if let Err(e) = selinux::check(&context) {
+        die!(1, "invalid context: {}: {}", quote(&context), strerror(&e));
+    }
+    if let Err(e) = selinux::set_exec(&context) {
+        die!(
+            1,
+            "unable to set security context {}: {}",
+            quote(&context),
+            strerror(&e)
+        );
+    }
+    exec(&program, args, Diagnostic::Name)
+}
@@ -0,0 +1,43 @@
+use std::ffi::OsString;
+use std::thread;
+use std::time::Duration;
+
+use crate::duration::parse_duration;
+use crate::error::ExitStatus;
+use crate::posix;
+use crate::show_error;
+use clap::{CommandFactory, Parser};
+
+#[derive(Parser)]
+#[command(version, about = "delay for a specified amount of time", long_about = None)]
+struct Cli {
+    /// NUMBER[SUFFIX]... where SUFFIX may be 's' for seconds (the default), 'm' for minutes, 'h' for hours or 'd' for days
+    #[arg(required = true, allow_negative_numbers = true)]
+    durations: Vec<String>,
+}
+
+pub fn command() -> clap::Command {
+    Cli::command()
+}
+
+pub fn run(args: Vec<OsString>) -> i32 {
+    let cli = posix::parse::<Cli>(args);
+    let mut total = Duration::ZERO;
+    let status = ExitStatus::new();
+    for operand in &cli.durations {
+        match parse_duration(operand) {
+            Some(duration) => total = total.saturating_add(duration),
+            None => {
+                show_error!("invalid time interval '{}'", operand);
+                status.fail();
+            }
+        }
+    }
+    if !status.is_success() {
+        status.exit();
+    }
+    // A signal with its default disposition still ends the process here, so
+    // the shell sees the usual 128+N status.
+    thread::sleep(total);
+    0
+}
@@ -0,0 +1,121 @@
+use std::env;
+use std::ffi::OsString;
+use std::process;
+
+use crate::error::{errno_text, strerror};
+use crate::posix;
+use crate::process::{exec, Diagnostic, EXIT_CANCELED};
+use crate::quote::quote;
+use crate::size::ParseSizeError;
+use crate::stdbuf::{library, Mode};
+use crate::{die, usage_error};
+use clap::{CommandFactory, Parser};
+
+const AFTER_HELP: &str = "\
+If MODE is 'L' the corresponding stream will be line buffered.
+This option is invalid with standard input.
+
+If MODE is '0' the corresponding stream will be unbuffered.
+
+Otherwise MODE is a number which may be followed by one of the following:
+KB 1000, K 1024, MB 1000*1000, M 1024*1024, and so on for G,T,P,E,Z,Y,R,Q.
+Binary prefixes can be used, too: KiB=K, MiB=M, and so on.
+In this case the corresponding stream will be fully buffered with the buffer
+size set to MODE bytes.
+
+NOTE: If COMMAND adjusts the buffering of its standard streams ('tee' does
+for example) then that will override corresponding changes by 'stdbuf'.
+Also some filters (like 'dd' and 'cat' etc.) don't use streams for I/O,
+and are thus unaffected by 'stdbuf' settings.";
+
+#[derive(Parser)]
+#[command(
+    version,
+    about = "run COMMAND, with modified buffering operations for its standard streams",
+    after_help = AFTER_HELP,
+    args_override_self = true,
+    long_about = None
+)]
+struct Cli {
+    /// adjust standard input stream buffering
+    #[arg(short, long, value_name = "MODE", allow_hyphen_values = true)]
+    input: Option<String>,
+
+    /// adjust standard output stream buffering
+    #[arg(short, long, value_name = "MODE", allow_hyphen_values = true)]
+    output: Option<String>,
+
+    /// adjust standard error stream buffering
+    #[arg(short, long, value_name = "MODE", allow_hyphen_values = true)]
+    error: Option<String>,
+
+    /// COMMAND [ARG]...
+    #[arg(trailing_var_arg = true)]
+    command: Vec<OsString>,
+}
+
+fn mode(text: &str) -> Mode {
+    Mode::parse(text).unwrap_or_else(|e| match e {
+        ParseSizeError::Invalid => die!(EXIT_CANCELED, "invalid mode {}", quote(text)),
+        ParseSizeError::Overflow => die!(
+            EXIT_CANCELED,
+            "invalid mode {}: {}",
+            quote(text),
+            errno_text(libc::EOVERFLOW)
+        ),
+    })
+}
+
+pub fn command() -> clap::Command {
+    Cli::command()
+}
+
+pub fn run(args: Vec<OsString>) -> i32 {
+    let cli = posix::try_parse::<Cli>(args).unwrap_or_else(|e| {
+        if e.use_stderr() {
+            let _ = e.print();
+            process::exit(EXIT_CANCELED);
+        }
+        e.exit()
+    });
+    if cli
+        .input
+        .as_deref()
+        .is_some_and(|text| text.trim_start().starts_with('L'))
+    {
+        usage_error!(EXIT_CANCELED, "line buffering stdin is meaningless");
+    }
+    let modes = [
+        ("_STDBUF_I", cli.input.as_deref().map(mode)),
+        ("_STDBUF_O", cli.output.as_deref().map(mode)),
+        ("_STDBUF_E", cli.error.as_deref().map(mode)),
+    ];
+    let mut command = cli.command.into_iter();
+    let Some(program) = command.next() else {
+        usage_error!(EXIT_CANCELED, "missing operand");
+    };
+    if modes.iter().all(|(_, mode)| mode.is_none()) {
+        usage_error!(EXIT_CANCELED, "you must specify a buffering mode option");
+    }
+
+    for (name, mode) in modes {
+        if let Some(mode) = mode {
+            env::set_var(name, mode.to_string());
+        }
+    }
+    let library = library().unwrap_or_else(|e| {
+        die!(
+            EXIT_CANCELED,
+            "failed to find libstdbuf.so: {}",
+            strerror(&e)
+        )
+    });
+    let mut preload = env::var_os("LD_PRELOAD").unwrap_or_default();
+    if !preload.is_empty() {
+        preload.push(":");
+    }
+    preload.push(library);
+    env::set_var("LD_PRELOAD", preload);
+
+    exec(&program, command, Diagnostic::FailedToRun)
+}
@@ -0,0 +1,713 @@
+use std::ffi::{CString, OsString};
+use std::io;
+use std::mem;
+use std::os::unix::ffi::OsStrExt;
+use std::os::unix::io::RawFd;
+use std::path::{Path, PathBuf};
+
+use crate::envcfg;
+use crate::error::{last_error, strerror};
+use crate::posix;
+use crate::{die, usage_error};
+use clap::{CommandFactory, Parser};
+use libc::{speed_t, tcflag_t, termios, winsize};
+
+#[derive(Parser)]
+#[command(version, about = "change and print terminal line settings", long_about = None)]
+struct Cli {
+    /// print all current settings in human-readable form
+    #[arg(short, long, conflicts_with = "save")]
+    all: bool,
+
+    /// print all current settings in a stty-readable form
+    #[arg(short = 'g', long)]
+    save: bool,
+
+    /// open and use the specified DEVICE instead of stdin
+    #[arg(short = 'F', long, value_name = "DEVICE")]
+    file: Option<PathBuf>,
+
+    /// SETTING...
+    #[arg(allow_hyphen_values = true)]
+    settings: Vec<String>,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Field {
+    Control,
+    Input,
+    Output,
+    Local,
+}
+
+/// What `sane` does with a flag.
+#[derive(Clone, Copy, PartialEq)]
+enum Sane {
+    Set,
+    Clear,
+    Keep,
+}
+
+struct Flag {
+    name: &'static str,
+    field: Field,
+    bits: tcflag_t,
+    mask: tcflag_t,
+    choice: bool,
+    sane: Sane,
+}
+
+const fn flag(name: &'static str, field: Field, bits: tcflag_t, sane: Sane) -> Flag {
+    Flag {
+        name,
+        field,
+        bits,
+        mask: bits,
+        choice: false,
+        sane,
+    }
+}
+
+/// One value of a multi-bit field such as the character size or a delay.
+const fn choice(
+    name: &'static str,
+    field: Field,
+    bits: tcflag_t,
+    mask: tcflag_t,
+    sane: Sane,
+) -> Flag {
+    Flag {
+        name,
+        field,
+        bits,
+        mask,
+        choice: true,
+        sane,
+    }
+}
+
+use Field::{Control, Input, Local, Output};
+use Sane::{Clear, Keep, Set};
+
+static FLAGS: &[Flag] = &[
+    flag("parenb", Control, libc::PARENB, Keep),
+    flag("parodd", Control, libc::PARODD, Keep),
+    flag("cmspar", Control, libc::CMSPAR, Keep),
+    choice("cs5", Control, libc::CS5, libc::CSIZE, Keep),
+    choice("cs6", Control, libc::CS6, libc::CSIZE, Keep),
+    choice("cs7", Control, libc::CS7, libc::CSIZE, Keep),
+    choice("cs8", Control, libc::CS8, libc::CSIZE, Keep),
+    flag("hupcl", Control, libc::HUPCL, Keep),
+    flag("cstopb", Control, libc::CSTOPB, Keep),
+    flag("cread", Control, libc::CREAD, Set),
+    flag("clocal", Control, libc::CLOCAL, Keep),
+    flag("crtscts", Control, libc::CRTSCTS, Keep),
+    flag("ignbrk", Input, libc::IGNBRK, Clear),
+    flag("brkint", Input, libc::BRKINT, Set),
+    flag("ignpar", Input, libc::IGNPAR, Keep),
+    flag("parmrk", Input, libc::PARMRK, Keep),
+    flag("inpck", Input, libc::INPCK, Keep),
+    flag("istrip", Input, libc::ISTRIP, Keep),
+    flag("inlcr", Input, libc::INLCR, Clear),
+    flag("igncr", Input, libc::IGNCR, Clear),
+    flag("icrnl", Input, libc::ICRNL, Set),
+    flag("ixon", Input, libc::IXON, Keep),
+    flag("ixoff", Input, libc::IXOFF, Clear),
+    flag("iuclc", Input, libc::IUCLC, Clear),
+    flag("ixany", Input, libc::IXANY, Clear),
+    flag("imaxbel", Input, libc::IMAXBEL, Set),
+    flag("iutf8", Input, libc::IUTF8, Keep),
+    flag("opost", Output, libc::OPOST, Set),
+    flag("olcuc", Output, libc::OLCUC, Clear),
+    flag("ocrnl", Output, libc::OCRNL, Clear),
+    flag("onlcr", Output, libc::ONLCR, Set),
+    flag("onocr", Output, libc::ONOCR, Clear),
+    flag("onlret", Output, libc::ONLRET, Clear),
+    flag("ofill", Output, libc::OFILL, Clear),
+    flag("ofdel", Output, libc::OFDEL, Clear),
+    choice("nl0", Output, libc::NL0, libc::NLDLY, Set),
+    choice("nl1", Output, libc::NL1, libc::NLDLY, Keep),
+    choice("cr0", Output, libc::CR0, libc::CRDLY, Set),
+    choice("cr1", Output, libc::CR1, libc::CRDLY, Keep),
+    choice("cr2", Output, libc::CR2, libc::CRDLY, Keep),
+    choice("cr3", Output, libc::CR3, libc::CRDLY, Keep),
+    choice("tab0", Output, libc::TAB0, libc::TABDLY, Set),
+    choice("tab1", Output, libc::TAB1, libc::TABDLY, Keep),
+    choice("tab2", Output, libc::TAB2, libc::TABDLY, Keep),
+    choice("tab3", Output, libc::TAB3, libc::TABDLY, Keep),
+    choice("bs0", Output, libc::BS0, libc::BSDLY, Set),
+    choice("bs1", Output, libc::BS1, libc::BSDLY, Keep),
+    choice("vt0", Output, libc::VT0, libc::VTDLY, Set),
+    choice("vt1", Output, libc::VT1, libc::VTDLY, Keep),
+    choice("ff0", Output, libc::FF0, libc::FFDLY, Set),
+    choice("ff1", Output, libc::FF1, libc::FFDLY, Keep),
+    flag("isig", Local, libc::ISIG, Set),
+    flag("icanon", Local, libc::ICANON, Set),
+    flag("iexten", Local, libc::IEXTEN, Set),
+    flag("echo", Local, libc::ECHO, Set),
+    flag("echoe", Local, libc::ECHOE, Set),
+    flag("echok", Local, libc::ECHOK, Set),
+    flag("echonl", Local, libc::ECHONL, Clear),
+    flag("noflsh", Local, libc::NOFLSH, Clear),
+    flag("xcase", Local, libc::XCASE, Clear),
+    flag("tostop", Local, libc::TOSTOP, Clear),
+    flag("echoprt", Local, libc::ECHOPRT, Clear),
+    flag("echoctl", Local, libc::ECHOCTL, Set),
+    flag("echoke", Local, libc::ECHOKE, Set),
+    flag("flusho", Local, libc::FLUSHO, Clear),
+    flag("extproc", Local, libc::EXTPROC, Clear),
+];
+
+const FLAG_ALIASES: [(&str, &str); 2] = [("hup", "hupcl"), ("crterase", "echoe")];
+
+/// Control characters with their `sane` defaults.
+const CONTROL_CHARS: [(&str, usize, u8); 17] = [
+    ("intr", libc::VINTR, 3),
+    ("quit", libc::VQUIT, 28),
+    ("erase", libc::VERASE, 127),
+    ("kill", libc::VKILL, 21),
+    ("eof", libc::VEOF, 4),
+    ("eol", libc::VEOL, 0),
+    ("eol2", libc::VEOL2, 0),
+    ("swtch", libc::VSWTC, 0),
+    ("start", libc::VSTART, 17),
+    ("stop", libc::VSTOP, 19),
+    ("susp", libc::VSUSP, 26),
+    ("rprnt", libc::VREPRINT, 18),
+    ("werase", libc::VWERASE, 23),
+    ("lnext", libc::VLNEXT, 22),
+    ("discard", libc::VDISCARD, 15),
+    ("min", libc::VMIN, 1),
+    ("time", libc::VTIME, 0),
+];
+
+/// Combination settings: the name, what it expands to, and what its `-`
+/// form expands to (empty when it cannot be negated).
+const COMBINATIONS: [(&str, &str, &str); 16] = [
+    (
+        "raw",
+        "-ignbrk -brkint -ignpar -parmrk -inpck -istrip -inlcr -igncr -icrnl -ixon -ixoff \
+         -iuclc -ixany -imaxbel -opost -isig -icanon -xcase min 1 time 0",
+        "brkint ignpar istrip icrnl ixon opost isig icanon eof ^D eol undef",
+    ),
+    (
+        "cooked",
+        "brkint ignpar istrip icrnl ixon opost isig icanon eof ^D eol undef",
+        "-ignbrk -brkint -ignpar -parmrk -inpck -istrip -inlcr -igncr -icrnl -ixon -ixoff \
+         -iuclc -ixany -imaxbel -opost -isig -icanon -xcase min 1 time 0",
+    ),
+    ("cbreak", "-icanon", "icanon"),
+    ("evenp", "parenb -parodd cs7", "-parenb cs8"),
+    ("parity", "parenb -parodd cs7", "-parenb cs8"),
+    ("oddp", "parenb parodd cs7", "-parenb cs8"),
+    ("pass8", "-parenb -istrip cs8", "parenb istrip cs7"),
+    (
+        "litout",
+        "-parenb -istrip -opost cs8",
+        "parenb istrip opost cs7",
+    ),
+    (
+        "nl",
+        "-icrnl -onlcr",
+        "icrnl -inlcr -igncr onlcr -ocrnl -onlret",
+    ),
+    ("ek", "erase ^? kill ^U", ""),
+    ("crt", "echoe echoctl echoke", ""),
+    (
+        "dec",
+        "echoe echoctl echoke -ixany intr ^C erase ^? kill ^U",
+        "",
+    ),
+    ("tabs", "tab0", "tab3"),
+    ("lcase", "xcase iuclc olcuc", "-xcase -iuclc -olcuc"),
+    ("LCASE", "xcase iuclc olcuc", "-xcase -iuclc -olcuc"),
+    ("decctlq", "-ixany", "ixany"),
+];
+
+const SPEEDS: [(u32, speed_t); 31] = [
+    (0, libc::B0),
+    (50, libc::B50),
+    (75, libc::B75),
+    (110, libc::B110),
+    (134, libc::B134),
+    (150, libc::B150),
+    (200, libc::B200),
+    (300, libc::B300),
+    (600, libc::B600),
+    (1200, libc::B1200),
+    (1800, libc::B1800),
+    (2400, libc::B2400),
+    (4800, libc::B4800),
+    (9600, libc::B9600),
+    (19200, libc::B19200),
+    (38400, libc::B38400),
+    (57600, libc::B57600),
+    (115200, libc::B115200),
+    (230400, libc::B230400),
+    (460800, libc::B460800),
+    (500000, libc::B500000),
+    (576000, libc::B576000),
+    (921600, libc::B921600),
+    (1000000, libc::B1000000),
+    (1152000, libc::B1152000),
+    (1500000, libc::B1500000),
+    (2000000, libc::B2000000),
+    (2500000, libc::B2500000),
+    (3000000, libc::B3000000),
+    (3500000, libc::B3500000),
+    (4000000, libc::B4000000),
+];
+
+fn field_mut(t: &mut termios, field: Field) -> &mut tcflag_t {
+    match field {
+        Control => &mut t.c_cflag,
+        Input => &mut t.c_iflag,
+        Output => &mut t.c_oflag,
+        Local => &mut t.c_lflag,
+    }
+}
+
+fn field_value(t: &termios, field: Field) -> tcflag_t {
+    match field {
+        Control => t.c_cflag,
+        Input => t.c_iflag,
+        Output => t.c_oflag,
+        Local => t.c_lflag,
+    }
+}
+
+fn is_set(t: &termios, flag: &Flag) -> bool {
+    field_value(t, flag.field) & flag.mask == flag.bits
+}
+
+fn speed_from_baud(baud: &str) -> Option<speed_t> {
+    let baud = baud.parse::<u32>().ok()?;
+    SPEEDS.iter().find(|(b, _)| *b == baud).map(|(_, s)| *s)
+}
+
+fn baud_from_speed(speed: speed_t) -> u32 {
+    SPEEDS
+        .iter()
+        .find(|(_, s)| *s == speed)
+        .map(|(b, _)| *b)
+        .unwrap_or(0)
+}
+
+/// Render a control character the way stty prints it: `^C`, `^?`, `M-x`
+/// or `<undef>`.
+fn format_char(c: u8) -> String {
+    match c {
+        0 => "<undef>".to_string(),
+        127 => "^?".to_string(),
+        c if c < 32 => format!("^{}", (c + 64) as char),
+        c if c >= 128 => format!("M-{}", format_char(c - 128)),
+        c => (c as char).to_string(),
+    }
+}
+
+fn parse_char(value: &str) -> Option<u8> {
+    let bytes = value.as_bytes();
+    match bytes {
+        [] => None,
+        [c] => Some(*c),
+        b"^-" => Some(0),
+        _ if value == "undef" => Some(0),
+        b"^?" => Some(127),
+        [b'^', c] => Some(c.to_ascii_uppercase() & 0x1f),
+        _ => parse_integer(value).filter(|&n| n <= 255).map(|n| n as u8),
+    }
+}
+
+fn parse_integer(value: &str) -> Option<u32> {
+    if let Some(hex) = value
+        .strip_prefix("0x")
+        .or_else(|| value.strip_prefix("0X"))
+    {
+        u32::from_str_radix(hex, 16).ok()
+    } else if value.len() > 1 && value.starts_with('0') {
+        u32::from_str_radix(&value[1..], 8).ok()
+    } else {
+        value.parse().ok()
+    }
+}
+
+struct Terminal {
+    fd: RawFd,
+    name: String,
+}
+
+impl Terminal {
+    fn attributes(&self) -> io::Result<termios> {
+        let mut t: termios = unsafe { mem::zeroed() };
+        match unsafe { libc::tcgetattr(self.fd, &mut t) } {
+            0 => Ok(t),
+            _ => Err(io::Error::last_os_error()),
+        }
+    }
+
+    fn window_size(&self) -> Option<winsize> {
+        let mut size: winsize = unsafe { mem::zeroed() };
+        match unsafe { libc::ioctl(self.fd, libc::TIOCGWINSZ, &mut size) } {
+            0 => Some(size),
+            _ => None,
+        }
+    }
+
+    fn set_window_size(&self, size: &winsize) -> io::Result<()> {
+        match unsafe { libc::ioctl(self.fd, libc::TIOCSWINSZ, size) } {
+            0 => Ok(()),
+            _ => Err(io::Error::last_os_error()),
+        }
+    }
+}
+
+/// Accumulates `; `-free items into lines no wider than the terminal.
+struct Wrapper {
+    width: usize,
+    line: String,
+    out: String,
+}
+
+impl Wrapper {
+    fn push(&mut self, item: &str) {
+        if !self.line.is_empty() && self.line.len() + 1 + item.len() > self.width {
+            self.end_line();
+        }
+        if !self.line.is_empty() {
+            self.line.push(' ');
+        }
+        self.line.push_str(item);
+    }
+
+    fn end_line(&mut self) {
+        if !self.line.is_empty() {
+            self.out.push_str(&self.line);
+            self.out.push('\n');
+            self.line.clear();
+        }
+    }
+}
+
+fn display(terminal: &Terminal, t: &termios, all: bool) -> String {
+    let size = terminal.window_size();
+    let width = envcfg::columns()
+        .or(size.filter(|s| s.ws_col > 0).map(|s| s.ws_col as usize))
+        .unwrap_or(80);
+    let mut w = Wrapper {
+        width,
+        line: String::new(),
+        out: String::new(),
+    };
+
+    let ispeed = baud_from_speed(unsafe { libc::cfgetispeed(t) });
+    let ospeed = baud_from_speed(unsafe { libc::cfgetospeed(t) });
+    match ispeed == ospeed || ispeed == 0 {
+        true => w.push(&format!("speed {} baud;", ospeed)),
+        false => w.push(&format!("ispeed {} baud; ospeed {} baud;", ispeed, ospeed)),
+    }
+    if all {
+        if let Some(size) = size {
+            w.push(&format!("rows {}; columns {};", size.ws_row, size.ws_col));
+        }
+    }
+    w.push(&format!("line = {};", t.c_line));
+    w.end_line();
+
+    let canonical = t.c_lflag & libc::ICANON != 0;
+    for (name, index, default) in CONTROL_CHARS {
+        let value = t.c_cc[index];
+        let numeric = index == libc::VMIN || index == libc::VTIME;
+        if !all && (value == default || (numeric && canonical)) {
+            continue;
+        }
+        match numeric {
+            true => w.push(&format!("{} = {};", name, value)),
+            false => w.push(&format!("{} = {};", name, format_char(value))),
+        }
+    }
+    w.end_line();
+
+    for field in [Control, Input, Output, Local] {
+        for flag in FLAGS.iter().filter(|f| f.field == field) {
+            let set = is_set(t, flag);
+            if !all {
+                let differs = match flag.sane {
+                    Set => !set,
+                    Clear => set,
+                    Keep => false,
+                };
+                if !differs {
+                    continue;
+                }
+            }
+            match (flag.choice, set) {
+                (false, true) => w.push(flag.name),
+                (false, false) => w.push(&format!("-{}", flag.name)),
+                (true, true) => w.push(flag.name),
+                (true, false) if !all && flag.sane == Set => {
+                    // The field holds some other value; show that one.
+                    if let Some(actual) = FLAGS
+                        .iter()
+                        .find(|f| f.mask == flag.mask && f.field == field && is_set(t, f))
+                    {
+                        w.push(actual.name);
+                    }
+                }
+                (true, false) => {}
+            }
+        }
+        w.end_line();
+    }
+    w.out
+}
+
+fn save_format(t: &termios) -> String {
+    let mut parts = vec![
+        format!("{:x}", t.c_iflag),
+        format!("{:x}", t.c_oflag),
+        format!("{:x}", t.c_cflag),
+        format!("{:x}", t.c_lflag),
+    ];
+    parts.extend(t.c_cc.iter().map(|c| format!("{:x}", c)));
+    parts.join(":")
+}
+
+fn restore_format(t: &mut termios, saved: &str) -> bool {
+    let parts = saved.split(':').collect::<Vec<&str>>();
+    if parts.len() != 4 + t.c_cc.len() {
+        return false;
+    }
+    let Ok(values) = parts
+        .iter()
+        .map(|p| tcflag_t::from_str_radix(p, 16))
+        .collect::<Result<Vec<tcflag_t>, _>>()
+    else {
+        return false;
+    };
+    t.c_iflag = values[0];
+    t.c_oflag = values[1];
+    t.c_cflag = values[2];
+    t.c_lflag = values[3];
+    for (cc, value) in t.c_cc.iter_mut().zip(&values[4..]) {
+        if *value > 255 {
+            return false;
+        }
+        *cc = *value as u8;
+    }
+    true
+}
+
+fn apply_sane(t: &mut termios) {
+    for flag in FLAGS {
+        let field = field_mut(t, flag.field);
+        match flag.sane {
+            Set => *field = (*field & !flag.mask) | flag.bits,
+            Clear => *field &= !flag.mask,
+            Keep => {}
+        }
+    }
+    for (_, index, default) in CONTROL_CHARS {
+        t.c_cc[index] = default;
+    }
+}
+
+/// Pending changes that are not part of the termios structure.
+#[derive(Default)]
+struct Extra {
+    rows: Option<u16>,
+    cols: Option<u16>,
+    print_size: bool,
+    print_speed: bool,
+}
+
+fn apply_settings(settings: &[String], t: &mut termios, extra: &mut Extra) {
+    let mut args = settings.iter();
+    while let Some(arg) = args.next() {
+        let (name, reversed) = match arg.strip_prefix('-') {
+            Some(name) => (name, true),
+            None => (arg.as_str(), false),
+        };
+        let mut value = |what: &str| -> &String {
+            args.next()
+                .unwrap_or_else(|| die!(1, "missing argument to '{}'", what))
+        };
+
+        let name = FLAG_ALIASES
+            .iter()
+            .find(|(alias, _)| *alias == name)
+            .map_or(name, |(_, real)| *real);
+        if let Some(flag) = FLAGS.iter().find(|f| f.name == name) {
+            if reversed && flag.choice {
+                usage_error!(1, "invalid argument \'{}\'", arg);
+            }
+            let field = field_mut(t, flag.field);
+            *field &= !flag.mask;
+            if !reversed {
+                *field |= flag.bits;
+            }
+            continue;
+        }
+        if let Some((_, on, off)) = COMBINATIONS.iter().find(|(n, _, _)| *n == name) {
+            let expansion = if reversed { off } else { on };
+            if expansion.is_empty() {
+                usage_error!(1, "invalid argument \'{}\'", arg);
+            }
+            let words = expansion
+                .split_whitespace()
+                .map(String::from)
+                .collect::<Vec<String>>();
+            apply_settings(&words, t, extra);
+            continue;
+        }
+        if reversed {
+            usage_error!(1, "invalid argument \'{}\'", arg);
+        }
+        if let Some((_, index, _)) = CONTROL_CHARS.iter().find(|(n, _, _)| *n == name) {
+            let setting = value(name);
+            let c = match *index == libc::VMIN || *index == libc::VTIME {
+                true => parse_integer(setting)
+                    .filter(|&n| n <= 255)
+                    .map(|n| n as u8),
+                false => parse_char(setting),
+            };
+            match c {
+                Some(c) => t.c_cc[*index] = c,
+                None => die!(1, "invalid integer argument: '{}'", setting),
+            }
+            continue;
+        }
+        match name {
+            "sane" => apply_sane(t),
+            "ispeed" | "ospeed" => {
+                let baud = value(name);
+                let speed = speed_from_baud(baud)
+                    .unwrap_or_else(|| usage_error!(1, "invalid argument \'{}\'", baud));
+                unsafe {
+                    match name {
+                        "ispeed" => libc::cfsetispeed(t, speed),
+                        _ => libc::cfsetospeed(t, speed),
+                    };
+                }
+            }
+            "rows" | "cols" | "columns" => {
+                let count = value(name);
+                let count = parse_integer(count)
+                    .filter(|&n| n <= u16::MAX as u32)
+                    .unwrap_or_else(|| die!(1, "invalid integer argument: '{}'", count))
+                    as u16;
+                match name {
+                    "rows" => extra.rows = Some(count),
+                    _ => extra.cols = Some(count),
+                }
+            }
+            "line" => {
+                let line = value(name);
+                t.c_line = parse_integer(line)
+                    .filter(|&n| n <= 255)
+                    .unwrap_or_else(|| die!(1, "invalid integer argument: '{}'", line))
+                    as u8;
+            }
+            "size" => extra.print_size = true,
+            "speed" => extra.print_speed = true,
+            _ if restore_format(t, name) => {}
+            _ => match speed_from_baud(name) {
+                Some(speed) => unsafe {
+                    libc::cfsetispeed(t, speed);
+                    libc::cfsetospeed(t, speed);
+                },
+                None => usage_error!(1, "invalid argument \'{}\'", arg),
+            },
+        }
+    }
+}
+
+fn open_device(path: &Path) -> RawFd {
+    let name = CString::new(path.as_os_str().as_bytes())
+        .unwrap_or_else(|_| die!(1, "{}: invalid file name", path.to_string_lossy()));
+    let fd = unsafe { libc::open(name.as_ptr(), libc::O_RDONLY | libc::O_NONBLOCK) };
+    if fd < 0 {
+        die!(1, "{}: {}", path.to_string_lossy(), last_error());
+    }
+    unsafe {
+        let flags = libc::fcntl(fd, libc::F_GETFL);
+        libc::fcntl(fd, libc::F_SETFL, flags & !libc::O_NONBLOCK);
+    }
+    fd
+}
+
+pub fn command() -> clap::Command {
+    Cli::command()
+}
+
+pub fn run(args: Vec<OsString>) -> i32 {
+    let cli = posix::parse::<Cli>(args);
+    if (cli.all || cli.save) && !cli.settings.is_empty() {
+        die!(1, "when specifying an output style, modes may not be set");
+    }
+
+    let terminal = match &cli.file {
+        Some(path) => Terminal {
+            fd: open_device(path),
+            name: path.to_string_lossy().to_string(),
+        },
+        None => Terminal {
+            fd: libc::STDIN_FILENO,
+            name: "standard input".to_string(),
+        },
+    };
+    let original = terminal
+        .attributes()
+        .unwrap_or_else(|e| die!(1, "'{}': {}", terminal.name, strerror(&e)));
+
+    if cli.settings.is_empty() {
+        let output = match cli.save {
+            true => format!("{}\n", save_format(&original)),
+            false => display(&terminal, &original, cli.all),
+        };
+        print!("{}", output);
+        return 0;
+    }
+
+    let mut t = original;
+    let mut extra = Extra::default();
+    apply_settings(&cli.settings, &mut t, &mut extra);
+
+    if extra.rows.is_some() || extra.cols.is_some() {
+        let mut size = terminal
+            .window_size()
+            .unwrap_or_else(|| unsafe { mem::zeroed() });
+        size.ws_row = extra.rows.unwrap_or(size.ws_row);
+        size.ws_col = extra.cols.unwrap_or(size.ws_col);
+        if let Err(e) = terminal.set_window_size(&size) {
+            die!(1, "'{}': {}", terminal.name, strerror(&e));
+        }
+    }
+    if save_format(&t) != save_format(&original)
+        || unsafe { libc::cfgetispeed(&t) != libc::cfgetispeed(&original) }
+        || unsafe { libc::cfgetospeed(&t) != libc::cfgetospeed(&original) }
+    {
+        if unsafe { libc::tcsetattr(terminal.fd, libc::TCSADRAIN, &t) } != 0 {
+            die!(1, "'{}': {}", terminal.name, last_error());
+        }
+        let applied = terminal
+            .attributes()
+            .unwrap_or_else(|e| die!(1, "'{}': {}", terminal.name, strerror(&e)));
+        if save_format(&applied) != save_format(&t) {
+            die!(
+                1,
+                "'{}': unable to perform all requested operations",
+                terminal.name
+            );
+        }
+    }
+    if extra.print_size {
+        match terminal.window_size() {
+            Some(size) => println!("{} {}", size.ws_row, size.ws_col),
+            None => die!(1, "'{}': unable to get window size", terminal.name),
+        }
+    }
+    if extra.print_speed {
+        println!("{}", baud_from_speed(unsafe { libc::cfgetospeed(&t) }));
+    }
+    0
+}
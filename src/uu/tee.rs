@@ -0,0 +1,138 @@
+use std::ffi::OsString;
+use std::fs::{File, OpenOptions};
+use std::io::{self, Read, Write};
+use std::path::PathBuf;
+
+use crate::error::{strerror, ExitStatus};
+use crate::posix;
+use crate::show_error;
+use crate::signal;
+use clap::{CommandFactory, Parser, ValueEnum};
+
+#[derive(Clone, Copy, PartialEq, ValueEnum)]
+enum OutputError {
+    /// diagnose errors writing to any output
+    Warn,
+    /// diagnose errors writing to any output not a pipe
+    WarnNopipe,
+    /// exit on error writing to any output
+    Exit,
+    /// exit on error writing to any output not a pipe
+    ExitNopipe,
+}
+
+#[derive(Parser)]
+#[command(version, about = "read from standard input and write to standard output and files", long_about = None)]
+struct Cli {
+    files: Vec<PathBuf>,
+
+    /// append to the given FILEs, do not overwrite
+    #[arg(short, long)]
+    append: bool,
+
+    /// ignore interrupt signals
+    #[arg(short, long)]
+    ignore_interrupts: bool,
+
+    /// operate in a more appropriate MODE with pipes
+    #[arg(short = 'p')]
+    pipe: bool,
+
+    /// set behavior on write error
+    #[arg(long, value_name = "MODE", value_enum, num_args = 0..=1, default_missing_value = "warn-nopipe", require_equals = true)]
+    output_error: Option<OutputError>,
+}
+
+struct Output {
+    name: String,
+    writer: Box<dyn Write>,
+}
+
+pub fn command() -> clap::Command {
+    Cli::command()
+}
+
+pub fn run(args: Vec<OsString>) -> i32 {
+    let cli = posix::parse::<Cli>(args);
+    let mode = match (cli.output_error, cli.pipe) {
+        (Some(mode), _) => Some(mode),
+        (None, true) => Some(OutputError::WarnNopipe),
+        (None, false) => None,
+    };
+    if cli.ignore_interrupts {
+        let _ = signal::ignore(libc::SIGINT);
+    }
+    // With --output-error tee sees closed pipes as write errors instead
+    // of being terminated by them.
+    if mode.is_some() {
+        let _ = signal::ignore(libc::SIGPIPE);
+    }
+
+    let status = ExitStatus::new();
+    let mut outputs = vec![Some(Output {
+        name: "standard output".to_string(),
+        writer: Box::new(io::stdout()),
+    })];
+    for path in &cli.files {
+        let file = match cli.append {
+            true => OpenOptions::new().append(true).create(true).open(path),
+            false => File::create(path),
+        };
+        match file {
+            Ok(file) => outputs.push(Some(Output {
+                name: path.to_string_lossy().to_string(),
+                writer: Box::new(file),
+            })),
+            Err(e) => {
+                show_error!("{}: {}", path.to_string_lossy(), strerror(&e));
+                status.fail();
+            }
+        }
+    }
+
+    let mut stdin = io::stdin().lock();
+    let mut buffer = vec![0; 8192];
+    loop {
+        let count = match stdin.read(&mut buffer) {
+            Ok(0) => break,
+            Ok(count) => count,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => {
+                show_error!("read error: {}", strerror(&e));
+                status.fail();
+                break;
+            }
+        };
+        for slot in outputs.iter_mut() {
+            let Some(output) = slot else {
+                continue;
+            };
+            let Err(e) = output
+                .writer
+                .write_all(&buffer[..count])
+                .and_then(|_| output.writer.flush())
+            else {
+                continue;
+            };
+            let broken_pipe = e.kind() == io::ErrorKind::BrokenPipe;
+            let quiet = broken_pipe
+                && matches!(
+                    mode,
+                    Some(OutputError::WarnNopipe | OutputError::ExitNopipe)
+                );
+            if !quiet {
+                show_error!("{}: {}", output.name, strerror(&e));
+                status.fail();
+            }
+            if !quiet && matches!(mode, Some(OutputError::Exit | OutputError::ExitNopipe)) {
+                return 1;
+            }
+            *slot = None;
+        }
+        if outputs.iter().all(Option::is_none) {
+            break;
+        }
+    }
+
+    status.code()
+}
@@ -0,0 +1,303 @@
+use std::ffi::{CString, OsStr, OsString};
+use std::fs::{self, Metadata};
+use std::os::unix::ffi::OsStrExt;
+use std::os::unix::fs::{FileTypeExt, MetadataExt};
+use std::path::Path;
+
+use crate::die;
+use crate::quote::quote;
+use crate::term::isatty;
+use crate::version;
+use clap::{CommandFactory, Parser};
+
+/// Evaluate conditional expressions. When invoked as `[` (through a link of
+/// that name) the expression must be closed by a final `]`.
+#[derive(Parser)]
+#[command(name = "test", version, about = "check file types and compare values", long_about = None)]
+struct Cli {}
+
+const UNARY_OPERATORS: [&str; 23] = [
+    "-b", "-c", "-d", "-e", "-f", "-g", "-G", "-h", "-k", "-L", "-n", "-N", "-O", "-p", "-r", "-s",
+    "-S", "-t", "-u", "-w", "-x", "-z", "-a",
+];
+
+const BINARY_OPERATORS: [&str; 13] = [
+    "=", "==", "!=", "<", ">", "-eq", "-ne", "-lt", "-le", "-gt", "-ge", "-ef", "-nt",
+];
+
+fn is_unary(arg: &OsStr) -> bool {
+    // -a as the deprecated synonym of -e is only recognised as a unary
+    // operator where it cannot be the binary "and".
+    arg != "-a" && UNARY_OPERATORS.iter().any(|op| arg == *op)
+}
+
+fn is_binary(arg: &OsStr) -> bool {
+    arg == "-ot" || BINARY_OPERATORS.iter().any(|op| arg == *op)
+}
+
+fn integer(arg: &OsStr) -> i128 {
+    let s = arg.to_string_lossy();
+    let trimmed = s.trim_matches(|c: char| c.is_ascii_whitespace());
+    let digits = trimmed.strip_prefix(['+', '-']).unwrap_or(trimmed);
+    if digits.is_empty() || !digits.chars().all(|c| c.is_ascii_digit()) {
+        die!(2, "invalid integer {}", quote(arg));
+    }
+    trimmed
+        .strip_prefix('+')
+        .unwrap_or(trimmed)
+        .parse()
+        .unwrap_or_else(|_| die!(2, "integer expression out of range {}", quote(arg)))
+}
+
+fn accessible(path: &OsStr, mode: libc::c_int) -> bool {
+    let Ok(path) = CString::new(path.as_bytes()) else {
+        return false;
+    };
+    unsafe { libc::faccessat(libc::AT_FDCWD, path.as_ptr(), mode, libc::AT_EACCESS) == 0 }
+}
+
+fn metadata(path: &OsStr) -> Option<Metadata> {
+    fs::metadata(path).ok()
+}
+
+fn mtime(metadata: &Metadata) -> (i64, i64) {
+    (metadata.mtime(), metadata.mtime_nsec())
+}
+
+fn unary(op: &OsStr, arg: &OsStr) -> bool {
+    let op = op.to_string_lossy();
+    match op.as_ref() {
+        "-n" => !arg.is_empty(),
+        "-z" => arg.is_empty(),
+        "-t" => {
+            let fd = integer(arg);
+            i32::try_from(fd).is_ok_and(isatty)
+        }
+        "-h" | "-L" => fs::symlink_metadata(arg).is_ok_and(|m| m.file_type().is_symlink()),
+        "-r" => accessible(arg, libc::R_OK),
+        "-w" => accessible(arg, libc::W_OK),
+        "-x" => accessible(arg, libc::X_OK),
+        _ => {
+            let Some(m) = metadata(arg) else {
+                return false;
+            };
+            let file_type = m.file_type();
+            match op.as_ref() {
+                "-a" | "-e" => true,
+                "-b" => file_type.is_block_device(),
+                "-c" => file_type.is_char_device(),
+                "-d" => file_type.is_dir(),
+                "-f" => file_type.is_file(),
+                "-p" => file_type.is_fifo(),
+                "-S" => file_type.is_socket(),
+                "-s" => m.size() > 0,
+                "-g" => m.mode() & libc::S_ISGID != 0,
+                "-u" => m.mode() & libc::S_ISUID != 0,
+                "-k" => m.mode() & libc::S_ISVTX != 0,
+                "-O" => m.uid() == users::get_effective_uid(),
+                "-G" => m.gid() == users::get_effective_gid(),
+                "-N" => mtime(&m) > (m.atime(), m.atime_nsec()),
+                _ => unreachable!(),
+            }
+        }
+    }
+}
+
+fn binary(left: &OsStr, op: &OsStr, right: &OsStr) -> bool {
+    let op = op.to_string_lossy();
+    match op.as_ref() {
+        "=" | "==" => left == right,
+        "!=" => left != right,
+        "<" => left.as_bytes() < right.as_bytes(),
+        ">" => left.as_bytes() > right.as_bytes(),
+        "-eq" => integer(left) == integer(right),
+        "-ne" => integer(left) != integer(right),
+        "-lt" => integer(left) < integer(right),
+        "-le" => integer(left) <= integer(right),
+        "-gt" => integer(left) > integer(right),
+        "-ge" => integer(left) >= integer(right),
+        "-ef" => match (metadata(left), metadata(right)) {
+            (Some(l), Some(r)) => l.dev() == r.dev() && l.ino() == r.ino(),
+            _ => false,
+        },
+        "-nt" => match (metadata(left), metadata(right)) {
+            (Some(l), Some(r)) => mtime(&l) > mtime(&r),
+            (Some(_), None) => true,
+            _ => false,
+        },
+        "-ot" => match (metadata(left), metadata(right)) {
+            (Some(l), Some(r)) => mtime(&l) < mtime(&r),
+            (None, Some(_)) => true,
+            _ => false,
+        },
+        "-a" => !left.is_empty() && !right.is_empty(),
+        "-o" => !left.is_empty() || !right.is_empty(),
+        _ => unreachable!(),
+    }
+}
+
+struct ExprParser<'a> {
+    args: &'a [OsString],
+    pos: usize,
+}
+
+impl<'a> ExprParser<'a> {
+    fn remaining(&self) -> usize {
+        self.args.len() - self.pos
+    }
+
+    fn peek(&self, offset: usize) -> Option<&'a OsStr> {
+        self.args.get(self.pos + offset).map(|a| a.as_os_str())
+    }
+
+    fn next(&mut self) -> &'a OsStr {
+        match self.peek(0) {
+            Some(arg) => {
+                self.pos += 1;
+                arg
+            }
+            None => die!(
+                2,
+                "missing argument after {}",
+                quote(&self.args[self.pos - 1])
+            ),
+        }
+    }
+
+    /// The POSIX rules that decide the meaning of short expressions purely
+    /// from their argument count.
+    fn posix(&mut self, count: usize) -> bool {
+        let (args, start) = (self.args, self.pos);
+        let arg = |i: usize| args[start + i].as_os_str();
+        match count {
+            0 => false,
+            1 => {
+                self.pos += 1;
+                !arg(0).is_empty()
+            }
+            2 if arg(0) == "!" => {
+                self.pos += 1;
+                !self.posix(1)
+            }
+            2 if is_unary(arg(0)) || arg(0) == "-a" => {
+                self.pos += 2;
+                unary(arg(0), arg(1))
+            }
+            2 => die!(2, "{}: unary operator expected", quote(arg(0))),
+            3 if is_binary(arg(1)) || arg(1) == "-a" || arg(1) == "-o" => {
+                self.pos += 3;
+                binary(arg(0), arg(1), arg(2))
+            }
+            3 if arg(0) == "!" => {
+                self.pos += 1;
+                !self.posix(2)
+            }
+            3 if arg(0) == "(" && arg(2) == ")" => {
+                self.pos += 3;
+                !arg(1).is_empty()
+            }
+            3 => die!(2, "{}: binary operator expected", quote(arg(1))),
+            4 if arg(0) == "!" => {
+                self.pos += 1;
+                !self.posix(3)
+            }
+            4 if arg(0) == "(" && arg(3) == ")" => {
+                self.pos += 1;
+                let result = self.posix(2);
+                self.pos += 1;
+                result
+            }
+            _ => self.or(),
+        }
+    }
+
+    fn or(&mut self) -> bool {
+        let mut result = self.and();
+        while self.peek(0).is_some_and(|a| a == "-o") {
+            self.pos += 1;
+            result = self.and() || result;
+        }
+        result
+    }
+
+    fn and(&mut self) -> bool {
+        let mut result = self.not();
+        while self.peek(0).is_some_and(|a| a == "-a") {
+            self.pos += 1;
+            result = self.not() && result;
+        }
+        result
+    }
+
+    fn not(&mut self) -> bool {
+        if self.peek(0).is_some_and(|a| a == "!") {
+            self.pos += 1;
+            return !self.not();
+        }
+        self.primary()
+    }
+
+    fn primary(&mut self) -> bool {
+        let arg = self.next();
+        if arg == "(" {
+            let result = self.or();
+            match self.peek(0) {
+                Some(close) if close == ")" => self.pos += 1,
+                Some(extra) => die!(2, "')' expected, found {}", quote(extra)),
+                None => die!(2, "')' expected"),
+            }
+            return result;
+        }
+        if self.peek(0).is_some_and(is_binary) && self.remaining() >= 2 {
+            let op = self.next();
+            let right = self.next();
+            return binary(arg, op, right);
+        }
+        if is_unary(arg) {
+            let operand = self.next();
+            return unary(arg, operand);
+        }
+        !arg.is_empty()
+    }
+}
+
+pub fn command() -> clap::Command {
+    Cli::command()
+}
+
+pub fn run(args: Vec<OsString>) -> i32 {
+    let mut args = args.into_iter().collect::<Vec<OsString>>();
+    let program = args.remove(0);
+    let bracket = Path::new(&program).file_name().is_some_and(|n| n == "[");
+
+    if bracket {
+        if args.len() == 1 && args[0] == "--help" {
+            let _ = Cli::command().name("[").print_help();
+            return 0;
+        }
+        if args.len() == 1 && args[0] == "--version" {
+            print!("{}", version::stamp(Cli::command()).render_version());
+            return 0;
+        }
+        match args.last() {
+            Some(last) if last == "]" => {
+                args.pop();
+            }
+            _ => die!(2, "missing ']'"),
+        }
+    }
+
+    let mut parser = ExprParser {
+        args: &args,
+        pos: 0,
+    };
+    let result = parser.posix(args.len());
+    if let Some(extra) = parser.peek(0) {
+        die!(2, "extra argument {}", quote(extra));
+    }
+    if result {
+        0
+    } else {
+        1
+    }
+}
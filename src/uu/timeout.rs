@@ -0,0 +1,173 @@
+use std::ffi::OsString;
+use std::io;
+use std::process::{self, Command};
+use std::time::{Duration, Instant};
+
+use crate::duration::parse_duration;
+use crate::error::last_error;
+use crate::posix;
+use crate::process::{cannot_run, spawn, Diagnostic, Termination, EXIT_CANCELED};
+use crate::quote::quote;
+use crate::signal::{self, SigSet};
+use crate::{die, show_error};
+use clap::{CommandFactory, Parser};
+
+const EXIT_TIMEDOUT: i32 = 124;
+
+/// Signals timeout relays to the monitored command when it receives them.
+const FORWARDED: [i32; 6] = [
+    libc::SIGINT,
+    libc::SIGTERM,
+    libc::SIGHUP,
+    libc::SIGQUIT,
+    libc::SIGUSR1,
+    libc::SIGUSR2,
+];
+
+#[derive(Parser)]
+#[command(version, about = "run a command with a time limit", long_about = None)]
+struct Cli {
+    /// when not running timeout directly from a shell prompt, allow COMMAND to read from the TTY and get TTY signals; in this mode, children of COMMAND will not be timed out
+    #[arg(long)]
+    foreground: bool,
+
+    /// also send a KILL signal if COMMAND is still running this long after the initial signal was sent
+    #[arg(short, long, value_name = "DURATION")]
+    kill_after: Option<String>,
+
+    /// exit with the same status as COMMAND, even when the command times out
+    #[arg(short, long)]
+    preserve_status: bool,
+
+    /// specify the signal to be sent on timeout; SIGNAL may be a name like 'HUP' or a number
+    #[arg(short, long, value_name = "SIGNAL")]
+    signal: Option<String>,
+
+    /// diagnose to stderr any signal sent upon timeout
+    #[arg(short, long)]
+    verbose: bool,
+
+    duration: String,
+
+    /// COMMAND [ARG]...
+    #[arg(trailing_var_arg = true, required = true)]
+    command: Vec<OsString>,
+}
+
+fn parse_interval(s: &str) -> Option<Duration> {
+    parse_duration(s).filter(|d| !d.is_zero())
+}
+
+struct Monitor {
+    pid: libc::pid_t,
+    foreground: bool,
+    verbose: bool,
+    program: String,
+}
+
+impl Monitor {
+    fn send(&self, signal: i32) {
+        if self.verbose {
+            show_error!(
+                "sending signal {} to command {}",
+                signal::name(signal),
+                self.program
+            );
+        }
+        let target = if self.foreground { self.pid } else { -self.pid };
+        let _ = signal::forward(target, signal);
+    }
+}
+
+pub fn command() -> clap::Command {
+    Cli::command()
+}
+
+pub fn run(args: Vec<OsString>) -> i32 {
+    let cli = posix::try_parse::<Cli>(args).unwrap_or_else(|e| {
+        if e.use_stderr() {
+            let _ = e.print();
+            process::exit(EXIT_CANCELED);
+        }
+        e.exit()
+    });
+
+    let timeout = parse_duration(&cli.duration)
+        .unwrap_or_else(|| die!(EXIT_CANCELED, "invalid time interval '{}'", cli.duration));
+    let kill_after = cli.kill_after.as_ref().map(|k| {
+        parse_duration(k).unwrap_or_else(|| die!(EXIT_CANCELED, "invalid time interval '{}'", k))
+    });
+    let term_signal = match &cli.signal {
+        Some(s) => signal::parse(s).unwrap_or_else(|| die!(EXIT_CANCELED, "{}: invalid signal", s)),
+        None => libc::SIGTERM,
+    };
+
+    // Block everything we wait for so that signals are only consumed by
+    // sigtimedwait below; the child restores an empty mask before exec.
+    let mut waited = SigSet::of(&FORWARDED);
+    waited.add(libc::SIGCHLD);
+    waited.add(libc::SIGALRM);
+    waited.add(term_signal);
+    waited.block();
+
+    let mut args = cli.command.into_iter();
+    let program = args.next().unwrap();
+    let mut command = Command::new(&program);
+    command.args(args);
+    let child = match spawn(&mut command, !cli.foreground) {
+        Ok(child) => child,
+        Err(e) => return cannot_run(&program, &e, Diagnostic::FailedToRun),
+    };
+    let monitor = Monitor {
+        pid: child.id() as libc::pid_t,
+        foreground: cli.foreground,
+        verbose: cli.verbose,
+        program: quote(&program),
+    };
+
+    let mut deadline = parse_interval(&cli.duration).map(|_| Instant::now() + timeout);
+    let mut timed_out = false;
+    let mut kill_pending = false;
+    let mut status = 0;
+    loop {
+        let reaped = unsafe { libc::waitpid(monitor.pid, &mut status, libc::WNOHANG) };
+        if reaped == monitor.pid {
+            break;
+        }
+        if reaped < 0 && io::Error::last_os_error().raw_os_error() != Some(libc::EINTR) {
+            die!(EXIT_CANCELED, "error waiting for command: {}", last_error());
+        }
+
+        let remaining = deadline.map(|d| d.saturating_duration_since(Instant::now()));
+        let signal = match waited.wait(remaining) {
+            Err(e) if e.kind() == io::ErrorKind::WouldBlock => {
+                if kill_pending {
+                    monitor.send(libc::SIGKILL);
+                    deadline = None;
+                    continue;
+                }
+                timed_out = true;
+                term_signal
+            }
+            Err(_) | Ok(libc::SIGCHLD) => continue,
+            Ok(signal) => signal,
+        };
+        monitor.send(signal);
+        deadline = kill_after.map(|k| Instant::now() + k);
+        kill_pending = deadline.is_some();
+    }
+
+    let mut exit_status = match Termination::from_wait(status) {
+        Some(Termination::Signaled(signal)) if !timed_out => {
+            // Die the same way the command did so callers see the signal.
+            signal::raise_default(signal);
+            128 + signal
+        }
+        Some(termination) => termination.code(),
+        None => EXIT_CANCELED,
+    };
+    if timed_out && !cli.preserve_status && exit_status != 128 + libc::SIGKILL {
+        exit_status = EXIT_TIMEDOUT;
+    }
+    exit_status
+}
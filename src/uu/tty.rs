@@ -0,0 +1,43 @@
+use std::ffi::{CStr, OsString};
+use std::io::{self, Write};
+
+use crate::posix;
+use crate::term::stdin_is_tty;
+use clap::{CommandFactory, Parser};
+
+const EXIT_NOT_TTY: i32 = 1;
+const EXIT_WRITE_ERROR: i32 = 3;
+
+#[derive(Parser)]
+#[command(version, about = "print the file name of the terminal connected to standard input", long_about = None)]
+struct Cli {
+    /// print nothing, only return an exit status
+    #[arg(short, long, visible_alias = "quiet")]
+    silent: bool,
+}
+
+pub fn command() -> clap::Command {
+    Cli::command()
+}
+
+pub fn run(args: Vec<OsString>) -> i32 {
+    let cli = posix::parse::<Cli>(args);
+    if cli.silent {
+        return if stdin_is_tty() { 0 } else { EXIT_NOT_TTY };
+    }
+
+    let name = unsafe { libc::ttyname(libc::STDIN_FILENO) };
+    let (line, status) = match name.is_null() {
+        true => ("not a tty".to_string(), EXIT_NOT_TTY),
+        false => (
+            unsafe { CStr::from_ptr(name) }
+                .to_string_lossy()
+                .to_string(),
+            0,
+        ),
+    };
+    if writeln!(io::stdout(), "{}", line).is_err() {
+        return EXIT_WRITE_ERROR;
+    }
+    status
+}
@@ -0,0 +1,106 @@
+use std::ffi::OsString;
+use std::io::{self, Write};
+
+use crate::error::strerror;
+use crate::posix;
+use crate::show_error;
+use crate::uname::{operating_system, uname};
+use clap::{CommandFactory, Parser};
+
+#[derive(Parser)]
+#[command(version, about = "print system information", long_about = None)]
+struct Cli {
+    /// print all information, in the following order, except omit -p and -i if unknown
+    #[arg(short, long)]
+    all: bool,
+
+    /// print the kernel name
+    #[arg(short = 's', long)]
+    kernel_name: bool,
+
+    /// print the network node hostname
+    #[arg(short, long)]
+    nodename: bool,
+
+    /// print the kernel release
+    #[arg(short = 'r', long)]
+    kernel_release: bool,
+
+    /// print the kernel version
+    #[arg(short = 'v', long)]
+    kernel_version: bool,
+
+    /// print the machine hardware name
+    #[arg(short, long)]
+    machine: bool,
+
+    /// print the processor type (non-portable)
+    #[arg(short, long)]
+    processor: bool,
+
+    /// print the hardware platform (non-portable)
+    #[arg(short = 'i', long)]
+    hardware_platform: bool,
+
+    /// print the operating system
+    #[arg(short, long)]
+    operating_system: bool,
+}
+
+const UNKNOWN: &str = "unknown";
+
+pub fn command() -> clap::Command {
+    Cli::command()
+}
+
+pub fn run(args: Vec<OsString>) -> i32 {
+    let cli = posix::parse::<Cli>(args);
+    let name = match uname() {
+        Ok(name) => name,
+        Err(e) => {
+            show_error!("cannot get system name: {}", strerror(&e));
+            return 1;
+        }
+    };
+
+    let any = cli.kernel_name
+        || cli.nodename
+        || cli.kernel_release
+        || cli.kernel_version
+        || cli.machine
+        || cli.processor
+        || cli.hardware_platform
+        || cli.operating_system;
+    let kernel_name = cli.kernel_name || cli.all || !any;
+
+    let mut fields: Vec<&str> = vec![];
+    if kernel_name {
+        fields.push(&name.sysname);
+    }
+    if cli.nodename || cli.all {
+        fields.push(&name.nodename);
+    }
+    if cli.kernel_release || cli.all {
+        fields.push(&name.release);
+    }
+    if cli.kernel_version || cli.all {
+        fields.push(&name.version);
+    }
+    if cli.machine || cli.all {
+        fields.push(&name.machine);
+    }
+    if cli.processor {
+        fields.push(UNKNOWN);
+    }
+    if cli.hardware_platform {
+        fields.push(UNKNOWN);
+    }
+    if cli.operating_system || cli.all {
+        fields.push(operating_system());
+    }
+
+    if writeln!(io::stdout(), "{}", fields.join(" ")).is_err() {
+        return 1;
+    }
+    0
+}
@@ -0,0 +1,29 @@
+use std::ffi::{CString, OsString};
+use std::os::unix::ffi::OsStrExt;
+use std::path::PathBuf;
+
+use crate::error::last_error;
+use crate::posix;
+use crate::quote::quote;
+use crate::show_error;
+use clap::{CommandFactory, Parser};
+
+#[derive(Parser)]
+#[command(version, about = "call the unlink function to remove the specified FILE", long_about = None)]
+struct Cli {
+    file: PathBuf,
+}
+
+pub fn command() -> clap::Command {
+    Cli::command()
+}
+
+pub fn run(args: Vec<OsString>) -> i32 {
+    let cli = posix::parse::<Cli>(args);
+    let path = CString::new(cli.file.as_os_str().as_bytes()).unwrap();
+    if unsafe { libc::unlink(path.as_ptr()) } != 0 {
+        show_error!("cannot unlink {}: {}", quote(&cli.file), last_error());
+        return 1;
+    }
+    0
+}
@@ -0,0 +1,93 @@
+use std::ffi::OsString;
+use std::fs;
+use std::io::{self, Write};
+use std::path::PathBuf;
+
+use crate::posix;
+use crate::show_error;
+use crate::timefmt::local_time;
+use crate::utmp::{read_utmp, RecordType, UTMP_FILE};
+use clap::{CommandFactory, Parser};
+use time::macros::format_description;
+use time::OffsetDateTime;
+
+#[derive(Parser)]
+#[command(version, about = "tell how long the system has been running", long_about = None)]
+struct Cli {
+    file: Option<PathBuf>,
+}
+
+/// Seconds since boot, from /proc/uptime where available and otherwise from
+/// the boot record in utmp.
+fn seconds_up(now: OffsetDateTime, boot: Option<OffsetDateTime>) -> Option<i64> {
+    let proc_uptime = fs::read_to_string("/proc/uptime").ok().and_then(|s| {
+        s.split_whitespace()
+            .next()
+            .and_then(|secs| secs.parse::<f64>().ok())
+    });
+    match proc_uptime {
+        Some(secs) => Some(secs as i64),
+        None => boot.map(|boot| (now - boot).whole_seconds()),
+    }
+}
+
+fn load_average() -> Option<[f64; 3]> {
+    let mut loads = [0.0; 3];
+    match unsafe { libc::getloadavg(loads.as_mut_ptr(), 3) } {
+        3 => Some(loads),
+        _ => None,
+    }
+}
+
+pub fn command() -> clap::Command {
+    Cli::command()
+}
+
+pub fn run(args: Vec<OsString>) -> i32 {
+    let cli = posix::parse::<Cli>(args);
+    let file = cli.file.unwrap_or(PathBuf::from(UTMP_FILE));
+    let records = read_utmp(&file);
+    let users = records.iter().filter(|r| r.is_user_process()).count();
+    let boot = records
+        .iter()
+        .find(|r| r.record_type == RecordType::BootTime)
+        .map(|r| r.time);
+
+    let now = OffsetDateTime::now_utc();
+    let Some(up) = seconds_up(now, boot) else {
+        show_error!("couldn't get boot time");
+        return 1;
+    };
+
+    let mut output = format!(
+        " {} up ",
+        local_time(now)
+            .format(format_description!("[hour]:[minute]:[second]"))
+            .unwrap_or_default()
+    );
+    let (days, hours, minutes) = (up / 86400, (up % 86400) / 3600, (up % 3600) / 60);
+    if days > 0 {
+        output.push_str(&format!(
+            "{} day{}, ",
+            days,
+            if days == 1 { "" } else { "s" }
+        ));
+    }
+    output.push_str(&format!("{:2}:{:02},  ", hours, minutes));
+    output.push_str(&format!(
+        "{} user{}",
+        users,
+        if users == 1 { "" } else { "s" }
+    ));
+    if let Some([one, five, fifteen]) = load_average() {
+        output.push_str(&format!(
+            ",  load average: {:.2}, {:.2}, {:.2}",
+            one, five, fifteen
+        ));
+    }
+
+    if writeln!(io::stdout(), "{}", output).is_err() {
+        return 1;
+    }
+    0
+}
@@ -0,0 +1,35 @@
+use std::ffi::OsString;
+use std::io::{self, Write};
+use std::path::PathBuf;
+
+use crate::posix;
+use crate::utmp::{read_utmp, UTMP_FILE};
+use clap::{CommandFactory, Parser};
+
+#[derive(Parser)]
+#[command(version, about = "print the user names of users currently logged in to the current host", long_about = None)]
+struct Cli {
+    file: Option<PathBuf>,
+}
+
+pub fn command() -> clap::Command {
+    Cli::command()
+}
+
+pub fn run(args: Vec<OsString>) -> i32 {
+    let cli = posix::parse::<Cli>(args);
+    let file = cli.file.unwrap_or(PathBuf::from(UTMP_FILE));
+    let mut names = read_utmp(&file)
+        .into_iter()
+        .filter(|record| record.is_user_process())
+        .map(|record| record.user)
+        .collect::<Vec<String>>();
+    if names.is_empty() {
+        return 0;
+    }
+    names.sort();
+    if writeln!(io::stdout(), "{}", names.join(" ")).is_err() {
+        return 1;
+    }
+    0
+}
@@ -0,0 +1,344 @@
+use std::ffi::{CStr, OsString};
+use std::io::{self, Write};
+use std::os::unix::fs::MetadataExt;
+use std::path::PathBuf;
+
+use crate::posix;
+use crate::table::{ColumnAlignment, Table, TableColumn, TableRow};
+use crate::timefmt::local_time;
+use crate::usage_error;
+use crate::utmp::{read_utmp, RecordType, UtmpRecord, UTMP_FILE};
+use clap::{ArgAction, CommandFactory, Parser};
+use time::macros::format_description;
+use time::OffsetDateTime;
+
+#[derive(Parser)]
+#[command(version, about = "show who is logged on", long_about = None, disable_help_flag(true))]
+struct Cli {
+    /// [ FILE | ARG1 ARG2 ]
+    args: Vec<OsString>,
+
+    #[arg(long, action(ArgAction::Help))]
+    help: Option<bool>,
+
+    /// same as -b -d --login -p -r -t -T -u
+    #[arg(short, long)]
+    all: bool,
+
+    /// time of last system boot
+    #[arg(short, long)]
+    boot: bool,
+
+    /// print dead processes
+    #[arg(short, long)]
+    dead: bool,
+
+    /// print line of column headings
+    #[arg(short = 'H', long)]
+    heading: bool,
+
+    /// print system login processes
+    #[arg(short, long)]
+    login: bool,
+
+    /// attempt to canonicalize hostnames via DNS
+    #[arg(long)]
+    lookup: bool,
+
+    /// only hostname and user associated with stdin
+    #[arg(short = 'm')]
+    only_stdin: bool,
+
+    /// print active processes spawned by init
+    #[arg(short = 'p', long)]
+    process: bool,
+
+    /// all login names and number of users logged on
+    #[arg(short = 'q', long)]
+    count: bool,
+
+    /// print current runlevel
+    #[arg(short, long)]
+    runlevel: bool,
+
+    /// print only name, line, and time (default)
+    #[arg(short, long)]
+    short: bool,
+
+    /// print last system clock change
+    #[arg(short, long)]
+    time: bool,
+
+    /// add user's message status as +, - or ?
+    #[arg(
+        short = 'T',
+        short_alias = 'w',
+        long,
+        visible_alias = "message",
+        alias = "writable"
+    )]
+    mesg: bool,
+
+    /// list users logged in
+    #[arg(short, long)]
+    users: bool,
+}
+
+fn format_time(time: OffsetDateTime) -> String {
+    local_time(time)
+        .format(format_description!("[year]-[month]-[day] [hour]:[minute]"))
+        .unwrap_or_default()
+}
+
+/// Message status and idle time of a terminal, derived from its device node.
+fn tty_status(record: &UtmpRecord) -> (String, String) {
+    let Some(metadata) = record.tty_metadata() else {
+        return ("?".to_string(), "?".to_string());
+    };
+    let mesg = match metadata.mode() & libc::S_IWGRP {
+        0 => "-",
+        _ => "+",
+    };
+    let idle = OffsetDateTime::now_utc().unix_timestamp() - metadata.atime();
+    let idle = if idle < 60 {
+        ".".to_string()
+    } else if idle < 24 * 60 * 60 {
+        format!("{:02}:{:02}", idle / 3600, (idle % 3600) / 60)
+    } else {
+        "old".to_string()
+    };
+    (mesg.to_string(), idle)
+}
+
+fn stdin_tty() -> Option<String> {
+    let name = unsafe { libc::ttyname(0) };
+    if name.is_null() {
+        return None;
+    }
+    let name = unsafe { CStr::from_ptr(name) }
+        .to_string_lossy()
+        .to_string();
+    Some(name.strip_prefix("/dev/").unwrap_or(&name).to_string())
+}
+
+struct Who {
+    boot: bool,
+    dead: bool,
+    login: bool,
+    process: bool,
+    runlevel: bool,
+    clock: bool,
+    users: bool,
+    mesg: bool,
+    idle: bool,
+}
+
+impl Who {
+    fn new(cli: &Cli) -> Self {
+        let mut who = Who {
+            boot: cli.boot || cli.all,
+            dead: cli.dead || cli.all,
+            login: cli.login || cli.all,
+            process: cli.process || cli.all,
+            runlevel: cli.runlevel || cli.all,
+            clock: cli.time || cli.all,
+            users: cli.users || cli.all,
+            mesg: cli.mesg || cli.all,
+            idle: cli.users || cli.all,
+        };
+        if !(who.boot || who.dead || who.login || who.process || who.runlevel || who.clock) {
+            who.users = true;
+        }
+        who
+    }
+
+    fn row(&self, record: &UtmpRecord) -> Option<[String; 8]> {
+        let comment_id = || format!("id={}", record.id);
+        let (mesg, idle) = match self.mesg || self.idle {
+            true => tty_status(record),
+            false => (String::new(), String::new()),
+        };
+        let pid = record.pid.to_string();
+        let host = match record.host.is_empty() {
+            true => String::new(),
+            false => format!("({})", record.host),
+        };
+        let time = format_time(record.time);
+        let row = match record.record_type {
+            RecordType::UserProcess if self.users && record.is_user_process() => [
+                record.user.clone(),
+                mesg,
+                record.line.clone(),
+                time,
+                idle,
+                pid,
+                host,
+                String::new(),
+            ],
+            RecordType::BootTime if self.boot => [
+                String::new(),
+                " ".to_string(),
+                "system boot".to_string(),
+                time,
+                String::new(),
+                String::new(),
+                String::new(),
+                String::new(),
+            ],
+            RecordType::RunLevel if self.runlevel => {
+                let current = (record.pid & 0xff) as u8 as char;
+                let previous = ((record.pid >> 8) & 0xff) as u8 as char;
+                let last = match previous {
+                    'N' | '\0' => "S".to_string(),
+                    _ => previous.to_string(),
+                };
+                [
+                    String::new(),
+                    " ".to_string(),
+                    format!("run-level {}", current),
+                    time,
+                    String::new(),
+                    String::new(),
+                    format!("last={}", last),
+                    String::new(),
+                ]
+            }
+            RecordType::NewTime | RecordType::OldTime if self.clock => [
+                String::new(),
+                " ".to_string(),
+                match record.record_type {
+                    RecordType::NewTime => "{".to_string(),
+                    _ => "|".to_string(),
+                },
+                time,
+                String::new(),
+                String::new(),
+                String::new(),
+                String::new(),
+            ],
+            RecordType::LoginProcess if self.login => [
+                "LOGIN".to_string(),
+                " ".to_string(),
+                record.line.clone(),
+                time,
+                idle,
+                pid,
+                comment_id(),
+                String::new(),
+            ],
+            RecordType::InitProcess if self.process => [
+                String::new(),
+                " ".to_string(),
+                record.line.clone(),
+                time,
+                idle,
+                pid,
+                comment_id(),
+                String::new(),
+            ],
+            RecordType::DeadProcess if self.dead => [
+                String::new(),
+                " ".to_string(),
+                record.line.clone(),
+                time,
+                String::new(),
+                pid,
+                comment_id(),
+                format!(
+                    "term={} exit={}",
+                    record.exit_status.0, record.exit_status.1
+                ),
+            ],
+            _ => return None,
+        };
+        let mut row = row;
+        if !self.mesg {
+            row[1] = String::new();
+        }
+        if !self.idle {
+            row[4] = String::new();
+            if record.record_type == RecordType::UserProcess {
+                row[5] = String::new();
+            }
+        }
+        Some(row)
+    }
+}
+
+pub fn command() -> clap::Command {
+    Cli::command()
+}
+
+pub fn run(args: Vec<OsString>) -> i32 {
+    let cli = posix::parse::<Cli>(args);
+    let (file, only_stdin) = match cli.args.len() {
+        0 => (PathBuf::from(UTMP_FILE), cli.only_stdin),
+        1 => (PathBuf::from(&cli.args[0]), cli.only_stdin),
+        2 => (PathBuf::from(UTMP_FILE), true),
+        _ => usage_error!(1, "extra operand '{}'", cli.args[2].to_string_lossy()),
+    };
+    let records = read_utmp(&file);
+    let mut stdout = io::stdout().lock();
+
+    if cli.count {
+        let names = records
+            .iter()
+            .filter(|r| r.is_user_process())
+            .map(|r| r.user.clone())
+            .collect::<Vec<String>>();
+        let result = writeln!(stdout, "{}", names.join(" "))
+            .and_then(|_| writeln!(stdout, "# users={}", names.len()));
+        if result.is_err() {
+            return 1;
+        }
+        return 0;
+    }
+
+    let who = Who::new(&cli);
+    let tty = match only_stdin {
+        true => stdin_tty(),
+        false => None,
+    };
+    let rows = records
+        .iter()
+        .filter(|r| !only_stdin || tty.as_ref().is_some_and(|t| *t == r.line))
+        .filter_map(|r| who.row(r))
+        .collect::<Vec<[String; 8]>>();
+
+    let mut table_rows = vec![];
+    if cli.heading {
+        let headings = ["NAME", "", "LINE", "TIME", "IDLE", "PID", "COMMENT", "EXIT"];
+        let mut heading: [String; 8] = Default::default();
+        for (col, title) in headings.iter().enumerate() {
+            if col == 0 || rows.iter().any(|r| !r[col].is_empty()) {
+                heading[col] = title.to_string();
+            }
+        }
+        if who.mesg {
+            heading[1] = " ".to_string();
+        }
+        table_rows.push(TableRow::new(heading));
+    }
+    table_rows.extend(rows.into_iter().map(TableRow::new));
+    if table_rows.is_empty() {
+        return 0;
+    }
+
+    let table = Table::new(
+        table_rows,
+        [
+            TableColumn::new(ColumnAlignment::Left),
+            TableColumn::new(ColumnAlignment::Left),
+            TableColumn::new(ColumnAlignment::Left),
+            TableColumn::new(ColumnAlignment::Left),
+            TableColumn::new(ColumnAlignment::Right),
+            TableColumn::new(ColumnAlignment::Right),
+            TableColumn::new(ColumnAlignment::Left),
+            TableColumn::new(ColumnAlignment::Left),
+        ],
+    );
+    if write!(stdout, "{}", table).is_err() {
+        return 1;
+    }
+    0
+}
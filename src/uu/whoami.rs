@@ -0,0 +1,31 @@
+use std::ffi::OsString;
+use std::io::{self, Write};
+use std::os::unix::ffi::OsStrExt;
+
+use crate::posix;
+use crate::show_error;
+use clap::{CommandFactory, Parser};
+use users::{get_effective_uid, get_user_by_uid};
+
+#[derive(Parser)]
+#[command(version, about = "print effective user name", long_about = None)]
+struct Cli {}
+
+pub fn command() -> clap::Command {
+    Cli::command()
+}
+
+pub fn run(args: Vec<OsString>) -> i32 {
+    posix::parse::<Cli>(args);
+    let uid = get_effective_uid();
+    let Some(user) = get_user_by_uid(uid) else {
+        show_error!("cannot find name for user ID {}", uid);
+        return 1;
+    };
+    let mut line = user.name().as_bytes().to_vec();
+    line.push(b'\n');
+    if io::stdout().write_all(&line).is_err() {
+        return 1;
+    }
+    0
+}
@@ -0,0 +1,48 @@
+use std::ffi::OsString;
+use std::io::{self, Write};
+use std::os::unix::ffi::OsStrExt;
+
+use crate::error::strerror;
+use crate::io::{Output, OUTPUT_BUFFER};
+use crate::posix;
+use crate::show_error;
+use clap::{CommandFactory, Parser};
+
+#[derive(Parser)]
+#[command(version, about = "output a string repeatedly until killed", long_about = None)]
+struct Cli {
+    #[arg(allow_hyphen_values = true)]
+    strings: Vec<OsString>,
+}
+
+pub fn command() -> clap::Command {
+    Cli::command()
+}
+
+pub fn run(args: Vec<OsString>) -> i32 {
+    let cli = posix::parse::<Cli>(args);
+    let mut line = match cli.strings.is_empty() {
+        true => b"y".to_vec(),
+        false => cli
+            .strings
+            .iter()
+            .map(|s| s.as_bytes())
+            .collect::<Vec<&[u8]>>()
+            .join(&b' '),
+    };
+    line.push(b'\n');
+
+    // Repeat the line to fill at least a whole output buffer, which goes
+    // out in one write without being copied into it.
+    let buffer = line.repeat(OUTPUT_BUFFER.div_ceil(line.len()));
+
+    let mut stdout = Output::new();
+    loop {
+        if let Err(e) = stdout.write_all(&buffer) {
+            if e.kind() != io::ErrorKind::BrokenPipe {
+                show_error!("standard output: {}", strerror(&e));
+            }
+            return 1;
+        }
+    }
+}
@@ -0,0 +1,23 @@
+//! What every utility's --version says, in the form GNU's takes: the
+//! program, the suite and its version, then what it was built from.
+
+use clap::Command;
+
+use crate::error::program_name;
+
+/// The version text after the program name.
+pub const VERSION: &str = concat!(
+    "(coreutils) ",
+    env!("CARGO_PKG_VERSION"),
+    "\nrevision: ",
+    env!("COREUTILS_REVISION"),
+    "\ntarget: ",
+    env!("COREUTILS_TARGET"),
+    "\nfeatures: ",
+    env!("COREUTILS_FEATURES"),
+);
+
+/// `command` named after the program running, and with the version text.
+pub fn stamp(command: Command) -> Command {
+    command.name(program_name()).version(VERSION)
+}
@@ -0,0 +1,252 @@
+use std::cmp::Ordering;
+
+/// The length of `s` without its file suffix: the longest tail matching
+/// `(\.[A-Za-z~][A-Za-z0-9~]*)*$`.
+fn prefix_len(s: &[u8]) -> usize {
+    let suffix_char = |c: u8| c.is_ascii_alphanumeric() || c == b'~';
+    let mut i = 0;
+    loop {
+        let prefix = i;
+        while i + 1 < s.len()
+            && s[i] == b'.'
+            && (s[i + 1].is_ascii_alphabetic() || s[i + 1] == b'~')
+        {
+            i += 2;
+            while i < s.len() && suffix_char(s[i]) {
+                i += 1;
+            }
+        }
+        if i == s.len() {
+            return prefix;
+        }
+        i += 1;
+    }
+}
+
+/// The weight of the byte at `pos` in a non-digit run: `~` before the end
+/// of the string, which is before letters, which are before everything
+/// else.
+fn order(s: &[u8], pos: usize) -> i32 {
+    match s.get(pos) {
+        None => -1,
+        Some(c) if c.is_ascii_digit() => 0,
+        Some(c) if c.is_ascii_alphabetic() => i32::from(*c),
+        Some(b'~') => -2,
+        Some(c) => i32::from(*c) + 256,
+    }
+}
+
+/// Compare alternating runs of non-digits and digits, the digits by
+/// numeric value.
+fn compare_runs(a: &[u8], b: &[u8]) -> Ordering {
+    let digit = |s: &[u8], i: usize| s.get(i).is_some_and(u8::is_ascii_digit);
+    let (mut i, mut j) = (0, 0);
+    while i < a.len() || j < b.len() {
+        while (i < a.len() && !digit(a, i)) || (j < b.len() && !digit(b, j)) {
+            let (x, y) = (order(a, i), order(b, j));
+            if x != y {
+                return x.cmp(&y);
+            }
+            i += 1;
+            j += 1;
+        }
+        while a.get(i) == Some(&b'0') {
+            i += 1;
+        }
+        while b.get(j) == Some(&b'0') {
+            j += 1;
+        }
+        let mut first_diff = Ordering::Equal;
+        while digit(a, i) && digit(b, j) {
+            if first_diff == Ordering::Equal {
+                first_diff = a[i].cmp(&b[j]);
+            }
+            i += 1;
+            j += 1;
+        }
+        if digit(a, i) {
+            return Ordering::Greater;
+        }
+        if digit(b, j) {
+            return Ordering::Less;
+        }
+        if first_diff != Ordering::Equal {
+            return first_diff;
+        }
+    }
+    Ordering::Equal
+}
+
+/// Compare file names the way `ls -v` and `sort -V` do, as gnulib's
+/// filevercmp does: numbers within names compare by value, `.` and `..`
+/// and then other hidden names sort first, and suffixes like `.tar.gz`
+/// only break ties.
+///
+/// Names that differ only in leading zeros compare equal; callers can
+/// fall back to comparing the bytes.
+pub fn version_cmp(a: &[u8], b: &[u8]) -> Ordering {
+    match (a.is_empty(), b.is_empty()) {
+        (true, true) => return Ordering::Equal,
+        (true, false) => return Ordering::Less,
+        (false, true) => return Ordering::Greater,
+        (false, false) => {}
+    }
+    let rank = |s: &[u8]| match s {
+        b"." => 0,
+        b".." => 1,
+        [b'.', ..] => 2,
+        _ => 3,
+    };
+    match rank(a).cmp(&rank(b)) {
+        Ordering::Equal if rank(a) < 2 => return Ordering::Equal,
+        Ordering::Equal => {}
+        order => return order,
+    }
+
+    let (a_prefix, b_prefix) = (prefix_len(a), prefix_len(b));
+    let result = compare_runs(&a[..a_prefix], &b[..b_prefix]);
+    if result != Ordering::Equal || (a_prefix == a.len() && b_prefix == b.len()) {
+        return result;
+    }
+    compare_runs(a, b)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Names in the order filevercmp puts them; each sorts strictly
+    /// before all of those after it.
+    const ORDERED: &[&str] = &[
+        "",
+        ".",
+        "..",
+        ".A",
+        ".Z",
+        ".a~",
+        ".a",
+        ".b~",
+        ".b",
+        ".z",
+        ".zz~",
+        ".zz",
+        ".zz.~1~",
+        ".0",
+        ".9",
+        ".zz.0",
+        ".\x01",
+        ".\x01.txt",
+        ".\x01x",
+        ".\x01x\x01",
+        ".\x01.0",
+        "0",
+        "9",
+        "A",
+        "Z",
+        "a~",
+        "a",
+        "a.b~",
+        "a.b",
+        "a.bc~",
+        "a.bc",
+        "a+",
+        "a.",
+        "a..a",
+        "a.+",
+        "b~",
+        "b",
+        "gcc-c++-10.fc9.tar.gz",
+        "gcc-c++-10.8.12-0.7rc2.fc9.tar.bz2",
+        "glibc-2-0.1.beta1.fc10.rpm",
+        "glibc-common-5-0.2.beta2.fc9.ebuild",
+        "glibc-common-5-0.2b.deb",
+        "glibc-common-11b.ebuild",
+        "glibc-common-11-0.6rc2.ebuild",
+        "libstdc++-0.5.8.11-0.7rc2.fc10.tar.gz",
+        "libstdc++-4a.fc8.tar.gz",
+        "libstdc++-4.10.4.20040204svn.rpm",
+        "libstdc++-devel-3.fc8.ebuild",
+        "libstdc++-devel-3a.fc9.tar.gz",
+        "libstdc++-devel-8.fc8.deb",
+        "libstdc++-devel-8.6.2-0.4b.fc8",
+        "nss_ldap-1-0.fc10.tar.gz",
+        "nss_ldap-1-0.2b.fc9.tar.bz2",
+        "nss_ldap-1-0.6rc2.fc8.tar.gz",
+        "z",
+        "zz~",
+        "zz",
+        "zz.~1~",
+        "zz.0",
+        "zz.0.txt",
+        "\x01",
+        "\x01.txt",
+        "\x01x",
+        "\x01x\x01",
+        "\x01.0",
+        "#\x01.b#",
+        "#.b#",
+    ];
+
+    #[test]
+    fn ordered_names() {
+        for (i, a) in ORDERED.iter().enumerate() {
+            for (j, b) in ORDERED.iter().enumerate() {
+                assert_eq!(
+                    version_cmp(a.as_bytes(), b.as_bytes()),
+                    i.cmp(&j),
+                    "comparing {:?} with {:?}",
+                    a,
+                    b
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn equivalent_names() {
+        let cases = [
+            ("a01", "a1"),
+            ("a0010", "a10"),
+            ("file-007.txt", "file-7.txt"),
+            ("v1.00", "v1.0"),
+            ("a", "a0"),
+            ("x.tar.gz", "x.tar.gz"),
+        ];
+        for (a, b) in cases {
+            assert_eq!(
+                version_cmp(a.as_bytes(), b.as_bytes()),
+                Ordering::Equal,
+                "{a} vs {b}"
+            );
+        }
+    }
+
+    #[test]
+    fn numbers_by_value() {
+        let cases = [
+            ("a2", "a10"),
+            ("1.2.9", "1.2.10"),
+            ("1.9", "1.10"),
+            ("foo-1.0", "foo-1.0.1"),
+            ("1.0~rc1", "1.0"),
+            ("1.0~rc1", "1.0~rc2"),
+            ("linux-5.4", "linux-5.15"),
+            ("img9.png", "img10.png"),
+            ("a.txt", "a1.txt"),
+            ("abc", "abd"),
+            ("x9y", "x10"),
+        ];
+        for (a, b) in cases {
+            assert_eq!(
+                version_cmp(a.as_bytes(), b.as_bytes()),
+                Ordering::Less,
+                "{a} vs {b}"
+            );
+            assert_eq!(
+                version_cmp(b.as_bytes(), a.as_bytes()),
+                Ordering::Greater,
+                "{b} vs {a}"
+            );
+        }
+    }
+}
@@ -0,0 +1,343 @@
+//! Waiting for files to change, as `tail -f` does: with inotify where the
+//! kernel has it, so that an append is seen at once, and otherwise by
+//! looking at each file again every so often. Names are watched as well as
+//! the files they refer to, so that `tail -F` can tell when a file is
+//! renamed or removed and when another takes its name.
+
+use std::fs::{self, Metadata};
+use std::io;
+use std::os::unix::fs::MetadataExt;
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::{Duration, SystemTime};
+
+/// What happened to a watched file.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ChangeKind {
+    /// It was written to, truncated or had its attributes changed, which
+    /// the caller tells apart by looking at it.
+    Written,
+    /// Its name no longer refers to it: it was renamed or removed.
+    Gone,
+    /// A file has been given the name, having had none or another.
+    Appeared,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Change {
+    /// Which file, as numbered by [`Watcher::watch`].
+    pub index: usize,
+    pub kind: ChangeKind,
+}
+
+/// What polling compares from one look to the next.
+#[derive(Clone, Copy, PartialEq, Eq)]
+struct Snapshot {
+    dev: u64,
+    ino: u64,
+    size: u64,
+    modified: Option<SystemTime>,
+    ctime: i64,
+}
+
+impl Snapshot {
+    fn of(path: &Path) -> Option<Self> {
+        fs::metadata(path).ok().as_ref().map(Snapshot::from)
+    }
+}
+
+impl From<&Metadata> for Snapshot {
+    fn from(metadata: &Metadata) -> Self {
+        Snapshot {
+            dev: metadata.dev(),
+            ino: metadata.ino(),
+            size: metadata.size(),
+            modified: metadata.modified().ok(),
+            ctime: metadata.ctime(),
+        }
+    }
+}
+
+struct Watched {
+    path: PathBuf,
+    snapshot: Option<Snapshot>,
+    /// The inotify watches on the file and on its directory.
+    file: Option<i32>,
+    dir: Option<i32>,
+}
+
+/// Files being waited on.
+pub struct Watcher {
+    files: Vec<Watched>,
+    interval: Duration,
+    inotify: Option<inotify::Inotify>,
+}
+
+impl Watcher {
+    /// A watcher that waits no longer than `interval` at a time, and uses
+    /// inotify if `notify` and the kernel allow, as `tail -s` and
+    /// `---disable-inotify` ask.
+    pub fn new(interval: Duration, notify: bool) -> Self {
+        Watcher {
+            files: Vec::new(),
+            interval,
+            inotify: notify.then(inotify::Inotify::new).flatten(),
+        }
+    }
+
+    /// Whether changes are seen as they happen rather than by polling.
+    pub fn is_notified(&self) -> bool {
+        self.inotify.is_some()
+    }
+
+    /// Start watching `path`, whether or not it exists yet, returning the
+    /// number its changes are reported under.
+    pub fn watch(&mut self, path: &Path) -> usize {
+        let mut watched = Watched {
+            path: path.to_path_buf(),
+            snapshot: Snapshot::of(path),
+            file: None,
+            dir: None,
+        };
+        if let Some(inotify) = &self.inotify {
+            let dir = match path.parent() {
+                Some(dir) if !dir.as_os_str().is_empty() => dir,
+                _ => Path::new("."),
+            };
+            watched.dir = inotify.add(dir, inotify::DIR_EVENTS).ok();
+            watched.file = inotify.add(path, inotify::FILE_EVENTS).ok();
+        }
+        self.files.push(watched);
+        self.files.len() - 1
+    }
+
+    /// Wait until something happens to the watched files or the interval
+    /// passes, returning what happened, which may be nothing.
+    pub fn wait(&mut self) -> io::Result<Vec<Change>> {
+        let mut changes = match &self.inotify {
+            Some(inotify) => self.notified(inotify.wait(self.interval)?),
+            None => {
+                thread::sleep(self.interval);
+                Vec::new()
+            }
+        };
+        // Polling catches what inotify cannot see, such as changes made
+        // on another machine to a file on a network file system.
+        for (index, watched) in self.files.iter_mut().enumerate() {
+            let now = Snapshot::of(&watched.path);
+            let kind = match (watched.snapshot, now) {
+                (Some(then), Some(now)) if (then.dev, then.ino) != (now.dev, now.ino) => {
+                    Some(ChangeKind::Appeared)
+                }
+                (Some(then), Some(now)) if then != now => Some(ChangeKind::Written),
+                (Some(_), None) => Some(ChangeKind::Gone),
+                (None, Some(_)) => Some(ChangeKind::Appeared),
+                _ => None,
+            };
+            watched.snapshot = now;
+            if let Some(kind) = kind {
+                changes.push(Change { index, kind });
+            }
+        }
+        changes.sort_by_key(|c| (c.index, c.kind as u8));
+        changes.dedup();
+        Ok(changes)
+    }
+
+    /// The changes inotify's `events` report, watching a file again once
+    /// another has taken its name.
+    fn notified(&mut self, events: Vec<inotify::Event>) -> Vec<Change> {
+        let mut changes = Vec::new();
+        // Watched afresh only after the whole batch, since later events in
+        // it may be for the file under its old name.
+        let mut named = Vec::new();
+        for event in events {
+            for (index, watched) in self.files.iter_mut().enumerate() {
+                let kind = if watched.file == Some(event.wd) {
+                    match event.mask & inotify::FILE_EVENTS {
+                        0 => None,
+                        mask if mask & inotify::WRITTEN != 0 => Some(ChangeKind::Written),
+                        _ => {
+                            watched.file = None;
+                            Some(ChangeKind::Gone)
+                        }
+                    }
+                } else if watched.dir == Some(event.wd)
+                    && Some(event.name.as_os_str()) == watched.path.file_name()
+                {
+                    match event.mask & inotify::NAMED {
+                        0 => Some(ChangeKind::Gone),
+                        _ => Some(ChangeKind::Appeared),
+                    }
+                } else {
+                    None
+                };
+                if kind == Some(ChangeKind::Appeared) {
+                    named.push(index);
+                }
+                if let Some(kind) = kind {
+                    changes.push(Change { index, kind });
+                }
+            }
+        }
+        if let Some(inotify) = &self.inotify {
+            for index in named {
+                if let Some(old) = self.files[index].file.take() {
+                    // Another name may be for the same file.
+                    if !self.files.iter().any(|f| f.file == Some(old)) {
+                        inotify.remove(old);
+                    }
+                }
+                let path = &self.files[index].path;
+                self.files[index].file = inotify.add(path, inotify::FILE_EVENTS).ok();
+            }
+        }
+        changes
+    }
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+mod inotify {
+    use std::ffi::{CString, OsString};
+    use std::io;
+    use std::os::unix::ffi::{OsStrExt, OsStringExt};
+    use std::path::Path;
+    use std::time::Duration;
+
+    pub const WRITTEN: u32 = libc::IN_MODIFY | libc::IN_ATTRIB;
+    pub const FILE_EVENTS: u32 = WRITTEN | libc::IN_DELETE_SELF | libc::IN_MOVE_SELF;
+    /// A name being given to a file.
+    pub const NAMED: u32 = libc::IN_CREATE | libc::IN_MOVED_TO;
+    pub const DIR_EVENTS: u32 = NAMED | libc::IN_MOVED_FROM | libc::IN_DELETE;
+
+    pub struct Event {
+        pub wd: i32,
+        pub mask: u32,
+        pub name: OsString,
+    }
+
+    pub struct Inotify {
+        fd: i32,
+    }
+
+    impl Inotify {
+        pub fn new() -> Option<Self> {
+            match unsafe { libc::inotify_init1(libc::IN_CLOEXEC | libc::IN_NONBLOCK) } {
+                -1 => None,
+                fd => Some(Inotify { fd }),
+            }
+        }
+
+        pub fn add(&self, path: &Path, mask: u32) -> io::Result<i32> {
+            let path = CString::new(path.as_os_str().as_bytes())
+                .map_err(|_| io::Error::from(io::ErrorKind::InvalidInput))?;
+            match unsafe { libc::inotify_add_watch(self.fd, path.as_ptr(), mask) } {
+                -1 => Err(io::Error::last_os_error()),
+                wd => Ok(wd),
+            }
+        }
+
+        pub fn remove(&self, wd: i32) {
+            unsafe {
+                libc::inotify_rm_watch(self.fd, wd);
+            }
+        }
+
+        /// The events that arrive within `timeout`, which stops waiting as
+        /// soon as there are any.
+        pub fn wait(&self, timeout: Duration) -> io::Result<Vec<Event>> {
+            let mut poll = libc::pollfd {
+                fd: self.fd,
+                events: libc::POLLIN,
+                revents: 0,
+            };
+            let millis = timeout.as_millis().min(libc::c_int::MAX as u128) as libc::c_int;
+            match unsafe { libc::poll(&mut poll, 1, millis) } {
+                -1 => {
+                    let e = io::Error::last_os_error();
+                    return match e.kind() {
+                        io::ErrorKind::Interrupted => Ok(Vec::new()),
+                        _ => Err(e),
+                    };
+                }
+                0 => return Ok(Vec::new()),
+                _ => {}
+            }
+            let mut events = Vec::new();
+            let mut buf = vec![0u8; 64 << 10];
+            loop {
+                let n = unsafe { libc::read(self.fd, buf.as_mut_ptr().cast(), buf.len()) };
+                if n < 0 {
+                    let e = io::Error::last_os_error();
+                    return match e.kind() {
+                        io::ErrorKind::WouldBlock | io::ErrorKind::Interrupted => Ok(events),
+                        _ => Err(e),
+                    };
+                }
+                let mut data = &buf[..n as usize];
+                let header = std::mem::size_of::<libc::inotify_event>();
+                while data.len() >= header {
+                    let event: libc::inotify_event =
+                        unsafe { std::ptr::read_unaligned(data.as_ptr().cast()) };
+                    let end = (header + event.len as usize).min(data.len());
+                    let name = &data[header..end];
+                    let name = &name[..name.iter().position(|&b| b == 0).unwrap_or(name.len())];
+                    events.push(Event {
+                        wd: event.wd,
+                        mask: event.mask,
+                        name: OsString::from_vec(name.to_vec()),
+                    });
+                    data = &data[end..];
+                }
+            }
+        }
+    }
+
+    impl Drop for Inotify {
+        fn drop(&mut self) {
+            unsafe {
+                libc::close(self.fd);
+            }
+        }
+    }
+}
+
+/// Elsewhere there is nothing to be notified by, and files are polled.
+#[cfg(not(any(target_os = "linux", target_os = "android")))]
+mod inotify {
+    use std::ffi::OsString;
+    use std::io;
+    use std::path::Path;
+    use std::time::Duration;
+
+    pub const WRITTEN: u32 = 0;
+    pub const FILE_EVENTS: u32 = 0;
+    pub const NAMED: u32 = 0;
+    pub const DIR_EVENTS: u32 = 0;
+
+    pub struct Event {
+        pub wd: i32,
+        pub mask: u32,
+        pub name: OsString,
+    }
+
+    pub enum Inotify {}
+
+    impl Inotify {
+        pub fn new() -> Option<Self> {
+            None
+        }
+
+        pub fn add(&self, _: &Path, _: u32) -> io::Result<i32> {
+            match *self {}
+        }
+
+        pub fn remove(&self, _: i32) {
+            match *self {}
+        }
+
+        pub fn wait(&self, _: Duration) -> io::Result<Vec<Event>> {
+            match *self {}
+        }
+    }
+}
@@ -0,0 +1,211 @@
+//! Extended attributes: listing, reading, writing and copying the
+//! name/value pairs file systems keep beside a file's data.
+//!
+//! Where the platform has no extended attributes every call fails with
+//! [`io::ErrorKind::Unsupported`].
+
+use std::ffi::{OsStr, OsString};
+use std::io;
+use std::path::Path;
+
+/// Whether `error` only says that `path` has no such attribute, or that its
+/// file system keeps none, rather than that something went wrong.
+pub fn is_absent(error: &io::Error) -> bool {
+    error.kind() == io::ErrorKind::Unsupported || platform::is_absent(error)
+}
+
+/// The names of the attributes of `path`, or of the symlink itself unless
+/// `follow`.
+pub fn list(path: &Path, follow: bool) -> io::Result<Vec<OsString>> {
+    let names = platform::list(path, follow)?;
+    Ok(names
+        .split(|&b| b == 0)
+        .filter(|name| !name.is_empty())
+        .map(|name| platform::os_string(name.to_vec()))
+        .collect())
+}
+
+/// The value of the attribute `name` of `path`.
+pub fn get(path: &Path, name: &OsStr, follow: bool) -> io::Result<Vec<u8>> {
+    platform::get(path, name, follow)
+}
+
+/// Set the attribute `name` of `path` to `value`, creating it if need be.
+pub fn set(path: &Path, name: &OsStr, value: &[u8], follow: bool) -> io::Result<()> {
+    platform::set(path, name, value, follow)
+}
+
+/// Give `to` every attribute of `from` that `wanted` accepts, carrying on
+/// past failures and reporting the first one.
+pub fn copy(from: &Path, to: &Path, wanted: impl Fn(&OsStr) -> bool) -> io::Result<()> {
+    let names = match list(from, true) {
+        Err(e) if is_absent(&e) => return Ok(()),
+        names => names?,
+    };
+    let mut result = Ok(());
+    for name in names.iter().filter(|name| wanted(name)) {
+        let copied = get(from, name, true).and_then(|value| set(to, name, &value, true));
+        if let Err(e) = copied {
+            if result.is_ok() && !is_absent(&e) {
+                result = Err(e);
+            }
+        }
+    }
+    result
+}
+
+#[cfg(any(target_os = "linux", target_os = "android", target_os = "macos"))]
+mod platform {
+    use std::ffi::{CString, OsStr, OsString};
+    use std::io;
+    use std::os::unix::ffi::{OsStrExt, OsStringExt};
+    use std::path::Path;
+
+    /// The error for a missing attribute.
+    #[cfg(target_os = "macos")]
+    const ENOATTR: i32 = libc::ENOATTR;
+    #[cfg(not(target_os = "macos"))]
+    const ENOATTR: i32 = libc::ENODATA;
+
+    pub fn is_absent(error: &io::Error) -> bool {
+        matches!(error.raw_os_error(), Some(libc::ENOTSUP) | Some(ENOATTR))
+    }
+
+    pub fn os_string(bytes: Vec<u8>) -> OsString {
+        OsString::from_vec(bytes)
+    }
+
+    fn c_string(s: &OsStr) -> io::Result<CString> {
+        CString::new(s.as_bytes()).map_err(|_| io::ErrorKind::InvalidInput.into())
+    }
+
+    /// Call `f` with a buffer until it is large enough for the answer, as
+    /// the attribute may grow between asking its size and reading it.
+    fn read(mut f: impl FnMut(*mut libc::c_void, usize) -> isize) -> io::Result<Vec<u8>> {
+        loop {
+            let size = f(std::ptr::null_mut(), 0);
+            if size < 0 {
+                return Err(io::Error::last_os_error());
+            }
+            let mut buf = vec![0u8; size as usize];
+            let read = f(buf.as_mut_ptr().cast(), buf.len());
+            if read >= 0 {
+                buf.truncate(read as usize);
+                return Ok(buf);
+            }
+            let error = io::Error::last_os_error();
+            if error.raw_os_error() != Some(libc::ERANGE) {
+                return Err(error);
+            }
+        }
+    }
+
+    #[cfg(target_os = "macos")]
+    fn options(follow: bool) -> libc::c_int {
+        if follow {
+            0
+        } else {
+            libc::XATTR_NOFOLLOW
+        }
+    }
+
+    #[cfg(target_os = "macos")]
+    pub fn list(path: &Path, follow: bool) -> io::Result<Vec<u8>> {
+        let path = c_string(path.as_os_str())?;
+        read(|buf, size| unsafe {
+            libc::listxattr(path.as_ptr(), buf.cast(), size, options(follow))
+        })
+    }
+
+    #[cfg(target_os = "macos")]
+    pub fn get(path: &Path, name: &OsStr, follow: bool) -> io::Result<Vec<u8>> {
+        let (path, name) = (c_string(path.as_os_str())?, c_string(name)?);
+        read(|buf, size| unsafe {
+            libc::getxattr(path.as_ptr(), name.as_ptr(), buf, size, 0, options(follow))
+        })
+    }
+
+    #[cfg(target_os = "macos")]
+    pub fn set(path: &Path, name: &OsStr, value: &[u8], follow: bool) -> io::Result<()> {
+        let (path, name) = (c_string(path.as_os_str())?, c_string(name)?);
+        let status = unsafe {
+            libc::setxattr(
+                path.as_ptr(),
+                name.as_ptr(),
+                value.as_ptr().cast(),
+                value.len(),
+                0,
+                options(follow),
+            )
+        };
+        match status {
+            0 => Ok(()),
+            _ => Err(io::Error::last_os_error()),
+        }
+    }
+
+    #[cfg(not(target_os = "macos"))]
+    pub fn list(path: &Path, follow: bool) -> io::Result<Vec<u8>> {
+        let path = c_string(path.as_os_str())?;
+        read(|buf, size| unsafe {
+            match follow {
+                true => libc::listxattr(path.as_ptr(), buf.cast(), size),
+                false => libc::llistxattr(path.as_ptr(), buf.cast(), size),
+            }
+        })
+    }
+
+    #[cfg(not(target_os = "macos"))]
+    pub fn get(path: &Path, name: &OsStr, follow: bool) -> io::Result<Vec<u8>> {
+        let (path, name) = (c_string(path.as_os_str())?, c_string(name)?);
+        read(|buf, size| unsafe {
+            match follow {
+                true => libc::getxattr(path.as_ptr(), name.as_ptr(), buf, size),
+                false => libc::lgetxattr(path.as_ptr(), name.as_ptr(), buf, size),
+            }
+        })
+    }
+
+    #[cfg(not(target_os = "macos"))]
+    pub fn set(path: &Path, name: &OsStr, value: &[u8], follow: bool) -> io::Result<()> {
+        let (path, name) = (c_string(path.as_os_str())?, c_string(name)?);
+        let (value, len) = (value.as_ptr().cast(), value.len());
+        let status = unsafe {
+            match follow {
+                true => libc::setxattr(path.as_ptr(), name.as_ptr(), value, len, 0),
+                false => libc::lsetxattr(path.as_ptr(), name.as_ptr(), value, len, 0),
+            }
+        };
+        match status {
+            0 => Ok(()),
+            _ => Err(io::Error::last_os_error()),
+        }
+    }
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "android", target_os = "macos")))]
+mod platform {
+    use std::ffi::{OsStr, OsString};
+    use std::io;
+    use std::path::Path;
+
+    pub fn is_absent(_: &io::Error) -> bool {
+        false
+    }
+
+    pub fn os_string(bytes: Vec<u8>) -> OsString {
+        String::from_utf8_lossy(&bytes).into_owned().into()
+    }
+
+    pub fn list(_: &Path, _: bool) -> io::Result<Vec<u8>> {
+        Err(io::ErrorKind::Unsupported.into())
+    }
+
+    pub fn get(_: &Path, _: &OsStr, _: bool) -> io::Result<Vec<u8>> {
+        Err(io::ErrorKind::Unsupported.into())
+    }
+
+    pub fn set(_: &Path, _: &OsStr, _: &[u8], _: bool) -> io::Result<()> {
+        Err(io::ErrorKind::Unsupported.into())
+    }
+}
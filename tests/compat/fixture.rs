@@ -0,0 +1,77 @@
+use std::fs::{self, File};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, SystemTime};
+
+/// A scratch directory tree the cases run in, removed when dropped. Its
+/// files have fixed timestamps and modes so that listings do not depend on
+/// when or by whom the tests are run, beyond the owner's name.
+pub struct Fixture {
+    root: PathBuf,
+}
+
+/// 2020-01-02 03:04:05 UTC.
+const MTIME: u64 = 1_577_934_245;
+
+impl Fixture {
+    pub fn new() -> Self {
+        static COUNT: AtomicUsize = AtomicUsize::new(0);
+        let root = std::env::temp_dir().join(format!(
+            "coreutils-compat-{}-{}",
+            std::process::id(),
+            COUNT.fetch_add(1, Ordering::Relaxed)
+        ));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(&root).expect("cannot create fixture directory");
+        let fixture = Fixture { root };
+        fixture.populate();
+        fixture
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.root
+    }
+
+    fn file(&self, name: &str, contents: &str, mode: u32) {
+        let path = self.root.join(name);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).unwrap();
+        }
+        fs::write(&path, contents).unwrap();
+        set_mode(&path, mode);
+        let file = File::options().write(true).open(&path).unwrap();
+        let time = SystemTime::UNIX_EPOCH + Duration::from_secs(MTIME);
+        file.set_times(fs::FileTimes::new().set_accessed(time).set_modified(time))
+            .unwrap();
+    }
+
+    fn populate(&self) {
+        self.file("a.txt", "alpha\n", 0o640);
+        self.file("with space", "", 0o644);
+        self.file(".hidden", "", 0o644);
+        self.file("backup~", "", 0o644);
+        self.file("file2", "2\n", 0o644);
+        self.file("file10", "10\n", 0o644);
+        self.file("script.sh", "#!/bin/sh\n", 0o755);
+        self.file("dir/inner", "inner\n", 0o644);
+        self.file("dir/sub/deep", "", 0o600);
+        self.file("numbers", "1000\n2048\n1500000\n", 0o644);
+        #[cfg(unix)]
+        std::os::unix::fs::symlink("a.txt", self.root.join("link")).unwrap();
+    }
+}
+
+impl Drop for Fixture {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.root);
+    }
+}
+
+#[cfg(unix)]
+fn set_mode(path: &Path, mode: u32) {
+    use std::os::unix::fs::PermissionsExt;
+    fs::set_permissions(path, fs::Permissions::from_mode(mode)).unwrap();
+}
+
+#[cfg(not(unix))]
+fn set_mode(_: &Path, _: u32) {}
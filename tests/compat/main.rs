@@ -0,0 +1,379 @@
+//! Runs each utility and the system's GNU one with the same arguments in
+//! the same fixture tree and compares their output and exit status. A
+//! utility is skipped where no GNU version of it is installed.
+//!
+//! The cases are ones that agree today; a failure is a regression.
+
+mod fixture;
+
+use std::collections::HashMap;
+use std::env;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output, Stdio};
+use std::sync::{Mutex, OnceLock};
+
+use fixture::Fixture;
+
+struct Case {
+    args: &'static [&'static str],
+    stdin: Option<&'static str>,
+}
+
+/// A case with no input.
+const fn case(args: &'static [&'static str]) -> Case {
+    Case { args, stdin: None }
+}
+
+/// A case reading `stdin`.
+const fn piped(args: &'static [&'static str], stdin: &'static str) -> Case {
+    Case {
+        args,
+        stdin: Some(stdin),
+    }
+}
+
+/// The environment both implementations run in, so that neither picks up
+/// the locale, time zone or options of whoever runs the tests.
+const ENV: [(&str, &str); 2] = [("LC_ALL", "C"), ("TZ", "UTC")];
+const UNSET: [&str; 6] = [
+    "COLUMNS",
+    "LS_COLORS",
+    "POSIXLY_CORRECT",
+    "QUOTING_STYLE",
+    "TIME_STYLE",
+    "VERSION_CONTROL",
+];
+
+/// The GNU `utility` on PATH, if there is one.
+fn gnu(utility: &str) -> Option<PathBuf> {
+    static FOUND: OnceLock<Mutex<HashMap<String, Option<PathBuf>>>> = OnceLock::new();
+    let mut found = FOUND.get_or_init(Default::default).lock().unwrap();
+    found
+        .entry(utility.to_string())
+        .or_insert_with(|| {
+            let ours = Path::new(env!("CARGO_BIN_EXE_coreutils")).parent()?;
+            env::split_paths(&env::var_os("PATH")?)
+                .map(|dir| dir.join(utility))
+                .filter(|path| path.is_file() && path.parent() != Some(ours))
+                .find(|path| {
+                    Command::new(path)
+                        .arg("--version")
+                        .output()
+                        .is_ok_and(|o| String::from_utf8_lossy(&o.stdout).contains("GNU"))
+                })
+        })
+        .clone()
+}
+
+/// Run `program` as `utility`, so that both name themselves the same way
+/// in diagnostics.
+fn run(utility: &str, program: &Path, case: &Case, dir: &Path) -> Output {
+    let mut command = Command::new(program);
+    #[cfg(unix)]
+    std::os::unix::process::CommandExt::arg0(&mut command, utility);
+    command
+        .args(case.args)
+        .current_dir(dir)
+        .envs(ENV)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+    for name in UNSET {
+        command.env_remove(name);
+    }
+    let mut child = command.spawn().expect("cannot run utility");
+    let mut stdin = child.stdin.take().unwrap();
+    if let Some(input) = case.stdin {
+        stdin.write_all(input.as_bytes()).unwrap();
+    }
+    drop(stdin);
+    child.wait_with_output().unwrap()
+}
+
+/// How `ours` and `theirs` differ, if they do.
+fn compare(ours: &Output, theirs: &Output) -> Option<String> {
+    let mut report = String::new();
+    if ours.status.code() != theirs.status.code() {
+        report += &format!(
+            "  status: {:?}, GNU {:?}\n",
+            ours.status.code(),
+            theirs.status.code()
+        );
+    }
+    for (stream, a, b) in [
+        ("stdout", &ours.stdout, &theirs.stdout),
+        ("stderr", &ours.stderr, &theirs.stderr),
+    ] {
+        if a != b {
+            report += &format!(
+                "  {stream}:\n    ours: {:?}\n    GNU:  {:?}\n",
+                String::from_utf8_lossy(a),
+                String::from_utf8_lossy(b)
+            );
+        }
+    }
+    (!report.is_empty()).then_some(report)
+}
+
+/// Run every case of `utility` under both implementations and fail with
+/// all the differences found.
+fn check(utility: &str, cases: &[Case]) {
+    let Some(theirs) = gnu(utility) else {
+        eprintln!("skipping {utility}: no GNU {utility} on PATH");
+        return;
+    };
+    let ours = Path::new(env!("CARGO_BIN_EXE_coreutils"))
+        .parent()
+        .unwrap()
+        .join(utility);
+    let fixture = Fixture::new();
+    let failures = cases
+        .iter()
+        .filter_map(|case| {
+            let diff = compare(
+                &run(utility, &ours, case, fixture.path()),
+                &run(utility, &theirs, case, fixture.path()),
+            )?;
+            Some(format!("{utility} {}\n{diff}", case.args.join(" ")))
+        })
+        .collect::<Vec<_>>();
+    assert!(failures.is_empty(), "\n{}", failures.join("\n"));
+}
+
+#[test]
+fn b2sum() {
+    check(
+        "b2sum",
+        &[
+            case(&["a.txt"]),
+            case(&["-l", "256", "--tag", "a.txt"]),
+            case(&["-l", "7", "a.txt"]),
+            piped(&["-c"], "nope\n"),
+        ],
+    );
+}
+
+#[test]
+fn cksum() {
+    check(
+        "cksum",
+        &[
+            case(&["a.txt", "with space"]),
+            case(&["-a", "bsd", "a.txt", "numbers"]),
+            case(&["-a", "sysv", "a.txt"]),
+            case(&["-a", "sha256", "a.txt"]),
+            case(&["-a", "sha1", "--untagged", "a.txt"]),
+            case(&["-a", "blake2b", "-l", "128", "a.txt"]),
+            #[cfg(feature = "sm3")]
+            case(&["-a", "sm3", "a.txt", "numbers"]),
+            piped(&["a.txt", "-"], "alpha\n"),
+            piped(
+                &["-c"],
+                "MD5 (a.txt) = 9f9f90dbe3e5ee1218c86b8839db1995\nSHA1 (numbers) = 00\n",
+            ),
+        ],
+    );
+}
+
+#[test]
+fn date() {
+    check(
+        "date",
+        &[
+            case(&["-d", "2020-01-02 03:04:05"]),
+            case(&["-u", "-d", "@0", "+%s %F %T"]),
+            case(&["-d", "@1600000000", "-R"]),
+            case(&["-I", "-d", "@0"]),
+            case(&["--rfc-3339=ns", "-d", "@0.5"]),
+            case(&["-r", "a.txt"]),
+            case(&["-d", "bogus"]),
+        ],
+    );
+}
+
+#[test]
+fn dircolors() {
+    check("dircolors", &[case(&["-b"]), case(&["-c"])]);
+}
+
+#[test]
+fn env() {
+    check(
+        "env",
+        &[
+            case(&["-i", "A=1", "env"]),
+            case(&["-u", "HOME", "printenv", "HOME"]),
+        ],
+    );
+}
+
+#[test]
+fn expr() {
+    check(
+        "expr",
+        &[
+            case(&["1", "+", "2"]),
+            case(&["length", "abc"]),
+            case(&["5", "/", "0"]),
+            case(&["abc", ":", "a\\(.\\)"]),
+            case(&["3", "<", "10"]),
+        ],
+    );
+}
+
+#[test]
+fn factor() {
+    check(
+        "factor",
+        &[
+            case(&["1", "2", "3", "100", "9999999967", "18446744073709551615"]),
+            case(&["abc"]),
+            piped(&[], "12 13\n"),
+        ],
+    );
+}
+
+#[test]
+fn id() {
+    check(
+        "id",
+        &[
+            case(&[]),
+            case(&["-un"]),
+            case(&["-G"]),
+            case(&["nosuchuser"]),
+        ],
+    );
+}
+
+#[test]
+fn ls() {
+    check("ls", &[case(&["nonexistent"])]);
+}
+
+#[test]
+fn md5sum() {
+    check(
+        "md5sum",
+        &[
+            case(&["a.txt", "with space", "nonexistent"]),
+            case(&["--tag", "-b", "a.txt"]),
+            case(&["-z", "a.txt"]),
+            case(&["--tag", "-c", "x"]),
+            piped(&["-"], "hello\n"),
+            piped(
+                &["-c"],
+                "d41d8cd98f00b204e9800998ecf8427e  with space\nd41d8cd98f00b204e9800998ecf8427e  a.txt\n",
+            ),
+            piped(
+                &["-c", "--quiet", "--warn"],
+                "junk\nd41d8cd98f00b204e9800998ecf8427e *with space\n",
+            ),
+            piped(&["-c", "--strict", "--status"], "junk\n"),
+            piped(
+                &["-c", "--ignore-missing"],
+                "d41d8cd98f00b204e9800998ecf8427e  nonexistent\n",
+            ),
+        ],
+    );
+}
+
+#[test]
+fn numfmt() {
+    check(
+        "numfmt",
+        &[
+            case(&["--to=si", "1000", "1500000"]),
+            case(&["--from=iec", "1K", "1M"]),
+            case(&["--to=iec-i", "4096"]),
+            case(&["--padding=8", "12"]),
+            case(&["--format=%08f", "42"]),
+            case(&["--grouping", "1234567"]),
+            case(&["abc"]),
+            piped(&["--to=si"], "1000\n2048\n1500000\n"),
+        ],
+    );
+}
+
+#[test]
+fn pathchk() {
+    check(
+        "pathchk",
+        &[
+            case(&["-p", "a:b", "ok/name"]),
+            case(&["-P", "a/-b", ""]),
+            case(&["--portability", "abcdefghijklmnopq"]),
+            case(&["/etc/passwd/x", "no/such/file"]),
+        ],
+    );
+}
+
+#[test]
+fn printenv() {
+    check("printenv", &[case(&["LC_ALL"]), case(&["NOPE"])]);
+}
+
+#[test]
+fn test() {
+    check(
+        "test",
+        &[
+            case(&["1", "-lt", "2"]),
+            case(&["a", "=", "b"]),
+            case(&["-f", "a.txt"]),
+            case(&["-d", "dir"]),
+            case(&["-L", "link"]),
+            case(&["-x", "script.sh"]),
+            case(&["(", "1", ")"]),
+        ],
+    );
+}
+
+#[test]
+fn timeout() {
+    check(
+        "timeout",
+        &[case(&["5", "true"]), case(&["0.1", "sleep", "1"])],
+    );
+}
+
+#[test]
+fn trivial() {
+    check("true", &[case(&[])]);
+    check("false", &[case(&[])]);
+    check("sleep", &[case(&["0"])]);
+    check("unlink", &[case(&["nonexistent"])]);
+}
+
+#[test]
+fn sha() {
+    for utility in [
+        "sha1sum",
+        "sha224sum",
+        "sha256sum",
+        "sha384sum",
+        "sha512sum",
+    ] {
+        check(
+            utility,
+            &[case(&["a.txt", "numbers"]), case(&["--tag", "a.txt"])],
+        );
+    }
+}
+
+#[test]
+fn system() {
+    check("uname", &[case(&["-a"]), case(&["-snrvmo"])]);
+    check("arch", &[case(&[])]);
+    check("hostid", &[case(&[])]);
+    check("nproc", &[case(&[])]);
+    check("groups", &[case(&[])]);
+    check("whoami", &[case(&[])]);
+    check("hostname", &[case(&[])]);
+    check("nice", &[case(&[]), case(&["-n", "5", "nice"])]);
+    check(
+        "stdbuf",
+        &[case(&["-o0", "printf", "x"]), case(&["-i", "L", "true"])],
+    );
+}
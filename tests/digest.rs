@@ -0,0 +1,185 @@
+//! Known answers for every algorithm, and the check line formats.
+
+use coreutils::digest::{self, parse_check_line, pool, Algorithm, CheckLine};
+
+/// The digests of `abc`, as hex or, for the legacy sums, as printed.
+const ABC: [(Algorithm, usize, &str); 11] = [
+    (Algorithm::Sysv, 16, "294 1"),
+    (Algorithm::Bsd, 16, "16556     1"),
+    (Algorithm::Crc, 32, "1219131554 3"),
+    (Algorithm::Md5, 128, "900150983cd24fb0d6963f7d28e17f72"),
+    (Algorithm::Sha1, 160, "a9993e364706816aba3e25717850c26c9cd0d89d"),
+    (
+        Algorithm::Sha224,
+        224,
+        "23097d223405d8228642a477bda255b32aadbce4bda0b3f7e36c9da7",
+    ),
+    (
+        Algorithm::Sha256,
+        256,
+        "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad",
+    ),
+    (
+        Algorithm::Sha384,
+        384,
+        "cb00753f45a35e8bb5a03d699ac65007272c32ab0eded1631a8b605a43ff5bed8086072ba1e7cc2358baeca134c825a7",
+    ),
+    (
+        Algorithm::Sha512,
+        512,
+        "ddaf35a193617abacc417349ae20413112e6fa4e89a97ea20a9eeee64b55d39a2192992a274fc1a836ba3c23a3feebbd454d4423643ce80e2a9ac94fa54ca49f",
+    ),
+    (
+        Algorithm::Blake2b,
+        512,
+        "ba80a53f981c4d0d6a2797b69f12f6e94c212f14685ac4b74b12bb6fdbffa2d17d87c5392aab792dc252d5de4533cc9518d38aa8dbf1925ab92386edd4009923",
+    ),
+    (
+        Algorithm::Blake2b,
+        256,
+        "bddd813c634239723171ef3fee98579b94964e3bb1cb3e427262c8c068d52319",
+    ),
+];
+
+#[test]
+fn known_answers() {
+    for (algorithm, bits, expected) in ABC {
+        let sum = digest::sum(&mut &b"abc"[..], algorithm, bits).unwrap();
+        let shown = match algorithm.is_legacy() {
+            true => sum.legacy(algorithm, None),
+            false => sum.hex(),
+        };
+        assert_eq!(shown, expected, "{} {}", algorithm.name(), bits);
+    }
+}
+
+/// The same for the algorithms built in by features.
+#[cfg(any(feature = "crc32c", feature = "sha3", feature = "sm3"))]
+#[test]
+fn optional_known_answers() {
+    let cases = [
+        #[cfg(feature = "crc32c")]
+        (Algorithm::Crc32c, 32, "910901175 3"),
+        #[cfg(feature = "sha3")]
+        (
+            Algorithm::Sha3,
+            224,
+            "e642824c3f8cf24ad09234ee7d3c766fc9a3a5168d0c94ad73b46fdf",
+        ),
+        #[cfg(feature = "sha3")]
+        (
+            Algorithm::Sha3,
+            512,
+            "b751850b1a57168a5693cd924b6b096e08f621827444f70d884f5d0240d2712e10e116e9192af3c91a7ec57647e3934057340b4cf408d5a56592f8274eec53f0",
+        ),
+        #[cfg(feature = "sm3")]
+        (
+            Algorithm::Sm3,
+            256,
+            "66c7f0f462eeedd9d1f2d46bdc10e4e24167c4875cf2f7a2297da02b8f4ba8e0",
+        ),
+    ];
+    for (algorithm, bits, expected) in cases {
+        let sum = digest::sum(&mut &b"abc"[..], algorithm, bits).unwrap();
+        let shown = match algorithm.is_legacy() {
+            true => sum.legacy(algorithm, None),
+            false => sum.hex(),
+        };
+        assert_eq!(shown, expected, "{} {}", algorithm.name(), bits);
+    }
+}
+
+#[cfg(feature = "sha3")]
+#[test]
+fn sha3_check_lines_name_their_length() {
+    let hex = "3a985da74fe225b2045c172d6bd390bd855f086e3e9d525b46bfe24511431532";
+    let parsed = |tag: &str| {
+        parse_check_line(&format!("{tag} (abc) = {hex}")).and_then(|line| line.algorithm)
+    };
+    assert_eq!(parsed("SHA3-256"), Some((Algorithm::Sha3, 256)));
+    assert_eq!(parsed("SHA3"), None);
+    assert_eq!(parsed("SHA3-384"), None);
+}
+
+#[test]
+fn digests_do_not_depend_on_how_input_is_split() {
+    let data = (0..200_000u32).map(|i| (i * 7) as u8).collect::<Vec<_>>();
+    for &(_, algorithm) in Algorithm::NAMES {
+        let whole = digest::sum(&mut &data[..], algorithm, algorithm.bits()).unwrap();
+        let mut pieces = algorithm.create(algorithm.bits());
+        for chunk in data.chunks(999) {
+            pieces.update(chunk);
+        }
+        assert_eq!(pieces.finalize(), whole.digest, "{}", algorithm.name());
+    }
+}
+
+#[test]
+fn check_lines() {
+    let md5 = |hex: &str| {
+        (0..hex.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap())
+            .collect::<Vec<_>>()
+    };
+    let digest = md5("900150983cd24fb0d6963f7d28e17f72");
+    let line = |algorithm, name: &str| CheckLine {
+        algorithm,
+        digest: digest.clone(),
+        name: name.to_string(),
+    };
+    let cases = [
+        (
+            "900150983cd24fb0d6963f7d28e17f72  abc",
+            Some(line(None, "abc")),
+        ),
+        (
+            "900150983cd24fb0d6963f7d28e17f72 *abc",
+            Some(line(None, "abc")),
+        ),
+        (
+            "900150983CD24FB0D6963F7D28E17F72  a b",
+            Some(line(None, "a b")),
+        ),
+        (
+            "\\900150983cd24fb0d6963f7d28e17f72  a\\nb\\\\c",
+            Some(line(None, "a\nb\\c")),
+        ),
+        (
+            "MD5 (a (1)) = 900150983cd24fb0d6963f7d28e17f72",
+            Some(line(Some((Algorithm::Md5, 128)), "a (1)")),
+        ),
+        ("900150983cd24fb0d6963f7d28e17f72 abc", None),
+        ("900150983cd24fb0d6963f7d28e17f7  abc", None),
+        ("900150983cd24fb0d6963f7d28e17f72  ", None),
+        ("\\900150983cd24fb0d6963f7d28e17f72  a\\tb", None),
+        ("SHA1 (abc) = 900150983cd24fb0d6963f7d28e17f72", None),
+        ("BLAKE2b-7 (abc) = 900150983cd24fb0d6963f7d28e17f72", None),
+    ];
+    for (text, expected) in cases {
+        assert_eq!(parse_check_line(text), expected, "{text}");
+    }
+    assert_eq!(
+        parse_check_line("BLAKE2b-128 (abc) = 900150983cd24fb0d6963f7d28e17f72")
+            .and_then(|line| line.algorithm),
+        Some((Algorithm::Blake2b, 128))
+    );
+}
+
+#[test]
+fn pooled_sums_are_reported_in_order() {
+    let inputs = (0..64u8)
+        .map(|n| vec![n; n as usize * 1000])
+        .collect::<Vec<_>>();
+    let sum = |input: &Vec<u8>| {
+        digest::sum(&mut &input[..], Algorithm::Sha256, 256)
+            .unwrap()
+            .hex()
+    };
+    let expected = inputs.iter().map(sum).collect::<Vec<_>>();
+    for threads in [1, 3, 16] {
+        let mut reported = Vec::new();
+        pool::in_order(&inputs, threads, sum, |_, sum| reported.push(sum));
+        assert_eq!(reported, expected, "{threads} threads");
+    }
+}
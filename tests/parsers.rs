@@ -0,0 +1,338 @@
+//! Properties of the shared parsers over generated input: that they agree
+//! with equivalent spellings, compute what they describe, and reject
+//! rather than panic on anything else.
+
+use std::time::Duration;
+
+use coreutils::datetime::{parse_date, parse_touch_stamp};
+use coreutils::duration::parse_duration;
+use coreutils::escape::{unescape, Dialect, EscapeError};
+use coreutils::mode::{FileMode, ModeSpec};
+use coreutils::ranges::{Numbering, Ranges};
+use coreutils::size::{
+    parse_block_size, parse_buffer_size, parse_dd_size, parse_size, ParseSizeError, SizeFormat,
+};
+use proptest::prelude::*;
+use time::{Date, Month, PrimitiveDateTime, Time};
+
+/// `rwx` letters for the low three bits of `bits`.
+fn letters(bits: u32) -> String {
+    [(4, 'r'), (2, 'w'), (1, 'x')]
+        .iter()
+        .filter(|&&(bit, _)| bits & bit != 0)
+        .map(|&(_, c)| c)
+        .collect()
+}
+
+/// SIZE suffixes and what they multiply by.
+const SIZE_SUFFIXES: [(&str, u64); 12] = [
+    ("", 1),
+    ("b", 512),
+    ("B", 1024),
+    ("k", 1024),
+    ("K", 1024),
+    ("KiB", 1024),
+    ("KB", 1000),
+    ("kD", 1000),
+    ("M", 1 << 20),
+    ("MB", 1_000_000),
+    ("GiB", 1 << 30),
+    ("T", 1 << 40),
+];
+
+const SECOND: i128 = 1_000_000_000;
+
+/// Nanoseconds since the epoch of a UTC time.
+fn utc(year: i32, month: u8, day: u8, hour: u8, minute: u8, second: u8) -> i128 {
+    let date = Date::from_calendar_date(year, Month::try_from(month).unwrap(), day).unwrap();
+    let time = Time::from_hms(hour, minute, second).unwrap();
+    PrimitiveDateTime::new(date, time)
+        .assume_utc()
+        .unix_timestamp() as i128
+        * SECOND
+}
+
+fn apply(spec: &str, mode: u32, is_dir: bool) -> u32 {
+    ModeSpec::parse(spec)
+        .unwrap_or_else(|e| panic!("{spec}: {e}"))
+        .apply(mode, is_dir, 0o022)
+}
+
+proptest! {
+    #[test]
+    fn octal_modes_match_symbolic(perms in 0u32..0o1000, mode in 0u32..0o10000) {
+        let symbolic = format!(
+            "u={},g={},o={}",
+            letters(perms >> 6),
+            letters(perms >> 3),
+            letters(perms)
+        );
+        let octal = format!("{perms:o}");
+        prop_assert_eq!(apply(&symbolic, mode, false), apply(&octal, mode, false));
+        prop_assert_eq!(apply(&octal, mode, false), perms);
+    }
+
+    #[test]
+    fn removing_what_was_added_clears_it(
+        who in "[ugoa]{1,3}",
+        perms in 1u32..8,
+        mode in 0u32..0o10000,
+        is_dir: bool,
+    ) {
+        let letters = letters(perms);
+        let result = apply(&format!("{who}+{letters},{who}-{letters}"), mode, is_dir);
+        for (class, shift) in [('u', 6), ('g', 3), ('o', 0)] {
+            if who.contains(class) || who.contains('a') {
+                prop_assert_eq!(result & (perms << shift), 0);
+            }
+        }
+    }
+
+    #[test]
+    fn file_mode_shows_every_permission_bit(mode in 0u32..0o10000) {
+        let shown = FileMode(0o100000 | mode).to_string();
+        prop_assert_eq!(shown.chars().count(), 10);
+        let granted = shown
+            .chars()
+            .skip(1)
+            .filter(|c| !matches!(c, '-' | 'S' | 'T'))
+            .count() as u32;
+        prop_assert_eq!(granted, (mode & 0o777).count_ones());
+    }
+
+    #[test]
+    fn mode_parser_never_panics(spec in "\\PC*") {
+        if let Ok(spec) = ModeSpec::parse(&spec) {
+            spec.apply(0o644, false, 0o022);
+        }
+    }
+
+    #[test]
+    fn whole_durations_scale_by_suffix(n in 0u64..1_000_000_000, suffix in "[smhd]?") {
+        let unit = match suffix.as_str() {
+            "m" => 60,
+            "h" => 60 * 60,
+            "d" => 24 * 60 * 60,
+            _ => 1,
+        };
+        prop_assert_eq!(
+            parse_duration(&format!("{n}{suffix}")),
+            Some(Duration::from_secs(n * unit))
+        );
+    }
+
+    #[test]
+    fn fractional_durations_are_close(whole in 0u64..100_000, millis in 0u64..1000) {
+        let parsed = parse_duration(&format!("{whole}.{millis:03}")).unwrap();
+        let exact = Duration::from_millis(whole * 1000 + millis);
+        prop_assert!(parsed.abs_diff(exact) < Duration::from_micros(1));
+    }
+
+    #[test]
+    fn negative_durations_are_rejected(n in 1u64.., suffix in "[smhd]?") {
+        prop_assert_eq!(parse_duration(&format!("-{n}{suffix}")), None);
+    }
+
+    #[test]
+    fn duration_parser_never_panics(s in "\\PC*") {
+        parse_duration(&s);
+    }
+
+    #[test]
+    fn sizes_scale_by_suffix(n in 0u64..1 << 20, suffix in 0..SIZE_SUFFIXES.len()) {
+        let (suffix, multiplier) = SIZE_SUFFIXES[suffix];
+        let text = format!("{n}{suffix}");
+        prop_assert_eq!(parse_size(&text), Ok(n * multiplier));
+        prop_assert_eq!(parse_dd_size(&text), Ok(n * multiplier));
+    }
+
+    #[test]
+    fn sizes_too_large_overflow(n in 1u64.., suffix in 1..SIZE_SUFFIXES.len()) {
+        let (suffix, multiplier) = SIZE_SUFFIXES[suffix];
+        let expected = n.checked_mul(multiplier).ok_or(ParseSizeError::Overflow);
+        prop_assert_eq!(parse_size(&format!("{n}{suffix}")), expected);
+        prop_assert_eq!(parse_size(&format!("{n}00000000000000000000")), Err(ParseSizeError::Overflow));
+    }
+
+    #[test]
+    fn dd_sizes_multiply(a in 0u64..1 << 16, b in 0u64..1 << 16, c in 0u64..1 << 16) {
+        prop_assert_eq!(parse_dd_size(&format!("{a}x{b}wx{c}c")), Ok(a * b * 2 * c));
+        prop_assert_eq!(parse_size(&format!("{a}x{b}")), Err(ParseSizeError::Invalid));
+    }
+
+    #[test]
+    fn block_sizes_are_positive_sizes(n in 1u64..1 << 20, suffix in 0..SIZE_SUFFIXES.len()) {
+        let (suffix, multiplier) = SIZE_SUFFIXES[suffix];
+        prop_assert_eq!(
+            parse_block_size(&format!("'{n}{suffix}")),
+            Ok(SizeFormat::Blocks(n * multiplier))
+        );
+        prop_assert_eq!(parse_block_size(&format!("0{suffix}")), Err(ParseSizeError::Invalid));
+    }
+
+    #[test]
+    fn buffer_sizes_default_to_kibibytes(n in 0u64..1 << 20, suffix in 2..SIZE_SUFFIXES.len()) {
+        let (suffix, multiplier) = SIZE_SUFFIXES[suffix];
+        prop_assert_eq!(parse_buffer_size(&format!("{n}")), Ok(n * 1024));
+        prop_assert_eq!(parse_buffer_size(&format!("{n}b")), Ok(n));
+        prop_assert_eq!(parse_buffer_size(&format!("{n}{suffix}")), Ok(n * multiplier));
+    }
+
+    #[test]
+    fn size_parsers_never_panic(s in "\\PC*") {
+        let _ = parse_size(&s);
+        let _ = parse_dd_size(&s);
+        let _ = parse_block_size(&s);
+        let _ = parse_buffer_size(&s);
+    }
+
+    #[test]
+    fn epoch_dates_are_exact(seconds in -(1i64 << 40)..1 << 40, nanos in 0u32..1_000_000_000) {
+        let expected = seconds as i128 * SECOND + nanos as i128;
+        prop_assert_eq!(parse_date(&format!("@{seconds}"), 0, true), Some(seconds as i128 * SECOND));
+        let text = match seconds < 0 {
+            true => format!("@-{}.{nanos:09}", -seconds - 1),
+            false => format!("@{seconds}.{nanos:09}"),
+        };
+        let expected = match seconds < 0 {
+            true => -((-seconds - 1) as i128 * SECOND + nanos as i128),
+            false => expected,
+        };
+        prop_assert_eq!(parse_date(&text, 0, true), Some(expected));
+    }
+
+    #[test]
+    fn iso_dates_are_read_in_utc(
+        year in 1900i32..2200,
+        month in 1u8..=12,
+        day in 1u8..=28,
+        hour in 0u8..24,
+        minute in 0u8..60,
+        second in 0u8..60,
+    ) {
+        let expected = utc(year, month, day, hour, minute, second);
+        let text = format!("{year:04}-{month:02}-{day:02} {hour:02}:{minute:02}:{second:02}");
+        prop_assert_eq!(parse_date(&text, 0, true), Some(expected));
+        let text = format!("{year:04}-{month:02}-{day:02}T{hour:02}:{minute:02}:{second:02}Z");
+        prop_assert_eq!(parse_date(&text, 0, false), Some(expected));
+        let text = format!("{year:04}-{month:02}-{day:02}T{hour:02}:{minute:02}:{second:02}+01:00");
+        prop_assert_eq!(parse_date(&text, 0, false), Some(expected - 3600 * SECOND));
+    }
+
+    #[test]
+    fn relative_dates_move_from_now(
+        n in 0i64..100_000,
+        unit in prop::sample::select(vec![("second", 1), ("minute", 60), ("hour", 3600), ("day", 86_400), ("week", 604_800)]),
+    ) {
+        let now = utc(2020, 2, 29, 12, 0, 0);
+        let (name, seconds) = unit;
+        let delta = (n * seconds) as i128 * SECOND;
+        prop_assert_eq!(parse_date(&format!("{n} {name}s ago"), now, true), Some(now - delta));
+        prop_assert_eq!(parse_date(&format!("+{n} {name}"), now, true), Some(now + delta));
+        prop_assert_eq!(parse_date(&format!("{n} {name} ago"), now, true), Some(now - delta));
+    }
+
+    #[test]
+    fn touch_stamps_match_their_fields(
+        year in 1970i32..2100,
+        month in 1u8..=12,
+        day in 1u8..=28,
+        hour in 0u8..24,
+        minute in 0u8..60,
+        second in 0u8..60,
+    ) {
+        let expected = utc(year, month, day, hour, minute, second);
+        let fields = format!("{month:02}{day:02}{hour:02}{minute:02}");
+        let stamp = format!("{year:04}{fields}.{second:02}");
+        prop_assert_eq!(parse_touch_stamp(&stamp, 0, true), Some(expected));
+        if (1969..2069).contains(&year) {
+            let stamp = format!("{:02}{fields}.{second:02}", year % 100);
+            prop_assert_eq!(parse_touch_stamp(&stamp, 0, true), Some(expected));
+        }
+        let now = utc(year, 6, 15, 0, 0, 0);
+        prop_assert_eq!(
+            parse_touch_stamp(&fields, now, true),
+            Some(expected - second as i128 * SECOND)
+        );
+    }
+
+    #[test]
+    fn date_parsers_never_panic(s in "\\PC*", n in any::<i64>(), word in "[a-z]{0,9}") {
+        let _ = parse_date(&s, 0, true);
+        let _ = parse_date(&format!("{n} {word}"), 0, true);
+        let _ = parse_date(&format!("{n} {word} ago"), 0, true);
+        let _ = parse_touch_stamp(&s, 0, true);
+    }
+
+    #[test]
+    fn octal_and_hex_escapes_give_their_byte(byte: u8) {
+        let octal = format!("\\{byte:03o}");
+        prop_assert_eq!(unescape(octal.as_bytes(), Dialect::Printf), Ok((vec![byte], false)));
+        prop_assert_eq!(unescape(octal.as_bytes(), Dialect::Tr), Ok((vec![byte], false)));
+        let octal = format!("\\0{byte:03o}");
+        prop_assert_eq!(unescape(octal.as_bytes(), Dialect::Echo), Ok((vec![byte], false)));
+        prop_assert_eq!(unescape(octal.as_bytes(), Dialect::PrintfArgument), Ok((vec![byte], false)));
+        let hex = format!("\\x{byte:02x}");
+        for dialect in [Dialect::Echo, Dialect::Printf, Dialect::PrintfArgument] {
+            prop_assert_eq!(unescape(hex.as_bytes(), dialect), Ok((vec![byte], false)));
+        }
+    }
+
+    #[test]
+    fn text_without_backslashes_is_unchanged(text in "[^\\\\]*") {
+        for dialect in [Dialect::Echo, Dialect::Printf, Dialect::PrintfArgument, Dialect::Tr] {
+            prop_assert_eq!(unescape(text.as_bytes(), dialect), Ok((text.clone().into_bytes(), false)));
+        }
+    }
+
+    #[test]
+    fn stop_escapes_end_the_output(before in "[a-z]*", after in "\\PC*") {
+        let text = format!("{before}\\c{after}");
+        prop_assert_eq!(unescape(text.as_bytes(), Dialect::Echo), Ok((before.into_bytes(), true)));
+    }
+
+    #[test]
+    fn surrogates_are_not_universal_characters(value in 0xD800u32..0xE000) {
+        prop_assert_eq!(
+            unescape(format!("\\u{value:04x}").as_bytes(), Dialect::Printf),
+            Err(EscapeError::InvalidUniversal('u', value))
+        );
+    }
+
+    #[test]
+    fn escape_interpreter_never_panics(text in "(\\\\|[0-9a-zA-Z]|\\PC)*") {
+        for dialect in [Dialect::Echo, Dialect::Printf, Dialect::PrintfArgument, Dialect::Tr] {
+            let _ = unescape(text.as_bytes(), dialect);
+        }
+    }
+
+    #[test]
+    fn range_lists_contain_what_they_list(
+        items in prop::collection::vec((1usize..200, 0usize..20), 1..8),
+        probe in 1usize..300,
+    ) {
+        let spec = items
+            .iter()
+            .map(|&(start, len)| format!("{}-{}", start, start + len))
+            .collect::<Vec<_>>()
+            .join(",");
+        let ranges = Ranges::parse(&spec, Numbering::Fields, false).unwrap();
+        let listed = items.iter().any(|&(start, len)| (start..=start + len).contains(&probe));
+        prop_assert_eq!(ranges.contains(probe), listed);
+        prop_assert_eq!(ranges.complement().contains(probe), !listed);
+    }
+
+    #[test]
+    fn range_intervals_are_sorted_and_apart(spec in "[0-9]{1,3}(-[0-9]{0,3})?(,[0-9]{1,3}(-[0-9]{0,3})?){0,6}") {
+        if let Ok(ranges) = Ranges::parse(&spec, Numbering::Fields, false) {
+            for pair in ranges.intervals().windows(2) {
+                prop_assert!(pair[0].1.saturating_add(1) < pair[1].0);
+            }
+        }
+    }
+
+    #[test]
+    fn range_parser_never_panics(spec in "[0-9, \\t-]*|\\PC*") {
+        let _ = Ranges::parse(&spec, Numbering::Positions, true);
+    }
+}